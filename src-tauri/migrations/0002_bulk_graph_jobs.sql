@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS bulk_graph_items (
+  job_id TEXT NOT NULL,
+  user_did TEXT NOT NULL,
+  action TEXT NOT NULL,
+  target TEXT NOT NULL,
+  status TEXT NOT NULL,
+  result_uri TEXT,
+  last_error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (job_id, target)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bulk_graph_items_status
+  ON bulk_graph_items(job_id, status);
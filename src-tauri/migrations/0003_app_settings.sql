@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS app_settings (
+  user_did TEXT NOT NULL,
+  setting_key TEXT NOT NULL,
+  value_json TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, setting_key)
+);
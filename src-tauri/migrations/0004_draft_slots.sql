@@ -0,0 +1,17 @@
+-- Drafts move from one row per composer context to named slots. Context autosave
+-- keys (post:new, reply:<uri>, quote:<uri>) carry over as slot ids of their own.
+CREATE TABLE IF NOT EXISTS draft_slots (
+  slot_id TEXT PRIMARY KEY,
+  name TEXT,
+  payload_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO draft_slots (slot_id, name, payload_json, created_at, updated_at)
+SELECT draft_key, NULL, payload_json, created_at, updated_at
+FROM post_drafts;
+
+DROP TABLE post_drafts;
+
+CREATE INDEX IF NOT EXISTS idx_draft_slots_updated_at ON draft_slots (updated_at);
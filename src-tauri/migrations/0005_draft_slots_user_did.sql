@@ -0,0 +1,20 @@
+-- Drafts become per-account. Existing drafts predate account scoping, so they get an
+-- empty owner and are claimed by the next account that opens its drafts.
+CREATE TABLE IF NOT EXISTS draft_slots_scoped (
+  user_did TEXT NOT NULL,
+  slot_id TEXT NOT NULL,
+  name TEXT,
+  payload_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, slot_id)
+);
+
+INSERT OR IGNORE INTO draft_slots_scoped (user_did, slot_id, name, payload_json, created_at, updated_at)
+SELECT '', slot_id, name, payload_json, created_at, updated_at
+FROM draft_slots;
+
+DROP TABLE draft_slots;
+ALTER TABLE draft_slots_scoped RENAME TO draft_slots;
+
+CREATE INDEX IF NOT EXISTS idx_draft_slots_updated_at ON draft_slots (user_did, updated_at);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS thread_read_positions (
+  user_did TEXT NOT NULL,
+  thread_root_uri TEXT NOT NULL,
+  last_seen_reply_uri TEXT,
+  last_seen_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, thread_root_uri)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS hidden_convos (
+  user_did TEXT NOT NULL,
+  convo_id TEXT NOT NULL,
+  last_message_id TEXT,
+  hidden_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, convo_id)
+);
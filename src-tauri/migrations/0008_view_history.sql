@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS view_history (
+  user_did TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  title TEXT NOT NULL,
+  subtitle TEXT,
+  avatar TEXT,
+  viewed_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, kind, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_view_history_recent
+  ON view_history (user_did, kind, viewed_at DESC);
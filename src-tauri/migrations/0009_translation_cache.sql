@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS translation_cache (
+  text_hash TEXT NOT NULL,
+  target_lang TEXT NOT NULL,
+  translated_text TEXT NOT NULL,
+  source_lang TEXT,
+  cached_at TEXT NOT NULL,
+  PRIMARY KEY (text_hash, target_lang)
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS hidden_posts (
+  user_did TEXT NOT NULL,
+  post_uri TEXT NOT NULL,
+  hidden_at TEXT NOT NULL,
+  PRIMARY KEY (user_did, post_uri)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS labeler_cache (
+  labeler_did TEXT PRIMARY KEY,
+  policies_json TEXT NOT NULL,
+  cached_at TEXT NOT NULL
+);
@@ -0,0 +1,992 @@
+use crate::commands::auth::{
+    agent_from_stored_session, current_agent, default_service, lock_agent, AgentState, AppAgent,
+};
+use crate::connectivity;
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::{
+    clear_session, get_stored_session, store_session, SessionInfo, StoredSession,
+};
+use atrium_xrpc::error::XrpcErrorKind;
+use atrium_xrpc_client::reqwest::ReqwestClient;
+use bsky_sdk::api::client::AtpServiceClient;
+use bsky_sdk::api::com::atproto::identity::resolve_handle;
+use bsky_sdk::api::com::atproto::server::create_account;
+use bsky_sdk::api::types::string::Handle;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Unauthenticated XRPC client for a PDS, used before there is a session
+fn service_client(service: &str) -> AtpServiceClient<ReqwestClient> {
+    AtpServiceClient::new(crate::http::xrpc_client(service))
+}
+
+pub(crate) fn service_or_default(service: Option<String>) -> String {
+    service
+        .map(|s| s.trim().trim_end_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(default_service)
+}
+
+#[derive(Serialize)]
+pub struct ServerDescription {
+    pub did: String,
+    pub available_user_domains: Vec<String>,
+    pub invite_code_required: bool,
+    pub phone_verification_required: bool,
+}
+
+pub(crate) async fn fetch_server_description(service: &str) -> Result<ServerDescription, AppError> {
+    let response = service_client(service)
+        .service
+        .com
+        .atproto
+        .server
+        .describe_server()
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(ServerDescription {
+        did: response.data.did.to_string(),
+        available_user_domains: response.data.available_user_domains,
+        invite_code_required: response.data.invite_code_required.unwrap_or(false),
+        phone_verification_required: response.data.phone_verification_required.unwrap_or(false),
+    })
+}
+
+/// Describe a PDS's signup requirements (handle domains, invite codes)
+#[tauri::command]
+pub async fn describe_server(service: Option<String>) -> Result<ServerDescription, AppError> {
+    fetch_server_description(&service_or_default(service)).await
+}
+
+#[derive(Serialize)]
+pub struct HandleAvailability {
+    pub handle: String,
+    pub available: bool,
+    /// Why the handle can't be used, when `available` is false
+    pub reason: Option<String>,
+}
+
+/// Check whether a handle is free to register: it is available when the server reports it
+/// doesn't resolve. Outages and rate limits are errors rather than a false "available".
+#[tauri::command]
+pub async fn check_handle_available(
+    handle: String,
+    service: Option<String>,
+) -> Result<HandleAvailability, AppError> {
+    let handle = handle.trim().trim_start_matches('@').to_lowercase();
+    let unavailable = |reason: &str| HandleAvailability {
+        handle: handle.clone(),
+        available: false,
+        reason: Some(reason.to_string()),
+    };
+
+    let Ok(parsed) = Handle::from_str(&handle) else {
+        return Ok(unavailable("Invalid handle"));
+    };
+
+    let result = service_client(&service_or_default(service))
+        .service
+        .com
+        .atproto
+        .identity
+        .resolve_handle(resolve_handle::ParametersData { handle: parsed }.into())
+        .await;
+
+    match result {
+        Ok(_) => Ok(unavailable("Handle is already taken")),
+        Err(err) if handle_not_found(&err) => Ok(HandleAvailability {
+            handle,
+            available: true,
+            reason: None,
+        }),
+        Err(err) => Err(connectivity::xrpc_error(err)),
+    }
+}
+
+/// Whether resolveHandle failed because nothing is registered under the handle, as opposed to
+/// the server being unreachable, overloaded or rate limiting
+fn handle_not_found<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    let atrium_xrpc::Error::XrpcResponse(resp) = err else {
+        return false;
+    };
+    match resp.status.as_u16() {
+        404 => true,
+        400 => match &resp.error {
+            Some(XrpcErrorKind::Undefined(body)) => matches!(
+                body.error.as_deref(),
+                Some("InvalidRequest" | "HandleNotFound" | "NotFound")
+            ),
+            Some(XrpcErrorKind::Custom(_)) => true,
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateAccountRequest {
+    pub email: String,
+    pub handle: String,
+    pub password: String,
+    pub invite_code: Option<String>,
+    pub service: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateAccountResponse {
+    pub did: String,
+    pub handle: String,
+    pub service: String,
+}
+
+fn validation_error(code: &'static str, message: Option<String>, fallback: &str) -> AppError {
+    AppError::ValidationError {
+        code,
+        message: message
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| fallback.to_string()),
+    }
+}
+
+/// Map createAccount failures to distinct validation codes the signup form can show per field
+fn create_account_error(err: atrium_xrpc::Error<create_account::Error>) -> AppError {
+    use create_account::Error as E;
+
+    let atrium_xrpc::Error::XrpcResponse(ref resp) = err else {
+        return connectivity::xrpc_error(err);
+    };
+
+    match &resp.error {
+        Some(XrpcErrorKind::Custom(custom)) => match custom {
+            E::HandleNotAvailable(msg) => {
+                validation_error("HANDLE_TAKEN", msg.clone(), "Handle is already taken")
+            }
+            E::InvalidHandle(msg) | E::UnsupportedDomain(msg) => {
+                validation_error("INVALID_HANDLE", msg.clone(), "Invalid handle")
+            }
+            E::InvalidPassword(msg) => {
+                validation_error("INVALID_PASSWORD", msg.clone(), "Password is not allowed")
+            }
+            E::InvalidInviteCode(msg) => {
+                validation_error("INVALID_INVITE_CODE", msg.clone(), "Invalid invite code")
+            }
+            _ => AppError::ApiError(err.to_string()),
+        },
+        Some(XrpcErrorKind::Undefined(body)) => {
+            let message = body.message.clone();
+            let lower = message.as_deref().unwrap_or_default().to_lowercase();
+            if lower.contains("invite code") {
+                validation_error("INVALID_INVITE_CODE", message, "Invalid invite code")
+            } else if lower.contains("password") {
+                validation_error("INVALID_PASSWORD", message, "Password is not allowed")
+            } else if lower.contains("email") {
+                validation_error("INVALID_EMAIL", message, "Invalid email address")
+            } else {
+                AppError::ApiError(err.to_string())
+            }
+        }
+        None => AppError::ApiError(err.to_string()),
+    }
+}
+
+/// Create a new account on a PDS and sign in to it
+#[tauri::command]
+pub async fn create_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    request: CreateAccountRequest,
+) -> Result<CreateAccountResponse, AppError> {
+    let service = service_or_default(request.service);
+    let email = request.email.trim().to_string();
+    let handle = request.handle.trim().trim_start_matches('@').to_lowercase();
+    let invite_code = request
+        .invite_code
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+
+    if email.is_empty() {
+        return Err(validation_error("INVALID_EMAIL", None, "Email is required"));
+    }
+    if request.password.is_empty() {
+        return Err(validation_error(
+            "INVALID_PASSWORD",
+            None,
+            "Password is required",
+        ));
+    }
+    let parsed_handle = Handle::from_str(&handle)
+        .map_err(|_| validation_error("INVALID_HANDLE", None, "Invalid handle"))?;
+
+    let server = fetch_server_description(&service).await?;
+    if server.invite_code_required && invite_code.is_none() {
+        return Err(validation_error(
+            "INVITE_CODE_REQUIRED",
+            None,
+            "This server requires an invite code",
+        ));
+    }
+
+    let response = service_client(&service)
+        .service
+        .com
+        .atproto
+        .server
+        .create_account(
+            create_account::InputData {
+                did: None,
+                email: Some(email.clone()),
+                handle: parsed_handle,
+                invite_code,
+                password: Some(request.password),
+                plc_op: None,
+                recovery_key: None,
+                verification_code: None,
+                verification_phone: None,
+            }
+            .into(),
+        )
+        .await
+        .map_err(create_account_error)?;
+
+    let stored = StoredSession {
+        did: response.data.did.to_string(),
+        handle: response.data.handle.to_string(),
+        access_jwt: response.data.access_jwt.clone(),
+        refresh_jwt: response.data.refresh_jwt.clone(),
+        service_url: service.clone(),
+        email: Some(email),
+        email_confirmed: Some(false),
+    };
+    store_session(&stored)?;
+
+    let agent = agent_from_stored_session(&stored).await?;
+    let mut state = lock_agent(&agent_state).await?;
+    *state = Some(agent);
+    drop(state);
+
+    let db = app.state::<crate::db::DbState>().inner().clone();
+    crate::commands::actions::trigger_retry_now(app.clone(), agent_state.inner().clone(), db);
+
+    Ok(CreateAccountResponse {
+        did: stored.did,
+        handle: stored.handle,
+        service,
+    })
+}
+
+/// Pull the account's email status from the server and save it with the stored session
+pub(crate) async fn sync_email_status(agent: &AppAgent) -> Result<StoredSession, AppError> {
+    let response = agent
+        .api
+        .com
+        .atproto
+        .server
+        .get_session()
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    let mut stored = get_stored_session()?;
+    stored.email = response.data.email.clone();
+    stored.email_confirmed = Some(response.data.email_confirmed.unwrap_or(false));
+    store_session(&stored)?;
+
+    Ok(stored)
+}
+
+fn token_error<E>(
+    err: atrium_xrpc::Error<E>,
+    custom_code: impl FnOnce(&E) -> Option<&'static str>,
+) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if let atrium_xrpc::Error::XrpcResponse(ref resp) = err {
+        if let Some(XrpcErrorKind::Custom(custom)) = &resp.error {
+            if let Some(code) = custom_code(custom) {
+                return AppError::ValidationError {
+                    code,
+                    message: err.to_string(),
+                };
+            }
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// Email the signed-in user a confirmation code
+#[tauri::command]
+pub async fn request_email_confirmation(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+) -> Result<(), AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .request_email_confirmation()
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(())
+}
+
+/// Confirm the signed-in user's email with the emailed code; returns the updated session
+#[tauri::command]
+pub async fn confirm_email(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    token: String,
+) -> Result<SessionInfo, AppError> {
+    use bsky_sdk::api::com::atproto::server::confirm_email::{Error as E, InputData};
+
+    let agent = current_agent(&agent_state).await?;
+
+    let mut stored = get_stored_session()?;
+    if stored.email.is_none() {
+        stored = sync_email_status(&agent)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    }
+    let email = stored
+        .email
+        .clone()
+        .ok_or_else(|| AppError::ApiError("Account has no email address".into()))?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .confirm_email(
+            InputData {
+                email,
+                token: token.trim().to_string(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| {
+            token_error(e, |custom| match custom {
+                E::ExpiredToken(_) => Some("EXPIRED_TOKEN"),
+                E::InvalidToken(_) => Some("INVALID_TOKEN"),
+                E::InvalidEmail(_) => Some("INVALID_EMAIL"),
+                _ => None,
+            })
+        })
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let stored = sync_email_status(&agent)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    Ok(SessionInfo::from(&stored))
+}
+
+/// Email a password reset code; works signed out
+#[tauri::command]
+pub async fn request_password_reset(
+    email: String,
+    service: Option<String>,
+) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::request_password_reset::InputData;
+
+    service_client(&service_or_default(service))
+        .service
+        .com
+        .atproto
+        .server
+        .request_password_reset(
+            InputData {
+                email: email.trim().to_string(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(())
+}
+
+/// Set a new password using the emailed reset code; works signed out
+#[tauri::command]
+pub async fn reset_password(
+    token: String,
+    new_password: String,
+    service: Option<String>,
+) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::reset_password::{Error as E, InputData};
+
+    if new_password.is_empty() {
+        return Err(validation_error(
+            "INVALID_PASSWORD",
+            None,
+            "Password is required",
+        ));
+    }
+
+    service_client(&service_or_default(service))
+        .service
+        .com
+        .atproto
+        .server
+        .reset_password(
+            InputData {
+                password: new_password,
+                token: token.trim().to_string(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| {
+            token_error(e, |custom| match custom {
+                E::ExpiredToken(_) => Some("EXPIRED_TOKEN"),
+                E::InvalidToken(_) => Some("INVALID_TOKEN"),
+            })
+        })?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct AppPasswordInfo {
+    pub name: String,
+    pub created_at: String,
+    pub privileged: bool,
+}
+
+#[derive(Serialize)]
+pub struct CreatedAppPassword {
+    pub name: String,
+    pub created_at: String,
+    pub privileged: bool,
+    /// The secret itself. The server only returns it here; it can't be retrieved again.
+    pub password: String,
+}
+
+/// App-password management rejects sessions that were themselves created with an app password
+fn app_password_error<E>(err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if let atrium_xrpc::Error::XrpcResponse(ref resp) = err {
+        let scope_rejected = match &resp.error {
+            Some(XrpcErrorKind::Undefined(body)) => {
+                body.error.as_deref() == Some("InvalidToken")
+                    || body
+                        .message
+                        .as_deref()
+                        .is_some_and(|m| m.to_lowercase().contains("scope"))
+            }
+            _ => false,
+        };
+        if scope_rejected || matches!(resp.status.as_u16(), 401 | 403) {
+            return AppError::AuthenticationFailed(
+                "App passwords can only be managed after signing in with your account password"
+                    .into(),
+            );
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// List the account's app passwords (names only; secrets are never returned)
+#[tauri::command]
+pub async fn list_app_passwords(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+) -> Result<Vec<AppPasswordInfo>, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let response = agent
+        .api
+        .com
+        .atproto
+        .server
+        .list_app_passwords()
+        .await
+        .map_err(app_password_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(response
+        .data
+        .passwords
+        .iter()
+        .map(|p| AppPasswordInfo {
+            name: p.name.clone(),
+            created_at: p.created_at.as_ref().to_string(),
+            privileged: p.privileged.unwrap_or(false),
+        })
+        .collect())
+}
+
+/// Create an app password. The returned secret is shown once and can't be fetched later.
+#[tauri::command]
+pub async fn create_app_password(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    name: String,
+    privileged: Option<bool>,
+) -> Result<CreatedAppPassword, AppError> {
+    use bsky_sdk::api::com::atproto::server::create_app_password::InputData;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(validation_error("INVALID_NAME", None, "Name is required"));
+    }
+
+    let agent = current_agent(&agent_state).await?;
+
+    let response = agent
+        .api
+        .com
+        .atproto
+        .server
+        .create_app_password(
+            InputData {
+                name,
+                privileged: Some(privileged.unwrap_or(false)),
+            }
+            .into(),
+        )
+        .await
+        .map_err(app_password_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(CreatedAppPassword {
+        name: response.data.name.clone(),
+        created_at: response.data.created_at.as_ref().to_string(),
+        privileged: response.data.privileged.unwrap_or(false),
+        password: response.data.password.clone(),
+    })
+}
+
+/// Revoke an app password by name
+#[tauri::command]
+pub async fn revoke_app_password(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    name: String,
+) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::revoke_app_password::InputData;
+
+    let agent = current_agent(&agent_state).await?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .revoke_app_password(InputData { name }.into())
+        .await
+        .map_err(app_password_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(())
+}
+
+/// Check the account password before an irreversible action. A wrong password comes
+/// back as `INVALID_PASSWORD` rather than an auth failure so the session stays intact.
+async fn verify_password(stored: &StoredSession, password: &str) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::create_session::InputData;
+
+    if password.is_empty() {
+        return Err(validation_error(
+            "INVALID_PASSWORD",
+            None,
+            "Password is required",
+        ));
+    }
+
+    let result = service_client(&stored.service_url)
+        .service
+        .com
+        .atproto
+        .server
+        .create_session(
+            InputData {
+                allow_takendown: None,
+                auth_factor_token: None,
+                identifier: stored.did.clone(),
+                password: password.to_string(),
+            }
+            .into(),
+        )
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(atrium_xrpc::Error::XrpcResponse(ref resp)) if resp.status.as_u16() == 401 => Err(
+            validation_error("INVALID_PASSWORD", None, "Password is incorrect"),
+        ),
+        Err(err) => Err(lifecycle_error(err)),
+    }
+}
+
+/// Rate limits (e.g. repeated deletion emails) get their own code; the server's message
+/// says how long to wait
+fn lifecycle_error<E>(err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if let atrium_xrpc::Error::XrpcResponse(ref resp) = err {
+        if resp.status.as_u16() == 429 {
+            return validation_error("RATE_LIMITED", Some(err.to_string()), "Try again later");
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// Deactivate the signed-in account after confirming the password. The account can be
+/// reactivated by signing in again; the local session is left as is.
+#[tauri::command]
+pub async fn deactivate_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    password: String,
+) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::deactivate_account::InputData;
+
+    let agent = current_agent(&agent_state).await?;
+    let stored = get_stored_session()?;
+    verify_password(&stored, &password)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .deactivate_account(InputData { delete_after: None }.into())
+        .await
+        .map_err(lifecycle_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    tracing::info!(target: "auth", did = %stored.did, "account deactivated");
+    Ok(())
+}
+
+/// Email the signed-in user a code required by `delete_account`
+#[tauri::command]
+pub async fn request_account_deletion(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+) -> Result<(), AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .request_account_delete()
+        .await
+        .map_err(lifecycle_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(())
+}
+
+/// Permanently delete the signed-in account using the emailed code and the password.
+/// On success the keyring session and every local cache for the account are removed.
+#[tauri::command]
+pub async fn delete_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    token: String,
+    password: String,
+) -> Result<(), AppError> {
+    use bsky_sdk::api::com::atproto::server::delete_account::{Error as E, InputData};
+
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err(validation_error(
+            "INVALID_TOKEN",
+            None,
+            "Deletion code is required",
+        ));
+    }
+    if password.is_empty() {
+        return Err(validation_error(
+            "INVALID_PASSWORD",
+            None,
+            "Password is required",
+        ));
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    let stored = get_stored_session()?;
+    let did = stored
+        .did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid stored DID".into()))?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .server
+        .delete_account(
+            InputData {
+                did,
+                password,
+                token,
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| {
+            if matches!(&e, atrium_xrpc::Error::XrpcResponse(resp) if resp.status.as_u16() == 401) {
+                return validation_error("INVALID_PASSWORD", None, "Password is incorrect");
+            }
+            token_error(e, |custom| match custom {
+                E::ExpiredToken(_) => Some("EXPIRED_TOKEN"),
+                E::InvalidToken(_) => Some("INVALID_TOKEN"),
+            })
+        })
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    tracing::info!(target: "auth", did = %stored.did, "account deleted");
+
+    // The account is gone server-side; local cleanup failures are logged, not returned
+    if let Err(err) = clear_session() {
+        tracing::warn!(target: "auth", "failed to clear session after deletion: {err}");
+    }
+    crate::unread::reset();
+    crate::commands::interactions::reset();
+    // No busy timeout here: the account is already gone, so the stale agent must be dropped
+    *agent_state.lock().await = None;
+    if let Err(err) = crate::db::purge_user_data(db.inner().as_ref(), &stored.did).await {
+        tracing::warn!(target: "cache", "failed to purge local data after deletion: {err}");
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Clone)]
+pub struct HandleChangedEvent {
+    pub did: String,
+    pub old_handle: String,
+    pub handle: String,
+}
+
+/// Check a custom-domain handle points at `did` before asking the PDS to switch to it.
+/// The `/.well-known/atproto-did` file is read directly; DNS TXT records are resolved by
+/// the PDS. A handle pointing elsewhere is `HANDLE_TAKEN`, one that doesn't resolve at all
+/// is `HANDLE_NOT_VERIFIED`.
+async fn verify_handle_domain(service: &str, handle: &Handle, did: &str) -> Result<(), AppError> {
+    let well_known = crate::http::client()
+        .get(format!(
+            "https://{}/.well-known/atproto-did",
+            handle.as_str()
+        ))
+        .send()
+        .await;
+    if let Ok(response) = well_known {
+        if response.status().is_success() {
+            if let Ok(body) = response.text().await {
+                let published = body.trim();
+                if published == did {
+                    return Ok(());
+                }
+                if published.starts_with("did:") {
+                    return Err(validation_error(
+                        "HANDLE_TAKEN",
+                        Some(format!("{} points to a different account", handle.as_str())),
+                        "Handle is already taken",
+                    ));
+                }
+            }
+        }
+    }
+
+    let result = service_client(service)
+        .service
+        .com
+        .atproto
+        .identity
+        .resolve_handle(
+            resolve_handle::ParametersData {
+                handle: handle.clone(),
+            }
+            .into(),
+        )
+        .await;
+
+    match result {
+        Ok(resolved) if resolved.data.did.as_str() == did => Ok(()),
+        Ok(_) => Err(validation_error(
+            "HANDLE_TAKEN",
+            Some(format!("{} points to a different account", handle.as_str())),
+            "Handle is already taken",
+        )),
+        Err(err) if connectivity::is_transport_error(&err) => Err(connectivity::xrpc_error(err)),
+        Err(_) => Err(validation_error(
+            "HANDLE_NOT_VERIFIED",
+            Some(format!(
+                "{} doesn't point to this account yet. Add a _atproto TXT record with \
+                 did={did} or serve the DID at /.well-known/atproto-did",
+                handle.as_str()
+            )),
+            "Handle domain is not verified",
+        )),
+    }
+}
+
+/// Map updateHandle failures; the PDS reports them as untyped errors
+fn update_handle_error<E>(err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if let atrium_xrpc::Error::XrpcResponse(ref resp) = err {
+        if let Some(XrpcErrorKind::Undefined(body)) = &resp.error {
+            let message = body.message.clone();
+            let lower = message.as_deref().unwrap_or_default().to_lowercase();
+            if lower.contains("taken") || lower.contains("not available") {
+                return validation_error("HANDLE_TAKEN", message, "Handle is already taken");
+            }
+            if lower.contains("resolve") || lower.contains("did not match") {
+                return validation_error(
+                    "HANDLE_NOT_VERIFIED",
+                    message,
+                    "Handle domain is not verified",
+                );
+            }
+            if lower.contains("invalid") || lower.contains("domain") {
+                return validation_error("INVALID_HANDLE", message, "Invalid handle");
+            }
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// Switch the signed-in account to a new handle. Custom domains are checked against the
+/// account's DID first; handles under the PDS's own domains are left to the server. On
+/// success the session is refreshed, the stored handle updated and `handle_changed` emitted.
+#[tauri::command]
+pub async fn update_handle(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    new_handle: String,
+) -> Result<SessionInfo, AppError> {
+    use bsky_sdk::api::com::atproto::identity::update_handle::InputData;
+
+    let handle = new_handle.trim().trim_start_matches('@').to_lowercase();
+    let parsed = Handle::from_str(&handle)
+        .map_err(|_| validation_error("INVALID_HANDLE", None, "Invalid handle"))?;
+
+    let agent = current_agent(&agent_state).await?;
+    let mut stored = get_stored_session()?;
+    if stored.handle == handle {
+        return Ok(SessionInfo::from(&stored));
+    }
+
+    let server = fetch_server_description(&stored.service_url)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    let on_server_domain = server
+        .available_user_domains
+        .iter()
+        .any(|domain| handle.ends_with(domain.as_str()));
+    if !on_server_domain {
+        verify_handle_domain(&stored.service_url, &parsed, &stored.did)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    }
+
+    agent
+        .api
+        .com
+        .atproto
+        .identity
+        .update_handle(InputData { handle: parsed }.into())
+        .await
+        .map_err(update_handle_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    // The old tokens and cached session still carry the previous handle
+    let refreshed = agent
+        .api
+        .com
+        .atproto
+        .server
+        .refresh_session()
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let old_handle = std::mem::replace(&mut stored.handle, refreshed.data.handle.to_string());
+    stored.access_jwt = refreshed.data.access_jwt.clone();
+    stored.refresh_jwt = refreshed.data.refresh_jwt.clone();
+    store_session(&stored)?;
+
+    let agent = agent_from_stored_session(&stored).await?;
+    *lock_agent(&agent_state).await? = Some(agent);
+
+    tracing::info!(target: "auth", did = %stored.did, handle = %stored.handle, "handle updated");
+    let _ = app.emit(
+        "handle_changed",
+        HandleChangedEvent {
+            did: stored.did.clone(),
+            old_handle,
+            handle: stored.handle.clone(),
+        },
+    );
+
+    // The account menu shows the handle; refresh its cached profile
+    let refresh_agent_state = agent_state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<DbState>().inner().clone();
+        if let Err(err) =
+            crate::commands::timeline::refresh_my_profile(&app, &refresh_agent_state, db.as_ref())
+                .await
+        {
+            tracing::warn!(target: "cache", cache = "profile", "own profile refresh failed: {err}");
+        }
+    });
+
+    Ok(SessionInfo::from(&stored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::{undefined, xrpc_failure};
+
+    fn is_available(err: atrium_xrpc::Error<resolve_handle::Error>) -> bool {
+        handle_not_found(&err)
+    }
+
+    #[test]
+    fn unresolved_handles_are_available() {
+        assert!(is_available(xrpc_failure(400, undefined("InvalidRequest"))));
+        assert!(is_available(xrpc_failure(400, undefined("HandleNotFound"))));
+        assert!(is_available(xrpc_failure(404, None)));
+    }
+
+    #[test]
+    fn outages_and_rate_limits_are_not_available() {
+        assert!(!is_available(xrpc_failure(
+            429,
+            undefined("RateLimitExceeded")
+        )));
+        assert!(!is_available(xrpc_failure(
+            500,
+            undefined("InternalServerError")
+        )));
+        assert!(!is_available(xrpc_failure(502, None)));
+        assert!(!is_available(xrpc_failure(400, None)));
+
+        let offline = atrium_xrpc::Error::<resolve_handle::Error>::HttpClient(Box::new(
+            std::io::Error::other("connection refused"),
+        ));
+        assert!(!is_available(offline));
+    }
+}
@@ -1,8 +1,14 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::commands::gifs::{gif_external, GifResult};
+use crate::commands::link_cards::ExternalEmbedInput;
+use crate::commands::postgate;
+use crate::commands::settings;
+use crate::commands::timeline;
+use crate::connectivity;
 use crate::db::DbState;
 use crate::error::AppError;
 use crate::session::get_stored_session;
-use crate::session_store::KeyringSessionStore;
+use atrium_xrpc::error::XrpcErrorKind;
 use bsky_sdk::api::app::bsky::feed::like::RecordData as LikeRecordData;
 use bsky_sdk::api::app::bsky::feed::repost::RecordData as RepostRecordData;
 use bsky_sdk::api::com::atproto::repo::create_record;
@@ -10,17 +16,17 @@ use bsky_sdk::api::com::atproto::repo::delete_record;
 use bsky_sdk::api::com::atproto::repo::strong_ref;
 use bsky_sdk::api::types::string::{AtIdentifier, Did, RecordKey};
 use bsky_sdk::api::types::TryIntoUnknown;
-use bsky_sdk::BskyAgent;
 use chrono::{Duration, Utc};
 use ipld_core::ipld::Ipld;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
 use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
 
-type AppAgent = BskyAgent<atrium_xrpc_client::reqwest::ReqwestClient, KeyringSessionStore>;
-
 fn parse_rkey_from_uri(uri: &str) -> Result<String, AppError> {
     // at://did:example/app.bsky.feed.like/<rkey>
     uri.split('/')
@@ -40,12 +46,12 @@ fn current_repo_did() -> Result<Did, AppError> {
 /// Like a post (creates app.bsky.feed.like record)
 #[tauri::command]
 pub async fn like_post(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     uri: String,
     cid: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -88,7 +94,8 @@ pub async fn like_post(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -96,11 +103,11 @@ pub async fn like_post(
 /// Unlike a post (deletes the like record)
 #[tauri::command]
 pub async fn unlike_post(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     like_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -126,7 +133,8 @@ pub async fn unlike_post(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -134,12 +142,12 @@ pub async fn unlike_post(
 /// Repost a post (creates app.bsky.feed.repost record)
 #[tauri::command]
 pub async fn repost_post(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     uri: String,
     cid: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -182,7 +190,8 @@ pub async fn repost_post(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -190,11 +199,11 @@ pub async fn repost_post(
 /// Unrepost a post (deletes the repost record)
 #[tauri::command]
 pub async fn unrepost_post(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     repost_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -220,7 +229,8 @@ pub async fn unrepost_post(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -231,17 +241,107 @@ pub struct ImageInput {
     pub alt: String,
 }
 
+/// `ALT_TEXT_MISSING` naming the images (1-based, in composer order) without alt text
+fn check_alt_text(images: &[ImageInput]) -> Result<(), AppError> {
+    let missing: Vec<String> = images
+        .iter()
+        .enumerate()
+        .filter(|(_, image)| image.alt.trim().is_empty())
+        .map(|(index, _)| (index + 1).to_string())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let noun = if missing.len() == 1 {
+        "image"
+    } else {
+        "images"
+    };
+    Err(AppError::ValidationError {
+        code: "ALT_TEXT_MISSING",
+        message: format!("Add alt text to {noun} {}", missing.join(", ")),
+    })
+}
+
+/// `check_alt_text` for every segment of a thread, naming the first segment that fails
+fn check_thread_alt_text(posts: &[CreatePostPayload]) -> Result<(), AppError> {
+    for (index, segment) in posts.iter().enumerate() {
+        if let Err(AppError::ValidationError { code, message }) = check_alt_text(&segment.images) {
+            return Err(AppError::ValidationError {
+                code,
+                message: format!("Post {} of the thread: {message}", index + 1),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Tag thread segments sent without languages with the account default, as
+/// `create_post` does for a single post
+fn apply_default_langs(posts: &mut [CreatePostPayload], defaults: &[String]) {
+    for segment in posts.iter_mut().filter(|segment| segment.langs.is_empty()) {
+        segment.langs = defaults.to_vec();
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
-struct CreatePostPayload {
+pub struct CreatePostPayload {
     pub text: String,
     pub reply_to: Option<String>,
     pub quote_uri: Option<String>,
     pub quote_cid: Option<String>,
+    #[serde(default)]
     pub images: Vec<ImageInput>,
+    /// `Some(false)` writes a postgate that disables quoting
+    #[serde(default)]
+    pub allow_quotes: Option<bool>,
+    /// BCP-47 language tags
+    #[serde(default)]
+    pub langs: Vec<String>,
+    /// Self-applied content labels (e.g. "sexual", "graphic-media")
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Link card from `fetch_link_card`; can't be combined with images
+    #[serde(default)]
+    pub external: Option<ExternalEmbedInput>,
+    /// Thread refs for `reply_to`. Passed by the composer (which has the parent's thread
+    /// on screen) or filled in once resolved, so a queued reply can be sent without the
+    /// parent resolving again.
+    #[serde(default)]
+    pub reply_refs: Option<ReplyRefs>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplyRefs {
+    pub root: PostedRef,
+    pub parent: PostedRef,
+}
+
+impl ReplyRefs {
+    fn from_reply_ref(reply: &bsky_sdk::api::app::bsky::feed::post::ReplyRef) -> Self {
+        Self {
+            root: PostedRef::from_strong_ref(&reply.data.root),
+            parent: PostedRef::from_strong_ref(&reply.data.parent),
+        }
+    }
+
+    fn to_reply_ref(&self) -> Result<bsky_sdk::api::app::bsky::feed::post::ReplyRef, AppError> {
+        use bsky_sdk::api::app::bsky::feed::post::{ReplyRef, ReplyRefData};
+
+        Ok(ReplyRef {
+            data: ReplyRefData {
+                root: self.root.to_strong_ref()?,
+                parent: self.parent.to_strong_ref()?,
+            },
+            extra_data: Ipld::Null,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PostDraft {
+    pub slot_id: String,
+    pub name: Option<String>,
     pub text: String,
     pub reply_to: Option<String>,
     pub quote_uri: Option<String>,
@@ -255,6 +355,7 @@ pub struct RetryQueueEvent {
     pub id: String,
 }
 
+/// Autosave slot for a composer context (new post, reply, quote)
 fn draft_key(reply_to: Option<&str>, quote_uri: Option<&str>) -> String {
     if let Some(reply) = reply_to {
         return format!("reply:{reply}");
@@ -265,43 +366,222 @@ fn draft_key(reply_to: Option<&str>, quote_uri: Option<&str>) -> String {
     "post:new".to_string()
 }
 
+/// XRPC error names servers use for conditions that clear up on their own
+const TRANSIENT_XRPC_ERRORS: [&str; 5] = [
+    "RateLimitExceeded",
+    "UpstreamFailure",
+    "UpstreamTimeout",
+    "InternalServerError",
+    "NotEnoughResources",
+];
+
+/// Whether an XRPC call failed in a way that clears up on its own: it never reached the
+/// server, or the server answered 429/5xx or named a transient error. Everything else
+/// (InvalidRequest, a threadgate rejection, a malformed record) fails the same way on
+/// every attempt.
+fn is_transient_xrpc_error<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    match err {
+        atrium_xrpc::Error::HttpClient(_) => true,
+        atrium_xrpc::Error::XrpcResponse(resp) => {
+            let status = resp.status.as_u16();
+            status == 429
+                || (500..=599).contains(&status)
+                || matches!(
+                    &resp.error,
+                    Some(XrpcErrorKind::Undefined(body))
+                        if body
+                            .error
+                            .as_deref()
+                            .is_some_and(|name| TRANSIENT_XRPC_ERRORS.contains(&name))
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Errors raised before any XRPC call (offline, signed out mid-resume) are worth
+/// queueing; validation and authorization errors go straight back to the caller
 fn should_enqueue_retry(error: &AppError) -> bool {
-    matches!(
-        error,
-        AppError::SessionNotFound | AppError::NetworkError(_) | AppError::ApiError(_)
-    )
+    matches!(error, AppError::SessionNotFound | AppError::NetworkError(_))
 }
 
-fn compute_next_retry_at(attempts: i64) -> String {
+/// A failed post send, classified while the XRPC error is still structured
+struct SendError {
+    error: AppError,
+    /// Retrying later can succeed, so the post is queued instead of failing
+    transient: bool,
+}
+
+impl SendError {
+    fn xrpc<E>(err: atrium_xrpc::Error<E>) -> Self
+    where
+        atrium_xrpc::Error<E>: std::fmt::Display,
+    {
+        SendError {
+            transient: is_transient_xrpc_error(&err),
+            error: crate::connectivity::xrpc_error(err),
+        }
+    }
+}
+
+impl From<AppError> for SendError {
+    fn from(error: AppError) -> Self {
+        SendError {
+            transient: should_enqueue_retry(&error),
+            error,
+        }
+    }
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+/// Backoff before retry number `attempts`: 15s doubling per attempt up to 30 minutes,
+/// with the upper half jittered so posts queued during one outage don't retry in lockstep
+fn retry_backoff_secs(attempts: i64, jitter: f64) -> i64 {
     let capped_attempts = attempts.clamp(1, 8);
-    let backoff_secs = 15_i64 * 2_i64.pow(capped_attempts as u32);
-    (Utc::now() + Duration::seconds(backoff_secs.min(1800))).to_rfc3339()
+    let backoff_secs = (15_i64 * 2_i64.pow(capped_attempts as u32)).min(1800);
+    let half = backoff_secs / 2;
+    half + (half as f64 * jitter.clamp(0.0, 1.0)).round() as i64
 }
 
-async fn save_draft_payload(
+fn compute_next_retry_at(attempts: i64) -> String {
+    // A v4 UUID is 122 random bits; its low bits are plenty for jitter
+    let jitter = (Uuid::new_v4().as_u128() as u32) as f64 / u32::MAX as f64;
+    (Utc::now() + Duration::seconds(retry_backoff_secs(attempts, jitter))).to_rfc3339()
+}
+
+/// DID that owns the signed-in account's drafts
+fn draft_owner() -> Result<String, AppError> {
+    Ok(current_repo_did()?.to_string())
+}
+
+/// Give drafts saved before drafts were scoped per account (empty owner) to the account
+/// that was signed in when they were written. Run at startup for the stored session, so
+/// no other account ever sees them. Where the account already has a draft in the same
+/// slot, the old one is kept beside it under a `legacy:` slot.
+pub(crate) async fn claim_legacy_drafts(db: &SqlitePool, user_did: &str) -> Result<(), AppError> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::InternalError(format!("draft claim begin failed: {e}")))?;
+
+    for query in [
+        "UPDATE OR IGNORE draft_slots SET user_did = ?1 WHERE user_did = ''",
+        "UPDATE OR IGNORE draft_slots SET user_did = ?1, slot_id = 'legacy:' || slot_id \
+         WHERE user_did = ''",
+    ] {
+        sqlx::query(query)
+            .bind(user_did)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalError(format!("draft claim failed: {e}")))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::InternalError(format!("draft claim commit failed: {e}")))
+}
+
+/// Autosaves of one slot arriving within this window of each other become one write
+const DRAFT_AUTOSAVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Latest autosave of a slot that hasn't been written yet; `None` payload clears the slot
+struct PendingDraft {
+    generation: u64,
+    payload: Option<CreatePostPayload>,
+    /// When the autosave was requested; older writes never replace newer ones
+    queued_at: String,
+    /// The slot's task is writing it; the entry stays until the write has landed
+    in_flight: bool,
+    /// The draft was cleared during that write, which may have re-created the row
+    cleared: bool,
+}
+
+/// (owner DID, slot id)
+type DraftSlotKey = (String, String);
+
+static PENDING_DRAFTS: LazyLock<Mutex<HashMap<DraftSlotKey, PendingDraft>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static DRAFT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn pending_drafts() -> std::sync::MutexGuard<'static, HashMap<DraftSlotKey, PendingDraft>> {
+    PENDING_DRAFTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// What a slot's task does once its write has landed
+#[derive(Debug, PartialEq)]
+enum AfterAutosave {
+    Done,
+    /// A newer autosave arrived during the write
+    Again,
+    /// The draft was cleared during the write; delete the row again
+    Delete,
+}
+
+fn finish_autosave(key: &DraftSlotKey, written: u64) -> AfterAutosave {
+    let mut drafts = pending_drafts();
+    let Some(pending) = drafts.get_mut(key) else {
+        // Replaced by a flushed save, which is newer than this write
+        return AfterAutosave::Done;
+    };
+    if pending.cleared {
+        drafts.remove(key);
+        AfterAutosave::Delete
+    } else if pending.generation != written {
+        pending.in_flight = false;
+        AfterAutosave::Again
+    } else {
+        drafts.remove(key);
+        AfterAutosave::Done
+    }
+}
+
+/// Drop a slot's pending autosave before its row is deleted. One that is being written
+/// is only flagged, so its task deletes the row again after the write lands.
+fn cancel_autosave(key: &DraftSlotKey) {
+    let mut drafts = pending_drafts();
+    match drafts.get_mut(key) {
+        Some(pending) if pending.in_flight => pending.cleared = true,
+        Some(_) => {
+            drafts.remove(key);
+        }
+        None => {}
+    }
+}
+
+/// Write a draft, keeping any name the slot already has when `name` is `None`, unless
+/// the slot already holds a newer one
+async fn upsert_draft(
     db: &SqlitePool,
-    reply_to: Option<&str>,
-    quote_uri: Option<&str>,
+    user_did: &str,
+    slot_id: &str,
+    name: Option<&str>,
     payload: &CreatePostPayload,
+    updated_at: &str,
 ) -> Result<(), AppError> {
-    let key = draft_key(reply_to, quote_uri);
-    let now = Utc::now().to_rfc3339();
     let payload_json = serde_json::to_string(payload)
         .map_err(|e| AppError::InternalError(format!("draft encode failed: {e}")))?;
 
     sqlx::query(
         r#"
-        INSERT INTO post_drafts (draft_key, payload_json, created_at, updated_at)
-        VALUES (?1, ?2, ?3, ?4)
-        ON CONFLICT(draft_key) DO UPDATE SET
+        INSERT INTO draft_slots (user_did, slot_id, name, payload_json, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+        ON CONFLICT(user_did, slot_id) DO UPDATE SET
+            name = COALESCE(excluded.name, draft_slots.name),
             payload_json = excluded.payload_json,
             updated_at = excluded.updated_at
+        WHERE excluded.updated_at >= draft_slots.updated_at
         "#,
     )
-    .bind(key)
+    .bind(user_did)
+    .bind(slot_id)
+    .bind(name)
     .bind(payload_json)
-    .bind(&now)
-    .bind(&now)
+    .bind(updated_at)
     .execute(db)
     .await
     .map_err(|e| AppError::InternalError(format!("draft save failed: {e}")))?;
@@ -309,55 +589,169 @@ async fn save_draft_payload(
     Ok(())
 }
 
+/// Write an autosave; `None` clears the slot
+async fn write_autosave(
+    db: &SqlitePool,
+    user_did: &str,
+    slot_id: &str,
+    payload: Option<&CreatePostPayload>,
+    queued_at: &str,
+) -> Result<(), AppError> {
+    match payload {
+        Some(payload) => upsert_draft(db, user_did, slot_id, None, payload, queued_at).await,
+        None => clear_draft_payload(db, user_did, slot_id).await,
+    }
+}
+
+/// Hold an autosave for `DRAFT_AUTOSAVE_DEBOUNCE`, replacing any pending one for the
+/// slot. Each slot gets its own short-lived task, so slots never wait on each other.
+fn queue_autosave(db: DbState, key: DraftSlotKey, payload: Option<CreatePostPayload>) {
+    let generation = DRAFT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    let queued_at = Utc::now().to_rfc3339();
+    {
+        let mut drafts = pending_drafts();
+        if let Some(pending) = drafts.get_mut(&key) {
+            // The slot's task is already waiting (or writing) and will pick this up
+            pending.generation = generation;
+            pending.payload = payload;
+            pending.queued_at = queued_at;
+            pending.cleared = false;
+            return;
+        }
+        drafts.insert(
+            key.clone(),
+            PendingDraft {
+                generation,
+                payload,
+                queued_at,
+                in_flight: false,
+                cleared: false,
+            },
+        );
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut seen = generation;
+        loop {
+            tokio::time::sleep(DRAFT_AUTOSAVE_DEBOUNCE).await;
+            let (payload, queued_at) = {
+                let mut drafts = pending_drafts();
+                match drafts.get_mut(&key) {
+                    // Flushed or cleared meanwhile
+                    None => return,
+                    // Still typing: wait for a quiet period after the newest autosave
+                    Some(pending) if pending.generation != seen => {
+                        seen = pending.generation;
+                        continue;
+                    }
+                    Some(pending) => {
+                        pending.in_flight = true;
+                        (pending.payload.clone(), pending.queued_at.clone())
+                    }
+                }
+            };
+
+            let (user_did, slot_id) = &key;
+            let written = match payload.as_ref() {
+                Some(payload) => {
+                    upsert_draft(db.as_ref(), user_did, slot_id, None, payload, &queued_at).await
+                }
+                None => delete_draft_row(db.as_ref(), user_did, slot_id).await,
+            };
+            if let Err(err) = written {
+                tracing::warn!("debounced draft save failed: {err}");
+            }
+
+            match finish_autosave(&key, seen) {
+                AfterAutosave::Done => return,
+                AfterAutosave::Again => continue,
+                AfterAutosave::Delete => {
+                    if let Err(err) = delete_draft_row(db.as_ref(), user_did, slot_id).await {
+                        tracing::warn!("debounced draft clear failed: {err}");
+                    }
+                    return;
+                }
+            }
+        }
+    });
+}
+
+async fn save_draft_payload(
+    db: &SqlitePool,
+    user_did: &str,
+    slot_id: &str,
+    name: Option<&str>,
+    payload: &CreatePostPayload,
+) -> Result<String, AppError> {
+    let now = Utc::now().to_rfc3339();
+    upsert_draft(db, user_did, slot_id, name, payload, &now).await?;
+    Ok(now)
+}
+
+fn draft_from_row(
+    slot_id: String,
+    name: Option<String>,
+    payload_json: &str,
+    updated_at: String,
+) -> Result<PostDraft, AppError> {
+    let payload = serde_json::from_str::<CreatePostPayload>(payload_json)
+        .map_err(|e| AppError::InternalError(format!("draft decode failed: {e}")))?;
+
+    Ok(PostDraft {
+        slot_id,
+        name,
+        text: payload.text,
+        reply_to: payload.reply_to,
+        quote_uri: payload.quote_uri,
+        quote_cid: payload.quote_cid,
+        images: payload.images,
+        updated_at,
+    })
+}
+
 async fn load_draft_payload(
     db: &SqlitePool,
-    reply_to: Option<&str>,
-    quote_uri: Option<&str>,
+    user_did: &str,
+    slot_id: &str,
 ) -> Result<Option<PostDraft>, AppError> {
-    let key = draft_key(reply_to, quote_uri);
-
-    let row = sqlx::query_as::<_, (String, String)>(
+    let row = sqlx::query_as::<_, (Option<String>, String, String)>(
         r#"
-        SELECT payload_json, updated_at
-        FROM post_drafts
-        WHERE draft_key = ?1
+        SELECT name, payload_json, updated_at
+        FROM draft_slots
+        WHERE user_did = ?1 AND slot_id = ?2
         "#,
     )
-    .bind(key)
+    .bind(user_did)
+    .bind(slot_id)
     .fetch_optional(db)
     .await
     .map_err(|e| AppError::InternalError(format!("draft load failed: {e}")))?;
 
-    row.map(|(payload_json, updated_at)| {
-        let payload = serde_json::from_str::<CreatePostPayload>(&payload_json)
-            .map_err(|e| AppError::InternalError(format!("draft decode failed: {e}")))?;
-
-        Ok(PostDraft {
-            text: payload.text,
-            reply_to: payload.reply_to,
-            quote_uri: payload.quote_uri,
-            quote_cid: payload.quote_cid,
-            images: payload.images,
-            updated_at,
-        })
+    row.map(|(name, payload_json, updated_at)| {
+        draft_from_row(slot_id.to_string(), name, &payload_json, updated_at)
     })
     .transpose()
 }
 
 async fn clear_draft_payload(
     db: &SqlitePool,
-    reply_to: Option<&str>,
-    quote_uri: Option<&str>,
+    user_did: &str,
+    slot_id: &str,
 ) -> Result<(), AppError> {
-    let key = draft_key(reply_to, quote_uri);
+    // A pending autosave would bring the draft back after it was sent or discarded
+    cancel_autosave(&(user_did.to_string(), slot_id.to_string()));
+    delete_draft_row(db, user_did, slot_id).await
+}
 
+async fn delete_draft_row(db: &SqlitePool, user_did: &str, slot_id: &str) -> Result<(), AppError> {
     sqlx::query(
         r#"
-        DELETE FROM post_drafts
-        WHERE draft_key = ?1
+        DELETE FROM draft_slots
+        WHERE user_did = ?1 AND slot_id = ?2
         "#,
     )
-    .bind(key)
+    .bind(user_did)
+    .bind(slot_id)
     .execute(db)
     .await
     .map_err(|e| AppError::InternalError(format!("draft clear failed: {e}")))?;
@@ -400,24 +794,179 @@ async fn enqueue_post_retry(
     Ok(id)
 }
 
-/// Create a new post
+/// Reply refs for replying to `reply_uri`, rooted at the parent's thread root
+async fn resolve_reply_ref(
+    agent: &AppAgent,
+    reply_uri: &str,
+) -> Result<Option<bsky_sdk::api::app::bsky::feed::post::ReplyRef>, SendError> {
+    use bsky_sdk::api::app::bsky::feed::post::{Record as PostRecord, ReplyRef, ReplyRefData};
+
+    let post_res = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_posts(
+            bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                uris: vec![reply_uri.to_string()],
+            }
+            .into(),
+        )
+        .await
+        .map_err(SendError::xrpc)?;
+
+    if let Some(parent_post) = post_res.data.posts.first() {
+        let parent_uri = parent_post.uri.clone();
+        let parent_cid = parent_post.cid.clone();
+
+        let root = if let Ok(record) = serde_json::from_value::<PostRecord>(
+            serde_json::to_value(&parent_post.record).unwrap_or(serde_json::Value::Null),
+        ) {
+            if let Some(reply_ref) = &record.reply {
+                reply_ref.data.root.clone()
+            } else {
+                strong_ref::Main {
+                    data: strong_ref::MainData {
+                        uri: parent_uri.clone(),
+                        cid: parent_cid.clone(),
+                    },
+                    extra_data: Ipld::Null,
+                }
+            }
+        } else {
+            strong_ref::Main {
+                data: strong_ref::MainData {
+                    uri: parent_uri.clone(),
+                    cid: parent_cid.clone(),
+                },
+                extra_data: Ipld::Null,
+            }
+        };
+
+        let parent = strong_ref::Main {
+            data: strong_ref::MainData {
+                uri: parent_uri,
+                cid: parent_cid,
+            },
+            extra_data: Ipld::Null,
+        };
+
+        Ok(Some(ReplyRef {
+            data: ReplyRefData { root, parent },
+            extra_data: Ipld::Null,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Create a new post, replying to `payload.reply_to` when set. Reply refs resolved here
+/// are saved into `payload.reply_refs`, so a payload queued after a failed send keeps them.
+/// Returns a strong ref to the created post.
 async fn send_post_via_agent(
+    agent: &AppAgent,
+    did: &Did,
+    payload: &mut CreatePostPayload,
+) -> Result<strong_ref::Main, SendError> {
+    let reply = payload_reply_ref(payload, |reply_uri| async move {
+        resolve_reply_ref(agent, &reply_uri).await
+    })
+    .await?;
+    send_post_record(agent, did, payload, reply).await
+}
+
+/// Reply refs to send `payload` with: the stored ones when present, otherwise `resolve`d
+/// from `reply_to` and stored, so the parent never has to resolve again
+async fn payload_reply_ref<F, Fut>(
+    payload: &mut CreatePostPayload,
+    resolve: F,
+) -> Result<Option<bsky_sdk::api::app::bsky::feed::post::ReplyRef>, SendError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<
+        Output = Result<Option<bsky_sdk::api::app::bsky::feed::post::ReplyRef>, SendError>,
+    >,
+{
+    match (&payload.reply_refs, payload.reply_to.clone()) {
+        (Some(refs), _) => Ok(Some(refs.to_reply_ref()?)),
+        (None, Some(reply_uri)) => {
+            let reply = resolve(reply_uri).await?;
+            payload.reply_refs = reply.as_ref().map(ReplyRefs::from_reply_ref);
+            Ok(reply)
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+/// Refresh a queued quote's cid before retrying: the one captured while offline may be
+/// missing or stale. A quoted post that's gone fails the retry instead of posting a
+/// broken embed.
+async fn revalidate_quote(
+    agent: &AppAgent,
+    payload: &mut CreatePostPayload,
+) -> Result<(), SendError> {
+    let Some(quote_uri) = payload.quote_uri.clone() else {
+        return Ok(());
+    };
+
+    let response = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_posts(
+            bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                uris: vec![quote_uri.clone()],
+            }
+            .into(),
+        )
+        .await
+        .map_err(SendError::xrpc)?;
+
+    let Some(quoted) = response.data.posts.first() else {
+        return Err(AppError::ValidationError {
+            code: "QUOTED_POST_UNAVAILABLE",
+            message: "The quoted post was deleted or is no longer visible".into(),
+        }
+        .into());
+    };
+    let cid = quoted.cid.as_ref().to_string();
+    if payload.quote_cid.as_deref() != Some(cid.as_str()) {
+        tracing::info!(target: "retry_queue", uri = %quote_uri, "quoted post cid updated");
+        payload.quote_cid = Some(cid);
+    }
+    Ok(())
+}
+
+/// Create a post record with explicit reply refs
+async fn send_post_record(
     agent: &AppAgent,
     did: &Did,
     payload: &CreatePostPayload,
-) -> Result<(), AppError> {
+    reply: Option<bsky_sdk::api::app::bsky::feed::post::ReplyRef>,
+) -> Result<strong_ref::Main, SendError> {
+    use bsky_sdk::api::app::bsky::embed::external::{
+        ExternalData, Main as ExternalMain, MainData as ExternalMainData,
+    };
     use bsky_sdk::api::app::bsky::embed::images::{
         Image, ImageData, Main as ImagesMain, MainData as ImagesMainData,
     };
     use bsky_sdk::api::app::bsky::embed::record::{Main as RecordMain, MainData as RecordMainData};
     use bsky_sdk::api::app::bsky::embed::record_with_media::{
-        Main as RecordWithMediaMain, MainData as RecordWithMediaMainData,
+        Main as RecordWithMediaMain, MainData as RecordWithMediaMainData, MainMediaRefs,
     };
-    use bsky_sdk::api::app::bsky::feed::post::{
-        Record as PostRecord, RecordEmbedRefs, ReplyRef, ReplyRefData,
+    use bsky_sdk::api::app::bsky::feed::post::{RecordEmbedRefs, RecordLabelsRefs};
+    use bsky_sdk::api::com::atproto::label::defs::{
+        SelfLabel, SelfLabelData, SelfLabels, SelfLabelsData,
     };
     use bsky_sdk::api::types::Union;
 
+    if payload.external.is_some() && !payload.images.is_empty() {
+        return Err(
+            AppError::ApiError("A post can't have both images and a link card".into()).into(),
+        );
+    }
+
     let mut image_blobs = Vec::new();
     for img in &payload.images {
         let path = std::path::PathBuf::from(&img.path);
@@ -435,7 +984,7 @@ async fn send_post_via_agent(
             .repo
             .upload_blob(bytes.to_vec())
             .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to upload blob: {e}")))?;
+            .map_err(SendError::xrpc)?;
 
         image_blobs.push(Image {
             data: ImageData {
@@ -467,101 +1016,80 @@ async fn send_post_via_agent(
         None
     };
 
-    let images_embed = if !image_blobs.is_empty() {
-        Some(ImagesMain {
-            data: ImagesMainData {
-                images: image_blobs,
+    let media = if !image_blobs.is_empty() {
+        Some(MainMediaRefs::AppBskyEmbedImagesMain(Box::new(
+            ImagesMain {
+                data: ImagesMainData {
+                    images: image_blobs,
+                },
+                extra_data: Ipld::Null,
             },
-            extra_data: Ipld::Null,
-        })
+        )))
+    } else if let Some(external) = &payload.external {
+        let thumb = external
+            .thumb
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| AppError::ApiError(format!("Invalid link card thumb: {e}")))?;
+        Some(MainMediaRefs::AppBskyEmbedExternalMain(Box::new(
+            ExternalMain::from(ExternalMainData {
+                external: ExternalData {
+                    description: external.description.clone(),
+                    thumb,
+                    title: external.title.clone(),
+                    uri: external.uri.clone(),
+                }
+                .into(),
+            }),
+        )))
     } else {
         None
     };
 
-    let embed = match (images_embed, quote_embed) {
-        (Some(imgs), Some(quote)) => Some(Union::Refs(
+    let embed = match (media, quote_embed) {
+        (Some(media), Some(quote)) => Some(Union::Refs(
             RecordEmbedRefs::AppBskyEmbedRecordWithMediaMain(Box::new(RecordWithMediaMain {
                 data: RecordWithMediaMainData {
-                    media: Union::Refs(
-                        bsky_sdk::api::app::bsky::embed::record_with_media::MainMediaRefs::AppBskyEmbedImagesMain(
-                            Box::new(imgs),
-                        ),
-                    ),
+                    media: Union::Refs(media),
                     record: quote,
                 },
                 extra_data: Ipld::Null,
             })),
         )),
-        (Some(imgs), None) => Some(Union::Refs(RecordEmbedRefs::AppBskyEmbedImagesMain(
-            Box::new(imgs),
-        ))),
+        (Some(MainMediaRefs::AppBskyEmbedImagesMain(imgs)), None) => {
+            Some(Union::Refs(RecordEmbedRefs::AppBskyEmbedImagesMain(imgs)))
+        }
+        (Some(MainMediaRefs::AppBskyEmbedExternalMain(external)), None) => Some(Union::Refs(
+            RecordEmbedRefs::AppBskyEmbedExternalMain(external),
+        )),
+        (Some(_), None) => None,
         (None, Some(quote)) => Some(Union::Refs(RecordEmbedRefs::AppBskyEmbedRecordMain(
             Box::new(quote),
         ))),
         (None, None) => None,
     };
 
-    let reply = if let Some(reply_uri) = payload.reply_to.clone() {
-        let post_res = agent
-            .api
-            .app
-            .bsky
-            .feed
-            .get_posts(
-                bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
-                    uris: vec![reply_uri.clone()],
-                }
-                .into(),
-            )
-            .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to fetch reply parent: {e}")))?;
-
-        if let Some(parent_post) = post_res.data.posts.first() {
-            let parent_uri = parent_post.uri.clone();
-            let parent_cid = parent_post.cid.clone();
-
-            let root = if let Ok(record) = serde_json::from_value::<PostRecord>(
-                serde_json::to_value(&parent_post.record).unwrap_or(serde_json::Value::Null),
-            ) {
-                if let Some(reply_ref) = &record.reply {
-                    reply_ref.data.root.clone()
-                } else {
-                    strong_ref::Main {
-                        data: strong_ref::MainData {
-                            uri: parent_uri.clone(),
-                            cid: parent_cid.clone(),
-                        },
-                        extra_data: Ipld::Null,
-                    }
-                }
-            } else {
-                strong_ref::Main {
-                    data: strong_ref::MainData {
-                        uri: parent_uri.clone(),
-                        cid: parent_cid.clone(),
-                    },
-                    extra_data: Ipld::Null,
-                }
-            };
-
-            let parent = strong_ref::Main {
-                data: strong_ref::MainData {
-                    uri: parent_uri,
-                    cid: parent_cid,
-                },
-                extra_data: Ipld::Null,
-            };
-
-            Some(ReplyRef {
-                data: ReplyRefData { root, parent },
-                extra_data: Ipld::Null,
-            })
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    let langs = payload
+        .langs
+        .iter()
+        .map(|lang| {
+            lang.parse()
+                .map_err(|_| AppError::ApiError(format!("Invalid language tag: {lang}")))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    let labels = (!payload.labels.is_empty()).then(|| {
+        Union::Refs(RecordLabelsRefs::ComAtprotoLabelDefsSelfLabels(Box::new(
+            SelfLabels::from(SelfLabelsData {
+                values: payload
+                    .labels
+                    .iter()
+                    .map(|val| SelfLabel::from(SelfLabelData { val: val.clone() }))
+                    .collect(),
+            }),
+        )))
+    });
 
     let record_data = bsky_sdk::api::app::bsky::feed::post::RecordData {
         created_at: bsky_sdk::api::types::string::Datetime::now(),
@@ -569,8 +1097,8 @@ async fn send_post_via_agent(
         embed,
         entities: None,
         facets: None,
-        labels: None,
-        langs: None,
+        labels,
+        langs: (!langs.is_empty()).then_some(langs),
         reply,
         tags: None,
     };
@@ -579,7 +1107,7 @@ async fn send_post_via_agent(
         .try_into_unknown()
         .map_err(|e| AppError::ApiError(e.to_string()))?;
 
-    agent
+    let created = agent
         .api
         .com
         .atproto
@@ -598,9 +1126,44 @@ async fn send_post_via_agent(
             .into(),
         )
         .await
-        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+        .map_err(SendError::xrpc)?;
 
-    Ok(())
+    // The post already exists at this point, so a failed postgate write must not
+    // surface as a send error (the retry queue would publish the post twice).
+    if payload.allow_quotes == Some(false) {
+        if let Err(err) = postgate::update_postgate(agent, did, &created.data.uri, |data| {
+            postgate::set_quotes_disabled(data, true)
+        })
+        .await
+        {
+            tracing::warn!("failed to disable quotes on new post: {err}");
+        }
+    }
+
+    Ok(strong_ref::Main {
+        data: strong_ref::MainData {
+            uri: created.data.uri,
+            cid: created.data.cid,
+        },
+        extra_data: Ipld::Null,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct CreatePostRequest {
+    #[serde(flatten)]
+    pub post: CreatePostPayload,
+    /// BCP-47 language tags; omit for the account default. Read here because the
+    /// payload's `langs` can't tell an omitted list from an empty one.
+    pub langs: Option<Vec<String>>,
+    /// GIF from `search_gifs`, posted as a GIF link card in place of `post.external`
+    pub gif: Option<GifResult>,
+    /// Alt text for the GIF; defaults to the provider's description
+    pub gif_alt: Option<String>,
+    /// Draft slot to clear once sent; defaults to the context's autosave slot
+    pub draft_slot: Option<String>,
+    /// Show the post in the cached timeline or thread right away (default true)
+    pub show_in_timeline: Option<bool>,
 }
 
 #[tauri::command]
@@ -608,61 +1171,84 @@ pub async fn create_post(
     app: AppHandle,
     agent_state: State<'_, AgentState>,
     db: State<'_, DbState>,
-    text: String,
-    reply_to: Option<String>,
-    quote_uri: Option<String>,
-    quote_cid: Option<String>,
-    images: Vec<ImageInput>,
+    request: CreatePostRequest,
 ) -> Result<(), AppError> {
+    let CreatePostRequest {
+        post: mut payload,
+        langs,
+        gif,
+        gif_alt,
+        draft_slot,
+        show_in_timeline,
+    } = request;
     let did = current_repo_did()?;
+    if settings::require_alt_text(db.inner().as_ref()).await {
+        check_alt_text(&payload.images)?;
+    }
+    // A picked GIF is posted as a GIF link card in place of any other link card
+    if let Some(gif) = gif {
+        let agent = current_agent(&agent_state).await?;
+        payload.external = Some(gif_external(&agent, &gif, gif_alt.as_deref()).await);
+    }
     let db_pool = db.inner().clone();
-    let payload = CreatePostPayload {
-        text,
-        reply_to,
-        quote_uri,
-        quote_cid,
-        images,
+    let did_str = did.to_string();
+    let slot_id = draft_slot
+        .unwrap_or_else(|| draft_key(payload.reply_to.as_deref(), payload.quote_uri.as_deref()));
+    // Refs only make sense alongside the parent they point at
+    if payload.reply_to.is_none() {
+        payload.reply_refs = None;
+    }
+    // Explicit languages (even none) win over the account default
+    payload.langs = match langs {
+        Some(langs) => langs,
+        None => settings::default_post_langs(db_pool.as_ref()).await,
     };
 
-    let send_result = {
-        let guard = agent_state.lock().await;
-        let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
-        send_post_via_agent(agent, &did, &payload).await
-    };
+    let agent = current_agent(&agent_state).await?;
+    let send_result = send_post_via_agent(&agent, &did, &mut payload).await;
 
     match send_result {
-        Ok(()) => {
-            if let Err(err) = clear_draft_payload(
-                db_pool.as_ref(),
-                payload.reply_to.as_deref(),
-                payload.quote_uri.as_deref(),
-            )
-            .await
-            {
-                eprintln!("[drafts] failed to clear after send: {err}");
+        Ok(posted) => {
+            if let Err(err) = clear_draft_payload(db_pool.as_ref(), &did_str, &slot_id).await {
+                tracing::warn!("failed to clear draft after send: {err}");
+            }
+            if show_in_timeline.unwrap_or(true) {
+                let parent_uri = payload
+                    .reply_refs
+                    .as_ref()
+                    .map(|refs| refs.parent.uri.clone())
+                    .or_else(|| payload.reply_to.clone());
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) = timeline::insert_own_post(
+                        &app,
+                        &agent,
+                        db_pool.as_ref(),
+                        posted.data.uri,
+                        parent_uri,
+                    )
+                    .await
+                    {
+                        tracing::warn!(target: "cache", cache = "timeline", "own post insert failed: {err}");
+                    }
+                });
             }
             Ok(())
         }
-        Err(err) => {
-            if !should_enqueue_retry(&err) {
-                return Err(err);
+        Err(SendError { error, transient }) => {
+            connectivity::watch_error(&app, &error);
+            if !transient {
+                return Err(error);
             }
 
-            let retry_id =
-                enqueue_post_retry(db_pool.as_ref(), &did.to_string(), &payload, &err).await?;
+            let retry_id = enqueue_post_retry(db_pool.as_ref(), &did_str, &payload, &error).await?;
 
             if let Err(emit_err) = app.emit("post_retry_queued", RetryQueueEvent { id: retry_id }) {
-                eprintln!("[retry-queue] emit queue event failed: {emit_err}");
+                tracing::warn!(target: "retry_queue", "emit queue event failed: {emit_err}");
             }
 
-            if let Err(clear_err) = clear_draft_payload(
-                db_pool.as_ref(),
-                payload.reply_to.as_deref(),
-                payload.quote_uri.as_deref(),
-            )
-            .await
+            if let Err(clear_err) = clear_draft_payload(db_pool.as_ref(), &did_str, &slot_id).await
             {
-                eprintln!("[drafts] failed to clear queued draft: {clear_err}");
+                tracing::warn!("failed to clear queued draft: {clear_err}");
             }
 
             Ok(())
@@ -670,36 +1256,205 @@ pub async fn create_post(
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostedRef {
+    pub uri: String,
+    pub cid: String,
+}
+
+impl PostedRef {
+    fn from_strong_ref(r: &strong_ref::Main) -> Self {
+        Self {
+            uri: r.data.uri.clone(),
+            cid: r.data.cid.as_ref().to_string(),
+        }
+    }
+
+    fn to_strong_ref(&self) -> Result<strong_ref::Main, AppError> {
+        Ok(strong_ref::Main {
+            data: strong_ref::MainData {
+                uri: self.uri.clone(),
+                cid: self
+                    .cid
+                    .parse()
+                    .map_err(|_| AppError::ApiError(format!("Invalid CID for {}", self.uri)))?,
+            },
+            extra_data: Ipld::Null,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreateThreadResponse {
+    /// Segments that were published, in order
+    pub posted: Vec<PostedRef>,
+    /// Index of the segment that failed; later segments were not attempted
+    pub failed_index: Option<usize>,
+    pub error: Option<String>,
+    /// Unsent segments, ready to pass back to `create_thread`. The first one
+    /// replies to the last published segment so the thread continues.
+    pub remaining: Vec<CreatePostPayload>,
+}
+
+/// Publish a self-thread: the first segment is posted as given, each later one as a
+/// reply to the previous segment under the same thread root. Alt text is checked on
+/// every segment before the first is sent, so the thread never stops half-published
+/// over a missing description. Segments without languages get the account default.
+#[tauri::command]
+pub async fn create_thread(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    mut posts: Vec<CreatePostPayload>,
+) -> Result<CreateThreadResponse, AppError> {
+    use bsky_sdk::api::app::bsky::feed::post::{ReplyRef, ReplyRefData};
+
+    if posts.is_empty() {
+        return Err(AppError::ApiError("Thread has no posts".into()));
+    }
+    if settings::require_alt_text(db.inner().as_ref()).await {
+        check_thread_alt_text(&posts)?;
+    }
+    apply_default_langs(
+        &mut posts,
+        &settings::default_post_langs(db.inner().as_ref()).await,
+    );
+
+    let did = current_repo_did()?;
+    let agent = current_agent(&agent_state).await?;
+
+    let mut posted: Vec<strong_ref::Main> = Vec::with_capacity(posts.len());
+    let mut root: Option<strong_ref::Main> = None;
+    let mut failure: Option<(usize, AppError)> = None;
+
+    for (index, segment) in posts.iter().enumerate() {
+        let result = match posted.last() {
+            None => {
+                let mut first = segment.clone();
+                let result = send_post_via_agent(&agent, &did, &mut first).await;
+                root = first
+                    .reply_refs
+                    .as_ref()
+                    .and_then(|refs| refs.root.to_strong_ref().ok());
+                result
+            }
+            Some(previous) => {
+                let reply = ReplyRef {
+                    data: ReplyRefData {
+                        root: root.clone().unwrap_or_else(|| posted[0].clone()),
+                        parent: previous.clone(),
+                    },
+                    extra_data: Ipld::Null,
+                };
+                send_post_record(&agent, &did, segment, Some(reply)).await
+            }
+        };
+
+        match result {
+            Ok(created) => posted.push(created),
+            Err(err) => {
+                tracing::warn!("thread segment {index} failed: {err}");
+                connectivity::watch_error(&app, &err.error);
+                failure = Some((index, err.error));
+                break;
+            }
+        }
+    }
+
+    let (failed_index, error, remaining) = match failure {
+        Some((index, err)) => {
+            let mut remaining = posts[index..].to_vec();
+            if let Some(last) = posted.last() {
+                remaining[0].reply_to = Some(last.data.uri.clone());
+                remaining[0].reply_refs = Some(ReplyRefs {
+                    root: PostedRef::from_strong_ref(root.as_ref().unwrap_or(&posted[0])),
+                    parent: PostedRef::from_strong_ref(last),
+                });
+            }
+            (Some(index), Some(err.to_string()), remaining)
+        }
+        None => (None, None, Vec::new()),
+    };
+
+    Ok(CreateThreadResponse {
+        posted: posted
+            .into_iter()
+            .map(|r| PostedRef {
+                uri: r.data.uri,
+                cid: r.data.cid.as_ref().to_string(),
+            })
+            .collect(),
+        failed_index,
+        error,
+        remaining,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct SaveDraftRequest {
+    pub text: String,
+    pub reply_to: Option<String>,
+    pub quote_uri: Option<String>,
+    pub quote_cid: Option<String>,
+    #[serde(default)]
+    pub images: Vec<ImageInput>,
+    /// Autosave into this slot instead of the context's default slot
+    pub slot_id: Option<String>,
+    /// Write immediately instead of waiting for typing to pause
+    #[serde(default)]
+    pub flush: bool,
+}
+
+/// Autosave the composer. Saves into `slot_id` when given, otherwise into the
+/// context's autosave slot (new post, reply, quote). Rapid calls are coalesced so only
+/// the latest payload is written, `DRAFT_AUTOSAVE_DEBOUNCE` after the last one; pass
+/// `flush` (e.g. when the composer closes) to write immediately.
 #[tauri::command]
 pub async fn save_post_draft(
     db: State<'_, DbState>,
-    text: String,
-    reply_to: Option<String>,
-    quote_uri: Option<String>,
-    quote_cid: Option<String>,
-    images: Vec<ImageInput>,
+    request: SaveDraftRequest,
 ) -> Result<(), AppError> {
+    let SaveDraftRequest {
+        text,
+        reply_to,
+        quote_uri,
+        quote_cid,
+        images,
+        slot_id,
+        flush,
+    } = request;
     let db_pool = db.inner().clone();
+    let user_did = draft_owner()?;
+    let slot_id = slot_id.unwrap_or_else(|| draft_key(reply_to.as_deref(), quote_uri.as_deref()));
 
     let is_empty = text.trim().is_empty() && images.is_empty();
-    if is_empty {
-        return clear_draft_payload(db_pool.as_ref(), reply_to.as_deref(), quote_uri.as_deref())
-            .await;
-    }
-
-    let payload = CreatePostPayload {
+    let payload = (!is_empty).then(|| CreatePostPayload {
         text,
         reply_to,
         quote_uri,
         quote_cid,
         images,
-    };
+        allow_quotes: None,
+        langs: Vec::new(),
+        labels: Vec::new(),
+        external: None,
+        reply_refs: None,
+    });
 
-    save_draft_payload(
+    if !flush {
+        queue_autosave(db_pool, (user_did, slot_id), payload);
+        return Ok(());
+    }
+    if payload.is_some() {
+        // A write already in flight is older and can't replace this one
+        pending_drafts().remove(&(user_did.clone(), slot_id.clone()));
+    }
+    write_autosave(
         db_pool.as_ref(),
-        payload.reply_to.as_deref(),
-        payload.quote_uri.as_deref(),
-        &payload,
+        &user_did,
+        &slot_id,
+        payload.as_ref(),
+        &Utc::now().to_rfc3339(),
     )
     .await
 }
@@ -711,7 +1466,9 @@ pub async fn get_post_draft(
     quote_uri: Option<String>,
 ) -> Result<Option<PostDraft>, AppError> {
     let db_pool = db.inner().clone();
-    load_draft_payload(db_pool.as_ref(), reply_to.as_deref(), quote_uri.as_deref()).await
+    let user_did = draft_owner()?;
+    let slot_id = draft_key(reply_to.as_deref(), quote_uri.as_deref());
+    load_draft_payload(db_pool.as_ref(), &user_did, &slot_id).await
 }
 
 #[tauri::command]
@@ -721,9 +1478,115 @@ pub async fn clear_post_draft(
     quote_uri: Option<String>,
 ) -> Result<(), AppError> {
     let db_pool = db.inner().clone();
-    clear_draft_payload(db_pool.as_ref(), reply_to.as_deref(), quote_uri.as_deref()).await
+    let user_did = draft_owner()?;
+    let slot_id = draft_key(reply_to.as_deref(), quote_uri.as_deref());
+    clear_draft_payload(db_pool.as_ref(), &user_did, &slot_id).await
+}
+
+#[derive(Deserialize)]
+pub struct DraftInput {
+    pub text: String,
+    pub reply_to: Option<String>,
+    pub quote_uri: Option<String>,
+    pub quote_cid: Option<String>,
+    #[serde(default)]
+    pub images: Vec<ImageInput>,
+}
+
+/// Save a draft into a named slot; a new slot is created when `slot_id` is omitted.
+/// Returns the saved draft so the caller learns the slot id.
+#[tauri::command]
+pub async fn save_named_draft(
+    db: State<'_, DbState>,
+    slot_id: Option<String>,
+    name: Option<String>,
+    payload: DraftInput,
+) -> Result<PostDraft, AppError> {
+    let user_did = draft_owner()?;
+    let slot_id = slot_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("slot:{}", Uuid::new_v4()));
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    let payload = CreatePostPayload {
+        text: payload.text,
+        reply_to: payload.reply_to,
+        quote_uri: payload.quote_uri,
+        quote_cid: payload.quote_cid,
+        images: payload.images,
+        allow_quotes: None,
+        langs: Vec::new(),
+        labels: Vec::new(),
+        external: None,
+        reply_refs: None,
+    };
+
+    let updated_at = save_draft_payload(
+        db.inner().as_ref(),
+        &user_did,
+        &slot_id,
+        name.as_deref(),
+        &payload,
+    )
+    .await?;
+
+    Ok(PostDraft {
+        slot_id,
+        name,
+        text: payload.text,
+        reply_to: payload.reply_to,
+        quote_uri: payload.quote_uri,
+        quote_cid: payload.quote_cid,
+        images: payload.images,
+        updated_at,
+    })
+}
+
+/// The account's saved drafts, named slots and context autosaves alike, most recent first
+#[tauri::command]
+pub async fn list_drafts(db: State<'_, DbState>) -> Result<Vec<PostDraft>, AppError> {
+    let user_did = draft_owner()?;
+    let rows = sqlx::query_as::<_, (String, Option<String>, String, String)>(
+        r#"
+        SELECT slot_id, name, payload_json, updated_at
+        FROM draft_slots
+        WHERE user_did = ?1
+        ORDER BY updated_at DESC
+        "#,
+    )
+    .bind(&user_did)
+    .fetch_all(db.inner().as_ref())
+    .await
+    .map_err(|e| AppError::InternalError(format!("draft list failed: {e}")))?;
+
+    let mut drafts = Vec::with_capacity(rows.len());
+    for (slot_id, name, payload_json, updated_at) in rows {
+        match draft_from_row(slot_id, name, &payload_json, updated_at) {
+            Ok(draft) => drafts.push(draft),
+            Err(err) => tracing::warn!("skipping unreadable draft: {err}"),
+        }
+    }
+
+    Ok(drafts)
+}
+
+#[tauri::command]
+pub async fn load_draft(
+    db: State<'_, DbState>,
+    slot_id: String,
+) -> Result<Option<PostDraft>, AppError> {
+    let user_did = draft_owner()?;
+    load_draft_payload(db.inner().as_ref(), &user_did, &slot_id).await
+}
+
+#[tauri::command]
+pub async fn delete_draft(db: State<'_, DbState>, slot_id: String) -> Result<(), AppError> {
+    let user_did = draft_owner()?;
+    clear_draft_payload(db.inner().as_ref(), &user_did, &slot_id).await
 }
 
+#[tracing::instrument(target = "retry_queue", level = "debug", skip_all)]
 pub async fn retry_queued_posts(
     app: AppHandle,
     agent_state: AgentState,
@@ -758,14 +1621,14 @@ pub async fn retry_queued_posts(
         return Ok(());
     }
 
-    let guard = agent_state.lock().await;
-    let agent = match guard.as_ref() {
-        Some(value) => value,
-        None => return Ok(()),
+    let max_attempts = settings::retry_max_attempts(db.as_ref()).await;
+    let agent = match current_agent(&agent_state).await {
+        Ok(value) => value,
+        Err(_) => return Ok(()),
     };
 
     for (id, payload_json, attempts) in queued_rows {
-        let payload = match serde_json::from_str::<CreatePostPayload>(&payload_json) {
+        let mut payload = match serde_json::from_str::<CreatePostPayload>(&payload_json) {
             Ok(value) => value,
             Err(err) => {
                 let updated_at = Utc::now().to_rfc3339();
@@ -804,8 +1667,13 @@ pub async fn retry_queued_posts(
         .await
         .map_err(|e| AppError::InternalError(format!("retry queue update failed: {e}")))?;
 
-        match send_post_via_agent(agent, &did, &payload).await {
-            Ok(()) => {
+        let result = match revalidate_quote(&agent, &mut payload).await {
+            Ok(()) => send_post_via_agent(&agent, &did, &mut payload).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(_) => {
                 let sent_at = Utc::now().to_rfc3339();
                 sqlx::query(
                     r#"
@@ -826,7 +1694,7 @@ pub async fn retry_queued_posts(
             }
             Err(err) => {
                 let next_attempts = attempts + 1;
-                let status = if next_attempts >= 8 {
+                let status = if next_attempts >= max_attempts || !err.transient {
                     "failed"
                 } else {
                     "queued"
@@ -838,6 +1706,8 @@ pub async fn retry_queued_posts(
                     Utc::now().to_rfc3339()
                 };
 
+                // Keep refs and quote cid resolved during this attempt for the next one
+                let payload_json = serde_json::to_string(&payload).unwrap_or(payload_json);
                 sqlx::query(
                     r#"
                     UPDATE post_retry_queue
@@ -845,7 +1715,8 @@ pub async fn retry_queued_posts(
                         attempts = ?3,
                         next_retry_at = ?4,
                         last_error = ?5,
-                        updated_at = ?6
+                        updated_at = ?6,
+                        payload_json = ?7
                     WHERE id = ?1
                     "#,
                 )
@@ -855,6 +1726,7 @@ pub async fn retry_queued_posts(
                 .bind(next_retry)
                 .bind(err.to_string())
                 .bind(Utc::now().to_rfc3339())
+                .bind(payload_json)
                 .execute(db.as_ref())
                 .await
                 .map_err(|e| AppError::InternalError(format!("retry queue update failed: {e}")))?;
@@ -868,35 +1740,112 @@ pub async fn retry_queued_posts(
 pub fn trigger_retry_now(app: AppHandle, agent_state: AgentState, db: DbState) {
     tauri::async_runtime::spawn(async move {
         if let Err(err) = retry_queued_posts(app, agent_state, db).await {
-            eprintln!("[retry-queue] immediate retry failed: {err}");
+            tracing::warn!(target: "retry_queue", "immediate retry failed: {err}");
         }
     });
 }
 
-/// Follow a user (creates app.bsky.graph.follow record)
-#[tauri::command]
-pub async fn follow_user(
-    agent_state: State<'_, AgentState>,
-    did: String,
-) -> Result<String, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+/// Patch cached profiles for a relationship change in the background. With
+/// `refresh_timeline`, the first home timeline page is refetched too, so posts from a
+/// new follow appear and muted or blocked accounts drop out without a manual refresh.
+fn after_relationship_change(
+    app: AppHandle,
+    agent_state: AgentState,
+    db: DbState,
+    change: timeline::ProfileChange,
+    refresh_timeline: bool,
+) {
+    tauri::async_runtime::spawn(async move {
+        timeline::patch_cached_profiles(&app, db.as_ref(), change).await;
+        if refresh_timeline {
+            timeline::prefetch_timeline(&app, &agent_state, db.as_ref()).await;
+        }
+    });
+}
 
-    let current_did = current_repo_did()?;
+/// The signed-in account's follow and block records for an actor
+#[derive(Default)]
+struct ViewerRelationship {
+    follow_uri: Option<String>,
+    block_uri: Option<String>,
+}
 
-    use bsky_sdk::api::app::bsky::graph::follow::RecordData as FollowRecordData;
+/// Read the existing follow/block records from the actor's profile, so following or
+/// blocking twice reuses them instead of writing duplicates. If the profile can't be
+/// read (other than the network being down) neither is known and the caller goes on
+/// to create the record.
+async fn viewer_relationship(
+    agent: &AppAgent,
+    subject: &Did,
+) -> Result<ViewerRelationship, AppError> {
+    let profile = agent
+        .api
+        .app
+        .bsky
+        .actor
+        .get_profile(
+            bsky_sdk::api::app::bsky::actor::get_profile::ParametersData {
+                actor: AtIdentifier::Did(subject.clone()),
+            }
+            .into(),
+        )
+        .await;
+    relationship_from_profile(subject, profile)
+}
 
-    let record_data = FollowRecordData {
-        created_at: bsky_sdk::api::types::string::Datetime::now(),
-        subject: did
-            .parse()
-            .map_err(|_| AppError::ApiError("Invalid DID".into()))?,
-    };
+fn relationship_from_profile<E>(
+    subject: &Did,
+    profile: Result<bsky_sdk::api::app::bsky::actor::get_profile::Output, atrium_xrpc::Error<E>>,
+) -> Result<ViewerRelationship, AppError>
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    match profile {
+        Ok(profile) => {
+            let viewer = profile.data.viewer.as_ref();
+            Ok(ViewerRelationship {
+                follow_uri: viewer
+                    .and_then(|v| v.following.as_ref())
+                    .map(|u| u.to_string()),
+                block_uri: viewer
+                    .and_then(|v| v.blocking.as_ref())
+                    .map(|u| u.to_string()),
+            })
+        }
+        Err(err) if crate::connectivity::is_transport_error(&err) => {
+            Err(crate::connectivity::xrpc_error(err))
+        }
+        Err(err) => {
+            tracing::debug!("relationship lookup failed for {}: {err}", subject.as_str());
+            Ok(ViewerRelationship::default())
+        }
+    }
+}
 
-    let record = record_data
-        .try_into_unknown()
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+/// URI of the record to report and whether it was just created. `create` only runs
+/// when there is no `existing` record, so repeating a follow or block never writes a
+/// second one.
+async fn reuse_or_create<F, Fut>(
+    existing: Option<String>,
+    create: F,
+) -> Result<(String, bool), AppError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<String, AppError>>,
+{
+    match existing {
+        Some(uri) => Ok((uri, false)),
+        None => Ok((create().await?, true)),
+    }
+}
 
+/// Create a graph record (follow, block) in the signed-in account's repo
+async fn create_graph_record(
+    agent: &AppAgent,
+    repo: Did,
+    collection: &str,
+    record: bsky_sdk::api::types::Unknown,
+) -> Result<String, AppError> {
     let response = agent
         .api
         .com
@@ -904,10 +1853,10 @@ pub async fn follow_user(
         .repo
         .create_record(
             create_record::InputData {
-                repo: AtIdentifier::Did(current_did),
-                collection: "app.bsky.graph.follow"
+                repo: AtIdentifier::Did(repo),
+                collection: collection
                     .parse()
-                    .map_err(|_| AppError::ApiError("Invalid follow NSID".into()))?,
+                    .map_err(|_| AppError::ApiError(format!("Invalid NSID {collection}")))?,
                 record,
                 rkey: None,
                 swap_commit: None,
@@ -916,19 +1865,68 @@ pub async fn follow_user(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
+        .map_err(connectivity::xrpc_error)?;
     Ok(response.data.uri.to_string())
 }
 
+/// Follow a user (creates app.bsky.graph.follow record). Already following returns the
+/// existing follow URI without creating another record.
+#[tauri::command]
+pub async fn follow_user(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    did: String,
+) -> Result<String, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let current_did = current_repo_did()?;
+    let subject: Did = did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid DID".into()))?;
+
+    use bsky_sdk::api::app::bsky::graph::follow::RecordData as FollowRecordData;
+
+    let existing = viewer_relationship(&agent, &subject)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?
+        .follow_uri;
+    let (follow_uri, created) = reuse_or_create(existing, move || async move {
+        let record = FollowRecordData {
+            created_at: bsky_sdk::api::types::string::Datetime::now(),
+            subject,
+        }
+        .try_into_unknown()
+        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        create_graph_record(&agent, current_did, "app.bsky.graph.follow", record).await
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    // Reusing a record means the caller's view was stale: cached profiles are brought up
+    // to date, but the timeline only changes for a new follow
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Followed {
+            did,
+            follow_uri: follow_uri.clone(),
+        },
+        created,
+    );
+    Ok(follow_uri)
+}
+
 /// Unfollow a user (deletes the follow record)
 #[tauri::command]
 pub async fn unfollow_user(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     follow_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -954,16 +1952,28 @@ pub async fn unfollow_user(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Unfollowed { follow_uri },
+        true,
+    );
     Ok(())
 }
 
 /// Mute a user
 #[tauri::command]
-pub async fn mute_actor(agent_state: State<'_, AgentState>, did: String) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+pub async fn mute_actor(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    did: String,
+) -> Result<(), AppError> {
+    let agent = current_agent(&agent_state).await?;
 
     agent
         .api
@@ -979,16 +1989,28 @@ pub async fn mute_actor(agent_state: State<'_, AgentState>, did: String) -> Resu
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Muted { did, muted: true },
+        true,
+    );
     Ok(())
 }
 
 /// Unmute a user
 #[tauri::command]
-pub async fn unmute_actor(agent_state: State<'_, AgentState>, did: String) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+pub async fn unmute_actor(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    did: String,
+) -> Result<(), AppError> {
+    let agent = current_agent(&agent_state).await?;
 
     agent
         .api
@@ -1004,67 +2026,77 @@ pub async fn unmute_actor(agent_state: State<'_, AgentState>, did: String) -> Re
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Muted { did, muted: false },
+        false,
+    );
     Ok(())
 }
 
-/// Block a user (creates app.bsky.graph.block record)
+/// Block a user (creates app.bsky.graph.block record). Already blocking returns the
+/// existing block URI without creating another record.
 #[tauri::command]
 pub async fn block_actor(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     did: String,
 ) -> Result<String, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let current_did = current_repo_did()?;
+    let subject: Did = did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid DID".into()))?;
 
     use bsky_sdk::api::app::bsky::graph::block::RecordData as BlockRecordData;
 
-    let record_data = BlockRecordData {
-        created_at: bsky_sdk::api::types::string::Datetime::now(),
-        subject: did
-            .parse()
-            .map_err(|_| AppError::ApiError("Invalid DID".into()))?,
-    };
-
-    let record = record_data
-        .try_into_unknown()
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
-    let response = agent
-        .api
-        .com
-        .atproto
-        .repo
-        .create_record(
-            create_record::InputData {
-                repo: AtIdentifier::Did(current_did),
-                collection: "app.bsky.graph.block"
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid block NSID".into()))?,
-                record,
-                rkey: None,
-                swap_commit: None,
-                validate: None,
-            }
-            .into(),
-        )
+    let existing = viewer_relationship(&agent, &subject)
         .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?
+        .block_uri;
+    let (block_uri, created) = reuse_or_create(existing, move || async move {
+        let record = BlockRecordData {
+            created_at: bsky_sdk::api::types::string::Datetime::now(),
+            subject,
+        }
+        .try_into_unknown()
         .map_err(|e| AppError::ApiError(e.to_string()))?;
-
-    Ok(response.data.uri.to_string())
+        create_graph_record(&agent, current_did, "app.bsky.graph.block", record).await
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    // Reusing a record means the caller's view was stale: cached profiles are brought up
+    // to date, but the timeline only changes for a new block
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Blocked {
+            did,
+            block_uri: block_uri.clone(),
+        },
+        created,
+    );
+    Ok(block_uri)
 }
 
 /// Unblock a user (deletes the block record)
 #[tauri::command]
 pub async fn unblock_actor(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     block_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -1090,7 +2122,414 @@ pub async fn unblock_actor(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    after_relationship_change(
+        app,
+        agent_state.inner().clone(),
+        db.inner().clone(),
+        timeline::ProfileChange::Unblocked { block_uri },
+        false,
+    );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::{undefined, xrpc_failure};
+    use crate::db::test_pool;
+
+    const ALICE: &str = "did:plc:alice";
+    const BOB: &str = "did:plc:bob";
+
+    fn is_transient(err: atrium_xrpc::Error<create_record::Error>) -> bool {
+        SendError::xrpc(err).transient
+    }
+
+    #[test]
+    fn transient_xrpc_errors_are_retried() {
+        let offline =
+            atrium_xrpc::Error::HttpClient(Box::new(std::io::Error::other("connection reset")));
+        assert!(is_transient(offline));
+        assert!(is_transient(xrpc_failure(
+            429,
+            undefined("RateLimitExceeded")
+        )));
+        assert!(is_transient(xrpc_failure(502, None)));
+        assert!(is_transient(xrpc_failure(
+            400,
+            undefined("UpstreamTimeout")
+        )));
+    }
+
+    #[test]
+    fn rejected_posts_are_not_retried() {
+        // Messages can contain anything; only the status and error name count
+        assert!(!is_transient(xrpc_failure(
+            400,
+            undefined("InvalidRequest")
+        )));
+        assert!(!is_transient(xrpc_failure(401, undefined("AuthRequired"))));
+        let swap = Some(XrpcErrorKind::Custom(create_record::Error::InvalidSwap(
+            Some("InternalServerError 503".into()),
+        )));
+        assert!(!is_transient(xrpc_failure(400, swap)));
+    }
+
+    #[test]
+    fn retry_backoff_doubles_up_to_the_cap() {
+        // No jitter is the lower half of the window, full jitter the whole window
+        assert_eq!(retry_backoff_secs(1, 0.0), 15);
+        assert_eq!(retry_backoff_secs(1, 1.0), 30);
+        assert_eq!(retry_backoff_secs(2, 1.0), 60);
+        assert_eq!(retry_backoff_secs(3, 0.5), 90);
+        assert_eq!(retry_backoff_secs(7, 1.0), 1800);
+        assert_eq!(retry_backoff_secs(50, 1.0), 1800);
+        assert_eq!(retry_backoff_secs(50, 0.0), 900);
+    }
+
+    #[test]
+    fn retry_backoff_clamps_its_inputs() {
+        assert_eq!(retry_backoff_secs(0, 0.0), retry_backoff_secs(1, 0.0));
+        assert_eq!(retry_backoff_secs(-3, 1.0), retry_backoff_secs(1, 1.0));
+        assert_eq!(retry_backoff_secs(2, 7.0), retry_backoff_secs(2, 1.0));
+        assert_eq!(retry_backoff_secs(2, -1.0), retry_backoff_secs(2, 0.0));
+    }
+
+    #[test]
+    fn only_sends_that_never_reached_the_server_are_queued() {
+        assert!(should_enqueue_retry(&AppError::NetworkError(
+            "timeout".into()
+        )));
+        assert!(should_enqueue_retry(&AppError::SessionNotFound));
+        assert!(!should_enqueue_retry(&AppError::AuthenticationFailed(
+            "expired".into()
+        )));
+        assert!(!should_enqueue_retry(&AppError::ApiError(
+            "bad record".into()
+        )));
+        assert!(!should_enqueue_retry(&AppError::Busy("locked".into())));
+    }
+
+    #[test]
+    fn only_offline_app_errors_are_retried() {
+        assert!(SendError::from(AppError::NetworkError("timeout".into())).transient);
+        assert!(SendError::from(AppError::SessionNotFound).transient);
+        assert!(!SendError::from(AppError::ApiError("UpstreamFailure (503)".into())).transient);
+        assert!(
+            !SendError::from(AppError::ValidationError {
+                code: "ALT_TEXT_MISSING",
+                message: "missing".into(),
+            })
+            .transient
+        );
+    }
+
+    #[tokio::test]
+    async fn existing_record_is_reused_without_creating_another() {
+        let creates = &std::cell::Cell::new(0);
+        let create = move || async move {
+            creates.set(creates.get() + 1);
+            Ok("at://did:plc:alice/app.bsky.graph.follow/new".to_string())
+        };
+
+        let existing = Some("at://did:plc:alice/app.bsky.graph.follow/old".to_string());
+        let (uri, created) = reuse_or_create(existing, create).await.unwrap();
+        assert_eq!(uri, "at://did:plc:alice/app.bsky.graph.follow/old");
+        assert!(!created);
+        assert_eq!(creates.get(), 0);
+
+        let (uri, created) = reuse_or_create(None, create).await.unwrap();
+        assert_eq!(uri, "at://did:plc:alice/app.bsky.graph.follow/new");
+        assert!(created);
+        assert_eq!(creates.get(), 1);
+    }
+
+    #[test]
+    fn relationship_is_read_from_the_viewer_state() {
+        let subject: Did = BOB.parse().unwrap();
+        let profile = serde_json::from_value(serde_json::json!({
+            "did": BOB,
+            "handle": "bob.test",
+            "viewer": {
+                "following": "at://did:plc:alice/app.bsky.graph.follow/1",
+                "blocking": "at://did:plc:alice/app.bsky.graph.block/1",
+            },
+        }))
+        .unwrap();
+        let relationship = relationship_from_profile::<
+            bsky_sdk::api::app::bsky::actor::get_profile::Error,
+        >(&subject, Ok(profile))
+        .unwrap();
+        assert_eq!(
+            relationship.follow_uri.as_deref(),
+            Some("at://did:plc:alice/app.bsky.graph.follow/1")
+        );
+        assert_eq!(
+            relationship.block_uri.as_deref(),
+            Some("at://did:plc:alice/app.bsky.graph.block/1")
+        );
+    }
+
+    #[test]
+    fn relationship_lookup_fails_only_when_offline() {
+        let subject: Did = BOB.parse().unwrap();
+        let offline =
+            atrium_xrpc::Error::<bsky_sdk::api::app::bsky::actor::get_profile::Error>::HttpClient(
+                Box::new(std::io::Error::other("connection refused")),
+            );
+        assert!(matches!(
+            relationship_from_profile(&subject, Err(offline)),
+            Err(AppError::NetworkError(_))
+        ));
+
+        let rejected =
+            xrpc_failure::<bsky_sdk::api::app::bsky::actor::get_profile::Error>(400, None);
+        let unknown = relationship_from_profile(&subject, Err(rejected)).unwrap();
+        assert!(unknown.follow_uri.is_none() && unknown.block_uri.is_none());
+    }
+
+    #[test]
+    fn thread_alt_text_is_checked_on_every_segment() {
+        let segment = |alt: &str| -> CreatePostPayload {
+            serde_json::from_value(serde_json::json!({
+                "text": "segment",
+                "images": [{ "path": "/tmp/a.png", "alt": alt }],
+            }))
+            .unwrap()
+        };
+        let complete = vec![
+            segment("a cat"),
+            draft_payload("no images"),
+            segment("a dog"),
+        ];
+        assert!(check_thread_alt_text(&complete).is_ok());
+
+        let last_missing = vec![segment("a cat"), draft_payload("no images"), segment(" ")];
+        match check_thread_alt_text(&last_missing) {
+            Err(AppError::ValidationError { code, message }) => {
+                assert_eq!(code, "ALT_TEXT_MISSING");
+                assert!(message.starts_with("Post 3 of the thread"), "{message}");
+            }
+            _ => panic!("a segment without alt text must fail the whole thread"),
+        }
+    }
+
+    #[test]
+    fn thread_segments_get_default_langs_unless_tagged() {
+        let mut posts = vec![draft_payload("first"), draft_payload("second")];
+        posts[1].langs = vec!["de".into()];
+        apply_default_langs(&mut posts, &["en".to_string(), "ja".to_string()]);
+        assert_eq!(posts[0].langs, ["en", "ja"]);
+        assert_eq!(posts[1].langs, ["de"]);
+    }
+
+    fn draft_payload(text: &str) -> CreatePostPayload {
+        serde_json::from_value(serde_json::json!({ "text": text })).unwrap()
+    }
+
+    const ROOT: &str = "at://did:plc:alice/app.bsky.feed.post/root";
+    const PARENT: &str = "at://did:plc:bob/app.bsky.feed.post/parent";
+    const CID: &str = "bafyreie5737gdxlw5i64vzichcalba3z2v5n6icifvx5xytvske7mr3hpm";
+
+    fn thread_refs() -> ReplyRefs {
+        let posted = |uri: &str| PostedRef {
+            uri: uri.into(),
+            cid: CID.into(),
+        };
+        ReplyRefs {
+            root: posted(ROOT),
+            parent: posted(PARENT),
+        }
+    }
+
+    /// What `retry_queued_posts` reads back once the connection is up again
+    async fn queued_payload(db: &SqlitePool, id: &str) -> CreatePostPayload {
+        let (json,): (String,) =
+            sqlx::query_as("SELECT payload_json FROM post_retry_queue WHERE id = ?1")
+                .bind(id)
+                .fetch_one(db)
+                .await
+                .unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    async fn offline_resolve(
+        _: String,
+    ) -> Result<Option<bsky_sdk::api::app::bsky::feed::post::ReplyRef>, SendError> {
+        panic!("a queued reply must not resolve its parent again")
+    }
+
+    #[tokio::test]
+    async fn queued_reply_keeps_its_thread_after_reconnect() {
+        let db = test_pool().await;
+        let mut payload = draft_payload("replying while offline");
+        payload.reply_to = Some(PARENT.into());
+        payload.reply_refs = Some(thread_refs());
+
+        let offline = AppError::NetworkError("offline".into());
+        let id = enqueue_post_retry(&db, ALICE, &payload, &offline)
+            .await
+            .unwrap();
+
+        let mut queued = queued_payload(&db, &id).await;
+        let reply = payload_reply_ref(&mut queued, offline_resolve)
+            .await
+            .ok()
+            .flatten()
+            .expect("reply refs");
+        assert_eq!(reply.data.root.data.uri, ROOT);
+        assert_eq!(reply.data.parent.data.uri, PARENT);
+        assert_eq!(reply.data.parent.data.cid.as_ref().to_string(), CID);
+    }
+
+    #[tokio::test]
+    async fn refs_resolved_before_a_failed_send_are_queued() {
+        let db = test_pool().await;
+        let mut payload = draft_payload("reply");
+        payload.reply_to = Some(PARENT.into());
+
+        let resolved = thread_refs().to_reply_ref().unwrap();
+        payload_reply_ref(&mut payload, |uri| async move {
+            assert_eq!(uri, PARENT);
+            Ok(Some(resolved))
+        })
+        .await
+        .ok()
+        .expect("resolved");
+
+        let offline = AppError::NetworkError("offline".into());
+        let id = enqueue_post_retry(&db, ALICE, &payload, &offline)
+            .await
+            .unwrap();
+        let mut queued = queued_payload(&db, &id).await;
+        let reply = payload_reply_ref(&mut queued, offline_resolve)
+            .await
+            .ok()
+            .flatten()
+            .expect("reply refs");
+        assert_eq!(reply.data.root.data.uri, ROOT);
+    }
+
+    #[tokio::test]
+    async fn drafts_are_invisible_to_other_accounts() {
+        let db = test_pool().await;
+        save_draft_payload(
+            &db,
+            ALICE,
+            "post:new",
+            None,
+            &draft_payload("alice's draft"),
+        )
+        .await
+        .unwrap();
+
+        assert!(load_draft_payload(&db, BOB, "post:new")
+            .await
+            .unwrap()
+            .is_none());
+        let own = load_draft_payload(&db, ALICE, "post:new").await.unwrap();
+        assert_eq!(own.unwrap().text, "alice's draft");
+    }
+
+    fn slot(name: &str) -> DraftSlotKey {
+        (ALICE.to_string(), name.to_string())
+    }
+
+    fn put_pending(key: &DraftSlotKey, generation: u64, in_flight: bool) {
+        pending_drafts().insert(
+            key.clone(),
+            PendingDraft {
+                generation,
+                payload: Some(draft_payload("typing")),
+                queued_at: Utc::now().to_rfc3339(),
+                in_flight,
+                cleared: false,
+            },
+        );
+    }
+
+    #[test]
+    fn clearing_during_an_autosave_write_deletes_again() {
+        let key = slot("test:cleared-in-flight");
+        put_pending(&key, 1, true);
+        cancel_autosave(&key);
+        assert_eq!(finish_autosave(&key, 1), AfterAutosave::Delete);
+        assert!(!pending_drafts().contains_key(&key));
+    }
+
+    #[test]
+    fn clearing_a_waiting_autosave_drops_it() {
+        let key = slot("test:cleared-waiting");
+        put_pending(&key, 1, false);
+        cancel_autosave(&key);
+        assert!(!pending_drafts().contains_key(&key));
+    }
+
+    #[test]
+    fn autosave_during_a_write_is_written_next() {
+        let key = slot("test:newer-in-flight");
+        put_pending(&key, 1, true);
+        pending_drafts().get_mut(&key).unwrap().generation = 2;
+        assert_eq!(finish_autosave(&key, 1), AfterAutosave::Again);
+        assert!(!pending_drafts().get(&key).unwrap().in_flight);
+        assert_eq!(finish_autosave(&key, 2), AfterAutosave::Done);
+    }
+
+    #[tokio::test]
+    async fn older_autosave_does_not_replace_newer_draft() {
+        let db = test_pool().await;
+        let newer = "2024-01-01T00:00:02Z";
+        let older = "2024-01-01T00:00:01Z";
+        upsert_draft(&db, ALICE, "post:new", None, &draft_payload("newer"), newer)
+            .await
+            .unwrap();
+        upsert_draft(&db, ALICE, "post:new", None, &draft_payload("older"), older)
+            .await
+            .unwrap();
+        let draft = load_draft_payload(&db, ALICE, "post:new").await.unwrap();
+        assert_eq!(draft.unwrap().text, "newer");
+    }
+
+    #[tokio::test]
+    async fn legacy_drafts_go_to_the_stored_account_only() {
+        let db = test_pool().await;
+        for (slot, text) in [("post:new", "old new post"), ("reply:at://x", "old reply")] {
+            sqlx::query(
+                "INSERT INTO draft_slots (user_did, slot_id, payload_json, created_at, updated_at) \
+                 VALUES ('', ?1, ?2, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            )
+            .bind(slot)
+            .bind(serde_json::to_string(&draft_payload(text)).unwrap())
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+        save_draft_payload(&db, ALICE, "post:new", None, &draft_payload("current"))
+            .await
+            .unwrap();
+
+        claim_legacy_drafts(&db, ALICE).await.unwrap();
+
+        let load = |did, slot| {
+            let db = db.clone();
+            async move { load_draft_payload(&db, did, slot).await.unwrap() }
+        };
+        assert_eq!(load(ALICE, "post:new").await.unwrap().text, "current");
+        assert_eq!(
+            load(ALICE, "legacy:post:new").await.unwrap().text,
+            "old new post"
+        );
+        assert_eq!(load(ALICE, "reply:at://x").await.unwrap().text, "old reply");
+        assert!(load(BOB, "reply:at://x").await.is_none());
+
+        let unclaimed: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM draft_slots WHERE user_did = ''")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!(unclaimed, 0);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::db::DbState;
 use crate::error::AppError;
 use crate::session::{
-    clear_session, get_stored_session, store_session, SessionInfo, StoredSession,
+    clear_session, get_stored_session, jwt_expires_within, store_session, SessionInfo,
+    StoredSession,
 };
 use crate::session_store::KeyringSessionStore;
 use bsky_sdk::agent::config::Config;
@@ -9,24 +10,101 @@ use bsky_sdk::api::types::Object;
 use bsky_sdk::BskyAgent;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Manager, State};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
 
 // BskyAgent with KeyringSessionStore for persistent session management
 // Using atrium_xrpc_client::reqwest::ReqwestClient as the HTTP client
-// Mutex serializes all access to the agent
-pub type AgentState =
-    Arc<Mutex<Option<BskyAgent<atrium_xrpc_client::reqwest::ReqwestClient, KeyringSessionStore>>>>;
+pub type AppAgent = BskyAgent<atrium_xrpc_client::reqwest::ReqwestClient, KeyringSessionStore>;
+
+// Mutex guards swapping the agent on login/logout; commands take a clone via `current_agent`
+pub type AgentState = Arc<Mutex<Option<AppAgent>>>;
+
+/// Longest a command waits for the agent lock before giving up with `AppError::Busy`
+const AGENT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Take the agent lock, waiting at most `AGENT_LOCK_TIMEOUT`; a timeout is `AppError::Busy`.
+/// Holders only clone or swap the agent, except `current_agent` resuming a stored session.
+pub(crate) async fn lock_agent(
+    agent_state: &AgentState,
+) -> Result<MutexGuard<'_, Option<AppAgent>>, AppError> {
+    tokio::time::timeout(AGENT_LOCK_TIMEOUT, agent_state.lock())
+        .await
+        .map_err(|_| {
+            tracing::warn!(target: "auth", "agent lock not acquired within {AGENT_LOCK_TIMEOUT:?}");
+            AppError::Busy("Another request is still running; try again shortly".into())
+        })
+}
+
+/// Clone the signed-in agent under a brief lock so network calls don't hold the mutex.
+/// Clones share one session store and token-refresh state, so a refresh triggered by one
+/// in-flight call is picked up by the others.
+///
+/// If no agent is loaded but credentials are stored (e.g. the frontend reloaded before
+/// calling `resume_session`), the agent is rebuilt from them once here. Without stored
+/// credentials this is `SessionNotFound`; a failed rebuild returns its auth error.
+pub async fn current_agent(agent_state: &AgentState) -> Result<AppAgent, AppError> {
+    let mut state = lock_agent(agent_state).await?;
+    if let Some(agent) = state.as_ref() {
+        return Ok(agent.clone());
+    }
+
+    let stored = get_stored_session()?;
+    // Rebuilt under the lock so concurrent commands wait for one resume instead of racing
+    tracing::info!(target: "auth", handle = %stored.handle, "agent missing; resuming stored session");
+    let agent = agent_from_stored_session(&stored).await?;
+    *state = Some(agent.clone());
+    Ok(agent)
+}
+
+/// Refresh an access token this many seconds before it expires
+pub(crate) const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+/// Serialises refreshes in `fresh_access_jwt`; the refresh token rotates, so two
+/// concurrent refreshes would leave one of them holding a revoked token
+static TOKEN_REFRESH: Mutex<()> = Mutex::const_new(());
+
+/// Access token for requests made outside the agent (raw HTTP downloads and uploads).
+/// The agent only refreshes when one of its own calls hits `ExpiredToken`, so a token
+/// close to expiry is refreshed here the way `update_handle` does it, and the agent is
+/// rebuilt around the new session.
+pub(crate) async fn fresh_access_jwt(agent_state: &AgentState) -> Result<String, AppError> {
+    let _refreshing = TOKEN_REFRESH.lock().await;
+    let mut stored = get_stored_session()?;
+    if !jwt_expires_within(&stored.access_jwt, TOKEN_REFRESH_MARGIN_SECS) {
+        return Ok(stored.access_jwt);
+    }
+
+    let refreshed = current_agent(agent_state)
+        .await?
+        .api
+        .com
+        .atproto
+        .server
+        .refresh_session()
+        .await
+        .map_err(crate::connectivity::xrpc_error)?;
+    stored.access_jwt = refreshed.data.access_jwt.clone();
+    stored.refresh_jwt = refreshed.data.refresh_jwt.clone();
+    store_session(&stored)?;
+
+    let agent = agent_from_stored_session(&stored).await?;
+    *lock_agent(agent_state).await? = Some(agent);
+    tracing::debug!(target: "auth", "access token refreshed for a direct request");
+    Ok(stored.access_jwt)
+}
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
     pub identifier: String,
     pub password: String,
+    /// PDS hosting the account. App reads are proxied by the PDS to its own AppView;
+    /// see `crate::endpoints` for the direct AppView settings.
     #[serde(default = "default_service")]
     pub service: String,
 }
 
-fn default_service() -> String {
+pub(crate) fn default_service() -> String {
     "https://bsky.social".to_string()
 }
 
@@ -39,11 +117,18 @@ pub struct LoginResponse {
 
 /// Login to AT Protocol
 #[tauri::command]
+#[tracing::instrument(target = "auth", skip_all, fields(service = %request.service))]
 pub async fn login(
     app: AppHandle,
     agent_state: State<'_, AgentState>,
-    request: LoginRequest,
+    mut request: LoginRequest,
 ) -> Result<LoginResponse, AppError> {
+    request.service = crate::endpoints::normalize_url(&request.service)?;
+    // A custom PDS gets a reachability check so a typo reads as such, not as bad credentials
+    if request.service != default_service() {
+        crate::endpoints::check_pds(&request.service).await?;
+    }
+
     // Create a new KeyringSessionStore for this agent
     let store = KeyringSessionStore::new();
     store.set_service_url(request.service.clone()).await;
@@ -58,6 +143,7 @@ pub async fn login(
 
     // Create agent with KeyringSessionStore for persistent session management
     let agent = BskyAgent::builder()
+        .client(crate::http::xrpc_client(&request.service))
         .config(config)
         .store(store)
         .build()
@@ -77,14 +163,18 @@ pub async fn login(
         access_jwt: session.access_jwt.clone(),
         refresh_jwt: session.refresh_jwt.clone(),
         service_url: request.service.clone(),
+        email: session.email.clone(),
+        email_confirmed: session.email_confirmed,
     };
     store_session(&stored)?;
-    println!("Login successful, session stored.");
+    tracing::info!(target: "auth", did = %stored.did, "login successful");
 
     // Update agent state
-    let mut state = agent_state.lock().await;
+    let mut state = lock_agent(&agent_state).await?;
     *state = Some(agent);
     drop(state);
+    // Interactions queued by a previous account must not go out under this one
+    crate::commands::interactions::reset();
 
     let db = app.state::<DbState>().inner().clone();
     crate::commands::actions::trigger_retry_now(app.clone(), agent_state.inner().clone(), db);
@@ -99,10 +189,12 @@ pub async fn login(
 /// Logout and clear session
 #[tauri::command]
 pub async fn logout(agent_state: State<'_, AgentState>) -> Result<(), AppError> {
-    println!("Logout command called");
+    tracing::info!(target: "auth", "logout");
+    // Locked first so a busy app fails the logout instead of leaving a stale agent behind
+    let mut state = lock_agent(&agent_state).await?;
     clear_session()?;
-
-    let mut state = agent_state.lock().await;
+    crate::unread::reset();
+    crate::commands::interactions::reset();
     *state = None;
 
     Ok(())
@@ -118,63 +210,110 @@ pub async fn get_session() -> Result<Option<SessionInfo>, AppError> {
     }
 }
 
-/// Resume session from stored credentials
-/// Recreates the agent with the stored access/refresh tokens using KeyringSessionStore
-/// which will automatically persist any token refreshes
-#[tauri::command]
-pub async fn resume_session(
-    app: AppHandle,
-    agent_state: State<'_, AgentState>,
-) -> Result<SessionInfo, AppError> {
-    println!("resume_session command called");
-
-    let stored = match get_stored_session() {
-        Ok(s) => s,
-        Err(e) => {
-            println!("resume_session: failed to get stored session: {}", e);
-            return Err(e);
-        }
-    };
-
-    println!("resume_session: found stored session for {}", stored.handle);
-
+/// Build an agent around stored credentials using KeyringSessionStore,
+/// which enables automatic token refresh and persistence of refreshed tokens.
+pub(crate) async fn agent_from_stored_session(
+    stored: &StoredSession,
+) -> Result<AppAgent, AppError> {
     // Create KeyringSessionStore from stored session
-    let (store, session) = KeyringSessionStore::from_stored_session(&stored).map_err(|e| {
-        println!("resume_session: failed to create session store: {}", e);
+    let (store, session) = KeyringSessionStore::from_stored_session(stored).map_err(|e| {
+        tracing::warn!(target: "auth", "resume: failed to create session store: {e}");
         e
     })?;
 
     // Create config (proxy_header is set dynamically per-request for chat calls)
     let config = Config {
         endpoint: stored.service_url.clone(),
-        session: Some(Object::from(session.clone())),
+        session: Some(Object::from(session)),
         labelers_header: None,
         proxy_header: None,
     };
 
-    println!("resume_session: rebuilding agent with KeyringSessionStore...");
+    tracing::debug!(target: "auth", "resume: rebuilding agent");
 
-    // Build the agent with KeyringSessionStore - this enables automatic token refresh
-    // and persistence of refreshed tokens
-    let agent = BskyAgent::builder()
+    BskyAgent::builder()
+        .client(crate::http::xrpc_client(&stored.service_url))
         .config(config)
         .store(store)
         .build()
         .await
         .map_err(|e| {
-            println!("resume_session: failed to build agent: {}", e);
+            tracing::warn!(target: "auth", "resume: failed to build agent: {e}");
             AppError::AuthenticationFailed(format!("Failed to resume session: {}", e))
-        })?;
+        })
+}
+
+/// Resume session from stored credentials
+/// Recreates the agent with the stored access/refresh tokens using KeyringSessionStore
+/// which will automatically persist any token refreshes.
+/// When `prefetch` is set, the first timeline and notifications pages are fetched in the
+/// background so the caches are warm by the time the UI mounts (callers should leave it
+/// off on metered connections).
+#[tauri::command]
+#[tracing::instrument(target = "auth", skip_all)]
+pub async fn resume_session(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    prefetch: Option<bool>,
+) -> Result<SessionInfo, AppError> {
+    let stored = match get_stored_session() {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::debug!(target: "auth", "resume: no stored session: {e}");
+            return Err(e);
+        }
+    };
+
+    tracing::debug!(target: "auth", handle = %stored.handle, "resume: found stored session");
+
+    let agent = agent_from_stored_session(&stored).await?;
 
     // Update agent state
-    let mut state = agent_state.lock().await;
+    let mut state = lock_agent(&agent_state).await?;
     *state = Some(agent);
     drop(state);
 
     let db = app.state::<DbState>().inner().clone();
-    crate::commands::actions::trigger_retry_now(app.clone(), agent_state.inner().clone(), db);
+    crate::commands::actions::trigger_retry_now(
+        app.clone(),
+        agent_state.inner().clone(),
+        db.clone(),
+    );
+
+    if prefetch.unwrap_or(false) {
+        let prefetch_app = app.clone();
+        let prefetch_agent_state = agent_state.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            crate::commands::timeline::prefetch_timeline(
+                &prefetch_app,
+                &prefetch_agent_state,
+                db.as_ref(),
+            )
+            .await;
+            crate::commands::notifications::prefetch_notifications(
+                &prefetch_app,
+                &prefetch_agent_state,
+                db.as_ref(),
+            )
+            .await;
+        });
+    }
 
-    println!("resume_session: successfully resumed session with persistent token storage");
+    tracing::info!(target: "auth", "session resumed");
+
+    // Sessions saved before email status was tracked don't know it yet
+    let stored = if stored.email_confirmed.is_none() {
+        let agent = current_agent(agent_state.inner()).await?;
+        match crate::commands::account::sync_email_status(&agent).await {
+            Ok(updated) => updated,
+            Err(err) => {
+                tracing::warn!(target: "auth", "email status refresh failed: {err}");
+                stored
+            }
+        }
+    } else {
+        stored
+    };
 
     Ok(SessionInfo::from(&stored))
 }
@@ -0,0 +1,642 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::commands::repo::list_records_page;
+use crate::connectivity;
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use bsky_sdk::api::app::bsky::graph::follow::RecordData as FollowRecordData;
+use bsky_sdk::api::com::atproto::repo::{create_record, delete_record};
+use bsky_sdk::api::types::string::{AtIdentifier, Datetime, Did, Nsid, RecordKey};
+use bsky_sdk::api::types::TryIntoUnknown;
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+/// Requests in flight at once within a chunk.
+const BULK_CONCURRENCY: usize = 4;
+/// Items processed between progress events and rate-limit checks.
+const BULK_CHUNK_SIZE: usize = 20;
+const RATE_LIMIT_PAUSE_SECS: u64 = 60;
+const MAX_RATE_LIMIT_PAUSES: u32 = 3;
+
+const FOLLOW_COLLECTION: &str = "app.bsky.graph.follow";
+
+#[derive(Clone, Copy, PartialEq)]
+enum BulkAction {
+    Follow,
+    Unfollow,
+}
+
+impl BulkAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            BulkAction::Follow => "follow",
+            BulkAction::Unfollow => "unfollow",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "follow" => Some(BulkAction::Follow),
+            "unfollow" => Some(BulkAction::Unfollow),
+            _ => None,
+        }
+    }
+}
+
+enum ItemOutcome {
+    Done(Option<String>),
+    RateLimited,
+    Failed(String),
+}
+
+#[derive(Serialize, Clone)]
+pub struct BulkGraphProgressEvent {
+    pub job_id: String,
+    pub action: String,
+    pub processed: u32,
+    pub total: u32,
+    pub rate_limited: bool,
+}
+
+#[derive(Serialize)]
+pub struct BulkGraphItemResult {
+    pub target: String,
+    pub status: String,
+    pub uri: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BulkGraphResponse {
+    pub job_id: String,
+    pub action: String,
+    pub results: Vec<BulkGraphItemResult>,
+    /// Items not attempted yet or paused by rate limiting; resume with the job id.
+    pub pending: u32,
+}
+
+fn current_repo_did() -> Result<Did, AppError> {
+    get_stored_session()?
+        .did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid stored DID".into()))
+}
+
+fn is_rate_limited<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    matches!(err, atrium_xrpc::Error::XrpcResponse(resp) if resp.status.as_u16() == 429)
+}
+
+fn follow_collection() -> Result<Nsid, AppError> {
+    FOLLOW_COLLECTION
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid follow NSID".into()))
+}
+
+async fn insert_job_items(
+    db: &SqlitePool,
+    job_id: &str,
+    user_did: &str,
+    action: BulkAction,
+    targets: &[String],
+) -> Result<(), AppError> {
+    let now = Utc::now().to_rfc3339();
+    // All or nothing, so a failed insert never leaves a job with only some of its targets
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job insert begin failed: {e}")))?;
+
+    for target in targets {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO bulk_graph_items (
+                job_id, user_did, action, target, status, result_uri,
+                last_error, created_at, updated_at
+            )
+            VALUES (?1, ?2, ?3, ?4, 'pending', NULL, NULL, ?5, ?5)
+            "#,
+        )
+        .bind(job_id)
+        .bind(user_did)
+        .bind(action.as_str())
+        .bind(target)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job insert failed: {e}")))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job insert commit failed: {e}")))
+}
+
+/// Mark items left over when a job stops on rate limiting, so they are told apart from
+/// items that were never attempted and picked up again on resume
+async fn pause_job_items(
+    db: &SqlitePool,
+    job_id: &str,
+    targets: &[String],
+) -> Result<(), AppError> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job pause begin failed: {e}")))?;
+
+    for target in targets {
+        sqlx::query(
+            r#"
+            UPDATE bulk_graph_items
+            SET status = 'paused', last_error = 'Rate limited', updated_at = ?3
+            WHERE job_id = ?1 AND target = ?2 AND status IN ('pending', 'failed')
+            "#,
+        )
+        .bind(job_id)
+        .bind(target)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job pause failed: {e}")))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::InternalError(format!("bulk job pause commit failed: {e}")))
+}
+
+/// Items still to do: never attempted, paused by rate limiting, or failed and retried on resume
+fn is_unfinished(status: &str) -> bool {
+    matches!(status, "pending" | "paused" | "failed")
+}
+
+async fn update_job_item(
+    db: &SqlitePool,
+    job_id: &str,
+    target: &str,
+    status: &str,
+    result_uri: Option<&str>,
+    last_error: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE bulk_graph_items
+        SET status = ?3,
+            result_uri = COALESCE(?4, result_uri),
+            last_error = ?5,
+            updated_at = ?6
+        WHERE job_id = ?1 AND target = ?2
+        "#,
+    )
+    .bind(job_id)
+    .bind(target)
+    .bind(status)
+    .bind(result_uri)
+    .bind(last_error)
+    .bind(Utc::now().to_rfc3339())
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("bulk job update failed: {e}")))?;
+
+    Ok(())
+}
+
+async fn load_job_items(
+    db: &SqlitePool,
+    job_id: &str,
+    user_did: &str,
+) -> Result<Vec<(String, String, String, Option<String>, Option<String>)>, AppError> {
+    sqlx::query_as::<_, (String, String, String, Option<String>, Option<String>)>(
+        r#"
+        SELECT action, target, status, result_uri, last_error
+        FROM bulk_graph_items
+        WHERE job_id = ?1 AND user_did = ?2
+        ORDER BY created_at ASC, target ASC
+        "#,
+    )
+    .bind(job_id)
+    .bind(user_did)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("bulk job read failed: {e}")))
+}
+
+/// Map of followed DID -> follow record URI for the current user.
+async fn fetch_existing_follows(
+    agent: &AppAgent,
+    repo: &Did,
+) -> Result<HashMap<String, String>, AppError> {
+    let mut follows = HashMap::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page = list_records_page(
+            agent,
+            AtIdentifier::Did(repo.clone()),
+            FOLLOW_COLLECTION,
+            cursor,
+            100,
+        )
+        .await?;
+
+        for record in page.records {
+            if let Some(subject) = record.value.get("subject").and_then(|v| v.as_str()) {
+                follows.insert(subject.to_string(), record.uri);
+            }
+        }
+
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(follows)
+}
+
+async fn follow_one(app: &AppHandle, agent: &AppAgent, repo: &Did, target: &str) -> ItemOutcome {
+    let subject: Did = match target.parse() {
+        Ok(did) => did,
+        Err(_) => return ItemOutcome::Failed("Invalid DID".into()),
+    };
+    let collection = match follow_collection() {
+        Ok(nsid) => nsid,
+        Err(err) => return ItemOutcome::Failed(err.to_string()),
+    };
+    let record = match (FollowRecordData {
+        created_at: Datetime::now(),
+        subject,
+    })
+    .try_into_unknown()
+    {
+        Ok(record) => record,
+        Err(err) => return ItemOutcome::Failed(err.to_string()),
+    };
+
+    let result = agent
+        .api
+        .com
+        .atproto
+        .repo
+        .create_record(
+            create_record::InputData {
+                repo: AtIdentifier::Did(repo.clone()),
+                collection,
+                record,
+                rkey: None,
+                swap_commit: None,
+                validate: None,
+            }
+            .into(),
+        )
+        .await;
+
+    match result {
+        Ok(output) => ItemOutcome::Done(Some(output.data.uri.to_string())),
+        Err(err) if is_rate_limited(&err) => ItemOutcome::RateLimited,
+        Err(err) => {
+            // The item stays resumable; the reconnect monitor covers the rest of the outage
+            if connectivity::is_transport_error(&err) {
+                connectivity::schedule_health_check(app);
+            }
+            ItemOutcome::Failed(err.to_string())
+        }
+    }
+}
+
+async fn unfollow_one(app: &AppHandle, agent: &AppAgent, repo: &Did, target: &str) -> ItemOutcome {
+    // at://<did>/app.bsky.graph.follow/<rkey>
+    let mut parts = target.trim_start_matches("at://").split('/');
+    let (Some(_), Some(collection), Some(rkey)) = (parts.next(), parts.next(), parts.next()) else {
+        return ItemOutcome::Failed("Invalid follow record URI".into());
+    };
+    if collection != FOLLOW_COLLECTION {
+        return ItemOutcome::Failed("Not a follow record URI".into());
+    }
+    let rkey = match RecordKey::from_str(rkey) {
+        Ok(rkey) => rkey,
+        Err(_) => return ItemOutcome::Failed("Invalid record key".into()),
+    };
+    let collection = match follow_collection() {
+        Ok(nsid) => nsid,
+        Err(err) => return ItemOutcome::Failed(err.to_string()),
+    };
+
+    let result = agent
+        .api
+        .com
+        .atproto
+        .repo
+        .delete_record(
+            delete_record::InputData {
+                repo: AtIdentifier::Did(repo.clone()),
+                collection,
+                rkey,
+                swap_record: None,
+                swap_commit: None,
+            }
+            .into(),
+        )
+        .await;
+
+    // deleteRecord is idempotent, so re-running an interrupted job is safe.
+    match result {
+        Ok(_) => ItemOutcome::Done(None),
+        Err(err) if is_rate_limited(&err) => ItemOutcome::RateLimited,
+        Err(err) => {
+            // The item stays resumable; the reconnect monitor covers the rest of the outage
+            if connectivity::is_transport_error(&err) {
+                connectivity::schedule_health_check(app);
+            }
+            ItemOutcome::Failed(err.to_string())
+        }
+    }
+}
+
+async fn run_bulk_job(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    db: &SqlitePool,
+    job_id: &str,
+    action: BulkAction,
+) -> Result<BulkGraphResponse, AppError> {
+    let repo = current_repo_did()?;
+    let user_did = repo.to_string();
+
+    let items = load_job_items(db, job_id, &user_did).await?;
+    let total = items.len() as u32;
+    let mut pending: Vec<String> = items
+        .into_iter()
+        .filter(|(_, _, status, _, _)| is_unfinished(status))
+        .map(|(_, target, _, _, _)| target)
+        .collect();
+    let mut processed = total - pending.len() as u32;
+
+    if action == BulkAction::Follow && !pending.is_empty() {
+        let existing = {
+            let agent = current_agent(agent_state).await?;
+            fetch_existing_follows(&agent, &repo).await?
+        };
+
+        let mut remaining = Vec::with_capacity(pending.len());
+        for target in pending {
+            if target == user_did {
+                update_job_item(
+                    db,
+                    job_id,
+                    &target,
+                    "skipped",
+                    None,
+                    Some("Cannot follow self"),
+                )
+                .await?;
+                processed += 1;
+            } else if let Some(uri) = existing.get(&target) {
+                update_job_item(db, job_id, &target, "skipped", Some(uri.as_str()), None).await?;
+                processed += 1;
+            } else {
+                remaining.push(target);
+            }
+        }
+        pending = remaining;
+    }
+
+    let mut rate_limit_pauses = 0;
+    let mut queue: Vec<String> = pending;
+
+    while !queue.is_empty() {
+        let chunk: Vec<String> = queue.drain(..queue.len().min(BULK_CHUNK_SIZE)).collect();
+
+        let outcomes: Vec<(String, ItemOutcome)> = {
+            let agent = &current_agent(agent_state).await?;
+            let repo = &repo;
+
+            stream::iter(chunk.into_iter().map(|target| async move {
+                let outcome = match action {
+                    BulkAction::Follow => follow_one(app, agent, repo, &target).await,
+                    BulkAction::Unfollow => unfollow_one(app, agent, repo, &target).await,
+                };
+                (target, outcome)
+            }))
+            .buffer_unordered(BULK_CONCURRENCY)
+            .collect()
+            .await
+        };
+
+        let mut rate_limited = Vec::new();
+        for (target, outcome) in outcomes {
+            match outcome {
+                ItemOutcome::Done(uri) => {
+                    update_job_item(db, job_id, &target, "done", uri.as_deref(), None).await?;
+                    processed += 1;
+                }
+                ItemOutcome::Failed(err) => {
+                    update_job_item(db, job_id, &target, "failed", None, Some(&err)).await?;
+                    processed += 1;
+                }
+                ItemOutcome::RateLimited => rate_limited.push(target),
+            }
+        }
+
+        let hit_rate_limit = !rate_limited.is_empty();
+        let _ = app.emit(
+            "bulk_graph_progress",
+            BulkGraphProgressEvent {
+                job_id: job_id.to_string(),
+                action: action.as_str().to_string(),
+                processed,
+                total,
+                rate_limited: hit_rate_limit,
+            },
+        );
+
+        if hit_rate_limit {
+            if rate_limit_pauses >= MAX_RATE_LIMIT_PAUSES {
+                // Park the rest; the job can be resumed later.
+                rate_limited.append(&mut queue);
+                pause_job_items(db, job_id, &rate_limited).await?;
+                break;
+            }
+            rate_limit_pauses += 1;
+            rate_limited.append(&mut queue);
+            queue = rate_limited;
+            tokio::time::sleep(tokio::time::Duration::from_secs(RATE_LIMIT_PAUSE_SECS)).await;
+        }
+    }
+
+    let results: Vec<BulkGraphItemResult> = load_job_items(db, job_id, &user_did)
+        .await?
+        .into_iter()
+        .map(|(_, target, status, uri, error)| BulkGraphItemResult {
+            target,
+            status,
+            uri,
+            error,
+        })
+        .collect();
+    let pending = results
+        .iter()
+        .filter(|r| r.status == "pending" || r.status == "paused")
+        .count() as u32;
+
+    Ok(BulkGraphResponse {
+        job_id: job_id.to_string(),
+        action: action.as_str().to_string(),
+        results,
+        pending,
+    })
+}
+
+async fn start_bulk_job(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    db: &SqlitePool,
+    action: BulkAction,
+    targets: Vec<String>,
+) -> Result<BulkGraphResponse, AppError> {
+    let user_did = current_repo_did()?.to_string();
+
+    let mut seen = HashSet::new();
+    let targets: Vec<String> = targets
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect();
+
+    let job_id = Uuid::new_v4().to_string();
+    insert_job_items(db, &job_id, &user_did, action, &targets).await?;
+
+    run_bulk_job(app, agent_state, db, &job_id, action).await
+}
+
+/// Follow many accounts at once, skipping ones already followed
+#[tauri::command]
+pub async fn bulk_follow(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    dids: Vec<String>,
+) -> Result<BulkGraphResponse, AppError> {
+    let db_pool = db.inner().clone();
+    start_bulk_job(
+        &app,
+        agent_state.inner(),
+        db_pool.as_ref(),
+        BulkAction::Follow,
+        dids,
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Delete many follow records at once
+#[tauri::command]
+pub async fn bulk_unfollow(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    follow_uris: Vec<String>,
+) -> Result<BulkGraphResponse, AppError> {
+    let db_pool = db.inner().clone();
+    start_bulk_job(
+        &app,
+        agent_state.inner(),
+        db_pool.as_ref(),
+        BulkAction::Unfollow,
+        follow_uris,
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Resume an interrupted bulk follow/unfollow job (pending, paused and failed items are retried)
+#[tauri::command]
+pub async fn resume_bulk_graph_job(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    job_id: String,
+) -> Result<BulkGraphResponse, AppError> {
+    let db_pool = db.inner().clone();
+    let user_did = current_repo_did()?.to_string();
+
+    let action = load_job_items(db_pool.as_ref(), &job_id, &user_did)
+        .await?
+        .first()
+        .and_then(|(action, _, _, _, _)| BulkAction::parse(action))
+        .ok_or_else(|| AppError::NotFound(format!("bulk job {job_id}")))?;
+
+    run_bulk_job(&app, agent_state.inner(), db_pool.as_ref(), &job_id, action)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    async fn statuses(db: &SqlitePool, job_id: &str) -> Vec<(String, String)> {
+        load_job_items(db, job_id, "did:plc:me")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, target, status, _, _)| (target, status))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn job_items_are_inserted_together() {
+        let db = crate::db::test_pool().await;
+        let all = targets(&["did:plc:a", "did:plc:b", "did:plc:c"]);
+        insert_job_items(&db, "job", "did:plc:me", BulkAction::Follow, &all)
+            .await
+            .unwrap();
+
+        let items = statuses(&db, "job").await;
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|(_, status)| status == "pending"));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_leftovers_are_paused_and_resumable() {
+        let db = crate::db::test_pool().await;
+        let all = targets(&["did:plc:a", "did:plc:b", "did:plc:c"]);
+        insert_job_items(&db, "job", "did:plc:me", BulkAction::Follow, &all)
+            .await
+            .unwrap();
+        update_job_item(&db, "job", "did:plc:a", "done", Some("at://x"), None)
+            .await
+            .unwrap();
+
+        pause_job_items(
+            &db,
+            "job",
+            &targets(&["did:plc:a", "did:plc:b", "did:plc:c"]),
+        )
+        .await
+        .unwrap();
+
+        let items = statuses(&db, "job").await;
+        let status_of = |target: &str| {
+            items
+                .iter()
+                .find(|(t, _)| t == target)
+                .map(|(_, s)| s.as_str())
+        };
+        // Finished items keep their status
+        assert_eq!(status_of("did:plc:a"), Some("done"));
+        assert_eq!(status_of("did:plc:b"), Some("paused"));
+        assert_eq!(status_of("did:plc:c"), Some("paused"));
+        assert_eq!(items.iter().filter(|(_, s)| is_unfinished(s)).count(), 2);
+    }
+}
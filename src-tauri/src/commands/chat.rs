@@ -1,14 +1,31 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::connectivity;
+use crate::db::DbState;
 use crate::error::AppError;
+use crate::session::{get_stored_session, require_chat_scope};
+use crate::unread;
+use bsky_sdk::api::chat::bsky::convo::defs::{
+    MessageInputEmbedRefs, MessageView, MessageViewEmbedRefs,
+};
 use bsky_sdk::api::types::string::Did;
 use bsky_sdk::api::types::LimitedNonZeroU8;
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
 
 const CHAT_PROXY_DID: &str = "did:web:api.bsky.chat";
 const CHAT_SERVICE_TYPE: &str = "bsky_chat";
 
+/// Chat service DID for `api_with_proxy`, once the session is known to have DM access
+fn chat_proxy_did() -> Result<Did, AppError> {
+    require_chat_scope()?;
+    CHAT_PROXY_DID
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))
+}
+
 /// Convert datetime string to ISO 8601 format for JavaScript Date parsing
 fn format_datetime_for_js(dt_str: &str) -> String {
     // The Bluesky SDK's Datetime type should already be in ISO 8601 format
@@ -43,6 +60,8 @@ pub struct ConversationInfo {
     pub last_message: Option<MessageInfo>,
     pub unread_count: u32,
     pub muted: bool,
+    /// Hidden on this device with `hide_convo`; only `get_conversations` reports it
+    pub hidden: bool,
 }
 
 #[derive(Serialize)]
@@ -60,6 +79,88 @@ pub struct MessageInfo {
     pub sender_did: String,
     pub text: String,
     pub sent_at: String,
+    pub embed: Option<MessageEmbedInfo>,
+}
+
+/// Summary of a post embedded in a message
+#[derive(Serialize)]
+pub struct MessageEmbedInfo {
+    pub uri: String,
+    /// "available", "not_found", "blocked" or "unsupported"
+    pub status: String,
+    pub cid: Option<String>,
+    pub author_did: Option<String>,
+    pub author_handle: Option<String>,
+    pub author_display_name: Option<String>,
+    pub author_avatar: Option<String>,
+    pub text: Option<String>,
+    pub created_at: Option<String>,
+}
+
+fn message_embed_to_info(
+    embed: &bsky_sdk::api::types::Union<MessageViewEmbedRefs>,
+) -> Option<MessageEmbedInfo> {
+    use bsky_sdk::api::types::Union;
+
+    let Union::Refs(MessageViewEmbedRefs::AppBskyEmbedRecordView(view)) = embed else {
+        return None;
+    };
+
+    // Same JSON walk as media.rs uses for post record embeds
+    let json = serde_json::to_value(view).ok()?;
+    let record = json.get("record")?;
+    let str_at = |value: &serde_json::Value, path: &[&str]| -> Option<String> {
+        path.iter()
+            .try_fold(value, |v, key| v.get(key))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+    };
+
+    let uri = str_at(record, &["uri"]).unwrap_or_default();
+    let record_type = str_at(record, &["$type"]).unwrap_or_default();
+    let status = match record_type.as_str() {
+        "app.bsky.embed.record#viewRecord" => "available",
+        "app.bsky.embed.record#viewNotFound" | "app.bsky.embed.record#viewDetached" => "not_found",
+        "app.bsky.embed.record#viewBlocked" => "blocked",
+        _ => "unsupported",
+    };
+
+    if status != "available" {
+        return Some(MessageEmbedInfo {
+            uri,
+            status: status.to_string(),
+            cid: None,
+            author_did: None,
+            author_handle: None,
+            author_display_name: None,
+            author_avatar: None,
+            text: None,
+            created_at: None,
+        });
+    }
+
+    Some(MessageEmbedInfo {
+        uri,
+        status: status.to_string(),
+        cid: str_at(record, &["cid"]),
+        author_did: str_at(record, &["author", "did"]),
+        author_handle: str_at(record, &["author", "handle"]),
+        author_display_name: str_at(record, &["author", "displayName"]),
+        author_avatar: str_at(record, &["author", "avatar"]),
+        text: str_at(record, &["value", "text"]),
+        created_at: str_at(record, &["value", "createdAt"]),
+    })
+}
+
+fn message_view_to_info(mv: &MessageView) -> MessageInfo {
+    MessageInfo {
+        id: mv.id.clone(),
+        rev: mv.rev.clone(),
+        sender_did: mv.sender.did.to_string(),
+        text: mv.text.clone(),
+        sent_at: format_datetime_for_js(&mv.sent_at.as_ref().to_string()),
+        embed: mv.embed.as_ref().and_then(message_embed_to_info),
+    }
 }
 
 #[derive(Serialize)]
@@ -68,19 +169,57 @@ pub struct ConversationsResponse {
     pub cursor: Option<String>,
 }
 
-/// Get list of DM conversations
+/// Convos hidden on this device, with the id of the last message each had when hidden
+async fn load_hidden_convos(
+    db: &SqlitePool,
+    user_did: &str,
+) -> Result<HashMap<String, Option<String>>, AppError> {
+    let rows = sqlx::query_as::<_, (String, Option<String>)>(
+        r#"
+        SELECT convo_id, last_message_id
+        FROM hidden_convos
+        WHERE user_did = ?1
+        "#,
+    )
+    .bind(user_did)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("hidden convos read failed: {e}")))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+async fn delete_hidden_convo(
+    db: &SqlitePool,
+    user_did: &str,
+    convo_id: &str,
+) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM hidden_convos WHERE user_did = ?1 AND convo_id = ?2")
+        .bind(user_did)
+        .bind(convo_id)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::InternalError(format!("unhide convo failed: {e}")))?;
+    Ok(())
+}
+
+/// Get list of DM conversations. Convos hidden with `hide_convo` are left out unless
+/// `include_hidden` is set; one that got a new message since it was hidden is unhidden.
 #[tauri::command]
 pub async fn get_conversations(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     cursor: Option<String>,
+    include_hidden: Option<bool>,
 ) -> Result<ConversationsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
+    let user_did = get_stored_session()?.did;
+    let db = db.inner().as_ref();
+    let hidden_convos = load_hidden_convos(db, &user_did).await?;
 
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
     let response = chat_api
@@ -97,7 +236,8 @@ pub async fn get_conversations(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let conversations: Vec<ConversationInfo> = response
         .data
@@ -120,13 +260,9 @@ pub async fn get_conversations(
                 use bsky_sdk::api::types::Union;
 
                 match lm {
-                    Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => Some(MessageInfo {
-                        id: mv.id.clone(),
-                        rev: mv.rev.clone(),
-                        sender_did: mv.sender.did.to_string(),
-                        text: mv.text.clone(),
-                        sent_at: format_datetime_for_js(&mv.sent_at.as_ref().to_string()),
-                    }),
+                    Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => {
+                        Some(message_view_to_info(mv))
+                    }
                     _ => None,
                 }
             });
@@ -138,12 +274,28 @@ pub async fn get_conversations(
                 last_message,
                 unread_count: c.unread_count as u32,
                 muted: c.muted,
+                hidden: false,
             }
         })
         .collect();
 
+    let mut visible = Vec::with_capacity(conversations.len());
+    for mut convo in conversations {
+        if let Some(hidden_last) = hidden_convos.get(&convo.id) {
+            let last_id = convo.last_message.as_ref().map(|m| &m.id);
+            if last_id != hidden_last.as_ref() {
+                delete_hidden_convo(db, &user_did, &convo.id).await?;
+            } else if include_hidden.unwrap_or(false) {
+                convo.hidden = true;
+            } else {
+                continue;
+            }
+        }
+        visible.push(convo);
+    }
+
     Ok(ConversationsResponse {
-        conversations,
+        conversations: visible,
         cursor: response.data.cursor,
     })
 }
@@ -163,16 +315,14 @@ pub struct MessagesResponse {
 /// Get messages in a conversation
 #[tauri::command]
 pub async fn get_messages(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: GetMessagesRequest,
 ) -> Result<MessagesResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
     let response = chat_api
@@ -188,7 +338,8 @@ pub async fn get_messages(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     use bsky_sdk::api::chat::bsky::convo::get_messages::OutputMessagesItem;
     use bsky_sdk::api::types::Union;
@@ -199,13 +350,7 @@ pub async fn get_messages(
         .iter()
         .filter_map(|m| match m {
             Union::Refs(OutputMessagesItem::ChatBskyConvoDefsMessageView(mv)) => {
-                Some(MessageInfo {
-                    id: mv.id.clone(),
-                    rev: mv.rev.clone(),
-                    sender_did: mv.sender.did.to_string(),
-                    text: mv.text.clone(),
-                    sent_at: format_datetime_for_js(&mv.sent_at.as_ref().to_string()),
-                })
+                Some(message_view_to_info(mv))
             }
             _ => None,
         })
@@ -221,23 +366,80 @@ pub async fn get_messages(
 pub struct SendMessageRequest {
     pub convo_id: String,
     pub text: String,
+    /// Post to embed as an app.bsky.embed.record
+    pub embed_uri: Option<String>,
+    /// CID of the embedded post; looked up when omitted
+    pub embed_cid: Option<String>,
+}
+
+/// Build a record embed for a message, checking that the post is still viewable
+async fn build_message_embed(
+    agent: &AppAgent,
+    uri: &str,
+    cid: Option<&str>,
+) -> Result<bsky_sdk::api::types::Union<MessageInputEmbedRefs>, AppError> {
+    let response = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_posts(
+            bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                uris: vec![uri.to_string()],
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    // Deleted posts and posts hidden by blocks are simply absent from getPosts
+    let post = response.data.posts.first().ok_or_else(|| {
+        AppError::NotFound("The post to share was deleted or is not available".into())
+    })?;
+
+    let cid = match cid {
+        Some(cid) => cid
+            .parse()
+            .map_err(|_| AppError::ApiError("Invalid embed CID".into()))?,
+        None => post.cid.clone(),
+    };
+
+    Ok(bsky_sdk::api::types::Union::Refs(
+        MessageInputEmbedRefs::AppBskyEmbedRecordMain(Box::new(
+            bsky_sdk::api::app::bsky::embed::record::MainData {
+                record: bsky_sdk::api::com::atproto::repo::strong_ref::MainData {
+                    uri: post.uri.clone(),
+                    cid,
+                }
+                .into(),
+            }
+            .into(),
+        )),
+    ))
 }
 
 /// Send a message in a conversation
 #[tauri::command]
 pub async fn send_message(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: SendMessageRequest,
 ) -> Result<MessageInfo, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
+    let embed = match request.embed_uri.as_deref() {
+        Some(uri) => Some(
+            build_message_embed(&agent, uri, request.embed_cid.as_deref())
+                .await
+                .inspect_err(|err| connectivity::watch_error(&app, err))?,
+        ),
+        None => None,
+    };
+
     let response = chat_api
         .chat
         .bsky
@@ -246,7 +448,7 @@ pub async fn send_message(
             bsky_sdk::api::chat::bsky::convo::send_message::InputData {
                 convo_id: request.convo_id,
                 message: bsky_sdk::api::chat::bsky::convo::defs::MessageInputData {
-                    embed: None,
+                    embed,
                     facets: None,
                     text: request.text,
                 }
@@ -255,15 +457,10 @@ pub async fn send_message(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
-    Ok(MessageInfo {
-        id: response.data.id,
-        rev: response.data.rev,
-        sender_did: response.data.sender.did.to_string(),
-        text: response.data.text,
-        sent_at: format_datetime_for_js(&response.data.sent_at.as_ref().to_string()),
-    })
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(message_view_to_info(&response))
 }
 
 #[derive(Deserialize)]
@@ -274,16 +471,14 @@ pub struct GetConvoForMembersRequest {
 /// Get or create a conversation with specific members
 #[tauri::command]
 pub async fn get_convo_for_members(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: GetConvoForMembersRequest,
 ) -> Result<ConversationInfo, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
     // Parse member DIDs
@@ -307,7 +502,8 @@ pub async fn get_convo_for_members(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let members: Vec<ConversationMember> = response
         .data
@@ -327,13 +523,9 @@ pub async fn get_convo_for_members(
         use bsky_sdk::api::types::Union;
 
         match lm {
-            Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => Some(MessageInfo {
-                id: mv.id.clone(),
-                rev: mv.rev.clone(),
-                sender_did: mv.sender.did.to_string(),
-                text: mv.text.clone(),
-                sent_at: format_datetime_for_js(&mv.sent_at.as_ref().to_string()),
-            }),
+            Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => {
+                Some(message_view_to_info(mv))
+            }
             _ => None,
         }
     });
@@ -345,6 +537,7 @@ pub async fn get_convo_for_members(
         last_message,
         unread_count: response.data.convo.unread_count as u32,
         muted: response.data.convo.muted,
+        hidden: false,
     })
 }
 
@@ -353,33 +546,18 @@ pub struct GetConvoRequest {
     pub convo_id: String,
 }
 
-/// Get a specific conversation by ID
-#[tauri::command]
-pub async fn get_convo(
-    agent_state: State<'_, AgentState>,
-    request: GetConvoRequest,
-) -> Result<ConversationInfo, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
-
+async fn fetch_convo(agent: &AppAgent, convo_id: String) -> Result<ConversationInfo, AppError> {
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
     let response = chat_api
         .chat
         .bsky
         .convo
-        .get_convo(
-            bsky_sdk::api::chat::bsky::convo::get_convo::ParametersData {
-                convo_id: request.convo_id,
-            }
-            .into(),
-        )
+        .get_convo(bsky_sdk::api::chat::bsky::convo::get_convo::ParametersData { convo_id }.into())
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)?;
 
     let members: Vec<ConversationMember> = response
         .data
@@ -399,13 +577,9 @@ pub async fn get_convo(
         use bsky_sdk::api::types::Union;
 
         match lm {
-            Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => Some(MessageInfo {
-                id: mv.id.clone(),
-                rev: mv.rev.clone(),
-                sender_did: mv.sender.did.to_string(),
-                text: mv.text.clone(),
-                sent_at: format_datetime_for_js(&mv.sent_at.as_ref().to_string()),
-            }),
+            Union::Refs(ConvoViewLastMessageRefs::MessageView(mv)) => {
+                Some(message_view_to_info(mv))
+            }
             _ => None,
         }
     });
@@ -417,9 +591,65 @@ pub async fn get_convo(
         last_message,
         unread_count: response.data.convo.unread_count as u32,
         muted: response.data.convo.muted,
+        hidden: false,
     })
 }
 
+/// Get a specific conversation by ID
+#[tauri::command]
+pub async fn get_convo(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    request: GetConvoRequest,
+) -> Result<ConversationInfo, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    fetch_convo(&agent, request.convo_id)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Remove a convo from the conversation list on this device only; the convo stays
+/// joined on the server. It comes back by itself when a new message arrives.
+#[tauri::command]
+pub async fn hide_convo(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    convo_id: String,
+) -> Result<(), AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let user_did = get_stored_session()?.did;
+    let convo = fetch_convo(&agent, convo_id)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO hidden_convos (user_did, convo_id, last_message_id, hidden_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(user_did, convo_id) DO UPDATE SET
+            last_message_id = excluded.last_message_id,
+            hidden_at = excluded.hidden_at
+        "#,
+    )
+    .bind(&user_did)
+    .bind(&convo.id)
+    .bind(convo.last_message.as_ref().map(|m| m.id.as_str()))
+    .bind(Utc::now().to_rfc3339())
+    .execute(db.inner().as_ref())
+    .await
+    .map_err(|e| AppError::InternalError(format!("hide convo failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Put a hidden convo back in the conversation list
+#[tauri::command]
+pub async fn unhide_convo(db: State<'_, DbState>, convo_id: String) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    delete_hidden_convo(db.inner().as_ref(), &user_did, &convo_id).await
+}
+
 #[derive(Deserialize)]
 pub struct UpdateReadRequest {
     pub convo_id: String,
@@ -435,16 +665,14 @@ pub struct UpdateReadResponse {
 /// Mark a conversation as read up to a specific message
 #[tauri::command]
 pub async fn update_read(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: UpdateReadRequest,
 ) -> Result<UpdateReadResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
-        .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
 
     let response = chat_api
@@ -459,60 +687,405 @@ pub async fn update_read(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let convo_id = response.data.convo.id.clone();
+    let unread_count = response.data.convo.unread_count as u32;
+    match unread::update_chat_convo(&app, &convo_id, unread_count) {
+        Some(count) => emit_chat_unread_count(&app, count),
+        None => emit_chat_unread_changed(&app, &agent).await,
+    }
 
     Ok(UpdateReadResponse {
-        convo_id: response.data.convo.id.clone(),
-        unread_count: response.data.convo.unread_count as u32,
+        convo_id,
+        unread_count,
     })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct ChatUnreadCountResponse {
     pub count: u32,
 }
 
+/// Collect every conversation with unread messages, following cursors to the end.
+/// The chat service has no count endpoint, so this is the accurate total: the
+/// `read_state` filter keeps pages to unread convos only.
+async fn list_unread_convos(agent: &AppAgent) -> Result<Vec<(String, u32)>, AppError> {
+    // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
+    let chat_did = chat_proxy_did()?;
+    let chat_api = &agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
+    let max_limit = LimitedNonZeroU8::<100>::try_from(100_u8)
+        .map_err(|_| AppError::InternalError("Invalid static chat unread limit".into()))?;
+
+    collect_unread_convos(|cursor| {
+        let limit = max_limit.clone();
+        async move {
+            let response = chat_api
+                .chat
+                .bsky
+                .convo
+                .list_convos(
+                    bsky_sdk::api::chat::bsky::convo::list_convos::ParametersData {
+                        cursor,
+                        limit: Some(limit),
+                        read_state: Some("unread".into()),
+                        status: None,
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(connectivity::xrpc_error)?;
+
+            let convos = response
+                .data
+                .convos
+                .iter()
+                .map(|c| (c.id.clone(), c.unread_count))
+                .collect();
+            Ok((convos, response.data.cursor.clone()))
+        }
+    })
+    .await
+}
+
+/// Page through `fetch_page` (convo id and unread count per convo, next cursor) and keep
+/// the convos with unread messages
+async fn collect_unread_convos<F, Fut>(mut fetch_page: F) -> Result<Vec<(String, u32)>, AppError>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<(String, i64)>, Option<String>), AppError>>,
+{
+    let mut unread = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut last_cursor: Option<String> = None;
+    loop {
+        let (convos, next_cursor) = fetch_page(cursor.take()).await?;
+
+        let page_empty = convos.is_empty();
+        unread.extend(
+            convos
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|(id, count)| (id, count as u32)),
+        );
+
+        match next_cursor {
+            // A cursor that doesn't advance would page forever
+            Some(next) if !page_empty && Some(&next) != last_cursor.as_ref() => {
+                last_cursor = Some(next.clone());
+                cursor = Some(next);
+            }
+            _ => break,
+        }
+    }
+
+    Ok(unread)
+}
+
+/// Recount unread messages from every conversation and cache the result; returns the total
+pub(crate) async fn refresh_chat_unread_count(
+    app: &AppHandle,
+    agent: &AppAgent,
+) -> Result<u32, AppError> {
+    let convos = list_unread_convos(agent).await?;
+    Ok(unread::store_chat_counts(app, &convos))
+}
+
+fn emit_chat_unread_count(app: &AppHandle, count: u32) {
+    if let Err(err) = app.emit("chat_unread_changed", ChatUnreadCountResponse { count }) {
+        tracing::warn!("emit unread change failed: {err}");
+    }
+}
+
+/// Recount unread messages and tell the frontend so the badge stays accurate
+async fn emit_chat_unread_changed(app: &AppHandle, agent: &AppAgent) {
+    match refresh_chat_unread_count(app, agent).await {
+        Ok(count) => emit_chat_unread_count(app, count),
+        Err(err) => tracing::warn!("unread recount failed: {err}"),
+    }
+}
+
 /// Get total unread message count across all conversations
 #[tauri::command]
 pub async fn get_chat_unread_count(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
 ) -> Result<ChatUnreadCountResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    if let Some(count) = unread::cached_chat_count() {
+        return Ok(ChatUnreadCountResponse { count });
+    }
 
-    // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
-    let chat_did: Did = CHAT_PROXY_DID
+    let agent = current_agent(&agent_state).await?;
+    let count = refresh_chat_unread_count(&app, &agent)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(ChatUnreadCountResponse { count })
+}
+
+#[derive(Serialize)]
+pub struct MarkAllConvosReadResponse {
+    pub marked: u32,
+}
+
+/// Mark every conversation with unread messages as read
+#[tauri::command]
+pub async fn mark_all_convos_read(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+) -> Result<MarkAllConvosReadResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    // Collect first: marking while paging the unread filter would shift the cursor
+    let unread = list_unread_convos(&agent)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let chat_did = chat_proxy_did()?;
+    let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
+
+    let mut marked = 0;
+    for (convo_id, _) in unread {
+        let result = chat_api
+            .chat
+            .bsky
+            .convo
+            .update_read(
+                bsky_sdk::api::chat::bsky::convo::update_read::InputData {
+                    convo_id: convo_id.clone(),
+                    message_id: None,
+                }
+                .into(),
+            )
+            .await;
+
+        match result {
+            Ok(_) => marked += 1,
+            Err(err) => tracing::warn!("mark read failed for {convo_id}: {err}"),
+        }
+    }
+
+    emit_chat_unread_changed(&app, &agent).await;
+
+    Ok(MarkAllConvosReadResponse { marked })
+}
+
+#[derive(Serialize)]
+pub struct CanMessageResponse {
+    pub can_chat: bool,
+    /// Why messaging is unavailable, suitable for showing next to a disabled button
+    pub reason: Option<String>,
+    /// Existing conversation with the member, if any
+    pub convo_id: Option<String>,
+}
+
+fn chat_allow_incoming(
+    profile: &bsky_sdk::api::app::bsky::actor::defs::ProfileViewDetailed,
+) -> Option<&str> {
+    profile
+        .associated
+        .as_ref()
+        .and_then(|a| a.chat.as_ref())
+        .map(|c| c.allow_incoming.as_str())
+}
+
+/// Explain why a DM can't be started, using both accounts' chat preferences
+async fn explain_chat_unavailable(agent: &AppAgent, viewer_did: &str, target: &Did) -> String {
+    let fallback = "This account can't be messaged right now".to_string();
+
+    let actors = [viewer_did.to_string(), target.to_string()]
+        .iter()
+        .filter_map(|actor| actor.parse().ok())
+        .collect();
+
+    let Ok(response) = agent
+        .api
+        .app
+        .bsky
+        .actor
+        .get_profiles(
+            bsky_sdk::api::app::bsky::actor::get_profiles::ParametersData { actors }.into(),
+        )
+        .await
+    else {
+        return fallback;
+    };
+
+    let viewer = response
+        .data
+        .profiles
+        .iter()
+        .find(|p| p.did.as_str() == viewer_did);
+    let target = response
+        .data
+        .profiles
+        .iter()
+        .find(|p| p.did.as_str() == target.as_str());
+
+    if let Some(target) = target {
+        let target_viewer = target.viewer.as_ref();
+        if target_viewer.and_then(|v| v.blocking.as_ref()).is_some() {
+            return "You have blocked this account".into();
+        }
+        if target_viewer.and_then(|v| v.blocked_by).unwrap_or(false) {
+            return "This account has blocked you".into();
+        }
+
+        match chat_allow_incoming(target) {
+            Some("none") => return "This account doesn't accept direct messages".into(),
+            Some("following") if target_viewer.and_then(|v| v.followed_by.as_ref()).is_none() => {
+                return "This account only accepts messages from accounts it follows".into();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(viewer) = viewer {
+        if chat_allow_incoming(viewer) == Some("none") {
+            return "Your chat settings don't allow direct messages".into();
+        }
+    }
+
+    fallback
+}
+
+/// Check whether a DM can be started with an account before opening a conversation
+#[tauri::command]
+pub async fn can_message(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did: String,
+) -> Result<CanMessageResponse, AppError> {
+    let viewer_did = crate::session::get_stored_session()?.did;
+    let target: Did = did
         .parse()
-        .map_err(|_| AppError::ApiError("Invalid chat proxy DID".into()))?;
+        .map_err(|_| AppError::ApiError(format!("Invalid DID: {}", did)))?;
+
+    if target.as_str() == viewer_did {
+        return Ok(CanMessageResponse {
+            can_chat: false,
+            reason: Some("You can't message yourself".into()),
+            convo_id: None,
+        });
+    }
+
+    let agent = current_agent(&agent_state).await?;
+
+    // Use api_with_proxy to get a service with proxy header set (avoids polluting shared agent state)
+    let chat_did = chat_proxy_did()?;
     let chat_api = agent.api_with_proxy(chat_did, CHAT_SERVICE_TYPE);
-    let max_limit = LimitedNonZeroU8::<100>::try_from(100_u8)
-        .map_err(|_| AppError::InternalError("Invalid static chat unread limit".into()))?;
 
-    // Fetch conversations and sum unread counts
     let response = chat_api
         .chat
         .bsky
         .convo
-        .list_convos(
-            bsky_sdk::api::chat::bsky::convo::list_convos::ParametersData {
-                cursor: None,
-                limit: Some(max_limit),
-                read_state: None,
-                status: None,
+        .get_convo_availability(
+            bsky_sdk::api::chat::bsky::convo::get_convo_availability::ParametersData {
+                members: vec![target.clone()],
             }
             .into(),
         )
+        .await;
+
+    match response {
+        Ok(output) if output.data.can_chat => Ok(CanMessageResponse {
+            can_chat: true,
+            reason: None,
+            convo_id: output.data.convo.as_ref().map(|c| c.id.clone()),
+        }),
+        Ok(_) => Ok(CanMessageResponse {
+            can_chat: false,
+            reason: Some(explain_chat_unavailable(&agent, &viewer_did, &target).await),
+            convo_id: None,
+        }),
+        // The chat service rejects the viewer outright (e.g. an app password without DM access)
+        Err(atrium_xrpc::Error::XrpcResponse(resp)) if resp.status.as_u16() == 401 => {
+            Ok(CanMessageResponse {
+                can_chat: false,
+                reason: Some("Direct messages aren't available for this session".into()),
+                convo_id: None,
+            })
+        }
+        Err(e) => {
+            let err = connectivity::xrpc_error(e);
+            connectivity::watch_error(&app, &err);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    type Page = (Vec<(String, i64)>, Option<String>);
+
+    fn page(convos: &[(&str, i64)], cursor: Option<&str>) -> Page {
+        (
+            convos.iter().map(|(id, n)| (id.to_string(), *n)).collect(),
+            cursor.map(str::to_string),
+        )
+    }
+
+    #[tokio::test]
+    async fn unread_convos_are_collected_across_pages() {
+        let requested = RefCell::new(Vec::new());
+        let unread = collect_unread_convos(|cursor| {
+            requested.borrow_mut().push(cursor.clone());
+            async move {
+                Ok(match cursor.as_deref() {
+                    None => page(&[("a", 2), ("b", 0)], Some("p2")),
+                    Some("p2") => page(&[("c", 5)], Some("p3")),
+                    _ => page(&[("d", 1)], None),
+                })
+            }
+        })
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .unwrap();
 
-    let total_unread: u32 = response
-        .data
-        .convos
-        .iter()
-        .map(|c| c.unread_count as u32)
-        .sum();
+        assert_eq!(
+            unread,
+            vec![
+                ("a".to_string(), 2),
+                ("c".to_string(), 5),
+                ("d".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            *requested.borrow(),
+            vec![None, Some("p2".to_string()), Some("p3".to_string())]
+        );
+    }
 
-    Ok(ChatUnreadCountResponse {
-        count: total_unread,
-    })
+    #[tokio::test]
+    async fn paging_stops_on_a_repeated_cursor_or_empty_page() {
+        let calls = RefCell::new(0);
+        let unread = collect_unread_convos(|_| {
+            *calls.borrow_mut() += 1;
+            async { Ok(page(&[("a", 1)], Some("same"))) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*calls.borrow(), 2);
+        assert_eq!(unread.len(), 2);
+
+        let unread = collect_unread_convos(|_| async { Ok(page(&[], Some("more"))) })
+            .await
+            .unwrap();
+        assert!(unread.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_fails_the_count() {
+        let result = collect_unread_convos(|cursor| async move {
+            match cursor {
+                None => Ok(page(&[("a", 1)], Some("p2"))),
+                Some(_) => Err(AppError::NetworkError("timeout".into())),
+            }
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::NetworkError(_))));
+    }
 }
@@ -0,0 +1,657 @@
+use crate::commands::auth::{current_agent, fresh_access_jwt, AgentState};
+use crate::connectivity;
+use crate::error::AppError;
+use crate::http;
+use crate::session::{get_stored_session, StoredSession};
+use chrono::Utc;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Cancel flags of running exports by job id, so cancelling one leaves the others
+static EXPORT_JOBS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A running export registered in `EXPORT_JOBS` until it is dropped
+struct ExportJob {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ExportJob {
+    /// Register a job under the caller's id, or a fresh one when none is given
+    fn start(id: Option<String>) -> Self {
+        let id = id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        EXPORT_JOBS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), cancelled.clone());
+        ExportJob { id, cancelled }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ExportJob {
+    fn drop(&mut self) {
+        EXPORT_JOBS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+/// Cancel the export registered as `job_id`, or every registered export for `None`.
+/// Returns whether any job was found.
+fn cancel_export_jobs(job_id: Option<&str>) -> bool {
+    let jobs = EXPORT_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut found = false;
+    for (id, cancelled) in jobs.iter() {
+        if job_id.is_none_or(|job_id| job_id == id) {
+            cancelled.store(true, Ordering::SeqCst);
+            found = true;
+        }
+    }
+    found
+}
+
+/// Output written under a `.part` name beside its destination and moved into place by
+/// `finish`. Dropped unfinished (an error or a cancel) it is deleted, so a failed export
+/// never leaves a truncated file that looks complete.
+struct PartFile {
+    part: PathBuf,
+    dest: PathBuf,
+    finished: bool,
+}
+
+impl PartFile {
+    fn new(dest: PathBuf) -> Self {
+        let mut part = dest.clone().into_os_string();
+        part.push(".part");
+        PartFile {
+            part: part.into(),
+            dest,
+            finished: false,
+        }
+    }
+
+    async fn finish(mut self) -> Result<(), AppError> {
+        tokio::fs::rename(&self.part, &self.dest)
+            .await
+            .map_err(|e| AppError::InternalError(format!("move {}: {e}", self.dest.display())))?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartFile {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = std::fs::remove_file(&self.part);
+        }
+    }
+}
+
+/// Emit a progress event at most once per this many bytes of CAR data.
+const PROGRESS_EMIT_BYTES: u64 = 1024 * 1024;
+
+#[derive(Deserialize)]
+pub struct ExportAccountRequest {
+    pub dest_dir: String,
+    #[serde(default)]
+    pub include_blobs: bool,
+    /// Id to cancel this export with; generated when left out
+    #[serde(default)]
+    pub job_id: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ExportProgressEvent {
+    pub job_id: String,
+    pub stage: String,
+    pub repo_bytes: u64,
+    pub blobs_done: u32,
+    pub blobs_total: u32,
+}
+
+#[derive(Serialize)]
+pub struct ExportManifest {
+    pub did: String,
+    pub handle: String,
+    pub service_url: String,
+    pub exported_at: String,
+    pub repo_file: String,
+    pub repo_bytes: u64,
+    pub blobs: Vec<String>,
+    pub failed_blobs: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportAccountResponse {
+    pub cancelled: bool,
+    pub manifest_path: Option<String>,
+    pub repo_bytes: u64,
+    pub blob_count: u32,
+    pub failed_blob_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ListBlobsOutput {
+    cids: Vec<String>,
+    cursor: Option<String>,
+}
+
+fn xrpc_url(session: &StoredSession, method: &str) -> String {
+    format!(
+        "{}/xrpc/{method}",
+        session.service_url.trim_end_matches('/')
+    )
+}
+
+/// Stream a response body to `path`, going through a `PartFile` so the file only
+/// appears once complete. Returns `None` if the job was cancelled mid-download.
+async fn stream_to_file(
+    response: reqwest::Response,
+    path: &Path,
+    job: &ExportJob,
+    mut on_progress: impl FnMut(u64),
+) -> Result<Option<u64>, AppError> {
+    let output = PartFile::new(path.to_path_buf());
+    let mut file = tokio::fs::File::create(&output.part)
+        .await
+        .map_err(|e| AppError::InternalError(format!("create {}: {e}", path.display())))?;
+
+    let mut written: u64 = 0;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        if job.is_cancelled() {
+            return Ok(None);
+        }
+
+        let chunk = chunk.map_err(|e| AppError::NetworkError(format!("export download: {e}")))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| AppError::InternalError(format!("write {}: {e}", path.display())))?;
+        written += chunk.len() as u64;
+        on_progress(written);
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| AppError::InternalError(format!("flush {}: {e}", path.display())))?;
+    drop(file);
+    output.finish().await?;
+
+    Ok(Some(written))
+}
+
+async fn fetch_blob_cids(
+    client: &reqwest::Client,
+    agent_state: &AgentState,
+    session: &StoredSession,
+) -> Result<Vec<String>, AppError> {
+    let url = xrpc_url(session, "com.atproto.sync.listBlobs");
+    let mut cids = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut request = client
+            .get(&url)
+            .query(&[("did", session.did.as_str()), ("limit", "1000")])
+            .bearer_auth(fresh_access_jwt(agent_state).await?);
+        if let Some(cursor) = cursor.as_deref() {
+            request = request.query(&[("cursor", cursor)]);
+        }
+
+        let page = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::NetworkError(format!("listBlobs failed: {e}")))?
+            .json::<ListBlobsOutput>()
+            .await
+            .map_err(|e| AppError::ApiError(format!("listBlobs decode failed: {e}")))?;
+
+        let page_empty = page.cids.is_empty();
+        cids.extend(page.cids);
+
+        match page.cursor {
+            Some(next) if !page_empty => cursor = Some(next),
+            _ => break,
+        }
+    }
+
+    Ok(cids)
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    job: &ExportJob,
+    stage: &str,
+    repo_bytes: u64,
+    blobs_done: u32,
+    blobs_total: u32,
+) {
+    let _ = app.emit(
+        "account_export_progress",
+        ExportProgressEvent {
+            job_id: job.id.clone(),
+            stage: stage.to_string(),
+            repo_bytes,
+            blobs_done,
+            blobs_total,
+        },
+    );
+}
+
+/// Export the current account's repo (CAR) and optionally its blobs to a directory.
+/// Downloads go straight from the PDS to disk so large repos are never fully buffered.
+/// Each file is written under a `.part` name and renamed once complete, and the manifest
+/// comes last, so its presence means the export finished. Requests use a token refreshed
+/// through the app session, since a large export can outlast one.
+#[tauri::command]
+pub async fn export_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    request: ExportAccountRequest,
+) -> Result<ExportAccountResponse, AppError> {
+    let session = get_stored_session()?;
+    let job = ExportJob::start(request.job_id);
+
+    let dest_dir = PathBuf::from(request.dest_dir.trim());
+    tokio::fs::create_dir_all(&dest_dir)
+        .await
+        .map_err(|e| AppError::InternalError(format!("create export dir: {e}")))?;
+
+    let cancelled = ExportAccountResponse {
+        cancelled: true,
+        manifest_path: None,
+        repo_bytes: 0,
+        blob_count: 0,
+        failed_blob_count: 0,
+    };
+
+    let client = http::streaming_client();
+
+    // Repo CAR
+    let repo_file = "repo.car";
+    let response = client
+        .get(xrpc_url(&session, "com.atproto.sync.getRepo"))
+        .query(&[("did", session.did.as_str())])
+        .bearer_auth(fresh_access_jwt(&agent_state).await?)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::NetworkError(format!("getRepo failed: {e}")))
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let mut last_emitted = 0_u64;
+    let Some(repo_bytes) = stream_to_file(response, &dest_dir.join(repo_file), &job, |written| {
+        if written - last_emitted >= PROGRESS_EMIT_BYTES {
+            last_emitted = written;
+            emit_progress(&app, &job, "repo", written, 0, 0);
+        }
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))?
+    else {
+        return Ok(cancelled);
+    };
+    emit_progress(&app, &job, "repo", repo_bytes, 0, 0);
+
+    // Blobs
+    let mut blobs = Vec::new();
+    let mut failed_blobs = Vec::new();
+    if request.include_blobs {
+        let cids = fetch_blob_cids(&client, &agent_state, &session)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+        let blobs_total = cids.len() as u32;
+        let blobs_dir = dest_dir.join("blobs");
+        tokio::fs::create_dir_all(&blobs_dir)
+            .await
+            .map_err(|e| AppError::InternalError(format!("create blobs dir: {e}")))?;
+
+        for (index, cid) in cids.into_iter().enumerate() {
+            if job.is_cancelled() {
+                return Ok(cancelled);
+            }
+
+            let token = fresh_access_jwt(&agent_state).await?;
+            let response = client
+                .get(xrpc_url(&session, "com.atproto.sync.getBlob"))
+                .query(&[("did", session.did.as_str()), ("cid", cid.as_str())])
+                .bearer_auth(token)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+
+            let saved = match response {
+                Ok(response) => stream_to_file(response, &blobs_dir.join(&cid), &job, |_| {}).await,
+                Err(e) => Err(AppError::NetworkError(format!("getBlob {cid}: {e}"))),
+            };
+
+            match saved {
+                Ok(Some(_)) => blobs.push(cid),
+                Ok(None) => return Ok(cancelled),
+                Err(err) => {
+                    tracing::warn!("export blob {cid} failed: {err}");
+                    connectivity::watch_error(&app, &err);
+                    failed_blobs.push(cid);
+                }
+            }
+
+            emit_progress(
+                &app,
+                &job,
+                "blobs",
+                repo_bytes,
+                index as u32 + 1,
+                blobs_total,
+            );
+        }
+    }
+
+    let manifest = ExportManifest {
+        did: session.did.clone(),
+        handle: session.handle.clone(),
+        service_url: session.service_url.clone(),
+        exported_at: Utc::now().to_rfc3339(),
+        repo_file: repo_file.to_string(),
+        repo_bytes,
+        blobs,
+        failed_blobs,
+    };
+
+    let manifest_path = dest_dir.join("manifest.json");
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| AppError::InternalError(format!("manifest encode failed: {e}")))?;
+    let manifest_file = PartFile::new(manifest_path.clone());
+    tokio::fs::write(&manifest_file.part, manifest_json)
+        .await
+        .map_err(|e| AppError::InternalError(format!("manifest write failed: {e}")))?;
+    manifest_file.finish().await?;
+
+    emit_progress(
+        &app,
+        &job,
+        "done",
+        repo_bytes,
+        manifest.blobs.len() as u32,
+        (manifest.blobs.len() + manifest.failed_blobs.len()) as u32,
+    );
+
+    Ok(ExportAccountResponse {
+        cancelled: false,
+        manifest_path: Some(manifest_path.display().to_string()),
+        repo_bytes,
+        blob_count: manifest.blobs.len() as u32,
+        failed_blob_count: manifest.failed_blobs.len() as u32,
+    })
+}
+
+/// Cancel the account or follows/followers CSV export started with `job_id`. Without
+/// one, every running export is cancelled.
+#[tauri::command]
+pub async fn cancel_account_export(job_id: Option<String>) -> Result<(), AppError> {
+    if !cancel_export_jobs(job_id.as_deref()) {
+        tracing::debug!("no running export to cancel for {job_id:?}");
+    }
+    Ok(())
+}
+
+/// Which side of the social graph `export_graph_csv` writes
+#[derive(Clone, Copy)]
+enum GraphExportKind {
+    Follows,
+    Followers,
+}
+
+impl GraphExportKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Follows => "follows",
+            Self::Followers => "followers",
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct GraphExportProgressEvent {
+    /// Id to pass to `cancel_account_export`
+    pub job_id: String,
+    /// "follows" or "followers"
+    pub kind: &'static str,
+    pub rows: u32,
+    pub done: bool,
+}
+
+#[derive(Serialize)]
+pub struct GraphExportResponse {
+    pub cancelled: bool,
+    pub rows: u32,
+    pub path: String,
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Page through the signed-in account's follows or followers and append each page to a
+/// CSV file as it arrives, so memory stays flat however large the graph is. The file is
+/// written as `<dest>.part` and only renamed to `dest_path` once complete. Cancelled by
+/// `cancel_account_export` for `job_id` between pages.
+async fn export_graph_csv(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    kind: GraphExportKind,
+    dest_path: &str,
+    job_id: Option<String>,
+) -> Result<GraphExportResponse, AppError> {
+    use bsky_sdk::api::app::bsky::graph::{get_followers, get_follows};
+
+    let agent = current_agent(agent_state).await?;
+    let actor: bsky_sdk::api::types::string::AtIdentifier = get_stored_session()?
+        .did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid stored DID".into()))?;
+    let job = ExportJob::start(job_id);
+
+    let path = PathBuf::from(dest_path.trim());
+    let output = PartFile::new(path.clone());
+    let file = tokio::fs::File::create(&output.part)
+        .await
+        .map_err(|e| AppError::InternalError(format!("create {}: {e}", path.display())))?;
+    let mut writer = tokio::io::BufWriter::new(file);
+    let write_err = |e: std::io::Error| AppError::InternalError(format!("CSV write failed: {e}"));
+    writer
+        .write_all(b"did,handle,display_name\n")
+        .await
+        .map_err(write_err)?;
+
+    let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(100).ok();
+    let mut cursor: Option<String> = None;
+    let mut rows = 0_u32;
+    loop {
+        if job.is_cancelled() {
+            return Ok(GraphExportResponse {
+                cancelled: true,
+                rows,
+                path: path.display().to_string(),
+            });
+        }
+
+        let (profiles, next) = match kind {
+            GraphExportKind::Follows => {
+                let page = agent
+                    .api
+                    .app
+                    .bsky
+                    .graph
+                    .get_follows(
+                        get_follows::ParametersData {
+                            actor: actor.clone(),
+                            cursor: cursor.clone(),
+                            limit: limit.clone(),
+                        }
+                        .into(),
+                    )
+                    .await
+                    .map_err(connectivity::xrpc_error)?;
+                (page.data.follows, page.data.cursor)
+            }
+            GraphExportKind::Followers => {
+                let page = agent
+                    .api
+                    .app
+                    .bsky
+                    .graph
+                    .get_followers(
+                        get_followers::ParametersData {
+                            actor: actor.clone(),
+                            cursor: cursor.clone(),
+                            limit: limit.clone(),
+                        }
+                        .into(),
+                    )
+                    .await
+                    .map_err(connectivity::xrpc_error)?;
+                (page.data.followers, page.data.cursor)
+            }
+        };
+
+        let mut chunk = String::new();
+        for profile in &profiles {
+            chunk.push_str(&format!(
+                "{},{},{}\n",
+                csv_field(profile.did.as_str()),
+                csv_field(profile.handle.as_str()),
+                csv_field(profile.display_name.as_deref().unwrap_or_default()),
+            ));
+        }
+        writer
+            .write_all(chunk.as_bytes())
+            .await
+            .map_err(write_err)?;
+        rows += profiles.len() as u32;
+
+        let done = next.is_none() || profiles.is_empty();
+        let _ = app.emit(
+            "graph_export_progress",
+            GraphExportProgressEvent {
+                job_id: job.id.clone(),
+                kind: kind.as_str(),
+                rows,
+                done,
+            },
+        );
+        if done {
+            break;
+        }
+        cursor = next;
+    }
+
+    writer.flush().await.map_err(write_err)?;
+    drop(writer);
+    output.finish().await?;
+    Ok(GraphExportResponse {
+        cancelled: false,
+        rows,
+        path: path.display().to_string(),
+    })
+}
+
+/// Write every account the signed-in user follows to a CSV file (did, handle, display_name)
+#[tauri::command]
+pub async fn export_follows(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    dest_path: String,
+    job_id: Option<String>,
+) -> Result<GraphExportResponse, AppError> {
+    export_graph_csv(
+        &app,
+        &agent_state,
+        GraphExportKind::Follows,
+        &dest_path,
+        job_id,
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Write every follower of the signed-in user to a CSV file (did, handle, display_name)
+#[tauri::command]
+pub async fn export_followers(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    dest_path: String,
+    job_id: Option<String>,
+) -> Result<GraphExportResponse, AppError> {
+    export_graph_csv(
+        &app,
+        &agent_state,
+        GraphExportKind::Followers,
+        &dest_path,
+        job_id,
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelling_one_export_leaves_the_others_running() {
+        let follows = ExportJob::start(Some("follows-job".into()));
+        let followers = ExportJob::start(Some("followers-job".into()));
+
+        assert!(cancel_export_jobs(Some("follows-job")));
+        assert!(follows.is_cancelled());
+        assert!(!followers.is_cancelled());
+
+        drop(follows);
+        assert!(!cancel_export_jobs(Some("follows-job")));
+        assert!(!followers.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn unfinished_output_is_removed() {
+        let dir = std::env::temp_dir().join(format!("export-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("follows.csv");
+
+        let failed = PartFile::new(dest.clone());
+        std::fs::write(&failed.part, "did,handle\n").unwrap();
+        let part = failed.part.clone();
+        drop(failed);
+        assert!(!part.exists());
+        assert!(!dest.exists());
+
+        let done = PartFile::new(dest.clone());
+        std::fs::write(&done.part, "did,handle\n").unwrap();
+        done.finish().await.unwrap();
+        assert!(!part.exists());
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "did,handle\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
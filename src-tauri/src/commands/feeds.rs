@@ -1,8 +1,22 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::commands::hidden_posts;
+use crate::commands::links::resolve_at_uri;
+use crate::commands::moderation::{self, LabelVisibility};
+use crate::commands::pagination::fetch_with_cursor_recovery;
+use crate::commands::settings::{load_setting, save_setting};
+use crate::connectivity;
+use crate::db::DbState;
 use crate::error::AppError;
 use crate::media;
+use crate::session::get_stored_session;
+use bsky_sdk::api::app::bsky::actor::defs::PreferencesItem;
+use bsky_sdk::api::app::bsky::actor::put_preferences;
+use bsky_sdk::api::app::bsky::feed::defs::GeneratorView;
+use bsky_sdk::api::types::Union;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, State};
 
 #[derive(Serialize)]
 pub struct FeedInfo {
@@ -18,6 +32,37 @@ pub struct FeedInfo {
     pub avatar: Option<String>,
     pub like_count: u32,
     pub is_saved: bool,
+    /// Only known when fetched through `get_feed_generators`.
+    pub is_online: Option<bool>,
+    pub is_valid: Option<bool>,
+}
+
+/// Concurrent getFeedGenerator calls when checking feed status.
+const FEED_STATUS_CONCURRENCY: usize = 4;
+/// Feeds per page of popular-feed search
+const POPULAR_FEEDS_PAGE_SIZE: u8 = 30;
+/// Times a saved-feeds edit is retried when the preferences change underneath it
+const SAVED_FEEDS_WRITE_ATTEMPTS: usize = 3;
+/// Value of the following timeline in the v2 saved-feeds layout
+const FOLLOWING_FEED_VALUE: &str = "following";
+
+fn generator_view_to_feed_info(f: &GeneratorView) -> FeedInfo {
+    FeedInfo {
+        uri: f.uri.to_string(),
+        cid: f.cid.as_ref().to_string(),
+        did: f.did.to_string(),
+        creator_did: f.creator.did.to_string(),
+        creator_handle: f.creator.handle.to_string(),
+        creator_display_name: f.creator.display_name.clone(),
+        creator_avatar: f.creator.avatar.clone(),
+        display_name: f.display_name.clone(),
+        description: f.description.clone(),
+        avatar: f.avatar.clone(),
+        like_count: f.like_count.unwrap_or(0) as u32,
+        is_saved: f.viewer.as_ref().and_then(|v| v.like.as_ref()).is_some(),
+        is_online: None,
+        is_valid: None,
+    }
 }
 
 #[derive(Serialize)]
@@ -29,11 +74,11 @@ pub struct SuggestedFeedsResponse {
 /// Get suggested feeds for discovery
 #[tauri::command]
 pub async fn get_suggested_feeds(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     cursor: Option<String>,
 ) -> Result<SuggestedFeedsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let response = agent
         .api
@@ -48,26 +93,14 @@ pub async fn get_suggested_feeds(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let feeds: Vec<FeedInfo> = response
         .data
         .feeds
         .iter()
-        .map(|f| FeedInfo {
-            uri: f.uri.to_string(),
-            cid: f.cid.as_ref().to_string(),
-            did: f.did.to_string(),
-            creator_did: f.creator.did.to_string(),
-            creator_handle: f.creator.handle.to_string(),
-            creator_display_name: f.creator.display_name.clone(),
-            creator_avatar: f.creator.avatar.clone(),
-            display_name: f.display_name.clone(),
-            description: f.description.clone(),
-            avatar: f.avatar.clone(),
-            like_count: f.like_count.unwrap_or(0) as u32,
-            is_saved: f.viewer.as_ref().and_then(|v| v.like.as_ref()).is_some(),
-        })
+        .map(generator_view_to_feed_info)
         .collect();
 
     Ok(SuggestedFeedsResponse {
@@ -76,6 +109,179 @@ pub async fn get_suggested_feeds(
     })
 }
 
+/// Search popular feeds for the discovery screen; an empty query lists the most popular.
+/// `getPopularFeedGenerators` is unspecced, so entries that don't parse as a generator
+/// view are skipped and an unexpected response shape yields an empty page. Saved state
+/// comes from the user's saved-feeds preference, since the public AppView has no viewer.
+#[tauri::command]
+pub async fn search_feed_generators(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    query: String,
+    cursor: Option<String>,
+) -> Result<SuggestedFeedsResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let query = query.trim();
+
+    let public_appview = crate::endpoints::current().public_appview_url;
+    let mut request = crate::http::client()
+        .get(format!(
+            "{public_appview}/xrpc/app.bsky.unspecced.getPopularFeedGenerators"
+        ))
+        .query(&[("limit", POPULAR_FEEDS_PAGE_SIZE.to_string())]);
+    if !query.is_empty() {
+        request = request.query(&[("query", query)]);
+    }
+    if let Some(cursor) = cursor.as_deref().filter(|c| !c.is_empty()) {
+        request = request.query(&[("cursor", cursor)]);
+    }
+
+    let (response, preferences) = futures::join!(request.send(), preferences_json(&agent));
+    let response = response
+        .map_err(|e| crate::http::network_error("feed search request failed", e))
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    if !response.status().is_success() {
+        return Err(AppError::ApiError(format!(
+            "Feed search failed with status {}",
+            response.status()
+        )));
+    }
+
+    let body: serde_json::Value = match response.json().await {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::warn!("feed search decode failed: {err}");
+            serde_json::Value::Null
+        }
+    };
+    let Some(views) = body.get("feeds").and_then(|f| f.as_array()) else {
+        tracing::warn!("feed search response has an unexpected shape");
+        return Ok(SuggestedFeedsResponse {
+            feeds: Vec::new(),
+            cursor: None,
+        });
+    };
+
+    let saved = match preferences {
+        Ok(preferences) => saved_feed_uris(&preferences),
+        Err(err) => {
+            tracing::warn!("saved feeds unavailable for feed search: {err}");
+            HashSet::new()
+        }
+    };
+    let feeds = views
+        .iter()
+        .filter_map(|view| serde_json::from_value::<GeneratorView>(view.clone()).ok())
+        .map(|view| {
+            let mut info = generator_view_to_feed_info(&view);
+            info.is_saved = saved.contains(&info.uri);
+            info
+        })
+        .collect();
+
+    Ok(SuggestedFeedsResponse {
+        feeds,
+        cursor: body
+            .get("cursor")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .map(str::to_string),
+    })
+}
+
+#[derive(Serialize)]
+pub struct FeedGeneratorsResponse {
+    pub feeds: Vec<FeedInfo>,
+    /// Requested URIs the AppView returned no generator for (deleted or unknown).
+    pub missing: Vec<String>,
+}
+
+/// Get metadata and online/valid status for several feed generators at once
+#[tauri::command]
+pub async fn get_feed_generators(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uris: Vec<String>,
+) -> Result<FeedGeneratorsResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let uris: Vec<String> = uris
+        .into_iter()
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .collect();
+    if uris.is_empty() {
+        return Ok(FeedGeneratorsResponse {
+            feeds: Vec::new(),
+            missing: Vec::new(),
+        });
+    }
+
+    let response = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_feed_generators(
+            bsky_sdk::api::app::bsky::feed::get_feed_generators::ParametersData {
+                feeds: uris.clone(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    // getFeedGenerators carries no status, so ask each generator individually.
+    // A failed status check marks that feed offline instead of failing the batch.
+    let statuses: HashMap<String, (bool, bool)> = stream::iter(response.data.feeds.iter())
+        .map(|view| async move {
+            let status = agent
+                .api
+                .app
+                .bsky
+                .feed
+                .get_feed_generator(
+                    bsky_sdk::api::app::bsky::feed::get_feed_generator::ParametersData {
+                        feed: view.uri.clone(),
+                    }
+                    .into(),
+                )
+                .await
+                .map(|output| (output.data.is_online, output.data.is_valid))
+                .unwrap_or((false, false));
+            (view.uri.to_string(), status)
+        })
+        .buffer_unordered(FEED_STATUS_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut by_uri: HashMap<String, FeedInfo> = response
+        .data
+        .feeds
+        .iter()
+        .map(|view| {
+            let mut info = generator_view_to_feed_info(view);
+            let (is_online, is_valid) = statuses.get(&info.uri).copied().unwrap_or((false, false));
+            info.is_online = Some(is_online);
+            info.is_valid = Some(is_valid);
+            (info.uri.clone(), info)
+        })
+        .collect();
+
+    // Preserve the caller's order (e.g. pinned-feed bar order).
+    let mut feeds = Vec::with_capacity(uris.len());
+    let mut missing = Vec::new();
+    for uri in uris {
+        match by_uri.remove(&uri) {
+            Some(info) => feeds.push(info),
+            None => missing.push(uri),
+        }
+    }
+
+    Ok(FeedGeneratorsResponse { feeds, missing })
+}
+
 #[derive(Deserialize)]
 pub struct GetFeedRequest {
     pub feed_uri: String,
@@ -87,6 +293,7 @@ pub struct GetFeedRequest {
 pub struct FeedPostsResponse {
     pub posts: Vec<super::timeline::TimelinePost>,
     pub cursor: Option<String>,
+    pub reset: bool,
 }
 
 /// Get posts from a specific feed
@@ -94,46 +301,45 @@ pub struct FeedPostsResponse {
 pub async fn get_feed(
     app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     request: GetFeedRequest,
 ) -> Result<FeedPostsResponse, AppError> {
-    println!("DEBUG: get_feed called with uri: {}", request.feed_uri);
-
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
+    let label_prefs = moderation::content_label_prefs(&agent).await;
 
     let limit_val = request.limit.unwrap_or(50).max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
 
-    let response = agent
-        .api
-        .app
-        .bsky
-        .feed
-        .get_feed(
+    let feed: bsky_sdk::api::types::string::AtUri = resolve_at_uri(&agent, &request.feed_uri)
+        .await?
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid feed URI".into()))?;
+
+    let (response, reset) = fetch_with_cursor_recovery(request.cursor, |cursor| {
+        agent.api.app.bsky.feed.get_feed(
             bsky_sdk::api::app::bsky::feed::get_feed::ParametersData {
-                feed: request
-                    .feed_uri
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid feed URI".into()))?,
-                cursor: request.cursor,
-                limit,
+                feed: feed.clone(),
+                cursor,
+                limit: limit.clone(),
             }
             .into(),
         )
-        .await
-        .map_err(|e| {
-            println!("DEBUG: get_feed API error: {}", e);
-            AppError::ApiError(e.to_string())
-        })?;
-
-    println!(
-        "DEBUG: get_feed success, items: {}",
-        response.data.feed.len()
-    );
+    })
+    .await
+    .map_err(connectivity::xrpc_error)
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
+    let hidden = hidden_posts::current_hidden_posts(db.inner()).await;
     let mut posts: Vec<super::timeline::TimelinePost> = Vec::new();
     for feed_view in &response.data.feed {
         let post = &feed_view.post;
+        if hidden.contains(post.uri.as_str()) {
+            continue;
+        }
+        let (label_visibility, content_warning) = label_prefs.moderate_post(post);
+        if label_visibility == LabelVisibility::Hide {
+            continue;
+        }
 
         let text = if let Ok(json) = serde_json::to_value(&post.record) {
             json.get("text")
@@ -154,8 +360,10 @@ pub async fn get_feed(
         };
 
         let embed = media::process_post_embed(post, &app).await?;
-        let (is_repost, reposted_by_handle, reposted_by_display_name) =
-            super::timeline::extract_repost_context(feed_view);
+        let repost_context = super::timeline::extract_repost_context(feed_view);
+        let (reposted_by_handle, reposted_by_display_name) = repost_context.reposted_by();
+        let (reply_parent_author_handle, reply_root_uri) =
+            super::timeline::extract_reply_context(feed_view);
 
         posts.push(super::timeline::TimelinePost {
             uri: post.uri.to_string(),
@@ -163,11 +371,16 @@ pub async fn get_feed(
             author_did: post.author.did.to_string(),
             author_handle: post.author.handle.to_string(),
             author_display_name: post.author.display_name.clone(),
-            author_avatar: post.author.avatar.clone(),
-            is_repost,
+            author_avatar: media::cached_profile_image(
+                post.author.avatar.as_deref(),
+                media::ProfileImageKind::Avatar,
+                &app,
+            ),
+            is_repost: repost_context.is_repost(),
             reposted_by_handle,
             reposted_by_display_name,
             text,
+            facets: super::timeline::extract_post_facets(post),
             created_at,
             reply_count: post.reply_count.unwrap_or(0) as u32,
             repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -189,11 +402,480 @@ pub async fn get_feed(
                 .and_then(|v| v.repost.as_ref())
                 .map(|u| u.to_string()),
             embed: embed.and_then(|value| serde_json::to_value(value).ok()),
+            feed_context: feed_view.feed_context.clone(),
+            reason: super::timeline::extract_feed_reason(feed_view),
+            repost_context,
+            reply_parent_author_handle,
+            reply_root_uri,
+            indexed_at: Some(super::timeline::feed_item_indexed_at(feed_view)),
+            content_warning,
         });
     }
 
     Ok(FeedPostsResponse {
         posts,
         cursor: response.data.cursor,
+        reset,
+    })
+}
+
+/// Pinned home tabs of the last successful load, for an instant cold start
+const HOME_TABS_KEY: &str = "home.tabs";
+/// Concurrent getList calls when naming pinned lists
+const PINNED_LIST_CONCURRENCY: usize = 4;
+
+/// One tab of the home screen's feed bar
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HomeFeedTab {
+    /// "following", "feed" or "list"
+    pub kind: String,
+    /// Feed generator or list URI; `None` for the following timeline
+    pub uri: Option<String>,
+    pub display_name: String,
+    pub avatar: Option<String>,
+    /// The pinned feed or list no longer exists
+    #[serde(default)]
+    pub missing: bool,
+}
+
+impl HomeFeedTab {
+    fn following() -> Self {
+        Self {
+            kind: "following".into(),
+            uri: None,
+            display_name: "Following".into(),
+            avatar: None,
+            missing: false,
+        }
+    }
+
+    fn pinned(kind: &str, uri: String) -> Self {
+        Self {
+            kind: kind.into(),
+            display_name: uri.clone(),
+            uri: Some(uri),
+            avatar: None,
+            missing: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct HomeConfig {
+    /// Pinned feeds in the user's order
+    pub tabs: Vec<HomeFeedTab>,
+    /// First page of the following timeline
+    pub timeline: super::timeline::TimelineResponse,
+}
+
+/// Pinned feeds from the saved-feeds preference, newest format first. Both the v2
+/// (`items`) and legacy (`pinned` URIs) layouts are read.
+fn pinned_tabs(preferences: &[serde_json::Value]) -> Vec<HomeFeedTab> {
+    let find = |ty: &str| {
+        preferences
+            .iter()
+            .find(|pref| pref.get("$type").and_then(|t| t.as_str()) == Some(ty))
+    };
+
+    if let Some(pref) = find("app.bsky.actor.defs#savedFeedsPrefV2") {
+        return pref
+            .get("items")
+            .and_then(|items| items.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|item| item.get("pinned").and_then(|p| p.as_bool()) == Some(true))
+            .filter_map(|item| {
+                let value = item.get("value")?.as_str()?.to_string();
+                match item.get("type")?.as_str()? {
+                    "timeline" => Some(HomeFeedTab::following()),
+                    kind @ ("feed" | "list") => Some(HomeFeedTab::pinned(kind, value)),
+                    _ => None,
+                }
+            })
+            .collect();
+    }
+
+    let mut tabs = vec![HomeFeedTab::following()];
+    if let Some(pref) = find("app.bsky.actor.defs#savedFeedsPref") {
+        tabs.extend(
+            pref.get("pinned")
+                .and_then(|pinned| pinned.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|uri| uri.as_str())
+                .map(|uri| {
+                    let kind = if uri.contains("/app.bsky.graph.list/") {
+                        "list"
+                    } else {
+                        "feed"
+                    };
+                    HomeFeedTab::pinned(kind, uri.to_string())
+                }),
+        );
+    }
+    tabs
+}
+
+/// URIs of every saved feed, pinned or not, in either saved-feeds layout
+fn saved_feed_uris(preferences: &[serde_json::Value]) -> HashSet<String> {
+    let mut saved = HashSet::new();
+    for pref in preferences {
+        match pref.get("$type").and_then(|t| t.as_str()) {
+            Some("app.bsky.actor.defs#savedFeedsPrefV2") => saved.extend(
+                pref.get("items")
+                    .and_then(|items| items.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("feed"))
+                    .filter_map(|item| item.get("value")?.as_str().map(str::to_string)),
+            ),
+            Some("app.bsky.actor.defs#savedFeedsPref") => saved.extend(
+                pref.get("saved")
+                    .and_then(|uris| uris.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|uri| uri.as_str().map(str::to_string)),
+            ),
+            _ => {}
+        }
+    }
+    saved
+}
+
+/// The user's preferences as JSON, for reading saved feeds without matching on every
+/// preference variant
+async fn preferences_json(agent: &AppAgent) -> Result<Vec<serde_json::Value>, AppError> {
+    Ok(moderation::fetch_preferences(agent)
+        .await?
+        .iter()
+        .filter_map(|pref| serde_json::to_value(pref).ok())
+        .collect())
+}
+
+/// Pinned tabs with names and avatars filled in
+async fn fetch_home_tabs(agent: &AppAgent) -> Result<Vec<HomeFeedTab>, AppError> {
+    let preferences = preferences_json(agent).await?;
+    let mut tabs = pinned_tabs(&preferences);
+    if !tabs.iter().any(|tab| tab.kind == "following") {
+        tabs.insert(0, HomeFeedTab::following());
+    }
+
+    let feed_uris: Vec<String> = tabs
+        .iter()
+        .filter(|tab| tab.kind == "feed")
+        .filter_map(|tab| tab.uri.clone())
+        .collect();
+    let mut generators: HashMap<String, GeneratorView> = HashMap::new();
+    if !feed_uris.is_empty() {
+        let response = agent
+            .api
+            .app
+            .bsky
+            .feed
+            .get_feed_generators(
+                bsky_sdk::api::app::bsky::feed::get_feed_generators::ParametersData {
+                    feeds: feed_uris,
+                }
+                .into(),
+            )
+            .await
+            .map_err(connectivity::xrpc_error)?;
+        generators.extend(
+            response
+                .data
+                .feeds
+                .into_iter()
+                .map(|view| (view.uri.to_string(), view)),
+        );
+    }
+
+    let lists: HashMap<String, (String, Option<String>)> = stream::iter(
+        tabs.iter()
+            .filter(|tab| tab.kind == "list")
+            .filter_map(|tab| tab.uri.clone()),
+    )
+    .map(|uri| async move {
+        let list = agent
+            .api
+            .app
+            .bsky
+            .graph
+            .get_list(
+                bsky_sdk::api::app::bsky::graph::get_list::ParametersData {
+                    list: uri.parse().ok()?,
+                    cursor: None,
+                    limit: 1_u8.try_into().ok(),
+                }
+                .into(),
+            )
+            .await
+            .ok()?;
+        Some((
+            uri,
+            (list.data.list.name.clone(), list.data.list.avatar.clone()),
+        ))
+    })
+    .buffered(PINNED_LIST_CONCURRENCY)
+    .filter_map(|found| async move { found })
+    .collect()
+    .await;
+
+    for tab in &mut tabs {
+        let Some(uri) = tab.uri.as_deref() else {
+            continue;
+        };
+        let found = match tab.kind.as_str() {
+            "feed" => generators
+                .get(uri)
+                .map(|view| (view.display_name.clone(), view.avatar.clone())),
+            _ => lists.get(uri).cloned(),
+        };
+        if let Some((display_name, avatar)) = found {
+            tab.display_name = display_name;
+            tab.avatar = avatar;
+            tab.missing = false;
+        }
+    }
+    Ok(tabs)
+}
+
+/// Everything the home screen needs on startup: the pinned feed tabs and the first
+/// following-timeline page. Both come from the local cache when available (refreshed in
+/// the background, emitting `home_tabs_updated` and `timeline_updated`). If preferences
+/// can't be loaded and nothing is cached, only the following tab is returned.
+#[tauri::command]
+pub async fn get_home_config(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+) -> Result<HomeConfig, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let did = get_stored_session()?.did;
+    let db_pool = db.inner().clone();
+
+    let tabs = async {
+        if let Some(cached) =
+            load_setting::<Vec<HomeFeedTab>>(db_pool.as_ref(), &did, HOME_TABS_KEY).await?
+        {
+            let app = app.clone();
+            let db_pool = db_pool.clone();
+            let did = did.clone();
+            tauri::async_runtime::spawn(async move {
+                match fetch_home_tabs(&agent).await {
+                    Ok(tabs) => {
+                        if let Err(err) =
+                            save_setting(db_pool.as_ref(), &did, HOME_TABS_KEY, &tabs).await
+                        {
+                            tracing::warn!(target: "cache", cache = "home", "save failed: {err}");
+                        }
+                        let _ = app.emit("home_tabs_updated", &tabs);
+                    }
+                    Err(err) => {
+                        tracing::warn!(target: "cache", cache = "home", "refresh failed: {err}");
+                        connectivity::watch_error(&app, &err);
+                    }
+                }
+            });
+            return Ok::<_, AppError>(cached);
+        }
+
+        Ok(match fetch_home_tabs(&agent).await {
+            Ok(tabs) => {
+                save_setting(db_pool.as_ref(), &did, HOME_TABS_KEY, &tabs).await?;
+                tabs
+            }
+            Err(err) => {
+                tracing::warn!(target: "cache", cache = "home", "pinned feeds unavailable: {err}");
+                connectivity::watch_error(&app, &err);
+                vec![HomeFeedTab::following()]
+            }
+        })
+    };
+    let timeline = super::timeline::get_timeline(
+        app.clone(),
+        agent_state.clone(),
+        db.clone(),
+        super::timeline::TimelineRequest {
+            limit: 50,
+            cursor: None,
+            since_uri: None,
+            since_indexed_at: None,
+            hide_reposts: false,
+            hide_replies: false,
+        },
+    );
+
+    let (tabs, timeline) = futures::join!(tabs, timeline);
+    Ok(HomeConfig {
+        tabs: tabs?,
+        timeline: timeline?,
+    })
+}
+
+/// The saved-feeds preferences (both layouts) as JSON, to tell whether they changed
+fn saved_feeds_snapshot(preferences: &[Union<PreferencesItem>]) -> Vec<serde_json::Value> {
+    preferences
+        .iter()
+        .filter(|pref| {
+            matches!(
+                pref,
+                Union::Refs(
+                    PreferencesItem::SavedFeedsPref(_) | PreferencesItem::SavedFeedsPrefV2(_)
+                )
+            )
+        })
+        .filter_map(|pref| serde_json::to_value(pref).ok())
+        .collect()
+}
+
+/// Saved feed values in their current order; the v2 layout wins when both exist
+fn saved_feed_order(preferences: &[Union<PreferencesItem>]) -> Vec<String> {
+    let v2 = preferences.iter().find_map(|pref| match pref {
+        Union::Refs(PreferencesItem::SavedFeedsPrefV2(pref)) => Some(
+            pref.items
+                .iter()
+                .map(|item| item.value.clone())
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    });
+    v2.or_else(|| {
+        preferences.iter().find_map(|pref| match pref {
+            Union::Refs(PreferencesItem::SavedFeedsPref(pref)) => Some(pref.saved.clone()),
+            _ => None,
+        })
+    })
+    .unwrap_or_default()
+}
+
+/// Put the listed items, in the given order, into the positions they already occupy;
+/// everything else stays where it is
+fn reorder_within<T: Clone>(items: &mut [T], key: impl Fn(&T) -> &str, order: &[String]) {
+    let position = |item: &T| order.iter().position(|uri| uri == key(item));
+    let slots: Vec<usize> = (0..items.len())
+        .filter(|&i| position(&items[i]).is_some())
+        .collect();
+    let mut moved: Vec<T> = slots.iter().map(|&i| items[i].clone()).collect();
+    moved.sort_by_key(|item| position(item));
+    for (slot, item) in slots.into_iter().zip(moved) {
+        items[slot] = item;
+    }
+}
+
+fn reorder_saved_feed_prefs(preferences: &mut [Union<PreferencesItem>], order: &[String]) {
+    for pref in preferences {
+        match pref {
+            Union::Refs(PreferencesItem::SavedFeedsPrefV2(pref)) => {
+                reorder_within(&mut pref.data.items, |item| item.value.as_str(), order);
+            }
+            Union::Refs(PreferencesItem::SavedFeedsPref(pref)) => {
+                reorder_within(&mut pref.data.pinned, String::as_str, order);
+                reorder_within(&mut pref.data.saved, String::as_str, order);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Apply a new tab order to the cached home tabs so `get_home_config` shows it before
+/// the next refresh
+async fn reorder_cached_home_tabs(
+    app: &AppHandle,
+    db: &sqlx::SqlitePool,
+    did: &str,
+    order: &[String],
+) -> Result<(), AppError> {
+    let Some(mut tabs) = load_setting::<Vec<HomeFeedTab>>(db, did, HOME_TABS_KEY).await? else {
+        return Ok(());
+    };
+    reorder_within(
+        &mut tabs,
+        |tab| tab.uri.as_deref().unwrap_or(FOLLOWING_FEED_VALUE),
+        order,
+    );
+    save_setting(db, did, HOME_TABS_KEY, &tabs).await?;
+    let _ = app.emit("home_tabs_updated", &tabs);
+    Ok(())
+}
+
+/// Reorder saved feeds (the drag-to-reorder feed bar). `ordered_uris` lists feed or list
+/// URIs, or "following" for the timeline, in their new order; they swap among the
+/// positions they held, so passing only the pinned feeds reorders just those. Every URI
+/// must be saved. The preferences are re-read before writing and the edit is retried if
+/// another client changed the saved feeds meanwhile. Returns all saved feeds in order.
+#[tauri::command]
+pub async fn reorder_saved_feeds(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    ordered_uris: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let order: Vec<String> = ordered_uris
+        .iter()
+        .map(|uri| uri.trim().to_string())
+        .collect();
+    if order.is_empty() {
+        return Err(AppError::ValidationError {
+            code: "FEED_ORDER_EMPTY",
+            message: "No feeds to reorder".into(),
+        });
+    }
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = order.iter().find(|uri| !seen.insert(uri.as_str())) {
+        return Err(AppError::ValidationError {
+            code: "FEED_ORDER_DUPLICATE",
+            message: format!("{duplicate} is listed more than once"),
+        });
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    for _ in 0..SAVED_FEEDS_WRITE_ATTEMPTS {
+        let mut preferences = moderation::fetch_preferences(&agent)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+        let saved = saved_feed_order(&preferences);
+        if let Some(unsaved) = order.iter().find(|uri| !saved.contains(uri)) {
+            return Err(AppError::ValidationError {
+                code: "FEED_NOT_SAVED",
+                message: format!("{unsaved} is not a saved feed"),
+            });
+        }
+        let snapshot = saved_feeds_snapshot(&preferences);
+        reorder_saved_feed_prefs(&mut preferences, &order);
+
+        // putPreferences has no compare-and-swap; check nothing moved since the read
+        let current = moderation::fetch_preferences(&agent)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+        if saved_feeds_snapshot(&current) != snapshot {
+            tracing::debug!("saved feeds changed during reorder, retrying");
+            continue;
+        }
+
+        agent
+            .api
+            .app
+            .bsky
+            .actor
+            .put_preferences(
+                put_preferences::InputData {
+                    preferences: preferences.clone(),
+                }
+                .into(),
+            )
+            .await
+            .map_err(connectivity::xrpc_error)
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+        let did = get_stored_session()?.did;
+        if let Err(err) = reorder_cached_home_tabs(&app, db.inner().as_ref(), &did, &order).await {
+            tracing::warn!(target: "cache", cache = "home", "reorder cached tabs failed: {err}");
+        }
+        return Ok(saved_feed_order(&preferences));
+    }
+
+    Err(AppError::ValidationError {
+        code: "PREFERENCES_CONFLICT",
+        message: "Saved feeds kept changing on another device; try again".into(),
     })
 }
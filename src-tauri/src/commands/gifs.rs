@@ -0,0 +1,224 @@
+use crate::commands::auth::AppAgent;
+use crate::commands::link_cards::{upload_thumb, ExternalEmbedInput};
+use crate::commands::settings::{load_setting, save_setting, DEVICE_SCOPE};
+use crate::db::DbState;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+/// Tenor API key (device-wide)
+pub(crate) const TENOR_API_KEY: &str = "gifs.tenor_api_key";
+const TENOR_ENDPOINT: &str = "https://tenor.googleapis.com/v2";
+/// Identifies this app to Tenor alongside the API key
+const TENOR_CLIENT_KEY: &str = "blue-horizon";
+/// Tenor's terms require this attribution wherever search results are shown
+const TENOR_ATTRIBUTION: &str = "Powered by Tenor";
+
+/// A GIF picked from search results; pass it back as `create_post`'s `gif`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GifResult {
+    pub id: String,
+    pub title: String,
+    /// Provider description of the GIF, used as the default alt text
+    pub alt: String,
+    /// Full GIF URL; Bluesky clients play external embeds pointing at it as GIFs
+    pub url: String,
+    /// Small animated preview for the picker grid
+    pub preview_url: String,
+    /// Still image used for the embed thumbnail
+    pub still_url: Option<String>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize)]
+pub struct GifSearchResponse {
+    pub gifs: Vec<GifResult>,
+    pub cursor: Option<String>,
+    /// Must be shown with the results
+    pub attribution: &'static str,
+}
+
+#[derive(Deserialize)]
+struct TenorResponse {
+    #[serde(default)]
+    results: Vec<TenorResult>,
+    #[serde(default)]
+    next: String,
+}
+
+#[derive(Deserialize)]
+struct TenorResult {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    content_description: String,
+    #[serde(default)]
+    media_formats: std::collections::HashMap<String, TenorMedia>,
+}
+
+#[derive(Deserialize)]
+struct TenorMedia {
+    url: String,
+    #[serde(default)]
+    dims: Vec<u32>,
+}
+
+async fn tenor_api_key(db: &SqlitePool) -> Result<String, AppError> {
+    load_setting::<String>(db, DEVICE_SCOPE, TENOR_API_KEY)
+        .await?
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| AppError::ValidationError {
+            code: "GIF_API_KEY_MISSING",
+            message: "Add a Tenor API key in settings to search GIFs".into(),
+        })
+}
+
+fn to_gif_result(result: TenorResult) -> Option<GifResult> {
+    let mut formats = result.media_formats;
+    let gif = formats.remove("gif")?;
+    let preview_url = formats
+        .remove("tinygif")
+        .map_or_else(|| gif.url.clone(), |tiny| tiny.url);
+    let still_url = formats.remove("gifpreview").map(|still| still.url);
+    let (width, height) = match gif.dims.as_slice() {
+        [w, h, ..] => (*w, *h),
+        _ => (0, 0),
+    };
+
+    Some(GifResult {
+        id: result.id,
+        alt: if result.content_description.is_empty() {
+            result.title.clone()
+        } else {
+            result.content_description
+        },
+        title: result.title,
+        url: gif.url,
+        preview_url,
+        still_url,
+        width,
+        height,
+    })
+}
+
+/// Search Tenor for GIFs; an empty query returns featured GIFs
+#[tauri::command]
+pub async fn search_gifs(
+    db: State<'_, DbState>,
+    query: String,
+    cursor: Option<String>,
+    limit: Option<u8>,
+) -> Result<GifSearchResponse, AppError> {
+    let key = tenor_api_key(db.inner().as_ref()).await?;
+    let query = query.trim();
+    let limit = limit.unwrap_or(24).clamp(1, 50).to_string();
+
+    let url = if query.is_empty() {
+        format!("{TENOR_ENDPOINT}/featured")
+    } else {
+        format!("{TENOR_ENDPOINT}/search")
+    };
+    let mut request = crate::http::client().get(url).query(&[
+        ("key", key.as_str()),
+        ("client_key", TENOR_CLIENT_KEY),
+        ("media_filter", "gif,tinygif,gifpreview"),
+        ("contentfilter", "high"),
+        ("limit", limit.as_str()),
+    ]);
+    if !query.is_empty() {
+        request = request.query(&[("q", query)]);
+    }
+    if let Some(pos) = cursor.as_deref().filter(|c| !c.is_empty()) {
+        request = request.query(&[("pos", pos)]);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error("gif search failed", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(if matches!(status.as_u16(), 400 | 401 | 403) {
+            AppError::ValidationError {
+                code: "GIF_API_KEY_INVALID",
+                message: format!("Tenor rejected the API key ({status})"),
+            }
+        } else {
+            AppError::ApiError(format!("GIF search failed with status {status}"))
+        });
+    }
+
+    let parsed = response
+        .json::<TenorResponse>()
+        .await
+        .map_err(|e| AppError::ApiError(format!("GIF search decode failed: {e}")))?;
+
+    Ok(GifSearchResponse {
+        gifs: parsed
+            .results
+            .into_iter()
+            .filter_map(to_gif_result)
+            .collect(),
+        cursor: (!parsed.next.is_empty()).then_some(parsed.next),
+        attribution: TENOR_ATTRIBUTION,
+    })
+}
+
+/// Whether a Tenor API key is configured
+#[tauri::command]
+pub async fn get_gif_search_enabled(db: State<'_, DbState>) -> Result<bool, AppError> {
+    Ok(tenor_api_key(db.inner().as_ref()).await.is_ok())
+}
+
+/// Set or clear (`None`/empty) the Tenor API key
+#[tauri::command]
+pub async fn set_tenor_api_key(
+    db: State<'_, DbState>,
+    api_key: Option<String>,
+) -> Result<(), AppError> {
+    let api_key = api_key
+        .map(|key| key.trim().to_string())
+        .unwrap_or_default();
+    save_setting(db.inner().as_ref(), DEVICE_SCOPE, TENOR_API_KEY, &api_key).await
+}
+
+/// Turn a picked GIF into the external embed Bluesky renders as a GIF: the GIF URL with
+/// its dimensions as `hh`/`ww`, the alt text as title and description, and a still
+/// thumbnail. A thumbnail that fails to upload is left out rather than failing the post.
+pub(crate) async fn gif_external(
+    agent: &AppAgent,
+    gif: &GifResult,
+    alt: Option<&str>,
+) -> ExternalEmbedInput {
+    let mut uri = gif.url.clone();
+    if gif.width > 0 && gif.height > 0 {
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        uri.push_str(&format!("{separator}hh={}&ww={}", gif.height, gif.width));
+    }
+
+    let alt = alt
+        .map(str::trim)
+        .filter(|alt| !alt.is_empty())
+        .unwrap_or(&gif.alt)
+        .to_string();
+
+    let thumb_source = gif.still_url.as_deref().unwrap_or(&gif.preview_url);
+    let thumb = match upload_thumb(agent, thumb_source).await {
+        Ok(blob) => Some(blob),
+        Err(err) => {
+            tracing::warn!("gif thumb skipped for {}: {err}", gif.id);
+            None
+        }
+    };
+
+    ExternalEmbedInput {
+        uri,
+        title: alt.clone(),
+        description: format!("Alt: {alt}"),
+        thumb_url: thumb.is_some().then(|| thumb_source.to_string()),
+        thumb,
+    }
+}
@@ -0,0 +1,110 @@
+use crate::commands::auth::AgentState;
+use crate::commands::interactions;
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Serialize, Clone)]
+struct PostHiddenEvent {
+    uri: String,
+}
+
+/// Posts the account hid on this device
+pub(crate) async fn hidden_post_uris(
+    db: &SqlitePool,
+    user_did: &str,
+) -> Result<HashSet<String>, AppError> {
+    let rows = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT post_uri
+        FROM hidden_posts
+        WHERE user_did = ?1
+        "#,
+    )
+    .bind(user_did)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("hidden posts read failed: {e}")))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Hidden posts of the signed-in account for filtering a page. If they can't be read
+/// nothing is filtered rather than failing the feed.
+pub(crate) async fn current_hidden_posts(db: &SqlitePool) -> HashSet<String> {
+    let Ok(user_did) = get_stored_session().map(|session| session.did) else {
+        return HashSet::new();
+    };
+    hidden_posts_or_none(db, &user_did).await
+}
+
+/// `hidden_post_uris` for filtering, with nothing filtered when they can't be read
+pub(crate) async fn hidden_posts_or_none(db: &SqlitePool, user_did: &str) -> HashSet<String> {
+    hidden_post_uris(db, user_did).await.unwrap_or_else(|err| {
+        tracing::warn!("hidden posts not applied: {err}");
+        HashSet::new()
+    })
+}
+
+/// Hide a post from every feed on this device; unlike mute or block nothing is sent to
+/// the server. When hidden from an algorithmic feed (`feed_uri`), the feed is also told
+/// the user wants less like it. Emits `post_hidden` so open views drop it.
+#[tauri::command]
+pub async fn hide_post(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    uri: String,
+    feed_uri: Option<String>,
+    feed_context: Option<String>,
+) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    let uri = uri.trim().to_string();
+    if !uri.starts_with("at://") {
+        return Err(AppError::ValidationError {
+            code: "INVALID_POST_URI",
+            message: format!("Not a post URI: {uri}"),
+        });
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO hidden_posts (user_did, post_uri, hidden_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(user_did, post_uri) DO NOTHING
+        "#,
+    )
+    .bind(&user_did)
+    .bind(&uri)
+    .bind(Utc::now().to_rfc3339())
+    .execute(db.inner().as_ref())
+    .await
+    .map_err(|e| AppError::InternalError(format!("hide post failed: {e}")))?;
+
+    if let Some(feed_uri) = feed_uri.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        interactions::queue_request_less(agent_state.inner(), feed_uri, &uri, feed_context);
+    }
+
+    if let Err(err) = app.emit("post_hidden", PostHiddenEvent { uri }) {
+        tracing::warn!("emit failed: {err}");
+    }
+    Ok(())
+}
+
+/// Show a hidden post again (it reappears the next time its feed loads)
+#[tauri::command]
+pub async fn unhide_post(db: State<'_, DbState>, uri: String) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    sqlx::query("DELETE FROM hidden_posts WHERE user_did = ?1 AND post_uri = ?2")
+        .bind(&user_did)
+        .bind(uri.trim())
+        .execute(db.inner().as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(format!("unhide post failed: {e}")))?;
+    Ok(())
+}
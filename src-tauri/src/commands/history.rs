@@ -0,0 +1,274 @@
+use crate::commands::settings::{load_setting, save_setting};
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+/// Record opened profiles and posts (per account, default on)
+pub(crate) const VIEW_HISTORY_ENABLED_KEY: &str = "history.enabled";
+/// Entries kept per kind; older ones are dropped as new views are recorded
+const VIEW_HISTORY_CAP: i64 = 200;
+const DEFAULT_VIEW_HISTORY_LIMIT: u32 = 20;
+/// Post text shown under a post entry
+const POST_SNIPPET_CHARS: usize = 140;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewKind {
+    Profile,
+    Post,
+}
+
+impl ViewKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Profile => "profile",
+            Self::Post => "post",
+        }
+    }
+
+    pub(crate) fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "profile" => Some(Self::Profile),
+            "post" => Some(Self::Post),
+            _ => None,
+        }
+    }
+}
+
+/// A recently opened profile or post, enough to render a quick-switcher row
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ViewHistoryEntry {
+    pub kind: ViewKind,
+    /// Profile DID or post AT-URI
+    pub subject: String,
+    /// Display name (or handle) of the profile or post author
+    pub title: String,
+    /// `@handle` for profiles, the start of the text for posts
+    pub subtitle: Option<String>,
+    pub avatar: Option<String>,
+    pub viewed_at: String,
+}
+
+impl ViewHistoryEntry {
+    pub(crate) fn profile(
+        did: &str,
+        handle: &str,
+        display_name: Option<&str>,
+        avatar: Option<&str>,
+    ) -> Self {
+        Self {
+            kind: ViewKind::Profile,
+            subject: did.to_string(),
+            title: display_name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or(handle)
+                .to_string(),
+            subtitle: Some(format!("@{handle}")),
+            avatar: avatar.map(str::to_string),
+            viewed_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub(crate) fn post(
+        uri: &str,
+        author_handle: &str,
+        author_display_name: Option<&str>,
+        author_avatar: Option<&str>,
+        text: &str,
+    ) -> Self {
+        let snippet: String = text.trim().chars().take(POST_SNIPPET_CHARS).collect();
+        Self {
+            kind: ViewKind::Post,
+            subject: uri.to_string(),
+            title: author_display_name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or(author_handle)
+                .to_string(),
+            subtitle: (!snippet.is_empty()).then_some(snippet),
+            avatar: author_avatar.map(str::to_string),
+            viewed_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+async fn view_history_enabled(db: &SqlitePool, user_did: &str) -> bool {
+    match load_setting::<bool>(db, user_did, VIEW_HISTORY_ENABLED_KEY).await {
+        Ok(value) => value.unwrap_or(true),
+        Err(err) => {
+            tracing::warn!("view history setting read failed: {err}");
+            false
+        }
+    }
+}
+
+/// Insert or refresh an entry, then trim its kind back to `VIEW_HISTORY_CAP`
+pub(crate) async fn save_view_entry(
+    db: &SqlitePool,
+    user_did: &str,
+    entry: &ViewHistoryEntry,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO view_history (user_did, kind, subject, title, subtitle, avatar, viewed_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        ON CONFLICT(user_did, kind, subject) DO UPDATE SET
+          title = excluded.title,
+          subtitle = excluded.subtitle,
+          avatar = excluded.avatar,
+          viewed_at = excluded.viewed_at
+        WHERE excluded.viewed_at > view_history.viewed_at
+        "#,
+    )
+    .bind(user_did)
+    .bind(entry.kind.as_str())
+    .bind(&entry.subject)
+    .bind(&entry.title)
+    .bind(&entry.subtitle)
+    .bind(&entry.avatar)
+    .bind(&entry.viewed_at)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("view history write failed: {e}")))?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM view_history
+        WHERE user_did = ?1 AND kind = ?2 AND subject NOT IN (
+          SELECT subject FROM view_history
+          WHERE user_did = ?1 AND kind = ?2
+          ORDER BY viewed_at DESC
+          LIMIT ?3
+        )
+        "#,
+    )
+    .bind(user_did)
+    .bind(entry.kind.as_str())
+    .bind(VIEW_HISTORY_CAP)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("view history trim failed: {e}")))?;
+    Ok(())
+}
+
+/// Record that the signed-in user opened a profile or post. History is a convenience,
+/// so a disabled setting or a failed write never fails the command that opened it.
+pub(crate) async fn record_view(db: &SqlitePool, entry: ViewHistoryEntry) {
+    let Ok(session) = get_stored_session() else {
+        return;
+    };
+    if !view_history_enabled(db, &session.did).await {
+        return;
+    }
+    if let Err(err) = save_view_entry(db, &session.did, &entry).await {
+        tracing::warn!("{err}");
+    }
+}
+
+/// An account's history entries, newest first
+pub(crate) async fn load_view_history(
+    db: &SqlitePool,
+    user_did: &str,
+    kind: Option<ViewKind>,
+    limit: i64,
+) -> Result<Vec<ViewHistoryEntry>, AppError> {
+    let rows = sqlx::query_as::<
+        _,
+        (
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+            String,
+        ),
+    >(
+        r#"
+        SELECT kind, subject, title, subtitle, avatar, viewed_at
+        FROM view_history
+        WHERE user_did = ?1 AND (?2 IS NULL OR kind = ?2)
+        ORDER BY viewed_at DESC
+        LIMIT ?3
+        "#,
+    )
+    .bind(user_did)
+    .bind(kind.map(ViewKind::as_str))
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("view history read failed: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(kind, subject, title, subtitle, avatar, viewed_at)| {
+            Some(ViewHistoryEntry {
+                kind: ViewKind::parse(&kind)?,
+                subject,
+                title,
+                subtitle,
+                avatar,
+                viewed_at,
+            })
+        })
+        .collect())
+}
+
+/// Recently opened profiles and/or posts, newest first. Omit `kind` for both.
+#[tauri::command]
+pub async fn get_view_history(
+    db: State<'_, DbState>,
+    kind: Option<ViewKind>,
+    limit: Option<u32>,
+) -> Result<Vec<ViewHistoryEntry>, AppError> {
+    let user_did = get_stored_session()?.did;
+    let limit = limit
+        .unwrap_or(DEFAULT_VIEW_HISTORY_LIMIT)
+        .clamp(1, VIEW_HISTORY_CAP as u32);
+    load_view_history(db.inner().as_ref(), &user_did, kind, i64::from(limit)).await
+}
+
+/// Forget recently opened profiles and/or posts. Omit `kind` for both.
+#[tauri::command]
+pub async fn clear_view_history(
+    db: State<'_, DbState>,
+    kind: Option<ViewKind>,
+) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    sqlx::query("DELETE FROM view_history WHERE user_did = ?1 AND (?2 IS NULL OR kind = ?2)")
+        .bind(&user_did)
+        .bind(kind.map(ViewKind::as_str))
+        .execute(db.inner().as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(format!("view history clear failed: {e}")))?;
+    Ok(())
+}
+
+/// Get whether opened profiles and posts are recorded
+#[tauri::command]
+pub async fn get_view_history_enabled(db: State<'_, DbState>) -> Result<bool, AppError> {
+    let user_did = get_stored_session()?.did;
+    Ok(view_history_enabled(db.inner().as_ref(), &user_did).await)
+}
+
+/// Turn view history on or off; turning it off also clears what was recorded
+#[tauri::command]
+pub async fn set_view_history_enabled(
+    db: State<'_, DbState>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    save_setting(
+        db.inner().as_ref(),
+        &user_did,
+        VIEW_HISTORY_ENABLED_KEY,
+        &enabled,
+    )
+    .await?;
+    if !enabled {
+        clear_view_history(db, None).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::connectivity;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use bsky_sdk::api::app::bsky::feed::defs::{Interaction, InteractionData};
+use bsky_sdk::api::app::bsky::feed::{get_feed_generator, send_interactions};
+use bsky_sdk::api::types::string::{AtUri, Did};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::State;
+
+/// Feed generators are reached through the AppView with this service type
+const FEED_GENERATOR_SERVICE_TYPE: &str = "bsky_fg";
+/// Flush a feed's queue early once it holds this many interactions
+const MAX_PENDING_PER_FEED: usize = 100;
+/// How often the background task flushes queued interactions
+pub(crate) const FLUSH_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingInteraction {
+    item: String,
+    event: &'static str,
+    feed_context: Option<String>,
+}
+
+/// Feed URI -> interactions waiting to be sent
+type FeedQueues = HashMap<String, Vec<PendingInteraction>>;
+
+/// Queued interactions per account DID, so one account's batch is never sent as another's
+static PENDING: LazyLock<Mutex<HashMap<String, FeedQueues>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+/// Feed URI -> feed generator service DID
+static FEED_SERVICE_DIDS: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Deserialize)]
+pub struct FeedInteraction {
+    pub item_uri: String,
+    /// "seen", "clickthrough", "like", "share", ... or a full lexicon token
+    pub event: String,
+    pub feed_context: Option<String>,
+}
+
+/// Map a short event name (or full lexicon token) to a known interaction event
+fn parse_event(event: &str) -> Result<&'static str, AppError> {
+    let short = event
+        .trim()
+        .trim_start_matches("app.bsky.feed.defs#")
+        .to_ascii_lowercase();
+
+    let token = match short.as_str() {
+        "seen" | "interactionseen" => "app.bsky.feed.defs#interactionSeen",
+        "clickthrough" | "clickthroughitem" => "app.bsky.feed.defs#clickthroughItem",
+        "clickthroughauthor" => "app.bsky.feed.defs#clickthroughAuthor",
+        "clickthroughreposter" => "app.bsky.feed.defs#clickthroughReposter",
+        "clickthroughembed" => "app.bsky.feed.defs#clickthroughEmbed",
+        "like" | "interactionlike" => "app.bsky.feed.defs#interactionLike",
+        "repost" | "interactionrepost" => "app.bsky.feed.defs#interactionRepost",
+        "reply" | "interactionreply" => "app.bsky.feed.defs#interactionReply",
+        "quote" | "interactionquote" => "app.bsky.feed.defs#interactionQuote",
+        "share" | "interactionshare" => "app.bsky.feed.defs#interactionShare",
+        "requestless" => "app.bsky.feed.defs#requestLess",
+        "requestmore" => "app.bsky.feed.defs#requestMore",
+        _ => {
+            return Err(AppError::ApiError(format!(
+                "Invalid interaction event: {event}"
+            )))
+        }
+    };
+
+    Ok(token)
+}
+
+/// Queue interactions for a feed; returns whether the feed's queue is due for an early flush
+fn enqueue(user_did: &str, feed_uri: &str, interactions: Vec<PendingInteraction>) -> bool {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let queue = pending
+        .entry(user_did.to_string())
+        .or_default()
+        .entry(feed_uri.to_string())
+        .or_default();
+    for interaction in interactions {
+        if !queue.contains(&interaction) {
+            queue.push(interaction);
+        }
+    }
+    queue.len() >= MAX_PENDING_PER_FEED
+}
+
+async fn feed_service_did(agent: &AppAgent, feed_uri: &str) -> Result<Did, AppError> {
+    let cached = FEED_SERVICE_DIDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(feed_uri)
+        .cloned();
+
+    let did = match cached {
+        Some(did) => did,
+        None => {
+            let feed: AtUri = feed_uri
+                .parse()
+                .map_err(|_| AppError::ApiError("Invalid feed URI".into()))?;
+            let response = agent
+                .api
+                .app
+                .bsky
+                .feed
+                .get_feed_generator(get_feed_generator::ParametersData { feed }.into())
+                .await
+                .map_err(connectivity::xrpc_error)?;
+            let did = response.data.view.did.to_string();
+            FEED_SERVICE_DIDS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(feed_uri.to_string(), did.clone());
+            did
+        }
+    };
+
+    did.parse()
+        .map_err(|_| AppError::ApiError("Invalid feed generator DID".into()))
+}
+
+async fn send_batch(
+    agent: &AppAgent,
+    feed_uri: &str,
+    batch: &[PendingInteraction],
+) -> Result<(), AppError> {
+    let service_did = feed_service_did(agent, feed_uri).await?;
+
+    let interactions = batch
+        .iter()
+        .map(|pending| {
+            Interaction::from(InteractionData {
+                event: Some(pending.event.to_string()),
+                feed_context: pending.feed_context.clone(),
+                item: pending.item.parse().ok(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    agent
+        .api_with_proxy(service_did, FEED_GENERATOR_SERVICE_TYPE)
+        .app
+        .bsky
+        .feed
+        .send_interactions(send_interactions::InputData { interactions }.into())
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(())
+}
+
+/// Take the account's queued batches, discarding any left behind by another account
+fn take_batches(user_did: &str) -> FeedQueues {
+    let mut pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    pending.remove(user_did).unwrap_or_default()
+}
+
+/// Drop every queued interaction (sign-out or account switch)
+pub(crate) fn reset() {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Send every queued interaction. Interactions are best-effort ranking hints,
+/// so a batch that fails to send is dropped rather than retried.
+pub(crate) async fn flush_feed_interactions(agent_state: &AgentState) {
+    let Ok(user_did) = get_stored_session().map(|session| session.did) else {
+        reset();
+        return;
+    };
+    let batches = take_batches(&user_did);
+    if batches.is_empty() {
+        return;
+    }
+
+    let Ok(agent) = current_agent(agent_state).await else {
+        return;
+    };
+
+    for (feed_uri, batch) in batches {
+        if let Err(err) = send_batch(&agent, &feed_uri, &batch).await {
+            tracing::warn!("dropped {} interactions for {feed_uri}: {err}", batch.len());
+        }
+    }
+}
+
+/// Queue feed interactions (seen, clickthrough, like, share, ...) for an algorithmic feed.
+/// They are sent in batches by a background task rather than one request per event.
+#[tauri::command]
+pub async fn send_feed_interactions(
+    agent_state: State<'_, AgentState>,
+    feed_uri: String,
+    interactions: Vec<FeedInteraction>,
+) -> Result<(), AppError> {
+    let feed_uri = feed_uri.trim();
+    if feed_uri.is_empty() || interactions.is_empty() {
+        return Ok(());
+    }
+
+    let pending = interactions
+        .into_iter()
+        .map(|interaction| {
+            Ok(PendingInteraction {
+                item: interaction.item_uri,
+                event: parse_event(&interaction.event)?,
+                feed_context: interaction.feed_context.filter(|c| !c.is_empty()),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    queue(agent_state.inner(), feed_uri, pending);
+    Ok(())
+}
+
+fn queue(agent_state: &AgentState, feed_uri: &str, pending: Vec<PendingInteraction>) {
+    let Ok(user_did) = get_stored_session().map(|session| session.did) else {
+        return;
+    };
+    if enqueue(&user_did, feed_uri, pending) {
+        let agent_state = agent_state.clone();
+        tauri::async_runtime::spawn(async move {
+            flush_feed_interactions(&agent_state).await;
+        });
+    }
+}
+
+/// Tell a feed the user wants less like `item_uri` (sent with the next batch)
+pub(crate) fn queue_request_less(
+    agent_state: &AgentState,
+    feed_uri: &str,
+    item_uri: &str,
+    feed_context: Option<String>,
+) {
+    queue(
+        agent_state,
+        feed_uri,
+        vec![PendingInteraction {
+            item: item_uri.to_string(),
+            event: "app.bsky.feed.defs#requestLess",
+            feed_context: feed_context.filter(|c| !c.is_empty()),
+        }],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seen(item: &str) -> PendingInteraction {
+        PendingInteraction {
+            item: item.to_string(),
+            event: "app.bsky.feed.defs#interactionSeen",
+            feed_context: None,
+        }
+    }
+
+    #[test]
+    fn queues_are_kept_per_account_and_cleared_on_reset() {
+        let (alice, bob) = (
+            format!("did:plc:{}", uuid::Uuid::new_v4()),
+            format!("did:plc:{}", uuid::Uuid::new_v4()),
+        );
+        let feed = "at://did:plc:feed/app.bsky.feed.generator/test";
+
+        enqueue(&alice, feed, vec![seen("at://a/1"), seen("at://a/1")]);
+        enqueue(&bob, feed, vec![seen("at://b/1")]);
+
+        // Bob's flush only sends Bob's queue; Alice's is discarded rather than sent as Bob
+        let batches = take_batches(&bob);
+        assert_eq!(batches.get(feed), Some(&vec![seen("at://b/1")]));
+        assert!(take_batches(&alice).is_empty());
+
+        enqueue(&alice, feed, vec![seen("at://a/2")]);
+        reset();
+        assert!(take_batches(&alice).is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::error::AppError;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Stop reading a page once this much HTML has arrived; meta tags live in the head
+const MAX_HTML_BYTES: usize = 1024 * 1024;
+/// Skip thumbnails larger than this before decoding
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+/// Longest edge of the uploaded thumbnail
+const THUMB_MAX_EDGE: u32 = 1000;
+const THUMB_JPEG_QUALITY: u8 = 80;
+
+/// Link card data for the composer, ready to attach to `create_post` as `external`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExternalEmbedInput {
+    /// Final URL after redirects
+    pub uri: String,
+    pub title: String,
+    pub description: String,
+    /// Uploaded thumbnail blob ref, as returned by uploadBlob
+    #[serde(default)]
+    pub thumb: Option<serde_json::Value>,
+    /// Remote image URL the thumb was made from, for the composer preview
+    #[serde(default)]
+    pub thumb_url: Option<String>,
+}
+
+#[derive(Default)]
+struct PageMeta {
+    title: Option<String>,
+    description: Option<String>,
+    image: Option<String>,
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.bytes().take(12).position(|b| b == b';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Attributes of a single tag body (`meta property="og:title" content="..."`)
+fn tag_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace() || c == '>')
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            break;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            attrs.push((name, String::new()));
+            continue;
+        };
+        let after_eq = after_eq.trim_start();
+        let (value, remaining) = match after_eq.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let body = &after_eq[1..];
+                let end = body.find(quote).unwrap_or(body.len());
+                (&body[..end], body.get(end + 1..).unwrap_or(""))
+            }
+            _ => {
+                let end = after_eq
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(after_eq.len());
+                (&after_eq[..end], &after_eq[end..])
+            }
+        };
+        attrs.push((name, decode_entities(value)));
+        rest = remaining;
+    }
+    attrs
+}
+
+/// Pull OpenGraph / Twitter card tags (falling back to `<title>` and the plain
+/// description meta). OpenGraph wins when both are present.
+fn parse_meta(html: &str) -> PageMeta {
+    let lower = html.to_ascii_lowercase();
+    let mut og = PageMeta::default();
+    let mut twitter = PageMeta::default();
+    let mut plain = PageMeta::default();
+
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find("<meta") {
+        let start = pos + found + "<meta".len();
+        let end = lower[start..]
+            .find('>')
+            .map_or(lower.len(), |end| start + end);
+        pos = end;
+
+        let attrs = tag_attributes(&html[start..end]);
+        let get = |key: &str| {
+            attrs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.trim().to_string())
+        };
+        let Some(content) = get("content").filter(|c| !c.is_empty()) else {
+            continue;
+        };
+        let key = get("property")
+            .or_else(|| get("name"))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let slot = match key.as_str() {
+            "og:title" => &mut og.title,
+            "og:description" => &mut og.description,
+            "og:image" | "og:image:url" | "og:image:secure_url" => &mut og.image,
+            "twitter:title" => &mut twitter.title,
+            "twitter:description" => &mut twitter.description,
+            "twitter:image" | "twitter:image:src" => &mut twitter.image,
+            "description" => &mut plain.description,
+            _ => continue,
+        };
+        slot.get_or_insert(content);
+    }
+
+    if let Some(start) = lower.find("<title") {
+        if let Some(open_end) = lower[start..].find('>') {
+            let body_start = start + open_end + 1;
+            if let Some(close) = lower[body_start..].find("</title") {
+                let title = decode_entities(html[body_start..body_start + close].trim());
+                plain.title = (!title.is_empty()).then_some(title);
+            }
+        }
+    }
+
+    PageMeta {
+        title: og.title.or(twitter.title).or(plain.title),
+        description: og.description.or(twitter.description).or(plain.description),
+        image: og.image.or(twitter.image),
+    }
+}
+
+/// Read at most `limit` bytes of a response body
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>, AppError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| crate::http::network_error("link card read failed", e))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() >= limit {
+            body.truncate(limit);
+            break;
+        }
+    }
+    Ok(body)
+}
+
+/// Download the card image, shrink it to a JPEG and upload it as a blob
+pub(crate) async fn upload_thumb(
+    agent: &AppAgent,
+    image_url: &str,
+) -> Result<serde_json::Value, AppError> {
+    let response = crate::http::client()
+        .get(image_url)
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error("link card image fetch failed", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::NetworkError(format!(
+            "link card image returned status {}",
+            response.status()
+        )));
+    }
+    let bytes = read_limited(response, MAX_IMAGE_BYTES).await?;
+
+    let jpeg = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, AppError> {
+        let img = image::load_from_memory(&bytes)
+            .map_err(|e| AppError::InternalError(format!("decode link card image: {e}")))?;
+        let img = if img.width() > THUMB_MAX_EDGE || img.height() > THUMB_MAX_EDGE {
+            img.resize(
+                THUMB_MAX_EDGE,
+                THUMB_MAX_EDGE,
+                imageops::FilterType::Triangle,
+            )
+        } else {
+            img
+        };
+
+        let mut out = Vec::new();
+        JpegEncoder::new_with_quality(&mut out, THUMB_JPEG_QUALITY)
+            .encode_image(&img.to_rgb8())
+            .map_err(|e| AppError::InternalError(format!("encode link card image: {e}")))?;
+        Ok(out)
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")))??;
+
+    let upload = agent
+        .api
+        .com
+        .atproto
+        .repo
+        .upload_blob(jpeg)
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Failed to upload blob: {e}")))?;
+
+    serde_json::to_value(&upload.data.blob)
+        .map_err(|e| AppError::InternalError(format!("blob encode failed: {e}")))
+}
+
+/// Build a link card for a URL pasted into the composer: fetch the page (following
+/// redirects), read its OpenGraph/Twitter tags and upload the preview image as a blob.
+/// Pages that aren't HTML, lack tags or whose image fails still yield a card titled
+/// with the URL, just without the missing parts.
+#[tauri::command]
+pub async fn fetch_link_card(
+    agent_state: State<'_, AgentState>,
+    url: String,
+) -> Result<ExternalEmbedInput, AppError> {
+    let requested = reqwest::Url::parse(url.trim())
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .ok_or_else(|| AppError::ApiError(format!("Invalid link: {}", url.trim())))?;
+
+    let response = crate::http::client()
+        .get(requested.clone())
+        .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error("link card fetch failed", e))?;
+
+    let final_url = response.url().clone();
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| {
+            let ct = ct.to_ascii_lowercase();
+            ct.contains("text/html") || ct.contains("application/xhtml")
+        });
+
+    let meta = if response.status().is_success() && is_html {
+        let body = read_limited(response, MAX_HTML_BYTES).await?;
+        parse_meta(&String::from_utf8_lossy(&body))
+    } else {
+        PageMeta::default()
+    };
+
+    let image_url = meta
+        .image
+        .as_deref()
+        .and_then(|image| final_url.join(image).ok())
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .map(|u| u.to_string());
+
+    let (thumb, thumb_url) = match image_url {
+        Some(image_url) => {
+            let agent = current_agent(&agent_state).await?;
+            match upload_thumb(&agent, &image_url).await {
+                Ok(blob) => (Some(blob), Some(image_url)),
+                Err(err) => {
+                    tracing::warn!("link card thumb skipped for {final_url}: {err}");
+                    (None, None)
+                }
+            }
+        }
+        None => (None, None),
+    };
+
+    Ok(ExternalEmbedInput {
+        uri: final_url.to_string(),
+        title: meta.title.unwrap_or_else(|| final_url.to_string()),
+        description: meta.description.unwrap_or_default(),
+        thumb,
+        thumb_url,
+    })
+}
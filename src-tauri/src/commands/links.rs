@@ -0,0 +1,534 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::connectivity;
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, State};
+
+const WEB_HOSTS: [&str; 2] = ["bsky.app", "www.bsky.app"];
+const WEB_ORIGIN: &str = "https://bsky.app";
+/// Placeholder handle the AppView reports when a handle no longer verifies
+const INVALID_HANDLE: &str = "handle.invalid";
+/// Drop the identity cache once it grows past this many entries
+const MAX_CACHED_IDENTITIES: usize = 5000;
+
+/// Handle <-> DID resolutions made while building and parsing links
+#[derive(Default)]
+struct IdentityCache {
+    dids_by_handle: HashMap<String, String>,
+    handles_by_did: HashMap<String, String>,
+}
+
+static IDENTITIES: LazyLock<Mutex<IdentityCache>> =
+    LazyLock::new(|| Mutex::new(IdentityCache::default()));
+
+fn remember_identity(handle: &str, did: &str) {
+    let mut cache = IDENTITIES.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.dids_by_handle.len() >= MAX_CACHED_IDENTITIES {
+        *cache = IdentityCache::default();
+    }
+    let handle = handle.to_lowercase();
+    cache.dids_by_handle.insert(handle.clone(), did.to_string());
+    cache.handles_by_did.insert(did.to_string(), handle);
+}
+
+#[derive(Serialize)]
+pub struct ResolvedLink {
+    pub at_uri: String,
+    /// "post", "profile", "feed", "list" or "starter_pack"
+    pub kind: String,
+    pub did: String,
+}
+
+/// A bsky.app path split into the actor and the record it points at
+struct WebLink<'a> {
+    actor: &'a str,
+    kind: &'static str,
+    collection: Option<&'static str>,
+    rkey: Option<&'a str>,
+}
+
+fn parse_web_path(path: &str) -> Option<WebLink<'_>> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let (kind, collection, actor, rkey) = match segments.as_slice() {
+        ["profile", actor] => ("profile", None, *actor, None),
+        ["profile", actor, "post", rkey] => {
+            ("post", Some("app.bsky.feed.post"), *actor, Some(*rkey))
+        }
+        ["profile", actor, "feed", rkey] => {
+            ("feed", Some("app.bsky.feed.generator"), *actor, Some(*rkey))
+        }
+        ["profile", actor, "lists", rkey] => {
+            ("list", Some("app.bsky.graph.list"), *actor, Some(*rkey))
+        }
+        ["starter-pack", actor, rkey] | ["start", actor, rkey] => (
+            "starter_pack",
+            Some("app.bsky.graph.starterpack"),
+            *actor,
+            Some(*rkey),
+        ),
+        _ => return None,
+    };
+
+    Some(WebLink {
+        actor,
+        kind,
+        collection,
+        rkey,
+    })
+}
+
+fn at_uri_kind(uri: &str) -> &'static str {
+    match uri.trim_start_matches("at://").split('/').nth(1) {
+        None => "profile",
+        Some("app.bsky.feed.post") => "post",
+        Some("app.bsky.feed.generator") => "feed",
+        Some("app.bsky.graph.list") => "list",
+        Some("app.bsky.graph.starterpack") => "starter_pack",
+        Some(_) => "record",
+    }
+}
+
+async fn resolve_actor_did(agent: &AppAgent, actor: &str) -> Result<String, AppError> {
+    if actor.starts_with("did:") {
+        return Ok(actor.to_string());
+    }
+
+    let actor = actor.trim_start_matches('@');
+    if let Some(did) = IDENTITIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .dids_by_handle
+        .get(&actor.to_lowercase())
+    {
+        return Ok(did.clone());
+    }
+
+    let handle = actor
+        .parse()
+        .map_err(|_| AppError::ApiError(format!("Invalid handle: {actor}")))?;
+
+    let response = agent
+        .api
+        .com
+        .atproto
+        .identity
+        .resolve_handle(
+            bsky_sdk::api::com::atproto::identity::resolve_handle::ParametersData { handle }.into(),
+        )
+        .await
+        .map_err(|e| resolve_error(actor, e))?;
+
+    let did = response.data.did.to_string();
+    remember_identity(actor, &did);
+    Ok(did)
+}
+
+/// Current handle for a DID, or `None` if it has no valid handle
+async fn resolve_did_handle(agent: &AppAgent, did: &str) -> Result<Option<String>, AppError> {
+    if let Some(handle) = IDENTITIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .handles_by_did
+        .get(did)
+    {
+        return Ok(Some(handle.clone()));
+    }
+
+    let response = agent
+        .api
+        .app
+        .bsky
+        .actor
+        .get_profile(
+            bsky_sdk::api::app::bsky::actor::get_profile::ParametersData {
+                actor: did
+                    .parse()
+                    .map_err(|_| AppError::ApiError(format!("Invalid DID: {did}")))?,
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| resolve_error(did, e))?;
+
+    let handle = response.data.handle.to_string();
+    if handle == INVALID_HANDLE {
+        return Ok(None);
+    }
+    remember_identity(&handle, did);
+    Ok(Some(handle))
+}
+
+/// Resolve a bsky.app web link (or pass through an at:// URI) to an at:// URI.
+/// Handles in links are resolved to DIDs so the URI is stable.
+pub(crate) async fn resolve_link(agent: &AppAgent, input: &str) -> Result<ResolvedLink, AppError> {
+    let input = input.trim();
+
+    if let Some(rest) = input.strip_prefix("at://") {
+        let authority = rest.split('/').next().unwrap_or_default();
+        let did = resolve_actor_did(agent, authority).await?;
+        let at_uri = if authority.starts_with("did:") {
+            input.to_string()
+        } else {
+            format!("at://{did}{}", &rest[authority.len()..])
+        };
+        return Ok(ResolvedLink {
+            kind: at_uri_kind(&at_uri).to_string(),
+            at_uri,
+            did,
+        });
+    }
+
+    let url = reqwest::Url::parse(input)
+        .map_err(|_| AppError::ApiError(format!("Not a Bluesky link: {input}")))?;
+    if !url.host_str().is_some_and(|host| WEB_HOSTS.contains(&host)) {
+        return Err(AppError::ApiError(format!("Not a Bluesky link: {input}")));
+    }
+
+    let link = parse_web_path(url.path())
+        .ok_or_else(|| AppError::ApiError(format!("Unsupported Bluesky link: {input}")))?;
+    let did = resolve_actor_did(agent, link.actor).await?;
+
+    let at_uri = match (link.collection, link.rkey) {
+        (Some(collection), Some(rkey)) => format!("at://{did}/{collection}/{rkey}"),
+        _ => format!("at://{did}"),
+    };
+
+    Ok(ResolvedLink {
+        at_uri,
+        kind: link.kind.to_string(),
+        did,
+    })
+}
+
+/// Accept either an at:// URI or a bsky.app link where a command expects an at:// URI
+pub(crate) async fn resolve_at_uri(agent: &AppAgent, input: &str) -> Result<String, AppError> {
+    let input = input.trim();
+    if input.starts_with("at://did:") {
+        return Ok(input.to_string());
+    }
+    Ok(resolve_link(agent, input).await?.at_uri)
+}
+
+/// Resolve a pasted bsky.app URL (post, profile, feed, list, starter pack) to an at:// URI
+#[tauri::command]
+pub async fn resolve_bsky_url(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    url: String,
+) -> Result<ResolvedLink, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    resolve_link(&agent, &url)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+#[derive(Serialize)]
+pub struct ShareLinks {
+    /// Canonical bsky.app URL, using the handle when it is valid
+    pub url: String,
+    pub at_uri: String,
+}
+
+/// Build share links for a post from its at:// URI (or a bsky.app link)
+#[tauri::command]
+pub async fn post_share_url(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uri: String,
+) -> Result<ShareLinks, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let link = resolve_link(&agent, &uri)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    if link.kind != "post" {
+        return Err(AppError::ApiError(format!("Not a post URI: {uri}")));
+    }
+
+    let rkey = link.at_uri.rsplit('/').next().unwrap_or_default();
+    let actor = resolve_did_handle(&agent, &link.did)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?
+        .unwrap_or_else(|| link.did.clone());
+
+    Ok(ShareLinks {
+        url: format!("{WEB_ORIGIN}/profile/{actor}/post/{rkey}"),
+        at_uri: link.at_uri,
+    })
+}
+
+/// Build share links for a profile from a DID or handle
+#[tauri::command]
+pub async fn profile_share_url(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did_or_handle: String,
+) -> Result<ShareLinks, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let actor = did_or_handle.trim().trim_start_matches('@');
+
+    let did = resolve_actor_did(&agent, actor)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    let handle = if actor.starts_with("did:") {
+        resolve_did_handle(&agent, &did)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?
+    } else {
+        Some(actor.to_lowercase())
+    };
+
+    Ok(ShareLinks {
+        url: format!(
+            "{WEB_ORIGIN}/profile/{}",
+            handle.unwrap_or_else(|| did.clone())
+        ),
+        at_uri: format!("at://{did}"),
+    })
+}
+
+/// What a deep link points at, with just enough to route to the right screen
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AtprotoTarget {
+    Post {
+        uri: String,
+        cid: String,
+        author_did: String,
+        author_handle: String,
+        /// Thread root, when the post is a reply
+        root_uri: Option<String>,
+    },
+    Profile {
+        did: String,
+        handle: String,
+        display_name: Option<String>,
+    },
+    Feed {
+        uri: String,
+        display_name: String,
+        creator_did: String,
+    },
+    List {
+        uri: String,
+        name: String,
+        /// "curatelist", "modlist" or "referencelist"
+        purpose: String,
+        creator_did: String,
+    },
+    StarterPack {
+        uri: String,
+        name: String,
+        creator_did: String,
+        /// Backing list of the pack's accounts
+        list_uri: Option<String>,
+    },
+    /// A collection this app has no screen for; the raw record value
+    Record {
+        uri: String,
+        collection: String,
+        cid: Option<String>,
+        value: serde_json::Value,
+    },
+}
+
+/// A lookup the server answered is a missing identity; one that never arrived is offline
+fn resolve_error<E>(id: &str, err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if connectivity::is_transport_error(&err) {
+        return connectivity::xrpc_error(err);
+    }
+    AppError::NotFound(format!("Could not resolve {id}: {err}"))
+}
+
+/// Lookups of the routed view fail as `NotFound` unless the network is the problem
+fn target_error<E>(what: &str, err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if connectivity::is_transport_error(&err) {
+        return connectivity::xrpc_error(err);
+    }
+    AppError::NotFound(format!("{what} not found: {err}"))
+}
+
+/// Resolve an at:// URI, bare DID or bsky.app link and fetch the view its collection
+/// calls for: post, profile, feed, list or starter pack. Other collections come back as
+/// the raw record.
+#[tauri::command]
+pub async fn resolve_atproto_uri(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uri: String,
+) -> Result<AtprotoTarget, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let input = uri.trim();
+    let input = if input.starts_with("did:") {
+        format!("at://{input}")
+    } else {
+        input.to_string()
+    };
+    let link = resolve_link(&agent, &input)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    let at_uri = link.at_uri.clone();
+    let invalid = || AppError::ApiError(format!("Invalid URI: {at_uri}"));
+
+    match link.kind.as_str() {
+        "profile" => {
+            let profile = agent
+                .api
+                .app
+                .bsky
+                .actor
+                .get_profile(
+                    bsky_sdk::api::app::bsky::actor::get_profile::ParametersData {
+                        actor: link.did.parse().map_err(|_| invalid())?,
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(|e| target_error("Profile", e))
+                .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            let handle = profile.data.handle.to_string();
+            remember_identity(&handle, &link.did);
+            Ok(AtprotoTarget::Profile {
+                did: profile.data.did.to_string(),
+                handle,
+                display_name: profile.data.display_name.clone(),
+            })
+        }
+        "post" => {
+            let response = agent
+                .api
+                .app
+                .bsky
+                .feed
+                .get_posts(
+                    bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                        uris: vec![at_uri.clone()],
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(|e| target_error("Post", e))
+                .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            let post = response
+                .data
+                .posts
+                .first()
+                .ok_or_else(|| AppError::NotFound(format!("Post not found: {at_uri}")))?;
+            let root_uri = serde_json::to_value(&post.record).ok().and_then(|record| {
+                record
+                    .get("reply")?
+                    .get("root")?
+                    .get("uri")?
+                    .as_str()
+                    .map(str::to_string)
+            });
+            Ok(AtprotoTarget::Post {
+                uri: post.uri.to_string(),
+                cid: post.cid.as_ref().to_string(),
+                author_did: post.author.did.to_string(),
+                author_handle: post.author.handle.to_string(),
+                root_uri,
+            })
+        }
+        "feed" => {
+            let response = agent
+                .api
+                .app
+                .bsky
+                .feed
+                .get_feed_generator(
+                    bsky_sdk::api::app::bsky::feed::get_feed_generator::ParametersData {
+                        feed: at_uri.clone(),
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(|e| target_error("Feed", e))
+                .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            let view = &response.data.view;
+            Ok(AtprotoTarget::Feed {
+                uri: view.uri.to_string(),
+                display_name: view.display_name.clone(),
+                creator_did: view.creator.did.to_string(),
+            })
+        }
+        "list" => {
+            let response = agent
+                .api
+                .app
+                .bsky
+                .graph
+                .get_list(
+                    bsky_sdk::api::app::bsky::graph::get_list::ParametersData {
+                        list: at_uri.parse().map_err(|_| invalid())?,
+                        cursor: None,
+                        limit: 1.try_into().ok(),
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(|e| target_error("List", e))
+                .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            let list = &response.data.list;
+            Ok(AtprotoTarget::List {
+                uri: list.uri.to_string(),
+                name: list.name.clone(),
+                purpose: list.purpose.as_str().to_string(),
+                creator_did: list.creator.did.to_string(),
+            })
+        }
+        "starter_pack" => {
+            let response = agent
+                .api
+                .app
+                .bsky
+                .graph
+                .get_starter_pack(
+                    bsky_sdk::api::app::bsky::graph::get_starter_pack::ParametersData {
+                        starter_pack: at_uri.parse().map_err(|_| invalid())?,
+                    }
+                    .into(),
+                )
+                .await
+                .map_err(|e| target_error("Starter pack", e))
+                .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            let pack = &response.data.starter_pack;
+            let (name, _) = crate::commands::lists::starter_pack_record_text(&pack.record);
+            Ok(AtprotoTarget::StarterPack {
+                uri: pack.uri.to_string(),
+                name,
+                creator_did: pack.creator.did.to_string(),
+                list_uri: pack.list.as_ref().map(|list| list.uri.to_string()),
+            })
+        }
+        _ => {
+            // at://did/collection/rkey
+            let mut parts = at_uri.trim_start_matches("at://").splitn(3, '/').skip(1);
+            let (Some(collection), Some(rkey)) = (parts.next(), parts.next()) else {
+                return Err(invalid());
+            };
+            let record = crate::commands::repo::fetch_record(
+                &agent,
+                link.did.parse().map_err(|_| invalid())?,
+                collection,
+                rkey,
+            )
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+            Ok(AtprotoTarget::Record {
+                uri: record.uri,
+                collection: collection.to_string(),
+                cid: record.cid,
+                value: record.value,
+            })
+        }
+    }
+}
@@ -1,16 +1,23 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState};
+use crate::commands::hidden_posts;
+use crate::commands::links::resolve_at_uri;
+use crate::commands::moderation::{self, LabelVisibility};
+use crate::commands::pagination::fetch_with_cursor_recovery;
+use crate::connectivity;
+use crate::db::DbState;
 use crate::error::AppError;
 use crate::session::get_stored_session;
-use bsky_sdk::api::app::bsky::graph::defs::ListPurpose;
-use bsky_sdk::api::app::bsky::graph::list::RecordData as ListRecordData;
+use bsky_sdk::api::app::bsky::graph::defs::{ListPurpose, ListView, StarterPackViewBasic};
+use bsky_sdk::api::app::bsky::graph::list::{Record as ListRecord, RecordData as ListRecordData};
 use bsky_sdk::api::app::bsky::graph::listitem::RecordData as ListItemRecordData;
 use bsky_sdk::api::com::atproto::repo::{create_record, delete_record, put_record};
-use bsky_sdk::api::types::string::{AtIdentifier, Datetime, Did, RecordKey};
+use bsky_sdk::api::types::string::{AtIdentifier, AtUri, Datetime, Did, RecordKey};
 use bsky_sdk::api::types::LimitedNonZeroU8;
 use bsky_sdk::api::types::TryIntoUnknown;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 fn parse_rkey_from_uri(uri: &str) -> Result<String, AppError> {
     uri.split('/')
@@ -45,6 +52,10 @@ pub struct ListInfo {
     pub creator_display_name: Option<String>,
     pub creator_avatar: Option<String>,
     pub member_count: u32,
+    /// The viewer mutes everyone on this (moderation) list
+    pub viewer_muted: bool,
+    /// URI of the viewer's list block record when they block everyone on it
+    pub viewer_blocked: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -60,63 +71,228 @@ pub struct ListMember {
 pub struct ActorListsResponse {
     pub lists: Vec<ListInfo>,
     pub cursor: Option<String>,
+    pub reset: bool,
 }
 
 /// Get lists created by an actor
 #[tauri::command]
 pub async fn get_actor_lists(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     actor: String,
     cursor: Option<String>,
 ) -> Result<ActorListsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
-    let response = agent
-        .api
-        .app
-        .bsky
-        .graph
-        .get_lists(
+    let actor: AtIdentifier = actor
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid actor".into()))?;
+
+    let (response, reset) = fetch_with_cursor_recovery(cursor, |cursor| {
+        agent.api.app.bsky.graph.get_lists(
             bsky_sdk::api::app::bsky::graph::get_lists::ParametersData {
-                actor: actor
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid actor".into()))?,
+                actor: actor.clone(),
                 cursor,
                 limit: None,
                 purposes: None,
             }
             .into(),
         )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+    })
+    .await
+    .map_err(connectivity::xrpc_error)
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
-    let lists: Vec<ListInfo> = response
-        .data
-        .lists
-        .iter()
-        .map(|l| {
-            let purpose = l.purpose.as_str();
-
-            ListInfo {
-                uri: l.uri.to_string(),
-                cid: l.cid.as_ref().to_string(),
-                name: l.name.clone(),
-                purpose: purpose.to_string(),
-                description: l.description.clone(),
-                avatar: l.avatar.clone(),
-                creator_did: l.creator.did.to_string(),
-                creator_handle: l.creator.handle.to_string(),
-                creator_display_name: l.creator.display_name.clone(),
-                creator_avatar: l.creator.avatar.clone(),
-                member_count: l.list_item_count.unwrap_or(0) as u32,
-            }
-        })
-        .collect();
+    let lists: Vec<ListInfo> = response.data.lists.iter().map(list_view_to_info).collect();
 
     Ok(ActorListsResponse {
         lists,
         cursor: response.data.cursor,
+        reset,
+    })
+}
+
+/// Starter pack as shown on a profile or a "joined via" badge
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StarterPackSummary {
+    pub uri: String,
+    pub cid: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub creator_did: String,
+    pub creator_handle: String,
+    /// Accounts in the pack's list
+    pub list_item_count: u32,
+    pub joined_all_time_count: u32,
+    pub joined_week_count: u32,
+}
+
+#[derive(Serialize)]
+pub struct ActorStarterPacksResponse {
+    pub starter_packs: Vec<StarterPackSummary>,
+    pub cursor: Option<String>,
+    pub reset: bool,
+}
+
+/// Name and description from a starter pack record, which the SDK leaves untyped
+pub(crate) fn starter_pack_record_text(record: &impl Serialize) -> (String, Option<String>) {
+    let record = serde_json::to_value(record).unwrap_or_default();
+    let text = |key: &str| record.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    (text("name").unwrap_or_default(), text("description"))
+}
+
+pub(crate) fn starter_pack_view_to_summary(p: &StarterPackViewBasic) -> StarterPackSummary {
+    let (name, description) = starter_pack_record_text(&p.record);
+    StarterPackSummary {
+        uri: p.uri.to_string(),
+        cid: p.cid.as_ref().to_string(),
+        name,
+        description,
+        creator_did: p.creator.did.to_string(),
+        creator_handle: p.creator.handle.to_string(),
+        list_item_count: p.list_item_count.unwrap_or(0) as u32,
+        joined_all_time_count: p.joined_all_time_count.unwrap_or(0) as u32,
+        joined_week_count: p.joined_week_count.unwrap_or(0) as u32,
+    }
+}
+
+/// Get starter packs created by an actor; empty when they have none
+#[tauri::command]
+pub async fn get_actor_starter_packs(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    actor: String,
+    cursor: Option<String>,
+) -> Result<ActorStarterPacksResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let actor: AtIdentifier = actor
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid actor".into()))?;
+
+    let (response, reset) = fetch_with_cursor_recovery(cursor, |cursor| {
+        agent.api.app.bsky.graph.get_actor_starter_packs(
+            bsky_sdk::api::app::bsky::graph::get_actor_starter_packs::ParametersData {
+                actor: actor.clone(),
+                cursor,
+                limit: None,
+            }
+            .into(),
+        )
+    })
+    .await
+    .map_err(connectivity::xrpc_error)
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(ActorStarterPacksResponse {
+        starter_packs: response
+            .data
+            .starter_packs
+            .iter()
+            .map(starter_pack_view_to_summary)
+            .collect(),
+        cursor: response.data.cursor,
+        reset,
+    })
+}
+
+#[derive(Serialize)]
+pub struct SearchListsResponse {
+    pub lists: Vec<ListInfo>,
+    /// Continues the underlying account search
+    pub cursor: Option<String>,
+}
+
+/// Accounts whose lists are gathered per `search_lists` page
+const SEARCH_LISTS_ACTORS: u8 = 25;
+/// Concurrent getLists requests while gathering
+const SEARCH_LISTS_CONCURRENCY: usize = 5;
+
+/// Find public lists. There is no list search endpoint, so this searches accounts and
+/// gathers the lists they created; lists whose name or description matches the query
+/// come first. Accounts whose lists fail to load are skipped.
+#[tauri::command]
+pub async fn search_lists(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    query: String,
+    cursor: Option<String>,
+) -> Result<SearchListsResponse, AppError> {
+    use futures::StreamExt;
+
+    let agent = current_agent(&agent_state).await?;
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(SearchListsResponse {
+            lists: Vec::new(),
+            cursor: None,
+        });
+    }
+
+    let actors = agent
+        .api
+        .app
+        .bsky
+        .actor
+        .search_actors(
+            bsky_sdk::api::app::bsky::actor::search_actors::ParametersData {
+                q: Some(query.clone()),
+                term: None,
+                limit: SEARCH_LISTS_ACTORS.try_into().ok(),
+                cursor,
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let per_actor: Vec<Vec<ListInfo>> = futures::stream::iter(&actors.data.actors)
+        .map(|actor| {
+            let agent = &agent;
+            async move {
+                let response = agent
+                    .api
+                    .app
+                    .bsky
+                    .graph
+                    .get_lists(
+                        bsky_sdk::api::app::bsky::graph::get_lists::ParametersData {
+                            actor: AtIdentifier::Did(actor.did.clone()),
+                            cursor: None,
+                            limit: None,
+                            purposes: None,
+                        }
+                        .into(),
+                    )
+                    .await;
+                match response {
+                    Ok(response) => response.data.lists.iter().map(list_view_to_info).collect(),
+                    Err(err) => {
+                        tracing::debug!("lists of {} skipped: {err}", actor.did.as_str());
+                        Vec::new()
+                    }
+                }
+            }
+        })
+        .buffered(SEARCH_LISTS_CONCURRENCY)
+        .collect()
+        .await;
+
+    let needle = query.to_lowercase();
+    let (mut lists, others): (Vec<ListInfo>, Vec<ListInfo>) =
+        per_actor.into_iter().flatten().partition(|list| {
+            list.name.to_lowercase().contains(&needle)
+                || list
+                    .description
+                    .as_deref()
+                    .is_some_and(|d| d.to_lowercase().contains(&needle))
+        });
+    lists.extend(others);
+
+    Ok(SearchListsResponse {
+        lists,
+        cursor: actors.data.cursor.clone(),
     })
 }
 
@@ -131,52 +307,39 @@ pub struct ListDetailsResponse {
     pub list: ListInfo,
     pub members: Vec<ListMember>,
     pub cursor: Option<String>,
+    pub reset: bool,
 }
 
 /// Get list details and members
 #[tauri::command]
 pub async fn get_list(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: GetListRequest,
 ) -> Result<ListDetailsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
-    let response = agent
-        .api
-        .app
-        .bsky
-        .graph
-        .get_list(
+    let list_uri: AtUri = resolve_at_uri(&agent, &request.list_uri)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid list URI".into()))?;
+
+    let (response, reset) = fetch_with_cursor_recovery(request.cursor, |cursor| {
+        agent.api.app.bsky.graph.get_list(
             bsky_sdk::api::app::bsky::graph::get_list::ParametersData {
-                list: request
-                    .list_uri
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid list URI".into()))?,
-                cursor: request.cursor,
+                list: list_uri.clone(),
+                cursor,
                 limit: None,
             }
             .into(),
         )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+    })
+    .await
+    .map_err(connectivity::xrpc_error)
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
-    let l = &response.data.list;
-    let purpose = l.purpose.as_str();
-
-    let list = ListInfo {
-        uri: l.uri.to_string(),
-        cid: l.cid.as_ref().to_string(),
-        name: l.name.clone(),
-        purpose: purpose.to_string(),
-        description: l.description.clone(),
-        avatar: l.avatar.clone(),
-        creator_did: l.creator.did.to_string(),
-        creator_handle: l.creator.handle.to_string(),
-        creator_display_name: l.creator.display_name.clone(),
-        creator_avatar: l.creator.avatar.clone(),
-        member_count: l.list_item_count.unwrap_or(0) as u32,
-    };
+    let list = list_view_to_info(&response.data.list);
 
     let members: Vec<ListMember> = response
         .data
@@ -195,6 +358,7 @@ pub async fn get_list(
         list,
         members,
         cursor: response.data.cursor,
+        reset,
     })
 }
 
@@ -212,11 +376,11 @@ pub struct SubjectMembershipsResponse {
 /// Check which of the current user's lists contain a specific subject
 #[tauri::command]
 pub async fn get_subject_list_memberships(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     subject_did: String,
 ) -> Result<SubjectMembershipsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let current_did = current_repo_did()?;
 
@@ -236,7 +400,8 @@ pub async fn get_subject_list_memberships(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let mut memberships = Vec::new();
 
@@ -256,7 +421,8 @@ pub async fn get_subject_list_memberships(
                 .into(),
             )
             .await
-            .map_err(|e| AppError::ApiError(e.to_string()))?;
+            .map_err(connectivity::xrpc_error)
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
         // Find the subject in this list's members
         for item in list_details.data.items {
@@ -273,6 +439,149 @@ pub async fn get_subject_list_memberships(
     Ok(SubjectMembershipsResponse { memberships })
 }
 
+fn list_view_to_info(l: &ListView) -> ListInfo {
+    ListInfo {
+        uri: l.uri.to_string(),
+        cid: l.cid.as_ref().to_string(),
+        name: l.name.clone(),
+        purpose: l.purpose.as_str().to_string(),
+        description: l.description.clone(),
+        avatar: l.avatar.clone(),
+        creator_did: l.creator.did.to_string(),
+        creator_handle: l.creator.handle.to_string(),
+        creator_display_name: l.creator.display_name.clone(),
+        creator_avatar: l.creator.avatar.clone(),
+        member_count: l.list_item_count.unwrap_or(0) as u32,
+        viewer_muted: l.viewer.as_ref().and_then(|v| v.muted).unwrap_or(false),
+        viewer_blocked: l
+            .viewer
+            .as_ref()
+            .and_then(|v| v.blocked.as_ref())
+            .map(|uri| uri.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+pub struct ListWithMembership {
+    pub list: ListInfo,
+    pub contains_subject: bool,
+    pub listitem_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ListsWithMembershipResponse {
+    pub lists: Vec<ListWithMembership>,
+}
+
+/// Get the current user's curate lists, each annotated with whether a subject is a member.
+/// Membership comes from the user's own listitem records instead of walking every list;
+/// the walk stops once every list has been matched.
+#[tauri::command]
+pub async fn get_lists_with_membership(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    subject_did: String,
+) -> Result<ListsWithMembershipResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let current_did = current_repo_did()?;
+    let subject_did = subject_did.trim().to_string();
+
+    let mut curate_lists = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = agent
+            .api
+            .app
+            .bsky
+            .graph
+            .get_lists(
+                bsky_sdk::api::app::bsky::graph::get_lists::ParametersData {
+                    actor: current_did.clone().into(),
+                    cursor: cursor.take(),
+                    limit: Some(max_list_fetch_limit()?),
+                    purposes: None,
+                }
+                .into(),
+            )
+            .await
+            .map_err(connectivity::xrpc_error)
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+        let page_empty = page.data.lists.is_empty();
+        curate_lists.extend(
+            page.data
+                .lists
+                .into_iter()
+                .filter(|l| l.purpose.as_str() == "app.bsky.graph.defs#curatelist"),
+        );
+        match page.data.cursor {
+            Some(next) if !page_empty => cursor = Some(next),
+            _ => break,
+        }
+    }
+
+    // list_uri -> listitem_uri for the subject's listitem record on each list
+    let list_uris: HashSet<String> = curate_lists.iter().map(|l| l.uri.to_string()).collect();
+    let mut subject_items: HashMap<String, String> = HashMap::new();
+    let mut cursor: Option<String> = None;
+    while !list_uris.is_empty() {
+        let page = crate::commands::repo::list_records_page(
+            &agent,
+            AtIdentifier::Did(current_did.clone()),
+            "app.bsky.graph.listitem",
+            cursor,
+            100,
+        )
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+        if match_subject_items(&subject_did, &list_uris, &page.records, &mut subject_items) {
+            break;
+        }
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    let lists = curate_lists
+        .iter()
+        .map(|l| {
+            let listitem_uri = subject_items.get(&l.uri.to_string()).cloned();
+            ListWithMembership {
+                list: list_view_to_info(l),
+                contains_subject: listitem_uri.is_some(),
+                listitem_uri,
+            }
+        })
+        .collect();
+
+    Ok(ListsWithMembershipResponse { lists })
+}
+
+/// Record the first listitem placing `subject` on each of `lists`; true once every list
+/// has one, so the caller can stop reading records
+fn match_subject_items(
+    subject: &str,
+    lists: &HashSet<String>,
+    records: &[crate::commands::repo::RecordListItem],
+    found: &mut HashMap<String, String>,
+) -> bool {
+    for record in records {
+        if record.value.get("subject").and_then(|v| v.as_str()) != Some(subject) {
+            continue;
+        }
+        let Some(list_uri) = record.value.get("list").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if lists.contains(list_uri) && !found.contains_key(list_uri) {
+            found.insert(list_uri.to_string(), record.uri.clone());
+        }
+    }
+    found.len() == lists.len()
+}
+
 #[derive(Deserialize)]
 pub struct CreateListRequest {
     pub name: String,
@@ -289,11 +598,11 @@ pub struct CreateListResponse {
 /// Create a new list
 #[tauri::command]
 pub async fn create_list(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: CreateListRequest,
 ) -> Result<CreateListResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -339,7 +648,8 @@ pub async fn create_list(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(CreateListResponse {
         uri: result.data.uri.to_string(),
@@ -357,49 +667,37 @@ pub struct UpdateListRequest {
 /// Update an existing list
 #[tauri::command]
 pub async fn update_list(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: UpdateListRequest,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
     let rkey_str = parse_rkey_from_uri(&request.list_uri)?;
     let rkey = RecordKey::from_str(&rkey_str)
         .map_err(|_| AppError::ApiError("Invalid record key".into()))?;
 
-    // First get the existing record to preserve fields
-    let existing = agent
-        .api
-        .app
-        .bsky
-        .graph
-        .get_list(
-            bsky_sdk::api::app::bsky::graph::get_list::ParametersData {
-                list: request
-                    .list_uri
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid list URI".into()))?,
-                cursor: None,
-                limit: None,
-            }
-            .into(),
-        )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
-
-    let purpose = existing.data.list.purpose.clone();
-    let description = request.description.filter(|d| !d.is_empty());
-
-    let record_data = ListRecordData {
-        name: request.name,
-        purpose,
-        description,
-        created_at: Datetime::now(),
-        avatar: None,
-        description_facets: None,
-        labels: None,
-    };
+    // Read the existing record so fields we don't edit (avatar, facets, labels,
+    // created_at) survive the write.
+    let existing = crate::commands::repo::fetch_record(
+        &agent,
+        AtIdentifier::Did(did.clone()),
+        "app.bsky.graph.list",
+        &rkey_str,
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    let swap_record = existing.cid.as_deref().and_then(|cid| cid.parse().ok());
+
+    let mut record_data = serde_json::from_value::<ListRecord>(existing.value)
+        .map_err(|e| AppError::InternalError(format!("list record decode failed: {e}")))?
+        .data;
+    record_data.name = request.name;
+    record_data.description = request.description.filter(|d| !d.is_empty());
+    if record_data.description.is_none() {
+        record_data.description_facets = None;
+    }
 
     let record = record_data
         .try_into_unknown()
@@ -419,13 +717,14 @@ pub async fn update_list(
                 record,
                 rkey,
                 swap_commit: None,
-                swap_record: None,
+                swap_record,
                 validate: None,
             }
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -433,11 +732,11 @@ pub async fn update_list(
 /// Delete a list
 #[tauri::command]
 pub async fn delete_list(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     list_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
     let rkey_str = parse_rkey_from_uri(&list_uri)?;
@@ -462,7 +761,8 @@ pub async fn delete_list(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -481,11 +781,11 @@ pub struct AddListMemberResponse {
 /// Add a member to a list
 #[tauri::command]
 pub async fn add_list_member(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: AddListMemberRequest,
 ) -> Result<AddListMemberResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
 
@@ -524,7 +824,8 @@ pub async fn add_list_member(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(AddListMemberResponse {
         uri: result.data.uri.to_string(),
@@ -534,11 +835,11 @@ pub async fn add_list_member(
 /// Remove a member from a list
 #[tauri::command]
 pub async fn remove_list_member(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     listitem_uri: String,
 ) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let did = current_repo_did()?;
     let rkey_str = parse_rkey_from_uri(&listitem_uri)?;
@@ -563,7 +864,8 @@ pub async fn remove_list_member(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(())
 }
@@ -574,12 +876,28 @@ pub struct GetListFeedRequest {
     #[serde(default = "default_feed_limit")]
     pub limit: u8,
     pub cursor: Option<String>,
+    /// Keep posts that reply to someone (default true)
+    #[serde(default = "default_include")]
+    pub include_replies: bool,
+    /// Keep reposts by list members (default true)
+    #[serde(default = "default_include")]
+    pub include_reposts: bool,
 }
 
 fn default_feed_limit() -> u8 {
     50
 }
 
+fn default_include() -> bool {
+    true
+}
+
+fn is_reply(post: &bsky_sdk::api::app::bsky::feed::defs::PostView) -> bool {
+    serde_json::to_value(&post.record)
+        .map(|json| json.get("reply").is_some())
+        .unwrap_or(false)
+}
+
 #[derive(Serialize)]
 pub struct ListFeedPost {
     pub uri: String,
@@ -600,12 +918,19 @@ pub struct ListFeedPost {
     pub is_reposted: bool,
     pub viewer_like: Option<String>,
     pub viewer_repost: Option<String>,
+    pub feed_context: Option<String>,
+    /// Why the item is in the feed: "repost", "pin", ...
+    pub reason: Option<String>,
+    pub repost_context: super::timeline::RepostContext,
+    /// Label the user asked to be warned about
+    pub content_warning: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct ListFeedResponse {
     pub posts: Vec<ListFeedPost>,
     pub cursor: Option<String>,
+    pub reset: bool,
 }
 
 fn extract_post_text(post: &bsky_sdk::api::app::bsky::feed::defs::PostView) -> String {
@@ -629,42 +954,58 @@ fn extract_created_at(post: &bsky_sdk::api::app::bsky::feed::defs::PostView) ->
 /// Get feed of posts from list members
 #[tauri::command]
 pub async fn get_list_feed(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     request: GetListFeedRequest,
 ) -> Result<ListFeedResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
+    let label_prefs = moderation::content_label_prefs(&agent).await;
 
     let limit_val = request.limit.max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
 
-    let response = agent
-        .api
-        .app
-        .bsky
-        .feed
-        .get_list_feed(
+    let list_uri: AtUri = resolve_at_uri(&agent, &request.list_uri)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid list URI".into()))?;
+
+    let (response, reset) = fetch_with_cursor_recovery(request.cursor, |cursor| {
+        agent.api.app.bsky.feed.get_list_feed(
             bsky_sdk::api::app::bsky::feed::get_list_feed::ParametersData {
-                list: request
-                    .list_uri
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid list URI".into()))?,
-                cursor: request.cursor,
-                limit,
+                list: list_uri.clone(),
+                cursor,
+                limit: limit.clone(),
             }
             .into(),
         )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+    })
+    .await
+    .map_err(connectivity::xrpc_error)
+    .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
+    // The list feed API has no filter params, so filtering happens on the fetched page
+    // and a page may come back shorter than `limit`
+    let hidden = hidden_posts::current_hidden_posts(db.inner()).await;
     let posts: Vec<ListFeedPost> = response
         .data
         .feed
         .iter()
+        .filter(|feed_view| {
+            if hidden.contains(feed_view.post.uri.as_str()) {
+                return false;
+            }
+            let repost = super::timeline::extract_repost_context(feed_view).is_repost();
+            (request.include_reposts || !repost)
+                // A reposted reply is shown for the repost, so only the repost flag applies
+                && (request.include_replies || repost || !is_reply(&feed_view.post))
+                && label_prefs.moderate_post(&feed_view.post).0 != LabelVisibility::Hide
+        })
         .map(|feed_view| {
             let post = &feed_view.post;
-            let (is_repost, reposted_by_handle, reposted_by_display_name) =
-                super::timeline::extract_repost_context(feed_view);
+            let repost_context = super::timeline::extract_repost_context(feed_view);
+            let (reposted_by_handle, reposted_by_display_name) = repost_context.reposted_by();
             ListFeedPost {
                 uri: post.uri.to_string(),
                 cid: post.cid.as_ref().to_string(),
@@ -672,7 +1013,7 @@ pub async fn get_list_feed(
                 author_handle: post.author.handle.to_string(),
                 author_display_name: post.author.display_name.clone(),
                 author_avatar: post.author.avatar.clone(),
-                is_repost,
+                is_repost: repost_context.is_repost(),
                 reposted_by_handle,
                 reposted_by_display_name,
                 text: extract_post_text(post),
@@ -696,6 +1037,10 @@ pub async fn get_list_feed(
                     .as_ref()
                     .and_then(|v| v.repost.as_ref())
                     .map(|u| u.to_string()),
+                feed_context: feed_view.feed_context.clone(),
+                reason: super::timeline::extract_feed_reason(feed_view),
+                repost_context,
+                content_warning: label_prefs.moderate_post(post).1,
             }
         })
         .collect();
@@ -703,5 +1048,45 @@ pub async fn get_list_feed(
     Ok(ListFeedResponse {
         posts,
         cursor: response.data.cursor,
+        reset,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::repo::RecordListItem;
+
+    const SUBJECT: &str = "did:plc:subject";
+    const FRIENDS: &str = "at://did:plc:me/app.bsky.graph.list/friends";
+    const ARTISTS: &str = "at://did:plc:me/app.bsky.graph.list/artists";
+
+    fn item(rkey: &str, list: &str, subject: &str) -> RecordListItem {
+        RecordListItem {
+            uri: format!("at://did:plc:me/app.bsky.graph.listitem/{rkey}"),
+            cid: "bafyreib".into(),
+            value: serde_json::json!({ "list": list, "subject": subject }),
+        }
+    }
+
+    #[test]
+    fn membership_is_matched_per_list() {
+        let lists: HashSet<String> = [FRIENDS, ARTISTS].map(String::from).into();
+        let mut found = HashMap::new();
+
+        let page = [
+            item("a", FRIENDS, "did:plc:someone-else"),
+            item("b", FRIENDS, SUBJECT),
+            item("c", "at://did:plc:me/app.bsky.graph.list/deleted", SUBJECT),
+        ];
+        assert!(!match_subject_items(SUBJECT, &lists, &page, &mut found));
+        assert_eq!(found.len(), 1);
+        assert!(found[FRIENDS].ends_with("/b"));
+
+        // A duplicate listitem for a list already matched keeps the first
+        let page = [item("d", FRIENDS, SUBJECT), item("e", ARTISTS, SUBJECT)];
+        assert!(match_subject_items(SUBJECT, &lists, &page, &mut found));
+        assert!(found[FRIENDS].ends_with("/b"));
+        assert!(found[ARTISTS].ends_with("/e"));
+    }
+}
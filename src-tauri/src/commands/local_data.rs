@@ -0,0 +1,336 @@
+use crate::commands::gifs::TENOR_API_KEY;
+use crate::commands::history::{load_view_history, save_view_entry, ViewHistoryEntry};
+use crate::commands::settings::{
+    apply_saved_blur_sensitive_media, apply_saved_endpoints, apply_saved_image_policy,
+    apply_saved_log_level, apply_saved_media_autoload, apply_saved_request_timeout, DEVICE_SCOPE,
+    SESSION_BACKEND_KEY,
+};
+use crate::commands::translate::TRANSLATE_API_KEY;
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Identifies a local data export file
+const EXPORT_FORMAT: &str = "blue-horizon.local-data";
+/// Bump when the file layout changes and add a step to `migrate_export`
+const EXPORT_VERSION: u32 = 1;
+/// Settings holding secrets never leave the machine, nor does where this machine keeps
+/// the session (importing it would strand the session in the old backend)
+const EXCLUDED_SETTING_KEYS: [&str; 3] = [TENOR_API_KEY, TRANSLATE_API_KEY, SESSION_BACKEND_KEY];
+
+#[derive(Serialize, Deserialize)]
+struct LocalDataExport {
+    format: String,
+    version: u32,
+    exported_at: String,
+    /// Account the per-account rows were exported from
+    did: String,
+    settings: Vec<ExportedSetting>,
+    drafts: Vec<ExportedDraft>,
+    /// Only present when the export opted in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    view_history: Vec<ViewHistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedSetting {
+    /// Device-wide setting rather than one of the account's
+    device: bool,
+    key: String,
+    value: serde_json::Value,
+    updated_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedDraft {
+    slot_id: String,
+    name: Option<String>,
+    payload: serde_json::Value,
+    created_at: String,
+    updated_at: String,
+}
+
+/// What to do with an imported row whose key already exists locally
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflict {
+    /// Keep the local row
+    #[default]
+    Skip,
+    /// Replace the local row
+    Overwrite,
+    /// Keep whichever row was updated last
+    Newer,
+}
+
+impl ImportConflict {
+    fn on_conflict(self, key: &str, updates: &str, table: &str) -> String {
+        match self {
+            Self::Skip => format!("ON CONFLICT({key}) DO NOTHING"),
+            Self::Overwrite => format!("ON CONFLICT({key}) DO UPDATE SET {updates}"),
+            Self::Newer => format!(
+                "ON CONFLICT({key}) DO UPDATE SET {updates} \
+                 WHERE excluded.updated_at > {table}.updated_at"
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct LocalDataImportSummary {
+    pub settings_imported: u32,
+    pub settings_skipped: u32,
+    pub drafts_imported: u32,
+    pub drafts_skipped: u32,
+    pub view_history_imported: u32,
+}
+
+async fn export_settings(
+    db: &SqlitePool,
+    user_did: &str,
+) -> Result<Vec<ExportedSetting>, AppError> {
+    let rows = sqlx::query_as::<_, (String, String, String, String)>(
+        r#"
+        SELECT user_did, setting_key, value_json, updated_at
+        FROM app_settings
+        WHERE user_did IN (?1, ?2)
+        ORDER BY user_did, setting_key
+        "#,
+    )
+    .bind(user_did)
+    .bind(DEVICE_SCOPE)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("settings export failed: {e}")))?;
+
+    let mut settings = Vec::with_capacity(rows.len());
+    for (scope, key, value_json, updated_at) in rows {
+        if EXCLUDED_SETTING_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        match serde_json::from_str(&value_json) {
+            Ok(value) => settings.push(ExportedSetting {
+                device: scope == DEVICE_SCOPE,
+                key,
+                value,
+                updated_at,
+            }),
+            Err(err) => tracing::warn!("skipping unreadable setting {key}: {err}"),
+        }
+    }
+    Ok(settings)
+}
+
+async fn export_drafts(db: &SqlitePool, user_did: &str) -> Result<Vec<ExportedDraft>, AppError> {
+    let rows = sqlx::query_as::<_, (String, Option<String>, String, String, String)>(
+        r#"
+        SELECT slot_id, name, payload_json, created_at, updated_at
+        FROM draft_slots
+        WHERE user_did = ?1
+        ORDER BY updated_at DESC
+        "#,
+    )
+    .bind(user_did)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("draft export failed: {e}")))?;
+
+    let mut drafts = Vec::with_capacity(rows.len());
+    for (slot_id, name, payload_json, created_at, updated_at) in rows {
+        match serde_json::from_str(&payload_json) {
+            Ok(payload) => drafts.push(ExportedDraft {
+                slot_id,
+                name,
+                payload,
+                created_at,
+                updated_at,
+            }),
+            Err(err) => tracing::warn!("skipping unreadable draft {slot_id}: {err}"),
+        }
+    }
+    Ok(drafts)
+}
+
+/// Bring an export written by any earlier version up to the current layout
+fn migrate_export(raw: serde_json::Value) -> Result<LocalDataExport, AppError> {
+    if raw.get("format").and_then(|f| f.as_str()) != Some(EXPORT_FORMAT) {
+        return Err(AppError::ValidationError {
+            code: "IMPORT_FORMAT_UNKNOWN",
+            message: "This file is not a Blue Horizon data export".into(),
+        });
+    }
+
+    let version = raw.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version == 0 || version > u64::from(EXPORT_VERSION) {
+        return Err(AppError::ValidationError {
+            code: "IMPORT_VERSION_UNSUPPORTED",
+            message: format!(
+                "Export version {version} is not supported; update the app and try again"
+            ),
+        });
+    }
+
+    serde_json::from_value(raw).map_err(|e| AppError::ValidationError {
+        code: "IMPORT_INVALID",
+        message: format!("Export file is damaged: {e}"),
+    })
+}
+
+/// Write the account's drafts and settings, plus device-wide settings, to a JSON file.
+/// Credentials stay in the keyring and secret settings (API keys) are left out. Recently
+/// viewed profiles and posts are only included with `include_view_history`.
+#[tauri::command]
+pub async fn export_local_data(
+    db: State<'_, DbState>,
+    path: String,
+    include_view_history: Option<bool>,
+) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    let db = db.inner().as_ref();
+    let view_history = if include_view_history.unwrap_or(false) {
+        load_view_history(db, &user_did, None, i64::MAX).await?
+    } else {
+        Vec::new()
+    };
+
+    let export = LocalDataExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        settings: export_settings(db, &user_did).await?,
+        drafts: export_drafts(db, &user_did).await?,
+        view_history,
+        did: user_did,
+    };
+
+    let json = serde_json::to_vec_pretty(&export)
+        .map_err(|e| AppError::InternalError(format!("local data encode failed: {e}")))?;
+    tokio::fs::write(PathBuf::from(path.trim()), json)
+        .await
+        .map_err(|e| AppError::InternalError(format!("local data write failed: {e}")))
+}
+
+/// Merge a file written by `export_local_data` into the signed-in account. Rows from
+/// another account are imported as this account's. Existing rows are kept unless
+/// `conflict` says otherwise; imported device settings take effect immediately.
+#[tauri::command]
+pub async fn import_local_data(
+    db: State<'_, DbState>,
+    path: String,
+    conflict: Option<ImportConflict>,
+) -> Result<LocalDataImportSummary, AppError> {
+    let user_did = get_stored_session()?.did;
+    let conflict = conflict.unwrap_or_default();
+    let db = db.inner().as_ref();
+
+    let bytes = tokio::fs::read(PathBuf::from(path.trim()))
+        .await
+        .map_err(|e| AppError::InternalError(format!("local data read failed: {e}")))?;
+    let raw = serde_json::from_slice::<serde_json::Value>(&bytes).map_err(|e| {
+        AppError::ValidationError {
+            code: "IMPORT_INVALID",
+            message: format!("Export file is not valid JSON: {e}"),
+        }
+    })?;
+    let export = migrate_export(raw)?;
+
+    let settings_sql = format!(
+        r#"
+        INSERT INTO app_settings (user_did, setting_key, value_json, updated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        {}
+        "#,
+        conflict.on_conflict(
+            "user_did, setting_key",
+            "value_json = excluded.value_json, updated_at = excluded.updated_at",
+            "app_settings",
+        )
+    );
+    let drafts_sql = format!(
+        r#"
+        INSERT INTO draft_slots (user_did, slot_id, name, payload_json, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        {}
+        "#,
+        conflict.on_conflict(
+            "user_did, slot_id",
+            "name = excluded.name, payload_json = excluded.payload_json, \
+             updated_at = excluded.updated_at",
+            "draft_slots",
+        )
+    );
+
+    let mut summary = LocalDataImportSummary::default();
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::InternalError(format!("import begin failed: {e}")))?;
+
+    for setting in &export.settings {
+        if EXCLUDED_SETTING_KEYS.contains(&setting.key.as_str()) {
+            continue;
+        }
+        let scope = if setting.device {
+            DEVICE_SCOPE
+        } else {
+            user_did.as_str()
+        };
+        let written = sqlx::query(&settings_sql)
+            .bind(scope)
+            .bind(&setting.key)
+            .bind(setting.value.to_string())
+            .bind(&setting.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalError(format!("settings import failed: {e}")))?
+            .rows_affected();
+        if written > 0 {
+            summary.settings_imported += 1;
+        } else {
+            summary.settings_skipped += 1;
+        }
+    }
+
+    for draft in &export.drafts {
+        let written = sqlx::query(&drafts_sql)
+            .bind(&user_did)
+            .bind(&draft.slot_id)
+            .bind(&draft.name)
+            .bind(draft.payload.to_string())
+            .bind(&draft.created_at)
+            .bind(&draft.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalError(format!("draft import failed: {e}")))?
+            .rows_affected();
+        if written > 0 {
+            summary.drafts_imported += 1;
+        } else {
+            summary.drafts_skipped += 1;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::InternalError(format!("import commit failed: {e}")))?;
+
+    // History always merges to the latest view of each subject, whatever `conflict` says
+    for entry in &export.view_history {
+        save_view_entry(db, &user_did, entry).await?;
+        summary.view_history_imported += 1;
+    }
+
+    apply_saved_request_timeout(db).await;
+    apply_saved_endpoints(db).await;
+    apply_saved_log_level(db).await;
+    apply_saved_image_policy(db).await;
+    apply_saved_blur_sensitive_media(db).await;
+    apply_saved_media_autoload(db).await;
+
+    Ok(summary)
+}
@@ -1,11 +1,18 @@
 use image::io::Reader as ImageReader;
 use image::ImageFormat;
 use std::path::PathBuf;
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, State};
 use tauri_plugin_dialog::DialogExt;
 
+use crate::commands::auth::{current_agent, AgentState};
+use crate::connectivity;
 use crate::error::AppError;
-use crate::media::CachedImage;
+use crate::media::{AvatarPreloadSummary, CachedImage, MediaCacheRebuildProgress};
+
+/// Set by `cancel_media_cache_rebuild`; checked between cache entries
+static REBUILD_CANCELLED: AtomicBool = AtomicBool::new(false);
+static REBUILD_RUNNING: AtomicBool = AtomicBool::new(false);
 
 #[tauri::command]
 pub async fn save_image(app: AppHandle, source_path: String) -> Result<Option<String>, AppError> {
@@ -122,12 +129,14 @@ pub async fn save_video(app: AppHandle, playlist_url: String) -> Result<(), AppE
         .to_path_buf();
 
     // Fetch the HLS playlist
-    let playlist_content = reqwest::get(&playlist_url)
+    let playlist_content = crate::http::client()
+        .get(&playlist_url)
+        .send()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to fetch playlist: {}", e)))?
+        .map_err(|e| crate::http::network_error("Failed to fetch playlist", e))?
         .text()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to read playlist: {}", e)))?;
+        .map_err(|e| crate::http::network_error("Failed to read playlist", e))?;
 
     // Parse the playlist to find the highest quality stream
     let base_url = playlist_url
@@ -172,12 +181,14 @@ pub async fn save_video(app: AppHandle, playlist_url: String) -> Result<(), AppE
     };
 
     // Fetch the actual segment playlist
-    let segment_playlist = reqwest::get(&stream_url)
+    let segment_playlist = crate::http::client()
+        .get(&stream_url)
+        .send()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to fetch segment playlist: {}", e)))?
+        .map_err(|e| crate::http::network_error("Failed to fetch segment playlist", e))?
         .text()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to read segment playlist: {}", e)))?;
+        .map_err(|e| crate::http::network_error("Failed to read segment playlist", e))?;
 
     let segment_base_url = stream_url
         .rsplit_once('/')
@@ -206,12 +217,14 @@ pub async fn save_video(app: AppHandle, playlist_url: String) -> Result<(), AppE
     // Download all segments and concatenate them
     let mut video_data: Vec<u8> = Vec::new();
     for segment_url in &segments {
-        let segment_bytes = reqwest::get(segment_url)
+        let segment_bytes = crate::http::client()
+            .get(segment_url)
+            .send()
             .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to fetch segment: {}", e)))?
+            .map_err(|e| crate::http::network_error("Failed to fetch segment", e))?
             .bytes()
             .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to read segment: {}", e)))?;
+            .map_err(|e| crate::http::network_error("Failed to read segment", e))?;
         video_data.extend_from_slice(&segment_bytes);
     }
 
@@ -226,12 +239,14 @@ pub async fn save_video(app: AppHandle, playlist_url: String) -> Result<(), AppE
 #[tauri::command]
 pub async fn download_and_save_gif(app: AppHandle, url: String) -> Result<(), AppError> {
     // Download the GIF
-    let bytes = reqwest::get(&url)
+    let bytes = crate::http::client()
+        .get(&url)
+        .send()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to fetch GIF: {}", e)))?
+        .map_err(|e| crate::http::network_error("Failed to fetch GIF", e))?
         .bytes()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to read GIF: {}", e)))?;
+        .map_err(|e| crate::http::network_error("Failed to read GIF", e))?;
 
     // Show save dialog
     let save_path = app
@@ -265,3 +280,76 @@ pub async fn get_cached_image(
 ) -> Result<Option<CachedImage>, AppError> {
     Ok(crate::media::get_cached_image_by_source(&source_url, &app))
 }
+
+/// Cache a batch of avatar URLs, e.g. when a conversation or member list loads
+#[tauri::command]
+pub async fn preload_avatars(
+    app: AppHandle,
+    urls: Vec<String>,
+) -> Result<AvatarPreloadSummary, AppError> {
+    Ok(crate::media::preload_avatars(urls, &app).await)
+}
+
+/// Download a post's media that autoload skipped. Returns the post's embed with
+/// placeholders for what is still downloading; each finished download emits
+/// "media_ready" as usual.
+#[tauri::command]
+pub async fn load_embed_media(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    post_uri: String,
+) -> Result<Option<serde_json::Value>, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let response = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_posts(
+            bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                uris: vec![post_uri.trim().to_string()],
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let Some(post) = response.data.posts.first() else {
+        return Err(AppError::NotFound(format!("Post {post_uri} not found")));
+    };
+    let embed = crate::media::load_post_embed(post, &app).await?;
+    Ok(embed.and_then(|embed| serde_json::to_value(embed).ok()))
+}
+
+/// Re-validate the whole post media cache: entries that no longer decode or lost their
+/// files are deleted, the rest are re-encoded to the current image policy. Runs on the
+/// blocking pool and emits `media_cache_rebuild_progress` as it goes.
+#[tauri::command]
+pub async fn rebuild_media_cache(app: AppHandle) -> Result<MediaCacheRebuildProgress, AppError> {
+    if REBUILD_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(AppError::ValidationError {
+            code: "MEDIA_REBUILD_RUNNING",
+            message: "The media cache is already being rebuilt".into(),
+        });
+    }
+    REBUILD_CANCELLED.store(false, Ordering::SeqCst);
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        crate::media::rebuild_media_cache_blocking(&app, &REBUILD_CANCELLED, |progress| {
+            let _ = app.emit("media_cache_rebuild_progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")));
+
+    REBUILD_RUNNING.store(false, Ordering::SeqCst);
+    result?
+}
+
+/// Stop an in-flight media cache rebuild after the current entry
+#[tauri::command]
+pub async fn cancel_media_cache_rebuild() -> Result<(), AppError> {
+    REBUILD_CANCELLED.store(true, Ordering::SeqCst);
+    Ok(())
+}
@@ -0,0 +1,940 @@
+use crate::commands::account::{fetch_server_description, service_or_default};
+use crate::commands::auth::{
+    agent_from_stored_session, fresh_access_jwt, lock_agent, AgentState, TOKEN_REFRESH_MARGIN_SECS,
+};
+use crate::commands::settings::{load_setting, save_setting};
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::http::XrpcErrorBody;
+use crate::session::{get_stored_session, jwt_expires_within, store_session, StoredSession};
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+/// How far a PDS migration got, so an interrupted one picks up where it stopped
+const MIGRATION_STATE_KEY: &str = "migration.state";
+/// Pause between blob copies so a large media library doesn't trip PDS rate limits
+const BLOB_COPY_INTERVAL: Duration = Duration::from_millis(250);
+/// Page size for listMissingBlobs
+const MISSING_BLOBS_PAGE: &str = "500";
+/// Retries of a rate-limited request before the step fails
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+/// Wait used when a 429 doesn't say when to come back; doubled per retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(2);
+/// Never sleep longer than this on one 429, whatever the server asks for
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(300);
+/// Runs a blob may fail on before it is skipped so the migration can go on
+const MAX_BLOB_ATTEMPTS: u32 = 3;
+
+/// Set by `cancel_account_migration`; checked between steps and between blobs
+static MIGRATION_CANCELLED: AtomicBool = AtomicBool::new(false);
+/// Only one migration may run at a time
+static MIGRATION_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Migration steps in the order they run. Each one is safe to repeat.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStep {
+    /// Create the (deactivated) account for the existing DID on the new PDS
+    CreateAccount,
+    /// Copy the repo CAR from the old PDS into the new one
+    ImportRepo,
+    /// Copy every blob the new PDS reports missing
+    TransferBlobs,
+    /// Copy app preferences (feeds, moderation, muted words)
+    Preferences,
+    /// Point the DID document at the new PDS; needs the code emailed by the old PDS
+    Identity,
+    /// Activate the new account, deactivate the old one and switch the session
+    Activate,
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MigrationState {
+    pub did: String,
+    pub old_service: String,
+    pub new_service: String,
+    pub handle: String,
+    pub step: MigrationStep,
+    /// The old PDS has emailed the identity-update code
+    #[serde(default)]
+    pub plc_token_requested: bool,
+    /// Failed copy attempts per blob CID, cleared once the blob copies
+    #[serde(default)]
+    pub blob_attempts: HashMap<String, u32>,
+    /// Blobs given up on: rejected by either PDS or failing `MAX_BLOB_ATTEMPTS` times.
+    /// They stay missing on the new PDS.
+    #[serde(default)]
+    pub skipped_blobs: Vec<String>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct MigrateAccountRequest {
+    pub new_service: String,
+    /// Handle on the new PDS, usually under one of its user domains
+    pub handle: String,
+    pub email: String,
+    /// Password for the account on the new PDS; also used to sign in there on resume
+    pub password: String,
+    #[serde(default)]
+    pub invite_code: Option<String>,
+    /// Code the old PDS emailed for the identity step; leave out until it arrives
+    #[serde(default)]
+    pub plc_token: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct MigrationProgressEvent {
+    pub step: MigrationStep,
+    pub blobs_done: u32,
+    pub blobs_failed: u32,
+    pub blobs_skipped: u32,
+}
+
+#[derive(Serialize)]
+pub struct MigrationStatus {
+    /// Next step to run, or `done`
+    pub step: MigrationStep,
+    /// Paused until `plc_token` is supplied from the email the old PDS sent
+    pub awaiting_plc_token: bool,
+    pub cancelled: bool,
+    /// Blobs that could not be copied this run; call again to retry them
+    pub blobs_failed: u32,
+    /// CIDs of blobs given up on; the migration continues without them
+    pub blobs_skipped: Vec<String>,
+}
+
+/// Where one side of the migration gets its access token
+enum PdsAuth {
+    /// The signed-in account on the old PDS, refreshed through the app session
+    Session(AgentState),
+    /// The session this run opened on the new PDS
+    Created(Arc<tokio::sync::Mutex<CreateSessionOutput>>),
+}
+
+/// One side of the migration: a PDS and the session used to call it
+struct Pds {
+    url: String,
+    auth: PdsAuth,
+}
+
+impl Pds {
+    fn endpoint(&self, method: &str) -> String {
+        format!("{}/xrpc/{method}", self.url.trim_end_matches('/'))
+    }
+
+    /// Current access token, refreshed first when it is about to expire; a large blob
+    /// transfer easily outlives one
+    async fn access_jwt(&self) -> Result<String, AppError> {
+        match &self.auth {
+            PdsAuth::Session(agent_state) => fresh_access_jwt(agent_state).await,
+            PdsAuth::Created(session) => {
+                let mut session = session.lock().await;
+                if jwt_expires_within(&session.access_jwt, TOKEN_REFRESH_MARGIN_SECS) {
+                    let refresh = crate::http::client()
+                        .post(self.endpoint("com.atproto.server.refreshSession"))
+                        .bearer_auth(&session.refresh_jwt);
+                    *session = send_json(refresh, "refreshSession").await?;
+                }
+                Ok(session.access_jwt.clone())
+            }
+        }
+    }
+
+    async fn get(&self, method: &str) -> Result<reqwest::RequestBuilder, AppError> {
+        Ok(crate::http::client()
+            .get(self.endpoint(method))
+            .bearer_auth(self.access_jwt().await?))
+    }
+
+    async fn post(&self, method: &str) -> Result<reqwest::RequestBuilder, AppError> {
+        Ok(crate::http::client()
+            .post(self.endpoint(method))
+            .bearer_auth(self.access_jwt().await?))
+    }
+}
+
+/// A failed request to either PDS
+enum RequestError {
+    /// The PDS answered with an error status
+    Status {
+        status: StatusCode,
+        body: XrpcErrorBody,
+    },
+    /// Anything else: the network, a token refresh, a broken download
+    Other(AppError),
+}
+
+impl From<AppError> for RequestError {
+    fn from(err: AppError) -> Self {
+        RequestError::Other(err)
+    }
+}
+
+impl RequestError {
+    fn into_app_error(self, method: &str) -> AppError {
+        match self {
+            RequestError::Status { status, body } => AppError::ApiError(format!(
+                "{method} failed ({status}): {} {}",
+                body.error, body.message
+            )),
+            RequestError::Other(err) => err,
+        }
+    }
+
+    /// Retrying won't help: a client error other than auth, timeouts and rate limits
+    fn is_permanent(&self) -> bool {
+        match self {
+            RequestError::Status { status, .. } => {
+                status.is_client_error()
+                    && !matches!(
+                        *status,
+                        StatusCode::UNAUTHORIZED
+                            | StatusCode::REQUEST_TIMEOUT
+                            | StatusCode::TOO_MANY_REQUESTS
+                    )
+            }
+            RequestError::Other(_) => false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateSessionOutput {
+    did: String,
+    handle: String,
+    access_jwt: String,
+    refresh_jwt: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_confirmed: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct MissingBlobsOutput {
+    #[serde(default)]
+    blobs: Vec<MissingBlob>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MissingBlob {
+    cid: String,
+}
+
+/// How long a 429 asks us to wait: `retry-after` seconds or the `ratelimit-reset` epoch,
+/// falling back to exponential backoff
+fn rate_limit_wait(headers: &HeaderMap, attempt: u32, now: i64) -> Duration {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    let secs = header("retry-after")
+        .or_else(|| header("ratelimit-reset").map(|reset| reset - now))
+        .filter(|secs| *secs > 0)
+        .map(|secs| Duration::from_secs(secs as u64));
+
+    secs.unwrap_or(RATE_LIMIT_BACKOFF * 2_u32.pow(attempt))
+        .min(MAX_RATE_LIMIT_WAIT)
+}
+
+/// Send an XRPC request, waiting out 429s; error statuses come back with their body
+async fn send_request(
+    mut request: reqwest::RequestBuilder,
+    method: &str,
+) -> Result<reqwest::Response, RequestError> {
+    let mut attempt = 0;
+    loop {
+        let retry = request.try_clone();
+        let response = request
+            .send()
+            .await
+            .map_err(|e| crate::http::network_error(method, e))?;
+        let status = response.status();
+
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RATE_LIMIT_RETRIES {
+            if let Some(retry) = retry {
+                let wait = rate_limit_wait(response.headers(), attempt, Utc::now().timestamp());
+                tracing::info!("{method} rate limited; retrying in {}s", wait.as_secs());
+                tokio::time::sleep(wait).await;
+                request = retry;
+                attempt += 1;
+                continue;
+            }
+        }
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.json::<XrpcErrorBody>().await.unwrap_or_default();
+        return Err(RequestError::Status { status, body });
+    }
+}
+
+/// `send_request` with error statuses turned into `AppError`
+async fn send(
+    request: reqwest::RequestBuilder,
+    method: &str,
+) -> Result<reqwest::Response, AppError> {
+    send_request(request, method)
+        .await
+        .map_err(|err| err.into_app_error(method))
+}
+
+async fn decode_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    method: &str,
+) -> Result<T, AppError> {
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| AppError::ApiError(format!("{method} decode failed: {e}")))
+}
+
+async fn send_json<T: serde::de::DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    method: &str,
+) -> Result<T, AppError> {
+    decode_json(send(request, method).await?, method).await
+}
+
+async fn save_state(db: &SqlitePool, state: &mut MigrationState) -> Result<(), AppError> {
+    state.updated_at = Utc::now().to_rfc3339();
+    save_setting(db, &state.did, MIGRATION_STATE_KEY, &*state).await
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    step: MigrationStep,
+    blobs_done: u32,
+    blobs_failed: u32,
+    blobs_skipped: u32,
+) {
+    let _ = app.emit(
+        "account_migration_progress",
+        MigrationProgressEvent {
+            step,
+            blobs_done,
+            blobs_failed,
+            blobs_skipped,
+        },
+    );
+}
+
+/// Sign in to the new PDS with the account's DID; fails until the account exists there
+async fn new_pds_session(
+    service: &str,
+    did: &str,
+    password: &str,
+) -> Result<CreateSessionOutput, AppError> {
+    let request = crate::http::client()
+        .post(format!(
+            "{}/xrpc/com.atproto.server.createSession",
+            service.trim_end_matches('/')
+        ))
+        .json(&serde_json::json!({ "identifier": did, "password": password }));
+    send_json(request, "createSession").await
+}
+
+/// Create the account for the existing DID on the new PDS, authorised by a service token
+/// from the old PDS. An account left by an earlier attempt is signed in to instead.
+async fn create_account_step(
+    old: &Pds,
+    state: &MigrationState,
+    request: &MigrateAccountRequest,
+) -> Result<CreateSessionOutput, AppError> {
+    if let Ok(session) = new_pds_session(&state.new_service, &state.did, &request.password).await {
+        return Ok(session);
+    }
+
+    let server = fetch_server_description(&state.new_service).await?;
+    if server.invite_code_required && request.invite_code.is_none() {
+        return Err(AppError::ValidationError {
+            code: "INVITE_CODE_REQUIRED",
+            message: "The new server requires an invite code".into(),
+        });
+    }
+
+    #[derive(Deserialize)]
+    struct ServiceAuth {
+        token: String,
+    }
+    let service_auth: ServiceAuth = send_json(
+        old.get("com.atproto.server.getServiceAuth").await?.query(&[
+            ("aud", server.did.as_str()),
+            ("lxm", "com.atproto.server.createAccount"),
+        ]),
+        "getServiceAuth",
+    )
+    .await?;
+
+    let create = crate::http::client()
+        .post(format!(
+            "{}/xrpc/com.atproto.server.createAccount",
+            state.new_service.trim_end_matches('/')
+        ))
+        .bearer_auth(&service_auth.token)
+        .json(&serde_json::json!({
+            "did": state.did,
+            "handle": state.handle,
+            "email": request.email.trim(),
+            "password": request.password,
+            "inviteCode": request.invite_code,
+        }));
+    send(create, "createAccount").await?;
+
+    new_pds_session(&state.new_service, &state.did, &request.password).await
+}
+
+/// Copy the repo CAR. Importing the same repo again is a no-op on the PDS.
+async fn import_repo_step(old: &Pds, new: &Pds, did: &str) -> Result<(), AppError> {
+    let car = send(
+        old.get("com.atproto.sync.getRepo")
+            .await?
+            .query(&[("did", did)]),
+        "getRepo",
+    )
+    .await?
+    .bytes()
+    .await
+    .map_err(|e| crate::http::network_error("getRepo download", e))?;
+
+    send(
+        new.post("com.atproto.repo.importRepo")
+            .await?
+            .header(reqwest::header::CONTENT_TYPE, "application/vnd.ipld.car")
+            .body(car),
+        "importRepo",
+    )
+    .await?;
+    Ok(())
+}
+
+/// Count a failed copy of `cid`. Returns whether the blob is now skipped: a PDS
+/// rejected it outright, or this was its `MAX_BLOB_ATTEMPTS`th failed run.
+fn record_blob_failure(state: &mut MigrationState, cid: &str, permanent: bool) -> bool {
+    let attempts = state.blob_attempts.entry(cid.to_string()).or_insert(0);
+    *attempts += 1;
+    if !permanent && *attempts < MAX_BLOB_ATTEMPTS {
+        return false;
+    }
+    state.blob_attempts.remove(cid);
+    state.skipped_blobs.push(cid.to_string());
+    true
+}
+
+/// Copy the blobs the new PDS is missing. Returns `None` when cancelled, otherwise the
+/// number that failed; those stay missing and are retried on the next run, up to
+/// `MAX_BLOB_ATTEMPTS`. Skipped blobs don't count as failed.
+async fn transfer_blobs_step(
+    app: &AppHandle,
+    db: &SqlitePool,
+    old: &Pds,
+    new: &Pds,
+    state: &mut MigrationState,
+) -> Result<Option<u32>, AppError> {
+    let mut done = 0_u32;
+    let mut failed = 0_u32;
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut list = new
+            .get("com.atproto.repo.listMissingBlobs")
+            .await?
+            .query(&[("limit", MISSING_BLOBS_PAGE)]);
+        if let Some(cursor) = cursor.as_deref() {
+            list = list.query(&[("cursor", cursor)]);
+        }
+        let page: MissingBlobsOutput = send_json(list, "listMissingBlobs").await?;
+        let page_empty = page.blobs.is_empty();
+
+        for blob in page.blobs {
+            if MIGRATION_CANCELLED.load(Ordering::SeqCst) {
+                return Ok(None);
+            }
+
+            if state.skipped_blobs.contains(&blob.cid) {
+                continue;
+            }
+
+            match copy_blob(old, new, &state.did, &blob.cid).await {
+                Ok(()) => {
+                    done += 1;
+                    if state.blob_attempts.remove(&blob.cid).is_some() {
+                        save_state(db, state).await?;
+                    }
+                }
+                Err((method, err)) => {
+                    let permanent = err.is_permanent();
+                    let err = err.into_app_error(method);
+                    if record_blob_failure(state, &blob.cid, permanent) {
+                        tracing::warn!("blob {} skipped: {err}", blob.cid);
+                    } else {
+                        tracing::warn!("blob {} not copied: {err}", blob.cid);
+                        failed += 1;
+                    }
+                    save_state(db, state).await?;
+                }
+            }
+            emit_progress(
+                app,
+                MigrationStep::TransferBlobs,
+                done,
+                failed,
+                state.skipped_blobs.len() as u32,
+            );
+            tokio::time::sleep(BLOB_COPY_INTERVAL).await;
+        }
+
+        match page.cursor {
+            Some(next) if !page_empty => cursor = Some(next),
+            _ => break,
+        }
+    }
+
+    Ok(Some(failed))
+}
+
+/// Download one blob from the old PDS and upload it to the new one. Errors carry the
+/// method that failed.
+async fn copy_blob(
+    old: &Pds,
+    new: &Pds,
+    did: &str,
+    cid: &str,
+) -> Result<(), (&'static str, RequestError)> {
+    let download = old
+        .get("com.atproto.sync.getBlob")
+        .await
+        .map_err(|e| ("getBlob", RequestError::Other(e)))?
+        .query(&[("did", did), ("cid", cid)]);
+    let response = send_request(download, "getBlob")
+        .await
+        .map_err(|e| ("getBlob", e))?;
+    let mime = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let bytes = response.bytes().await.map_err(|e| {
+        (
+            "getBlob",
+            RequestError::Other(crate::http::network_error("getBlob download", e)),
+        )
+    })?;
+
+    let upload = new
+        .post("com.atproto.repo.uploadBlob")
+        .await
+        .map_err(|e| ("uploadBlob", RequestError::Other(e)))?
+        .header(reqwest::header::CONTENT_TYPE, mime)
+        .body(bytes);
+    send_request(upload, "uploadBlob")
+        .await
+        .map_err(|e| ("uploadBlob", e))?;
+    Ok(())
+}
+
+async fn preferences_step(old: &Pds, new: &Pds) -> Result<(), AppError> {
+    let prefs: serde_json::Value = send_json(
+        old.get("app.bsky.actor.getPreferences").await?,
+        "getPreferences",
+    )
+    .await?;
+    send(
+        new.post("app.bsky.actor.putPreferences")
+            .await?
+            .json(&prefs),
+        "putPreferences",
+    )
+    .await?;
+    Ok(())
+}
+
+/// Whether signPlcOperation turned down the emailed confirmation code
+fn plc_token_rejected(err: &RequestError) -> bool {
+    matches!(
+        err,
+        RequestError::Status { status: StatusCode::BAD_REQUEST, body }
+            if matches!(body.error.as_str(), "InvalidToken" | "ExpiredToken")
+    )
+}
+
+/// Have the old PDS sign a PLC operation with the new PDS's recommended credentials and
+/// submit it through the new PDS
+async fn identity_step(old: &Pds, new: &Pds, plc_token: &str) -> Result<(), AppError> {
+    let mut credentials: serde_json::Value = send_json(
+        new.get("com.atproto.identity.getRecommendedDidCredentials")
+            .await?,
+        "getRecommendedDidCredentials",
+    )
+    .await?;
+    if let Some(fields) = credentials.as_object_mut() {
+        fields.insert("token".into(), plc_token.trim().into());
+    }
+
+    #[derive(Deserialize)]
+    struct SignedOperation {
+        operation: serde_json::Value,
+    }
+    let response = send_request(
+        old.post("com.atproto.identity.signPlcOperation")
+            .await?
+            .json(&credentials),
+        "signPlcOperation",
+    )
+    .await
+    .map_err(|err| {
+        if plc_token_rejected(&err) {
+            AppError::ValidationError {
+                code: "MIGRATION_PLC_TOKEN_INVALID",
+                message: "The confirmation code is invalid or expired".into(),
+            }
+        } else {
+            err.into_app_error("signPlcOperation")
+        }
+    })?;
+    let signed: SignedOperation = decode_json(response, "signPlcOperation").await?;
+
+    send(
+        new.post("com.atproto.identity.submitPlcOperation")
+            .await?
+            .json(&serde_json::json!({ "operation": signed.operation })),
+        "submitPlcOperation",
+    )
+    .await?;
+    Ok(())
+}
+
+/// Activate the account on the new PDS and retire it on the old one. The old PDS may
+/// already refuse requests once the DID points elsewhere, so that part is best-effort.
+async fn activate_step(old: &Pds, new: &Pds) -> Result<(), AppError> {
+    send(
+        new.post("com.atproto.server.activateAccount").await?,
+        "activateAccount",
+    )
+    .await?;
+    let deactivate = match old.post("com.atproto.server.deactivateAccount").await {
+        Ok(request) => send(request.json(&serde_json::json!({})), "deactivateAccount").await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = deactivate {
+        tracing::warn!("old account not deactivated: {err}");
+    }
+    Ok(())
+}
+
+/// Clears `MIGRATION_RUNNING` however the migration ends
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        MIGRATION_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Move the signed-in account to another PDS: create it there, copy the repo, blobs and
+/// preferences, update the DID document and switch the session over. Progress is saved
+/// after each step and every step is safe to repeat, so after a failure or cancel just
+/// call this again with the same request. The identity step pauses (`awaiting_plc_token`)
+/// until the code the old PDS emails is passed as `plc_token`.
+#[tauri::command]
+pub async fn migrate_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    request: MigrateAccountRequest,
+) -> Result<MigrationStatus, AppError> {
+    if MIGRATION_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(AppError::ValidationError {
+            code: "MIGRATION_RUNNING",
+            message: "An account migration is already running".into(),
+        });
+    }
+    let _running = RunningGuard;
+    MIGRATION_CANCELLED.store(false, Ordering::SeqCst);
+
+    let db = db.inner().as_ref();
+    let session = get_stored_session()?;
+    if !session.did.starts_with("did:plc:") {
+        return Err(AppError::ValidationError {
+            code: "MIGRATION_UNSUPPORTED_DID",
+            message: "Only did:plc accounts can be migrated from the app".into(),
+        });
+    }
+
+    let new_service = service_or_default(Some(request.new_service.clone()));
+    let mut state =
+        match load_setting::<MigrationState>(db, &session.did, MIGRATION_STATE_KEY).await? {
+            Some(state) if state.step != MigrationStep::Done => {
+                if state.new_service != new_service {
+                    return Err(AppError::ValidationError {
+                        code: "MIGRATION_IN_PROGRESS",
+                        message: format!(
+                            "A migration to {} is already in progress",
+                            state.new_service
+                        ),
+                    });
+                }
+                state
+            }
+            _ => {
+                if new_service == session.service_url.trim_end_matches('/') {
+                    return Err(AppError::ValidationError {
+                        code: "MIGRATION_SAME_SERVER",
+                        message: "The account is already hosted on this server".into(),
+                    });
+                }
+                let now = Utc::now().to_rfc3339();
+                MigrationState {
+                    did: session.did.clone(),
+                    old_service: session.service_url.clone(),
+                    new_service,
+                    handle: request.handle.trim().trim_start_matches('@').to_lowercase(),
+                    step: MigrationStep::CreateAccount,
+                    plc_token_requested: false,
+                    blob_attempts: HashMap::new(),
+                    skipped_blobs: Vec::new(),
+                    started_at: now.clone(),
+                    updated_at: now,
+                }
+            }
+        };
+
+    let old = Pds {
+        url: state.old_service.clone(),
+        auth: PdsAuth::Session(agent_state.inner().clone()),
+    };
+    let status = |state: &MigrationState, cancelled: bool, blobs_failed: u32| MigrationStatus {
+        step: state.step,
+        awaiting_plc_token: state.step == MigrationStep::Identity && state.plc_token_requested,
+        cancelled,
+        blobs_failed,
+        blobs_skipped: state.skipped_blobs.clone(),
+    };
+
+    tracing::info!(did = %state.did, to = %state.new_service, step = ?state.step, "migration running");
+    emit_progress(&app, state.step, 0, 0, 0);
+    let new_session = if state.step == MigrationStep::CreateAccount {
+        create_account_step(&old, &state, &request).await?
+    } else {
+        new_pds_session(&state.new_service, &state.did, &request.password).await?
+    };
+    let new_session = Arc::new(tokio::sync::Mutex::new(new_session));
+    let new = Pds {
+        url: state.new_service.clone(),
+        auth: PdsAuth::Created(new_session.clone()),
+    };
+    if state.step == MigrationStep::CreateAccount {
+        state.step = MigrationStep::ImportRepo;
+        save_state(db, &mut state).await?;
+    }
+
+    while state.step != MigrationStep::Done {
+        if MIGRATION_CANCELLED.load(Ordering::SeqCst) {
+            return Ok(status(&state, true, 0));
+        }
+        emit_progress(&app, state.step, 0, 0, 0);
+
+        state.step = match state.step {
+            MigrationStep::CreateAccount | MigrationStep::ImportRepo => {
+                import_repo_step(&old, &new, &state.did).await?;
+                MigrationStep::TransferBlobs
+            }
+            MigrationStep::TransferBlobs => {
+                match transfer_blobs_step(&app, db, &old, &new, &mut state).await? {
+                    None => return Ok(status(&state, true, 0)),
+                    Some(0) => MigrationStep::Preferences,
+                    Some(failed) => return Ok(status(&state, false, failed)),
+                }
+            }
+            MigrationStep::Preferences => {
+                preferences_step(&old, &new).await?;
+                MigrationStep::Identity
+            }
+            MigrationStep::Identity => {
+                match request
+                    .plc_token
+                    .as_deref()
+                    .filter(|t| !t.trim().is_empty())
+                {
+                    Some(token) => {
+                        identity_step(&old, &new, token).await?;
+                        MigrationStep::Activate
+                    }
+                    None => {
+                        if !state.plc_token_requested {
+                            send(
+                                old.post("com.atproto.identity.requestPlcOperationSignature")
+                                    .await?,
+                                "requestPlcOperationSignature",
+                            )
+                            .await?;
+                            state.plc_token_requested = true;
+                            save_state(db, &mut state).await?;
+                        }
+                        return Ok(status(&state, false, 0));
+                    }
+                }
+            }
+            MigrationStep::Activate => {
+                activate_step(&old, &new).await?;
+
+                // Tokens as last refreshed during the run
+                let new_session = new_session.lock().await;
+                let stored = StoredSession {
+                    did: new_session.did.clone(),
+                    handle: new_session.handle.clone(),
+                    access_jwt: new_session.access_jwt.clone(),
+                    refresh_jwt: new_session.refresh_jwt.clone(),
+                    service_url: state.new_service.clone(),
+                    email: new_session.email.clone().or(session.email.clone()),
+                    email_confirmed: new_session.email_confirmed,
+                };
+                store_session(&stored)?;
+                let agent = agent_from_stored_session(&stored).await?;
+                *lock_agent(&agent_state).await? = Some(agent);
+                tracing::info!(target: "auth", did = %stored.did, service = %stored.service_url, "session moved to new PDS");
+                MigrationStep::Done
+            }
+            MigrationStep::Done => MigrationStep::Done,
+        };
+        save_state(db, &mut state).await?;
+    }
+
+    emit_progress(&app, MigrationStep::Done, 0, 0, 0);
+    Ok(status(&state, false, 0))
+}
+
+/// Saved progress of the signed-in account's migration, if one was started
+#[tauri::command]
+pub async fn get_account_migration(
+    db: State<'_, DbState>,
+) -> Result<Option<MigrationState>, AppError> {
+    let did = get_stored_session()?.did;
+    load_setting(db.inner().as_ref(), &did, MIGRATION_STATE_KEY).await
+}
+
+/// Stop a running migration after the current request; progress so far is kept
+#[tauri::command]
+pub async fn cancel_account_migration() -> Result<(), AppError> {
+    MIGRATION_CANCELLED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    /// Migration calls PDSes over plain HTTP, so its failures are `RequestError`s rather
+    /// than the `connectivity::xrpc_failure` fixtures the XRPC commands test with
+    fn status_error(status: u16, error: &str) -> RequestError {
+        RequestError::Status {
+            status: StatusCode::from_u16(status).unwrap(),
+            body: XrpcErrorBody {
+                error: error.into(),
+                message: String::new(),
+            },
+        }
+    }
+
+    fn state() -> MigrationState {
+        serde_json::from_value(serde_json::json!({
+            "did": "did:plc:abc",
+            "old_service": "https://old.example",
+            "new_service": "https://new.example",
+            "handle": "alice.new.example",
+            "step": "transfer_blobs",
+            "started_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .expect("state saved before blob tracking still loads")
+    }
+
+    #[test]
+    fn rate_limit_wait_follows_the_server() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+        assert_eq!(rate_limit_wait(&headers, 0, 1_000), Duration::from_secs(30));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-reset", HeaderValue::from_static("1045"));
+        assert_eq!(rate_limit_wait(&headers, 0, 1_000), Duration::from_secs(45));
+
+        headers.insert("retry-after", HeaderValue::from_static("100000"));
+        assert_eq!(rate_limit_wait(&headers, 0, 1_000), MAX_RATE_LIMIT_WAIT);
+    }
+
+    #[test]
+    fn rate_limit_wait_backs_off_without_headers() {
+        let mut headers = HeaderMap::new();
+        // A reset already in the past is ignored
+        headers.insert("ratelimit-reset", HeaderValue::from_static("900"));
+        assert_eq!(rate_limit_wait(&headers, 0, 1_000), RATE_LIMIT_BACKOFF);
+        assert_eq!(rate_limit_wait(&headers, 2, 1_000), RATE_LIMIT_BACKOFF * 4);
+        assert_eq!(rate_limit_wait(&headers, 20, 1_000), MAX_RATE_LIMIT_WAIT);
+    }
+
+    #[test]
+    fn only_client_errors_are_permanent() {
+        assert!(status_error(404, "BlobNotFound").is_permanent());
+        assert!(status_error(400, "InvalidRequest").is_permanent());
+        assert!(status_error(413, "").is_permanent());
+        assert!(!status_error(401, "AuthRequired").is_permanent());
+        assert!(!status_error(429, "RateLimitExceeded").is_permanent());
+        assert!(!status_error(502, "").is_permanent());
+        assert!(!RequestError::Other(AppError::NetworkError("reset".into())).is_permanent());
+    }
+
+    #[test]
+    fn plc_token_rejection_is_read_from_the_status_and_error() {
+        assert!(plc_token_rejected(&status_error(400, "InvalidToken")));
+        assert!(plc_token_rejected(&status_error(400, "ExpiredToken")));
+        assert!(!plc_token_rejected(&status_error(400, "InvalidRequest")));
+        assert!(!plc_token_rejected(&status_error(401, "ExpiredToken")));
+        assert!(!plc_token_rejected(&RequestError::Other(
+            AppError::NetworkError("(400) timed out".into())
+        )));
+    }
+
+    #[test]
+    fn rejected_blob_is_skipped_at_once() {
+        let mut state = state();
+        assert!(record_blob_failure(&mut state, "bafy-gone", true));
+        assert_eq!(state.skipped_blobs, vec!["bafy-gone".to_string()]);
+        assert!(state.blob_attempts.is_empty());
+    }
+
+    #[test]
+    fn failing_blob_is_skipped_after_max_attempts() {
+        let mut state = state();
+        for _ in 1..MAX_BLOB_ATTEMPTS {
+            assert!(!record_blob_failure(&mut state, "bafy-flaky", false));
+        }
+        assert_eq!(
+            state.blob_attempts.get("bafy-flaky"),
+            Some(&(MAX_BLOB_ATTEMPTS - 1))
+        );
+        assert!(state.skipped_blobs.is_empty());
+
+        assert!(record_blob_failure(&mut state, "bafy-flaky", false));
+        assert_eq!(state.skipped_blobs, vec!["bafy-flaky".to_string()]);
+        assert!(!state.blob_attempts.contains_key("bafy-flaky"));
+    }
+}
@@ -1,11 +1,29 @@
+pub mod account;
 pub mod actions;
 pub mod auth;
+pub mod bulk;
 pub mod chat;
+pub mod export;
 pub mod feeds;
+pub mod gifs;
+pub mod hidden_posts;
+pub mod history;
+pub mod interactions;
+pub mod link_cards;
+pub mod links;
 pub mod lists;
+pub mod local_data;
 pub mod media;
+pub mod migration;
+pub mod moderation;
 pub mod notifications;
+pub mod pagination;
+pub mod post_card;
+pub mod postgate;
+pub mod repo;
 pub mod search;
+pub mod settings;
 pub mod system;
 pub mod timeline;
+pub mod translate;
 pub mod window;
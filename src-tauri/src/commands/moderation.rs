@@ -0,0 +1,776 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::connectivity;
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use bsky_sdk::api::app::bsky::actor::defs::{
+    AdultContentPrefData, ContentLabelPrefData, PreferencesItem,
+};
+use bsky_sdk::api::app::bsky::actor::{get_preferences, put_preferences};
+use bsky_sdk::api::app::bsky::feed::defs::PostView;
+use bsky_sdk::api::app::bsky::labeler::get_services;
+use bsky_sdk::api::com::atproto::admin::defs::{RepoRef, RepoRefData};
+use bsky_sdk::api::com::atproto::moderation::create_report::{self, InputSubjectRefs};
+use bsky_sdk::api::com::atproto::repo::strong_ref;
+use bsky_sdk::api::types::string::Did;
+use bsky_sdk::api::types::Union;
+use chrono::Utc;
+use ipld_core::ipld::Ipld;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+use tauri::{AppHandle, State};
+
+/// Bluesky's default moderation service; reports are proxied to it.
+const MODERATION_PROXY_DID: &str = "did:plc:ar7c4by46qjdydhdevvrndac";
+const MODERATION_SERVICE_TYPE: &str = "atproto_labeler";
+
+/// Map a short reason name (or full lexicon token) to a known report reason type.
+fn parse_reason_type(reason_type: &str) -> Result<String, AppError> {
+    let short = reason_type
+        .trim()
+        .trim_start_matches("com.atproto.moderation.defs#reason")
+        .to_ascii_lowercase();
+
+    let token = match short.as_str() {
+        "spam" => "com.atproto.moderation.defs#reasonSpam",
+        "violation" => "com.atproto.moderation.defs#reasonViolation",
+        "misleading" => "com.atproto.moderation.defs#reasonMisleading",
+        "sexual" => "com.atproto.moderation.defs#reasonSexual",
+        "rude" => "com.atproto.moderation.defs#reasonRude",
+        "other" => "com.atproto.moderation.defs#reasonOther",
+        "appeal" => "com.atproto.moderation.defs#reasonAppeal",
+        _ => {
+            return Err(AppError::ApiError(format!(
+                "Invalid report reason: {reason_type}"
+            )))
+        }
+    };
+
+    Ok(token.to_string())
+}
+
+#[derive(Serialize)]
+pub struct ReportResponse {
+    pub id: i64,
+    pub created_at: String,
+}
+
+async fn submit_report(
+    agent_state: &AgentState,
+    subject: Union<InputSubjectRefs>,
+    reason_type: &str,
+    comment: Option<String>,
+) -> Result<ReportResponse, AppError> {
+    let reason_type = parse_reason_type(reason_type)?;
+    let reason = comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+
+    let agent = current_agent(agent_state).await?;
+
+    let moderation_did: Did = MODERATION_PROXY_DID
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid moderation proxy DID".into()))?;
+    let moderation_api = agent.api_with_proxy(moderation_did, MODERATION_SERVICE_TYPE);
+
+    let response = moderation_api
+        .com
+        .atproto
+        .moderation
+        .create_report(
+            create_report::InputData {
+                mod_tool: None,
+                reason,
+                reason_type,
+                subject,
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(ReportResponse {
+        id: response.data.id,
+        created_at: response.data.created_at.as_ref().to_string(),
+    })
+}
+
+/// Report a post to the moderation service
+#[tauri::command]
+pub async fn report_post(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uri: String,
+    cid: String,
+    reason_type: String,
+    comment: Option<String>,
+) -> Result<ReportResponse, AppError> {
+    let subject = Union::Refs(InputSubjectRefs::ComAtprotoRepoStrongRefMain(Box::new(
+        strong_ref::Main {
+            data: strong_ref::MainData {
+                uri: uri
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid post URI".into()))?,
+                cid: cid
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid post CID".into()))?,
+            },
+            extra_data: Ipld::Null,
+        },
+    )));
+
+    submit_report(agent_state.inner(), subject, &reason_type, comment)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Report an account to the moderation service
+#[tauri::command]
+pub async fn report_account(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did: String,
+    reason_type: String,
+    comment: Option<String>,
+) -> Result<ReportResponse, AppError> {
+    let subject = Union::Refs(InputSubjectRefs::ComAtprotoAdminDefsRepoRef(Box::new(
+        RepoRef {
+            data: RepoRefData {
+                did: did
+                    .trim()
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid DID".into()))?,
+            },
+            extra_data: Ipld::Null,
+        },
+    )));
+
+    submit_report(agent_state.inner(), subject, &reason_type, comment)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// How posts carrying a label are shown
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelVisibility {
+    Show,
+    Warn,
+    Hide,
+}
+
+impl LabelVisibility {
+    /// Parse a `contentLabelPref.visibility` value ("ignore" is the lexicon's "show")
+    fn from_pref(value: &str) -> Option<Self> {
+        match value {
+            "ignore" | "show" => Some(Self::Show),
+            "warn" => Some(Self::Warn),
+            "hide" => Some(Self::Hide),
+            _ => None,
+        }
+    }
+
+    fn as_pref(self) -> &'static str {
+        match self {
+            Self::Show => "ignore",
+            Self::Warn => "warn",
+            Self::Hide => "hide",
+        }
+    }
+}
+
+/// Self-labels and Bluesky moderation labels the user can configure:
+/// (label, adult only, default visibility)
+const CONFIGURABLE_LABELS: [(&str, bool, LabelVisibility); 4] = [
+    ("porn", true, LabelVisibility::Hide),
+    ("sexual", true, LabelVisibility::Warn),
+    ("graphic-media", true, LabelVisibility::Warn),
+    ("nudity", false, LabelVisibility::Show),
+];
+
+#[derive(Serialize, Clone)]
+pub struct ContentLabelPref {
+    pub label: String,
+    pub visibility: LabelVisibility,
+    /// Always hidden while adult content is disabled
+    pub adult_only: bool,
+}
+
+/// Adult-content switch and per-label visibility from the account's preferences
+#[derive(Serialize, Clone)]
+pub struct ContentLabelPrefs {
+    pub adult_content_enabled: bool,
+    /// The account's birth date says it's under 18, so adult content can't be enabled
+    pub adult_content_locked: bool,
+    pub labels: Vec<ContentLabelPref>,
+}
+
+impl Default for ContentLabelPrefs {
+    fn default() -> Self {
+        Self {
+            adult_content_enabled: false,
+            adult_content_locked: false,
+            labels: CONFIGURABLE_LABELS
+                .iter()
+                .map(|(label, adult_only, visibility)| ContentLabelPref {
+                    label: label.to_string(),
+                    visibility: *visibility,
+                    adult_only: *adult_only,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ContentLabelPrefs {
+    fn from_preferences(preferences: &[Union<PreferencesItem>]) -> Self {
+        let mut prefs = Self::default();
+        for item in preferences {
+            match item {
+                Union::Refs(PreferencesItem::AdultContentPref(pref)) => {
+                    prefs.adult_content_enabled = pref.enabled;
+                }
+                // Labeler-specific prefs aren't configurable here yet
+                Union::Refs(PreferencesItem::ContentLabelPref(pref))
+                    if pref.labeler_did.is_none() =>
+                {
+                    let Some(visibility) = LabelVisibility::from_pref(&pref.visibility) else {
+                        continue;
+                    };
+                    if let Some(entry) = prefs.labels.iter_mut().find(|p| p.label == pref.label) {
+                        entry.visibility = visibility;
+                    }
+                }
+                Union::Refs(PreferencesItem::PersonalDetailsPref(pref)) => {
+                    prefs.adult_content_locked = pref
+                        .birth_date
+                        .as_ref()
+                        .is_some_and(|birth| is_minor(birth.as_ref()));
+                }
+                _ => {}
+            }
+        }
+        if prefs.adult_content_locked {
+            prefs.adult_content_enabled = false;
+        }
+        prefs
+    }
+
+    fn label_visibility(&self, label: &str) -> LabelVisibility {
+        match label {
+            "!hide" => return LabelVisibility::Hide,
+            "!warn" => return LabelVisibility::Warn,
+            _ => {}
+        }
+        match self.labels.iter().find(|p| p.label == label) {
+            Some(pref) if pref.adult_only && !self.adult_content_enabled => LabelVisibility::Hide,
+            Some(pref) => pref.visibility,
+            None => LabelVisibility::Show,
+        }
+    }
+
+    /// Strongest treatment across the post's and its author's labels, with the label
+    /// that caused it
+    pub(crate) fn moderate_post(&self, post: &PostView) -> (LabelVisibility, Option<String>) {
+        post.labels
+            .iter()
+            .chain(post.author.labels.iter())
+            .flatten()
+            .map(|label| (self.label_visibility(&label.val), Some(label.val.clone())))
+            .max_by_key(|(visibility, _)| *visibility)
+            .filter(|(visibility, _)| *visibility != LabelVisibility::Show)
+            .unwrap_or((LabelVisibility::Show, None))
+    }
+}
+
+fn is_minor(birth_date: &chrono::DateTime<chrono::FixedOffset>) -> bool {
+    let today = Utc::now().date_naive();
+    let birth = birth_date.date_naive();
+    today.years_since(birth).is_some_and(|age| age < 18)
+}
+
+/// Preferences of the last account they were loaded for
+static CONTENT_LABEL_PREFS: LazyLock<RwLock<Option<(String, ContentLabelPrefs)>>> =
+    LazyLock::new(|| RwLock::new(None));
+
+fn cache_content_label_prefs(did: &str, prefs: &ContentLabelPrefs) {
+    if let Ok(mut cached) = CONTENT_LABEL_PREFS.write() {
+        *cached = Some((did.to_string(), prefs.clone()));
+    }
+}
+
+pub(crate) async fn fetch_preferences(
+    agent: &AppAgent,
+) -> Result<Vec<Union<PreferencesItem>>, AppError> {
+    Ok(agent
+        .api
+        .app
+        .bsky
+        .actor
+        .get_preferences(get_preferences::ParametersData {}.into())
+        .await
+        .map_err(connectivity::xrpc_error)?
+        .data
+        .preferences)
+}
+
+/// Label preferences used to filter feeds. Cached per account after the first load; if
+/// they can't be fetched the defaults apply (adult content off) and the next call retries.
+pub(crate) async fn content_label_prefs(agent: &AppAgent) -> ContentLabelPrefs {
+    let Ok(did) = get_stored_session().map(|session| session.did) else {
+        return ContentLabelPrefs::default();
+    };
+    if let Ok(cached) = CONTENT_LABEL_PREFS.read() {
+        if let Some((cached_did, prefs)) = cached.as_ref() {
+            if *cached_did == did {
+                return prefs.clone();
+            }
+        }
+    }
+
+    match fetch_preferences(agent).await {
+        Ok(preferences) => {
+            let prefs = ContentLabelPrefs::from_preferences(&preferences);
+            cache_content_label_prefs(&did, &prefs);
+            prefs
+        }
+        Err(err) => {
+            tracing::warn!("content label preferences unavailable, using defaults: {err}");
+            ContentLabelPrefs::default()
+        }
+    }
+}
+
+/// Read the preferences, let `edit` change them and write them back. Other preference
+/// types are passed through untouched.
+async fn update_preferences(
+    agent: &AppAgent,
+    edit: impl FnOnce(&mut Vec<Union<PreferencesItem>>) -> Result<(), AppError>,
+) -> Result<ContentLabelPrefs, AppError> {
+    let mut preferences = fetch_preferences(agent).await?;
+    edit(&mut preferences)?;
+
+    agent
+        .api
+        .app
+        .bsky
+        .actor
+        .put_preferences(
+            put_preferences::InputData {
+                preferences: preferences.clone(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(|err| {
+            // The AppView enforces age gating too
+            let message = err.to_string();
+            if message.to_lowercase().contains("adult") || message.to_lowercase().contains("age") {
+                AppError::ValidationError {
+                    code: "ADULT_CONTENT_NOT_ALLOWED",
+                    message,
+                }
+            } else {
+                connectivity::xrpc_error(err)
+            }
+        })?;
+
+    let prefs = ContentLabelPrefs::from_preferences(&preferences);
+    cache_content_label_prefs(&get_stored_session()?.did, &prefs);
+    Ok(prefs)
+}
+
+/// The account's adult-content setting and label visibilities
+#[tauri::command]
+pub async fn get_content_label_prefs(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+) -> Result<ContentLabelPrefs, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let preferences = fetch_preferences(&agent)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    let prefs = ContentLabelPrefs::from_preferences(&preferences);
+    cache_content_label_prefs(&get_stored_session()?.did, &prefs);
+    Ok(prefs)
+}
+
+/// Set how posts with `label` are shown: "show", "warn" or "hide"
+#[tauri::command]
+pub async fn set_content_label_pref(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    label: String,
+    visibility: LabelVisibility,
+) -> Result<ContentLabelPrefs, AppError> {
+    let label = label.trim().to_string();
+    if !CONFIGURABLE_LABELS
+        .iter()
+        .any(|(known, _, _)| *known == label)
+    {
+        return Err(AppError::ValidationError {
+            code: "INVALID_LABEL",
+            message: format!("Label {label} can't be configured"),
+        });
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    update_preferences(&agent, |preferences| {
+        preferences.retain(|item| {
+            !matches!(item,
+                Union::Refs(PreferencesItem::ContentLabelPref(pref))
+                    if pref.labeler_did.is_none() && pref.label == label)
+        });
+        preferences.push(Union::Refs(PreferencesItem::ContentLabelPref(Box::new(
+            ContentLabelPrefData {
+                label,
+                labeler_did: None,
+                visibility: visibility.as_pref().to_string(),
+            }
+            .into(),
+        ))));
+        Ok(())
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Turn adult content on or off. Accounts whose birth date is under 18 can't enable it.
+#[tauri::command]
+pub async fn set_adult_content_enabled(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    enabled: bool,
+) -> Result<ContentLabelPrefs, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    update_preferences(&agent, |preferences| {
+        if enabled && ContentLabelPrefs::from_preferences(preferences).adult_content_locked {
+            return Err(AppError::ValidationError {
+                code: "ADULT_CONTENT_NOT_ALLOWED",
+                message: "Adult content can't be enabled for this account".into(),
+            });
+        }
+        preferences
+            .retain(|item| !matches!(item, Union::Refs(PreferencesItem::AdultContentPref(_))));
+        preferences.push(Union::Refs(PreferencesItem::AdultContentPref(Box::new(
+            AdultContentPrefData { enabled }.into(),
+        ))));
+        Ok(())
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Labeler definitions are refetched once the cached copy is older than this
+const LABELER_CACHE_HOURS: i64 = 24;
+/// Names of the global labels, which labelers apply without defining them
+const GLOBAL_LABEL_NAMES: [(&str, &str); 6] = [
+    ("porn", "Adult Content"),
+    ("sexual", "Sexually Suggestive"),
+    ("nudity", "Non-sexual Nudity"),
+    ("graphic-media", "Graphic Media"),
+    ("!hide", "Hidden by Moderators"),
+    ("!warn", "Content Warning"),
+];
+
+/// Display text for one label value a labeler emits
+#[derive(Serialize, Clone)]
+pub struct LabelDefinition {
+    /// The raw label value found on posts and accounts
+    pub value: String,
+    /// Localized name; the raw value when the labeler doesn't define one
+    pub name: String,
+    pub description: Option<String>,
+    /// "inform", "alert" or "none"
+    pub severity: Option<String>,
+    pub adult_only: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LabelerServiceInfo {
+    pub did: String,
+    pub labels: Vec<LabelDefinition>,
+    /// False when the labeler couldn't be reached and nothing was cached; render raw
+    /// label values
+    pub available: bool,
+    pub cached_at: Option<String>,
+}
+
+fn global_label_name(value: &str) -> Option<&'static str> {
+    GLOBAL_LABEL_NAMES
+        .iter()
+        .find(|(label, _)| *label == value)
+        .map(|(_, name)| *name)
+}
+
+/// Pick the locale matching `lang` (or its primary subtag), then English, then the first
+fn pick_locale<'a>(locales: &'a [serde_json::Value], lang: &str) -> Option<&'a serde_json::Value> {
+    let primary = lang.split('-').next().unwrap_or(lang);
+    let lang_of = |locale: &serde_json::Value| {
+        locale
+            .get("lang")
+            .and_then(|l| l.as_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    locales
+        .iter()
+        .find(|locale| lang_of(locale) == lang)
+        .or_else(|| {
+            locales
+                .iter()
+                .find(|locale| lang_of(locale).split('-').next() == Some(primary))
+        })
+        .or_else(|| {
+            locales
+                .iter()
+                .find(|locale| lang_of(locale).split('-').next() == Some("en"))
+        })
+        .or_else(|| locales.first())
+}
+
+/// Label definitions from a labeler's `policies`. Values it emits without a definition
+/// (including the global labels) fall back to a known name or the raw value.
+fn label_definitions(policies: &serde_json::Value, lang: &str) -> Vec<LabelDefinition> {
+    let mut labels: Vec<LabelDefinition> = Vec::new();
+    let definitions = policies
+        .get("labelValueDefinitions")
+        .and_then(|d| d.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for definition in definitions {
+        let Some(value) = definition.get("identifier").and_then(|i| i.as_str()) else {
+            continue;
+        };
+        if labels.iter().any(|label| label.value == value) {
+            continue;
+        }
+        let locale = definition
+            .get("locales")
+            .and_then(|l| l.as_array())
+            .and_then(|locales| pick_locale(locales, lang));
+        let text = |key: &str| {
+            locale
+                .and_then(|l| l.get(key))
+                .and_then(|t| t.as_str())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+        };
+        labels.push(LabelDefinition {
+            value: value.to_string(),
+            name: text("name")
+                .or_else(|| global_label_name(value).map(str::to_string))
+                .unwrap_or_else(|| value.to_string()),
+            description: text("description"),
+            severity: definition
+                .get("severity")
+                .and_then(|s| s.as_str())
+                .map(str::to_string),
+            adult_only: definition
+                .get("adultOnly")
+                .and_then(|a| a.as_bool())
+                .unwrap_or(false),
+        });
+    }
+
+    let values = policies
+        .get("labelValues")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for value in values.iter().filter_map(|v| v.as_str()) {
+        if labels.iter().any(|label| label.value == value) {
+            continue;
+        }
+        labels.push(LabelDefinition {
+            value: value.to_string(),
+            name: global_label_name(value).unwrap_or(value).to_string(),
+            description: None,
+            severity: None,
+            adult_only: CONFIGURABLE_LABELS
+                .iter()
+                .any(|(label, adult_only, _)| *label == value && *adult_only),
+        });
+    }
+    labels
+}
+
+async fn load_cached_labelers(
+    db: &SqlitePool,
+    dids: &[String],
+) -> Result<HashMap<String, (serde_json::Value, String)>, AppError> {
+    let mut cached = HashMap::new();
+    for did in dids {
+        let row = sqlx::query_as::<_, (String, String)>(
+            "SELECT policies_json, cached_at FROM labeler_cache WHERE labeler_did = ?1",
+        )
+        .bind(did)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| AppError::InternalError(format!("labeler cache read failed: {e}")))?;
+        let Some((policies_json, cached_at)) = row else {
+            continue;
+        };
+        match serde_json::from_str(&policies_json) {
+            Ok(policies) => {
+                cached.insert(did.clone(), (policies, cached_at));
+            }
+            Err(err) => tracing::warn!("skipping unreadable labeler cache {did}: {err}"),
+        }
+    }
+    Ok(cached)
+}
+
+async fn save_cached_labeler(
+    db: &SqlitePool,
+    did: &str,
+    policies: &serde_json::Value,
+    cached_at: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO labeler_cache (labeler_did, policies_json, cached_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(labeler_did) DO UPDATE SET
+            policies_json = excluded.policies_json,
+            cached_at = excluded.cached_at
+        "#,
+    )
+    .bind(did)
+    .bind(policies.to_string())
+    .bind(cached_at)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("labeler cache write failed: {e}")))?;
+    Ok(())
+}
+
+fn is_fresh(cached_at: &str) -> bool {
+    chrono::DateTime::parse_from_rfc3339(cached_at).is_ok_and(|at| {
+        Utc::now().signed_duration_since(at) < chrono::Duration::hours(LABELER_CACHE_HOURS)
+    })
+}
+
+/// `policies` of each labeler view, keyed by the labeler's DID
+async fn fetch_labeler_policies(
+    agent: &AppAgent,
+    dids: &[Did],
+) -> Result<HashMap<String, serde_json::Value>, AppError> {
+    let response = agent
+        .api
+        .app
+        .bsky
+        .labeler
+        .get_services(
+            get_services::ParametersData {
+                detailed: Some(true),
+                dids: dids.to_vec(),
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(response
+        .data
+        .views
+        .iter()
+        .filter_map(|view| serde_json::to_value(view).ok())
+        .filter_map(|view| {
+            let did = view.pointer("/creator/did")?.as_str()?.to_string();
+            // Non-detailed views carry no policies; cache them as empty
+            let policies = view
+                .get("policies")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({}));
+            Some((did, policies))
+        })
+        .collect())
+}
+
+/// Label names, descriptions and severities declared by each labeler, so labels can
+/// be shown as "Adult Content" rather than `porn`. Definitions are cached for a day;
+/// when a labeler can't be reached its cached copy (however old) is used, and with
+/// none it comes back `available: false` with only the global label names. Values
+/// without a definition are named after the raw value.
+#[tauri::command]
+pub async fn get_labeler_services(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    dids: Vec<String>,
+    lang: Option<String>,
+) -> Result<Vec<LabelerServiceInfo>, AppError> {
+    let mut requested: Vec<Did> = Vec::new();
+    for did in &dids {
+        let did: Did = did.trim().parse().map_err(|_| AppError::ValidationError {
+            code: "INVALID_DID",
+            message: format!("Invalid DID: {did}"),
+        })?;
+        if !requested.contains(&did) {
+            requested.push(did);
+        }
+    }
+    let lang = lang
+        .map(|l| l.trim().to_ascii_lowercase())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| "en".to_string());
+    let db = db.inner().as_ref();
+
+    let requested_dids: Vec<String> = requested.iter().map(|d| d.as_str().to_string()).collect();
+    let mut cached = load_cached_labelers(db, &requested_dids).await?;
+    let stale: Vec<Did> = requested
+        .iter()
+        .filter(|did| {
+            cached
+                .get(did.as_str())
+                .is_none_or(|(_, cached_at)| !is_fresh(cached_at))
+        })
+        .cloned()
+        .collect();
+
+    if !stale.is_empty() {
+        let fetched = match current_agent(&agent_state).await {
+            Ok(agent) => fetch_labeler_policies(&agent, &stale).await,
+            Err(err) => Err(err),
+        };
+        match fetched {
+            Ok(fetched) => {
+                let now = Utc::now().to_rfc3339();
+                for (did, policies) in fetched {
+                    if let Err(err) = save_cached_labeler(db, &did, &policies, &now).await {
+                        tracing::warn!("labeler {did} not cached: {err}");
+                    }
+                    cached.insert(did, (policies, now.clone()));
+                }
+            }
+            Err(err) => {
+                tracing::warn!("labeler definitions unavailable, using cache: {err}");
+                connectivity::watch_error(&app, &err);
+            }
+        }
+    }
+
+    let global_policies =
+        serde_json::json!({ "labelValues": GLOBAL_LABEL_NAMES.map(|(value, _)| value) });
+    Ok(requested_dids
+        .into_iter()
+        .map(|did| match cached.remove(&did) {
+            Some((policies, cached_at)) => LabelerServiceInfo {
+                labels: label_definitions(&policies, &lang),
+                did,
+                available: true,
+                cached_at: Some(cached_at),
+            },
+            None => LabelerServiceInfo {
+                labels: label_definitions(&global_policies, &lang),
+                did,
+                available: false,
+                cached_at: None,
+            },
+        })
+        .collect())
+}
@@ -1,10 +1,21 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState};
+use crate::commands::pagination::fetch_with_cursor_recovery;
+use crate::commands::settings::{load_setting, notification_priority_enabled, save_setting};
+use crate::commands::timeline::NewItemsCount;
+use crate::connectivity;
 use crate::db::DbState;
 use crate::error::AppError;
 use crate::session::get_stored_session;
+use crate::unread;
+use atrium_xrpc::error::XrpcErrorKind;
+use bsky_sdk::api::app::bsky::notification::{
+    defs as notification_defs, put_activity_subscription,
+};
+use bsky_sdk::api::types::string::Did;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::time::Instant;
 use tauri::{AppHandle, Emitter, State};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -53,20 +64,37 @@ pub struct PostRecord {
 pub struct NotificationsResponse {
     pub notifications: Vec<NotificationInfo>,
     pub cursor: Option<String>,
+    #[serde(default)]
+    pub reset: bool,
 }
 
 fn current_user_did() -> Result<String, AppError> {
     Ok(get_stored_session()?.did)
 }
 
-fn cursor_key(cursor: Option<&str>) -> String {
-    cursor.unwrap_or_default().to_string()
+/// Priority-mode pages are cached separately from the full notification list
+fn cursor_key(cursor: Option<&str>, priority: bool) -> String {
+    let cursor = cursor.unwrap_or_default();
+    if priority {
+        format!("priority:{cursor}")
+    } else {
+        cursor.to_string()
+    }
+}
+
+/// Resolve the priority flag for a request, falling back to the saved setting
+async fn resolve_priority(db: &SqlitePool, requested: Option<bool>) -> bool {
+    match requested {
+        Some(priority) => priority,
+        None => notification_priority_enabled(db).await,
+    }
 }
 
 async fn load_notifications_cache(
     db: &SqlitePool,
     user_did: &str,
     cursor: Option<&str>,
+    priority: bool,
 ) -> Result<Option<NotificationsResponse>, AppError> {
     let payload = sqlx::query_scalar::<_, String>(
         r#"
@@ -76,7 +104,7 @@ async fn load_notifications_cache(
         "#,
     )
     .bind(user_did)
-    .bind(cursor_key(cursor))
+    .bind(cursor_key(cursor, priority))
     .fetch_optional(db)
     .await
     .map_err(|e| AppError::InternalError(format!("notifications cache read failed: {e}")))?;
@@ -94,6 +122,7 @@ async fn save_notifications_cache(
     db: &SqlitePool,
     user_did: &str,
     cursor: Option<&str>,
+    priority: bool,
     payload: &NotificationsResponse,
 ) -> Result<(), AppError> {
     let payload_json = serde_json::to_string(payload)
@@ -109,7 +138,7 @@ async fn save_notifications_cache(
         "#,
     )
     .bind(user_did)
-    .bind(cursor_key(cursor))
+    .bind(cursor_key(cursor, priority))
     .bind(payload_json)
     .bind(Utc::now().to_rfc3339())
     .execute(db)
@@ -121,32 +150,30 @@ async fn save_notifications_cache(
 
 async fn fetch_notifications_remote(
     agent_state: &AgentState,
+    db: &SqlitePool,
     cursor: Option<String>,
     limit: Option<u8>,
+    priority: bool,
 ) -> Result<NotificationsResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(agent_state).await?;
 
     let limit_val = bsky_sdk::api::types::LimitedNonZeroU8::try_from(limit.unwrap_or(25))
         .map_err(|_| AppError::ApiError("Limit must be between 1 and 100".into()))?;
 
-    let response = agent
-        .api
-        .app
-        .bsky
-        .notification
-        .list_notifications(
+    let (response, reset) = fetch_with_cursor_recovery(cursor, |cursor| {
+        agent.api.app.bsky.notification.list_notifications(
             bsky_sdk::api::app::bsky::notification::list_notifications::ParametersData {
                 cursor,
-                limit: Some(limit_val),
+                limit: Some(limit_val.clone()),
                 seen_at: None,
-                priority: None,
+                priority: Some(priority),
                 reasons: None,
             }
             .into(),
         )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+    })
+    .await
+    .map_err(connectivity::xrpc_error)?;
 
     let notifications = response
         .data
@@ -197,10 +224,81 @@ async fn fetch_notifications_remote(
         })
         .collect();
 
-    Ok(NotificationsResponse {
+    let mut page = NotificationsResponse {
         notifications,
         cursor: response.data.cursor,
-    })
+        reset,
+    };
+    // The server can lag behind (or not yet know about) a local mark-as-read
+    if let Some(mark_ms) = read_mark_millis(db).await {
+        apply_read_mark(&mut page, mark_ms);
+    }
+    Ok(page)
+}
+
+/// Save a refreshed first page and tell the UI: `notifications_new_count` when it has
+/// notifications above `shown_top` (applied when the UI asks for the list again),
+/// `notifications_updated` otherwise
+async fn publish_notifications_refresh(
+    app: &AppHandle,
+    db: &SqlitePool,
+    user_did: &str,
+    priority: bool,
+    shown_top: Option<&NotificationInfo>,
+    remote: &NotificationsResponse,
+) {
+    if let Err(err) = save_notifications_cache(db, user_did, None, priority, remote).await {
+        tracing::warn!(target: "cache", cache = "notifications", "refresh save failed: {err}");
+    }
+
+    let new_items = shown_top.map(|top| {
+        let top_ms = indexed_at_millis(&top.indexed_at);
+        let known = remote.notifications.iter().position(|n| {
+            n.uri == top.uri
+                || matches!((indexed_at_millis(&n.indexed_at), top_ms), (Some(ms), Some(top)) if ms < top)
+        });
+        NewItemsCount {
+            count: known.unwrap_or(remote.notifications.len()),
+            more: known.is_none(),
+        }
+    });
+    let emitted = match new_items {
+        Some(new_items) if new_items.count > 0 => app.emit("notifications_new_count", new_items),
+        _ => app.emit("notifications_updated", remote),
+    };
+    if let Err(err) = emitted {
+        tracing::warn!(target: "cache", cache = "notifications", "emit refresh failed: {err}");
+    }
+}
+
+/// Warm the first notifications page cache in the background (used on session resume)
+pub(crate) async fn prefetch_notifications(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    db: &SqlitePool,
+) {
+    let user_did = match current_user_did() {
+        Ok(did) => did,
+        Err(err) => {
+            tracing::warn!(target: "cache", cache = "notifications", "prefetch skipped: {err}");
+            return;
+        }
+    };
+
+    let priority = notification_priority_enabled(db).await;
+    match fetch_notifications_remote(agent_state, db, None, None, priority).await {
+        Ok(remote) => {
+            let shown = load_notifications_cache(db, &user_did, None, priority)
+                .await
+                .ok()
+                .flatten();
+            let shown_top = shown.as_ref().and_then(|page| page.notifications.first());
+            publish_notifications_refresh(app, db, &user_did, priority, shown_top, &remote).await;
+        }
+        Err(err) => {
+            tracing::warn!(target: "cache", cache = "notifications", "prefetch fetch failed: {err}");
+        }
+    }
 }
 
 /// Get notifications
@@ -211,45 +309,49 @@ pub async fn get_notifications(
     db: State<'_, DbState>,
     cursor: Option<String>,
     limit: Option<u8>,
+    priority: Option<bool>,
 ) -> Result<NotificationsResponse, AppError> {
     let user_did = current_user_did()?;
     let db_pool = db.inner().clone();
     let cursor_for_cache = cursor.clone();
+    let priority = resolve_priority(db_pool.as_ref(), priority).await;
 
     if cursor.is_none() {
-        if let Some(cached) = load_notifications_cache(db_pool.as_ref(), &user_did, None).await? {
+        if let Some(cached) =
+            load_notifications_cache(db_pool.as_ref(), &user_did, None, priority).await?
+        {
             let refresh_app = app.clone();
             let refresh_agent_state = agent_state.inner().clone();
             let refresh_db = db_pool.clone();
             let refresh_cursor = cursor.clone();
             let refresh_limit = limit;
             let refresh_user_did = user_did.clone();
+            let shown_top = cached.notifications.first().cloned();
 
             tauri::async_runtime::spawn(async move {
                 match fetch_notifications_remote(
                     &refresh_agent_state,
+                    refresh_db.as_ref(),
                     refresh_cursor,
                     refresh_limit,
+                    priority,
                 )
                 .await
                 {
                     Ok(remote) => {
-                        if let Err(err) = save_notifications_cache(
+                        publish_notifications_refresh(
+                            &refresh_app,
                             refresh_db.as_ref(),
                             &refresh_user_did,
-                            None,
+                            priority,
+                            shown_top.as_ref(),
                             &remote,
                         )
-                        .await
-                        {
-                            eprintln!("[notifications-cache] refresh save failed: {err}");
-                        }
-                        if let Err(err) = refresh_app.emit("notifications_updated", &remote) {
-                            eprintln!("[notifications-cache] emit refresh failed: {err}");
-                        }
+                        .await;
                     }
                     Err(err) => {
-                        eprintln!("[notifications-cache] refresh fetch failed: {err}");
+                        tracing::warn!(target: "cache", cache = "notifications", "refresh fetch failed: {err}");
+                        connectivity::watch_error(&refresh_app, &err);
                     }
                 }
             });
@@ -258,21 +360,35 @@ pub async fn get_notifications(
         }
     }
 
-    match fetch_notifications_remote(agent_state.inner(), cursor.clone(), limit).await {
+    match fetch_notifications_remote(
+        agent_state.inner(),
+        db_pool.as_ref(),
+        cursor.clone(),
+        limit,
+        priority,
+    )
+    .await
+    {
         Ok(remote) => {
             save_notifications_cache(
                 db_pool.as_ref(),
                 &user_did,
                 cursor_for_cache.as_deref(),
+                priority,
                 &remote,
             )
             .await?;
             Ok(remote)
         }
         Err(remote_err) => {
-            if let Some(cached) =
-                load_notifications_cache(db_pool.as_ref(), &user_did, cursor_for_cache.as_deref())
-                    .await?
+            connectivity::watch_error(&app, &remote_err);
+            if let Some(cached) = load_notifications_cache(
+                db_pool.as_ref(),
+                &user_did,
+                cursor_for_cache.as_deref(),
+                priority,
+            )
+            .await?
             {
                 return Ok(cached);
             }
@@ -282,11 +398,22 @@ pub async fn get_notifications(
     }
 }
 
-/// Get unread count
+/// Get unread count (in priority mode, only notifications from followed accounts count).
+/// Served from the shared unread cache when the poller fetched it recently.
 #[tauri::command]
-pub async fn get_unread_count(agent_state: State<'_, AgentState>) -> Result<u32, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+pub async fn get_unread_count(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    priority: Option<bool>,
+) -> Result<u32, AppError> {
+    let priority = resolve_priority(db.inner().as_ref(), priority).await;
+    if let Some(count) = unread::cached_notification_count(priority) {
+        return Ok(count);
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    let started = Instant::now();
 
     let response = agent
         .api
@@ -296,22 +423,129 @@ pub async fn get_unread_count(agent_state: State<'_, AgentState>) -> Result<u32,
         .get_unread_count(
             bsky_sdk::api::app::bsky::notification::get_unread_count::ParametersData {
                 seen_at: None,
-                priority: None,
+                priority: Some(priority),
             }
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let count = response.data.count as u32;
+    unread::store_notification_count(&app, priority, count, started);
+    Ok(count)
+}
+
+/// Everything indexed at or before this is read (per account); the local half of the
+/// read state, merged over the server's `isRead`
+const READ_UP_TO_KEY: &str = "notifications.read_up_to";
 
-    Ok(response.data.count as u32)
+async fn read_mark_millis(db: &SqlitePool) -> Option<i64> {
+    let user_did = current_user_did().ok()?;
+    load_setting::<String>(db, &user_did, READ_UP_TO_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|mark| indexed_at_millis(&mark))
+}
+
+/// Flag notifications indexed at or before the mark as read; returns whether any changed
+fn apply_read_mark(page: &mut NotificationsResponse, mark_ms: i64) -> bool {
+    let mut changed = false;
+    for n in page.notifications.iter_mut().filter(|n| !n.is_read) {
+        if indexed_at_millis(&n.indexed_at).is_some_and(|ms| ms <= mark_ms) {
+            n.is_read = true;
+            changed = true;
+        }
+    }
+    changed
 }
 
-/// Mark notifications as read (update seen_at)
+/// Rewrite every cached notifications page of the account with the read mark applied
+async fn patch_cached_read_state(
+    db: &SqlitePool,
+    user_did: &str,
+    mark_ms: i64,
+) -> Result<(), AppError> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT cursor_key, payload_json
+        FROM notifications_cache
+        WHERE user_did = ?1
+        "#,
+    )
+    .bind(user_did)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("notifications cache read failed: {e}")))?;
+
+    for (key, payload_json) in rows {
+        let Ok(mut page) = serde_json::from_str::<NotificationsResponse>(&payload_json) else {
+            continue;
+        };
+        if !apply_read_mark(&mut page, mark_ms) {
+            continue;
+        }
+        let payload_json = serde_json::to_string(&page).map_err(|e| {
+            AppError::InternalError(format!("notifications cache encode failed: {e}"))
+        })?;
+        sqlx::query(
+            r#"
+            UPDATE notifications_cache
+            SET payload_json = ?3
+            WHERE user_did = ?1 AND cursor_key = ?2
+            "#,
+        )
+        .bind(user_did)
+        .bind(key)
+        .bind(payload_json)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::InternalError(format!("notifications cache write failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Mark notifications as read: everything when `up_to` is `None`, otherwise only those
+/// indexed at or before `up_to` (an `indexed_at` from the list). The mark never moves
+/// back. Cached pages are patched right away and the first page is re-emitted as
+/// `notifications_updated`; the server's `seen_at` is updated afterwards.
 #[tauri::command]
-pub async fn mark_notifications_read(agent_state: State<'_, AgentState>) -> Result<(), AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+pub async fn mark_notifications_read(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    up_to: Option<String>,
+) -> Result<(), AppError> {
+    let user_did = current_user_did()?;
+    let db = db.inner().as_ref();
+
+    let requested = match up_to.as_deref().map(str::trim) {
+        Some(up_to) => {
+            chrono::DateTime::parse_from_rfc3339(up_to).map_err(|_| AppError::ValidationError {
+                code: "INVALID_READ_MARK",
+                message: format!("Not a notification timestamp: {up_to}"),
+            })?
+        }
+        None => Utc::now().fixed_offset(),
+    };
+    let saved = load_setting::<String>(db, &user_did, READ_UP_TO_KEY)
+        .await?
+        .and_then(|mark| chrono::DateTime::parse_from_rfc3339(&mark).ok());
+    let mark = saved
+        .filter(|saved| *saved > requested)
+        .unwrap_or(requested);
+
+    save_setting(db, &user_did, READ_UP_TO_KEY, &mark.to_rfc3339()).await?;
+    patch_cached_read_state(db, &user_did, mark.timestamp_millis()).await?;
+    let priority = notification_priority_enabled(db).await;
+    if let Some(first_page) = load_notifications_cache(db, &user_did, None, priority).await? {
+        if let Err(err) = app.emit("notifications_updated", &first_page) {
+            tracing::warn!(target: "cache", cache = "notifications", "emit read state failed: {err}");
+        }
+    }
 
+    let agent = current_agent(&agent_state).await?;
     agent
         .api
         .app
@@ -319,12 +553,305 @@ pub async fn mark_notifications_read(agent_state: State<'_, AgentState>) -> Resu
         .notification
         .update_seen(
             bsky_sdk::api::app::bsky::notification::update_seen::InputData {
-                seen_at: bsky_sdk::api::types::string::Datetime::now(),
+                seen_at: bsky_sdk::api::types::string::Datetime::new(mark),
             }
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    if up_to.is_none() {
+        unread::mark_notifications_seen(&app);
+    } else {
+        // Some notifications may still be unread; refetch the count
+        unread::invalidate_notification_count();
+    }
 
     Ok(())
 }
+
+/// Newest notification already alerted on (per account)
+const LAST_NOTIFIED_KEY: &str = "notifications.last_notified";
+
+/// High-water mark of alerted notifications in the server's indexedAt order, so the local
+/// clock never decides what counts as new
+#[derive(Serialize, Deserialize, Default)]
+struct NotifiedCursor {
+    indexed_at: String,
+    /// Notifications sharing `indexed_at` that were already alerted on
+    #[serde(default)]
+    uris: Vec<String>,
+}
+
+fn indexed_at_millis(indexed_at: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(indexed_at)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+/// Unread notifications newer than the account's saved cursor, oldest first; the cursor
+/// then moves to the newest one. Without a saved cursor (first run) nothing is returned and
+/// the cursor starts at the newest, so reopening the app never re-alerts the whole list.
+async fn take_unnotified(
+    db: &SqlitePool,
+    user_did: &str,
+    notifications: &[NotificationInfo],
+) -> Result<Vec<NotificationInfo>, AppError> {
+    let saved = load_setting::<NotifiedCursor>(db, user_did, LAST_NOTIFIED_KEY).await?;
+    let saved_ms = saved
+        .as_ref()
+        .and_then(|cursor| indexed_at_millis(&cursor.indexed_at));
+
+    let mut fresh: Vec<&NotificationInfo> = notifications
+        .iter()
+        .filter(|n| !n.is_read)
+        .filter(
+            |n| match (saved.as_ref(), saved_ms, indexed_at_millis(&n.indexed_at)) {
+                (Some(cursor), Some(saved_ms), Some(ms)) => {
+                    ms > saved_ms || (ms == saved_ms && !cursor.uris.contains(&n.uri))
+                }
+                _ => false,
+            },
+        )
+        .collect();
+    fresh.sort_by_key(|n| indexed_at_millis(&n.indexed_at));
+
+    let newest_ms = notifications
+        .iter()
+        .filter_map(|n| indexed_at_millis(&n.indexed_at))
+        .max();
+    if let Some(newest_ms) = newest_ms.filter(|ms| saved_ms.map_or(true, |saved| *ms >= saved)) {
+        let mut cursor = NotifiedCursor::default();
+        for n in notifications {
+            if indexed_at_millis(&n.indexed_at) == Some(newest_ms) {
+                cursor.indexed_at = n.indexed_at.clone();
+                cursor.uris.push(n.uri.clone());
+            }
+        }
+        if saved_ms == Some(newest_ms) {
+            if let Some(saved) = saved {
+                cursor.uris.extend(
+                    saved
+                        .uris
+                        .into_iter()
+                        .filter(|uri| !cursor.uris.contains(uri)),
+                );
+            }
+        }
+        save_setting(db, user_did, LAST_NOTIFIED_KEY, &cursor).await?;
+    }
+
+    Ok(fresh.into_iter().cloned().collect())
+}
+
+/// Called by the background poller when the unread count is non-zero: fetch the first
+/// page and emit `notifications_new` with the items not alerted on before
+pub(crate) async fn poll_new_notifications(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    db: &SqlitePool,
+    priority: bool,
+) -> Result<(), AppError> {
+    let user_did = current_user_did()?;
+    let page = fetch_notifications_remote(agent_state, db, None, None, priority).await?;
+    let fresh = take_unnotified(db, &user_did, &page.notifications).await?;
+    if !fresh.is_empty() {
+        if let Err(err) = app.emit("notifications_new", &fresh) {
+            tracing::warn!("emit failed: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// Which of an account's new posts notify the user (the profile "bell")
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ActivitySubscription {
+    pub posts: bool,
+    pub replies: bool,
+}
+
+impl ActivitySubscription {
+    /// Read `viewer.activitySubscription` from a profile view's viewer state
+    pub(crate) fn from_viewer(viewer: &serde_json::Value) -> Option<Self> {
+        let sub = viewer.get("activitySubscription")?;
+        let flag = |key: &str| sub.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        let subscription = Self {
+            posts: flag("post"),
+            replies: flag("reply"),
+        };
+        (subscription.posts || subscription.replies).then_some(subscription)
+    }
+}
+
+/// Set the activity subscription for `did` through the PDS, which forwards it to the
+/// AppView. Turning both flags off removes the subscription.
+async fn set_activity_subscription(
+    agent_state: &AgentState,
+    did: &str,
+    subscription: ActivitySubscription,
+) -> Result<(), AppError> {
+    let subject: Did = did.parse().map_err(|_| AppError::ValidationError {
+        code: "INVALID_DID",
+        message: format!("Invalid DID: {did}"),
+    })?;
+    let agent = current_agent(agent_state).await?;
+
+    agent
+        .api
+        .app
+        .bsky
+        .notification
+        .put_activity_subscription(
+            put_activity_subscription::InputData {
+                activity_subscription: notification_defs::ActivitySubscriptionData {
+                    post: subscription.posts,
+                    reply: subscription.replies,
+                }
+                .into(),
+                subject,
+            }
+            .into(),
+        )
+        .await
+        .map(|_| ())
+        .map_err(activity_subscription_error)
+}
+
+/// Rate limits are recognised by status or error name; the AppView reports its cap on
+/// subscribed accounts as a plain 400 whose message mentions the limit
+fn activity_subscription_error<E>(err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if let atrium_xrpc::Error::XrpcResponse(ref resp) = err {
+        let body = match &resp.error {
+            Some(XrpcErrorKind::Undefined(body)) => Some(body),
+            _ => None,
+        };
+        let name = body.and_then(|b| b.error.as_deref());
+        let message = body.and_then(|b| b.message.clone()).unwrap_or_default();
+
+        if resp.status.as_u16() == 429 || name == Some("RateLimitExceeded") {
+            return AppError::ValidationError {
+                code: "RATE_LIMITED",
+                message: "Too many requests, try again later".into(),
+            };
+        }
+        if resp.status.as_u16() == 400 && message.to_ascii_lowercase().contains("limit") {
+            return AppError::ValidationError {
+                code: "ACTIVITY_SUBSCRIPTION_LIMIT",
+                message,
+            };
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// Get notified when `did` posts and/or replies
+#[tauri::command]
+pub async fn subscribe_to_user_activity(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did: String,
+    include_posts: bool,
+    include_replies: bool,
+) -> Result<(), AppError> {
+    if !include_posts && !include_replies {
+        return Err(AppError::ValidationError {
+            code: "ACTIVITY_SUBSCRIPTION_EMPTY",
+            message: "Choose posts, replies, or both".into(),
+        });
+    }
+    set_activity_subscription(
+        &agent_state,
+        did.trim(),
+        ActivitySubscription {
+            posts: include_posts,
+            replies: include_replies,
+        },
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Stop activity notifications for `did`
+#[tauri::command]
+pub async fn unsubscribe_to_user_activity(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did: String,
+) -> Result<(), AppError> {
+    set_activity_subscription(
+        &agent_state,
+        did.trim(),
+        ActivitySubscription {
+            posts: false,
+            replies: false,
+        },
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::{undefined, undefined_with_message, xrpc_failure};
+
+    type PutError = put_activity_subscription::Error;
+
+    fn code(err: AppError) -> Option<&'static str> {
+        match err {
+            AppError::ValidationError { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn rate_limits_are_not_reported_as_the_subscription_cap() {
+        let throttled = xrpc_failure::<PutError>(429, undefined("RateLimitExceeded"));
+        assert_eq!(
+            code(activity_subscription_error(throttled)),
+            Some("RATE_LIMITED")
+        );
+
+        let named = xrpc_failure::<PutError>(400, undefined("RateLimitExceeded"));
+        assert_eq!(
+            code(activity_subscription_error(named)),
+            Some("RATE_LIMITED")
+        );
+    }
+
+    #[test]
+    fn subscription_cap_is_a_validation_error() {
+        let capped = xrpc_failure::<PutError>(
+            400,
+            undefined_with_message("InvalidRequest", "Activity subscription limit reached"),
+        );
+        assert_eq!(
+            code(activity_subscription_error(capped)),
+            Some("ACTIVITY_SUBSCRIPTION_LIMIT")
+        );
+    }
+
+    #[test]
+    fn other_failures_keep_their_kind() {
+        let outage = xrpc_failure::<PutError>(
+            502,
+            undefined_with_message("UpstreamFailure", "limit of retries hit upstream"),
+        );
+        assert!(matches!(
+            activity_subscription_error(outage),
+            AppError::ApiError(_)
+        ));
+
+        let offline = atrium_xrpc::Error::<PutError>::HttpClient(Box::new(std::io::Error::other(
+            "connection reset",
+        )));
+        assert!(matches!(
+            activity_subscription_error(offline),
+            AppError::NetworkError(_)
+        ));
+    }
+}
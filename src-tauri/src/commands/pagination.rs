@@ -0,0 +1,116 @@
+use atrium_xrpc::error::XrpcErrorKind;
+use std::future::Future;
+
+/// Whether an XRPC error is the server rejecting a stale or invalid pagination cursor.
+/// Other bad requests (an unknown actor, a malformed URI) fail the same way with or without
+/// a cursor, so only errors that name the cursor count.
+fn is_invalid_cursor<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    let atrium_xrpc::Error::XrpcResponse(resp) = err else {
+        return false;
+    };
+    if resp.status.as_u16() != 400 {
+        return false;
+    }
+
+    match &resp.error {
+        Some(XrpcErrorKind::Undefined(body)) => {
+            let mentions_cursor =
+                |text: Option<&str>| text.is_some_and(|t| t.to_lowercase().contains("cursor"));
+            mentions_cursor(body.error.as_deref())
+                || (body.error.as_deref() == Some("InvalidRequest")
+                    && mentions_cursor(body.message.as_deref()))
+        }
+        _ => false,
+    }
+}
+
+/// Run a paginated fetch, restarting from the first page if the server rejects the cursor
+/// (e.g. after a feed algorithm change), so "load more" never gets stuck on a dead cursor.
+/// Returns the output and whether it was reset; responses pass that on as `reset` so the
+/// UI drops the pages it has accumulated.
+pub(crate) async fn fetch_with_cursor_recovery<T, E, F, Fut>(
+    cursor: Option<String>,
+    fetch: F,
+) -> Result<(T, bool), atrium_xrpc::Error<E>>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<T, atrium_xrpc::Error<E>>>,
+{
+    let had_cursor = cursor.is_some();
+    match fetch(cursor).await {
+        Ok(output) => Ok((output, false)),
+        Err(err) if had_cursor && is_invalid_cursor(&err) => {
+            tracing::debug!("cursor rejected, restarting from first page");
+            fetch(None).await.map(|output| (output, true))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::{undefined_with_message, xrpc_failure};
+    use std::cell::RefCell;
+
+    #[test]
+    fn only_cursor_errors_are_invalid_cursors() {
+        assert!(is_invalid_cursor(&xrpc_failure::<()>(
+            400,
+            undefined_with_message("InvalidRequest", "Malformed cursor")
+        )));
+        assert!(is_invalid_cursor(&xrpc_failure::<()>(
+            400,
+            undefined_with_message("InvalidCursor", "")
+        )));
+        assert!(!is_invalid_cursor(&xrpc_failure::<()>(
+            400,
+            undefined_with_message("InvalidRequest", "Profile not found")
+        )));
+        assert!(!is_invalid_cursor(&xrpc_failure::<()>(
+            400,
+            undefined_with_message("NotFound", "cursor")
+        )));
+    }
+
+    #[tokio::test]
+    async fn rejected_cursor_restarts_from_the_first_page() {
+        let requested = RefCell::new(Vec::new());
+        let (page, reset) = fetch_with_cursor_recovery(Some("stale".into()), |cursor| {
+            requested.borrow_mut().push(cursor.clone());
+            async move {
+                match cursor {
+                    Some(_) => Err(xrpc_failure::<()>(
+                        400,
+                        undefined_with_message("InvalidRequest", "Invalid cursor"),
+                    )),
+                    None => Ok("first page"),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(page, "first page");
+        assert!(reset);
+        assert_eq!(*requested.borrow(), vec![Some("stale".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let calls = RefCell::new(0);
+        let result = fetch_with_cursor_recovery(Some("next".into()), |_| {
+            *calls.borrow_mut() += 1;
+            async {
+                Err::<(), _>(xrpc_failure::<()>(
+                    400,
+                    undefined_with_message("InvalidRequest", "Profile not found"),
+                ))
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(*calls.borrow(), 1);
+    }
+}
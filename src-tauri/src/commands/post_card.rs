@@ -0,0 +1,617 @@
+use crate::commands::auth::{current_agent, AgentState};
+use crate::commands::timeline::{fetch_timeline_post, TimelinePost};
+use crate::connectivity;
+use crate::error::AppError;
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use image::{imageops, DynamicImage, ImageFormat, Rgba, RgbaImage};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tauri::{AppHandle, State};
+
+const CARD_WIDTH: u32 = 1200;
+const PADDING: u32 = 64;
+const AVATAR_SIZE: u32 = 96;
+const NAME_SIZE: f32 = 40.0;
+const HANDLE_SIZE: f32 = 32.0;
+const BODY_SIZE: f32 = 44.0;
+const FOOTER_SIZE: f32 = 28.0;
+/// Line advance as a multiple of the font size
+const LINE_SPACING: f32 = 1.35;
+/// Longer posts are cut off with an ellipsis
+const MAX_BODY_LINES: usize = 16;
+/// Images shown side by side under the text
+const MAX_MEDIA: usize = 4;
+const MEDIA_HEIGHT: u32 = 420;
+const MEDIA_GAP: u32 = 12;
+const SECTION_GAP: u32 = 36;
+/// Blur applied to thumbnails the user asked to be warned about
+const SENSITIVE_BLUR: f32 = 40.0;
+
+/// DejaVu Sans ships with the app so cards render on any system
+const BUNDLED_REGULAR: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+const BUNDLED_BOLD: &[u8] = include_bytes!("../../fonts/DejaVuSans-Bold.ttf");
+
+/// (regular, bold) system faces preferred over the bundled one, tried in order
+const SYSTEM_FONTS: [(&str, &str); 5] = [
+    (
+        "C:\\Windows\\Fonts\\segoeui.ttf",
+        "C:\\Windows\\Fonts\\segoeuib.ttf",
+    ),
+    (
+        "/System/Library/Fonts/Supplemental/Arial.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Bold.ttf",
+    ),
+    ("/Library/Fonts/Arial.ttf", "/Library/Fonts/Arial Bold.ttf"),
+    (
+        "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+        "/usr/share/fonts/truetype/noto/NotoSans-Bold.ttf",
+    ),
+    (
+        "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+        "/usr/share/fonts/noto/NotoSans-Bold.ttf",
+    ),
+];
+
+struct CardFonts {
+    regular: FontArc,
+    bold: FontArc,
+}
+
+impl CardFonts {
+    fn bundled() -> Self {
+        CardFonts {
+            regular: FontArc::try_from_slice(BUNDLED_REGULAR).expect("bundled regular font"),
+            bold: FontArc::try_from_slice(BUNDLED_BOLD).expect("bundled bold font"),
+        }
+    }
+
+    fn bold(&self) -> &FontArc {
+        &self.bold
+    }
+}
+
+fn load_font(path: &str) -> Option<FontArc> {
+    let bytes = std::fs::read(path).ok()?;
+    FontArc::try_from_vec(bytes).ok()
+}
+
+static FONTS: LazyLock<CardFonts> = LazyLock::new(|| {
+    SYSTEM_FONTS
+        .iter()
+        .find_map(|(regular, bold)| {
+            Some(CardFonts {
+                regular: load_font(regular)?,
+                bold: load_font(bold)?,
+            })
+        })
+        .unwrap_or_else(CardFonts::bundled)
+});
+
+/// Color scheme of a post card
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CardTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+struct Palette {
+    background: Rgba<u8>,
+    text: Rgba<u8>,
+    muted: Rgba<u8>,
+    placeholder: Rgba<u8>,
+}
+
+impl CardTheme {
+    fn palette(self) -> Palette {
+        match self {
+            Self::Light => Palette {
+                background: Rgba([255, 255, 255, 255]),
+                text: Rgba([15, 20, 25, 255]),
+                muted: Rgba([83, 100, 113, 255]),
+                placeholder: Rgba([230, 236, 240, 255]),
+            },
+            Self::Dark => Palette {
+                background: Rgba([22, 30, 39, 255]),
+                text: Rgba([241, 243, 245, 255]),
+                muted: Rgba([139, 152, 165, 255]),
+                placeholder: Rgba([46, 58, 71, 255]),
+            },
+        }
+    }
+}
+
+fn line_height(size: f32) -> u32 {
+    (size * LINE_SPACING).ceil() as u32
+}
+
+/// Glyphs the font doesn't have (mostly emoji) are skipped rather than drawn as boxes
+fn text_width(font: &FontArc, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if id.0 == 0 {
+            continue;
+        }
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Blend `text` onto the image with its baseline box starting at (x, top)
+fn draw_text(
+    canvas: &mut RgbaImage,
+    font: &FontArc,
+    size: f32,
+    color: Rgba<u8>,
+    x: f32,
+    top: f32,
+    text: &str,
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let baseline = top + scaled.ascent();
+    let mut caret = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if id.0 == 0 {
+            continue;
+        }
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + i64::from(gx);
+            let py = bounds.min.y as i64 + i64::from(gy);
+            if px < 0
+                || py < 0
+                || px >= i64::from(canvas.width())
+                || py >= i64::from(canvas.height())
+            {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+            let alpha = coverage.clamp(0.0, 1.0);
+            for channel in 0..3 {
+                pixel.0[channel] = (f32::from(color.0[channel]) * alpha
+                    + f32::from(pixel.0[channel]) * (1.0 - alpha))
+                    .round() as u8;
+            }
+        });
+    }
+}
+
+/// Cut `text` to fit `max_width`, ending it with an ellipsis when anything was dropped
+fn truncate_to_width(font: &FontArc, size: f32, text: &str, max_width: f32) -> String {
+    if text_width(font, size, text) <= max_width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.to_string();
+    while !truncated.is_empty() && text_width(font, size, &format!("{truncated}…")) > max_width {
+        truncated.pop();
+    }
+    format!("{}…", truncated.trim_end())
+}
+
+/// Word-wrap `text` to `max_width`, breaking words longer than a line. Past `max_lines`
+/// the last line ends with an ellipsis.
+fn wrap_text(
+    font: &FontArc,
+    size: f32,
+    text: &str,
+    max_width: f32,
+    max_lines: usize,
+) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.trim().lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            if text_width(font, size, &candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // A word wider than the card (long URLs) is split across lines
+            for c in word.chars() {
+                line.push(c);
+                if text_width(font, size, &line) > max_width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            *last = truncate_to_width(font, size, &format!("{last}…"), max_width);
+        }
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Thumbnail sources in a processed embed, and whether any is marked sensitive. Alt
+/// text is left out; only the pictures go on the card.
+fn media_thumbs(embed: Option<&serde_json::Value>) -> (Vec<String>, bool) {
+    let Some(embed) = embed else {
+        return (Vec::new(), false);
+    };
+    let media = embed.get("media").unwrap_or(embed);
+    let mut thumbs = Vec::new();
+    let mut sensitive = false;
+
+    if let Some(images) = media.get("images").and_then(|i| i.as_array()) {
+        for image in images {
+            // Deferred images (autoload off) only have their source URL
+            let thumb = image
+                .get("thumb")
+                .and_then(|t| t.as_str())
+                .filter(|t| !t.is_empty())
+                .or_else(|| image.get("source_url").and_then(|u| u.as_str()));
+            if let Some(thumb) = thumb {
+                thumbs.push(thumb.to_string());
+            }
+            sensitive |= image
+                .get("sensitive")
+                .and_then(|s| s.as_bool())
+                .unwrap_or(false);
+        }
+    } else if let Some(video) = embed.get("video") {
+        if let Some(thumb) = video.get("thumbnail").and_then(|t| t.as_str()) {
+            thumbs.push(thumb.to_string());
+        }
+        sensitive = video
+            .get("sensitive")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+    } else if let Some(thumb) = media.pointer("/external/thumb").and_then(|t| t.as_str()) {
+        thumbs.push(thumb.to_string());
+    }
+
+    thumbs.truncate(MAX_MEDIA);
+    (thumbs, sensitive)
+}
+
+/// Load an image from the media cache (file:// URL) or download it. Missing images
+/// leave a placeholder on the card rather than failing it.
+async fn load_card_image(url: &str) -> Option<DynamicImage> {
+    let bytes = match url.strip_prefix("file://") {
+        Some(path) => tokio::fs::read(path).await.ok()?,
+        None => crate::http::client()
+            .get(url)
+            .send()
+            .await
+            .ok()?
+            .bytes()
+            .await
+            .ok()?
+            .to_vec(),
+    };
+    match image::load_from_memory(&bytes) {
+        Ok(img) => Some(img),
+        Err(err) => {
+            tracing::debug!(target: "media", "post card image not decoded: {err}");
+            None
+        }
+    }
+}
+
+fn fill_rect(canvas: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(canvas.height()) {
+        for px in x..(x + width).min(canvas.width()) {
+            canvas.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn draw_avatar(
+    canvas: &mut RgbaImage,
+    avatar: Option<&DynamicImage>,
+    x: u32,
+    y: u32,
+    placeholder: Rgba<u8>,
+) {
+    let avatar = avatar.map(|img| {
+        img.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, imageops::FilterType::Triangle)
+            .to_rgba8()
+    });
+    let radius = AVATAR_SIZE as f32 / 2.0;
+    for ay in 0..AVATAR_SIZE {
+        for ax in 0..AVATAR_SIZE {
+            let dx = ax as f32 + 0.5 - radius;
+            let dy = ay as f32 + 0.5 - radius;
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+            let color = avatar
+                .as_ref()
+                .map(|img| *img.get_pixel(ax, ay))
+                .unwrap_or(placeholder);
+            canvas.put_pixel(
+                x + ax,
+                y + ay,
+                Rgba([color.0[0], color.0[1], color.0[2], 255]),
+            );
+        }
+    }
+}
+
+fn format_card_date(created_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(created_at)
+        .map(|at| {
+            at.with_timezone(&chrono::Local)
+                .format("%b %-d, %Y · %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+struct CardContent {
+    post: TimelinePost,
+    avatar: Option<DynamicImage>,
+    media: Vec<Option<DynamicImage>>,
+    blur_media: bool,
+}
+
+fn render_card(fonts: &CardFonts, content: &CardContent, theme: CardTheme) -> RgbaImage {
+    let palette = theme.palette();
+    let content_width = CARD_WIDTH - 2 * PADDING;
+    let post = &content.post;
+
+    let text_x = PADDING + AVATAR_SIZE + 24;
+    let header_width = (CARD_WIDTH - PADDING - text_x) as f32;
+    let display_name = post
+        .author_display_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&post.author_handle);
+    let display_name = truncate_to_width(fonts.bold(), NAME_SIZE, display_name, header_width);
+    let handle = truncate_to_width(
+        &fonts.regular,
+        HANDLE_SIZE,
+        &format!("@{}", post.author_handle),
+        header_width,
+    );
+    let body = wrap_text(
+        &fonts.regular,
+        BODY_SIZE,
+        &post.text,
+        content_width as f32,
+        MAX_BODY_LINES,
+    );
+
+    let body_top = PADDING + AVATAR_SIZE + SECTION_GAP;
+    let body_height = body.len() as u32 * line_height(BODY_SIZE);
+    let media_top = body_top + body_height + if body.is_empty() { 0 } else { SECTION_GAP };
+    let media_height = if content.media.is_empty() {
+        0
+    } else {
+        MEDIA_HEIGHT + SECTION_GAP
+    };
+    let footer_top = media_top + media_height;
+    let height = footer_top + line_height(FOOTER_SIZE) + PADDING;
+
+    let mut canvas = RgbaImage::from_pixel(CARD_WIDTH, height, palette.background);
+
+    draw_avatar(
+        &mut canvas,
+        content.avatar.as_ref(),
+        PADDING,
+        PADDING,
+        palette.placeholder,
+    );
+    // The two header lines are a little taller than the avatar, so this can be negative
+    let header_height = (line_height(NAME_SIZE) + line_height(HANDLE_SIZE)) as f32;
+    let header_top = PADDING as f32 + (AVATAR_SIZE as f32 - header_height) / 2.0;
+    draw_text(
+        &mut canvas,
+        fonts.bold(),
+        NAME_SIZE,
+        palette.text,
+        text_x as f32,
+        header_top,
+        &display_name,
+    );
+    draw_text(
+        &mut canvas,
+        &fonts.regular,
+        HANDLE_SIZE,
+        palette.muted,
+        text_x as f32,
+        header_top + line_height(NAME_SIZE) as f32,
+        &handle,
+    );
+
+    for (i, line) in body.iter().enumerate() {
+        let top = body_top + i as u32 * line_height(BODY_SIZE);
+        draw_text(
+            &mut canvas,
+            &fonts.regular,
+            BODY_SIZE,
+            palette.text,
+            PADDING as f32,
+            top as f32,
+            line,
+        );
+    }
+
+    let count = content.media.len() as u32;
+    let gaps = MEDIA_GAP * count.saturating_sub(1);
+    if let Some(tile_width) = (content_width - gaps).checked_div(count) {
+        for (i, image) in content.media.iter().enumerate() {
+            let x = PADDING + i as u32 * (tile_width + MEDIA_GAP);
+            match image {
+                Some(image) => {
+                    let mut tile = image.resize_to_fill(
+                        tile_width,
+                        MEDIA_HEIGHT,
+                        imageops::FilterType::Triangle,
+                    );
+                    if content.blur_media {
+                        tile = tile.blur(SENSITIVE_BLUR);
+                    }
+                    imageops::overlay(
+                        &mut canvas,
+                        &tile.to_rgba8(),
+                        i64::from(x),
+                        i64::from(media_top),
+                    );
+                }
+                None => fill_rect(
+                    &mut canvas,
+                    x,
+                    media_top,
+                    tile_width,
+                    MEDIA_HEIGHT,
+                    palette.placeholder,
+                ),
+            }
+        }
+    }
+
+    let footer = format_card_date(&post.created_at);
+    draw_text(
+        &mut canvas,
+        &fonts.regular,
+        FOOTER_SIZE,
+        palette.muted,
+        PADDING as f32,
+        footer_top as f32,
+        &footer,
+    );
+
+    canvas
+}
+
+/// Render a post as a PNG card (author, avatar, text and up to four media thumbnails)
+/// and write it to `dest_path`. Long text is wrapped and cut off with an ellipsis;
+/// media the user asked to be warned about is blurred.
+#[tauri::command]
+pub async fn render_post_card(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uri: String,
+    dest_path: String,
+    theme: Option<CardTheme>,
+) -> Result<(), AppError> {
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err(AppError::ValidationError {
+            code: "INVALID_PATH",
+            message: "No destination for the post card".into(),
+        });
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    let post = fetch_timeline_post(&app, &agent, uri.trim().to_string())
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    let (thumbs, sensitive) = media_thumbs(post.embed.as_ref());
+    let (avatar, media) = futures::join!(
+        async {
+            match post.author_avatar.as_deref() {
+                Some(url) => load_card_image(url).await,
+                None => None,
+            }
+        },
+        futures::future::join_all(thumbs.iter().map(|url| load_card_image(url)))
+    );
+    let content = CardContent {
+        blur_media: sensitive || post.content_warning.is_some(),
+        post,
+        avatar,
+        media,
+    };
+    let theme = theme.unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || -> Result<(), AppError> {
+        render_card(&FONTS, &content, theme)
+            .save_with_format(&dest, ImageFormat::Png)
+            .map_err(|e| AppError::InternalError(format!("write {}: {e}", dest.display())))
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_post(text: &str) -> TimelinePost {
+        serde_json::from_value(serde_json::json!({
+            "uri": "at://did:plc:alice/app.bsky.feed.post/3kabc",
+            "cid": "bafyreib",
+            "author_did": "did:plc:alice",
+            "author_handle": "alice.bsky.social",
+            "author_display_name": "Alice",
+            "text": text,
+            "created_at": "2024-05-01T12:00:00Z",
+            "reply_count": 0,
+            "repost_count": 0,
+            "like_count": 0,
+            "is_liked": false,
+            "is_reposted": false,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn header_is_drawn_next_to_the_avatar() {
+        let fonts = CardFonts::bundled();
+        let content = CardContent {
+            post: sample_post("Hello from a post card"),
+            avatar: None,
+            media: Vec::new(),
+            blur_media: false,
+        };
+        let card = render_card(&fonts, &content, CardTheme::Light);
+        let background = CardTheme::Light.palette().background;
+
+        let text_x = PADDING + AVATAR_SIZE + 24;
+        let header_ink = (PADDING..PADDING + AVATAR_SIZE)
+            .flat_map(|y| (text_x..CARD_WIDTH - PADDING).map(move |x| (x, y)))
+            .filter(|&(x, y)| *card.get_pixel(x, y) != background)
+            .count();
+        assert!(header_ink > 0, "name and handle were not drawn");
+    }
+
+    #[test]
+    fn long_text_is_cut_to_max_lines() {
+        let fonts = CardFonts::bundled();
+        let text = "word ".repeat(2000);
+        let lines = wrap_text(&fonts.regular, BODY_SIZE, &text, 800.0, MAX_BODY_LINES);
+        assert_eq!(lines.len(), MAX_BODY_LINES);
+        assert!(lines.last().unwrap().ends_with('…'));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::commands::repo::fetch_record;
+use crate::connectivity;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use bsky_sdk::api::app::bsky::feed::postgate::{
+    DisableRule, DisableRuleData, Record as PostgateRecord, RecordData as PostgateRecordData,
+    RecordEmbeddingRulesItem,
+};
+use bsky_sdk::api::com::atproto::repo::put_record;
+use bsky_sdk::api::types::string::{AtIdentifier, AtUri, Datetime, Did, RecordKey};
+use bsky_sdk::api::types::{TryIntoUnknown, Union};
+use serde::Serialize;
+use std::str::FromStr;
+use tauri::{AppHandle, State};
+
+const POSTGATE_COLLECTION: &str = "app.bsky.feed.postgate";
+
+#[derive(Serialize)]
+pub struct PostgateInfo {
+    pub post_uri: String,
+    pub detached_quote_uris: Vec<String>,
+    pub quotes_disabled: bool,
+}
+
+fn current_repo_did() -> Result<Did, AppError> {
+    let stored = get_stored_session()?;
+    stored
+        .did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid stored DID".into()))
+}
+
+/// The postgate for a post must live in the same repo under the post's rkey
+fn postgate_rkey(did: &Did, post_uri: &str) -> Result<String, AppError> {
+    let rest = post_uri
+        .strip_prefix("at://")
+        .ok_or_else(|| AppError::ApiError("Invalid post URI".into()))?;
+    let mut parts = rest.split('/');
+    let (Some(authority), Some("app.bsky.feed.post"), Some(rkey), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(AppError::ApiError("Invalid post URI".into()));
+    };
+
+    if authority != did.to_string() {
+        return Err(AppError::ApiError(
+            "Quote settings can only be changed on your own posts".into(),
+        ));
+    }
+
+    Ok(rkey.to_string())
+}
+
+fn is_disable_rule(rule: &Union<RecordEmbeddingRulesItem>) -> bool {
+    matches!(rule, Union::Refs(RecordEmbeddingRulesItem::DisableRule(_)))
+}
+
+fn disable_rule() -> Union<RecordEmbeddingRulesItem> {
+    Union::Refs(RecordEmbeddingRulesItem::DisableRule(Box::new(
+        DisableRule::from(DisableRuleData {}),
+    )))
+}
+
+fn postgate_info(post_uri: &str, data: &PostgateRecordData) -> PostgateInfo {
+    PostgateInfo {
+        post_uri: post_uri.to_string(),
+        detached_quote_uris: data
+            .detached_embedding_uris
+            .iter()
+            .flatten()
+            .map(|uri| uri.to_string())
+            .collect(),
+        quotes_disabled: data.embedding_rules.iter().flatten().any(is_disable_rule),
+    }
+}
+
+/// Read-modify-write the postgate for one of the user's posts, creating it if missing.
+/// Fields we don't touch survive the write, and the swap CID guards against races.
+pub(crate) async fn update_postgate<F>(
+    agent: &AppAgent,
+    did: &Did,
+    post_uri: &str,
+    modify: F,
+) -> Result<PostgateInfo, AppError>
+where
+    F: FnOnce(&mut PostgateRecordData),
+{
+    let rkey_str = postgate_rkey(did, post_uri)?;
+    let rkey = RecordKey::from_str(&rkey_str)
+        .map_err(|_| AppError::ApiError("Invalid record key".into()))?;
+    let post: AtUri = post_uri
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid post URI".into()))?;
+
+    let (mut record_data, swap_record) = match fetch_record(
+        agent,
+        AtIdentifier::Did(did.clone()),
+        POSTGATE_COLLECTION,
+        &rkey_str,
+    )
+    .await
+    {
+        Ok(existing) => {
+            let swap_record = existing.cid.as_deref().and_then(|cid| cid.parse().ok());
+            let data = serde_json::from_value::<PostgateRecord>(existing.value)
+                .map_err(|e| AppError::InternalError(format!("postgate decode failed: {e}")))?
+                .data;
+            (data, swap_record)
+        }
+        Err(AppError::NotFound(_)) => (
+            PostgateRecordData {
+                created_at: Datetime::now(),
+                detached_embedding_uris: None,
+                embedding_rules: None,
+                post,
+            },
+            None,
+        ),
+        Err(err) => return Err(err),
+    };
+
+    modify(&mut record_data);
+    let info = postgate_info(post_uri, &record_data);
+
+    let record = record_data
+        .try_into_unknown()
+        .map_err(|e| AppError::ApiError(e.to_string()))?;
+
+    agent
+        .api
+        .com
+        .atproto
+        .repo
+        .put_record(
+            put_record::InputData {
+                repo: AtIdentifier::Did(did.clone()),
+                collection: POSTGATE_COLLECTION
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid postgate NSID".into()))?,
+                record,
+                rkey,
+                swap_commit: None,
+                swap_record,
+                validate: None,
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    Ok(info)
+}
+
+pub(crate) fn set_quotes_disabled(data: &mut PostgateRecordData, disabled: bool) {
+    let mut rules = data.embedding_rules.take().unwrap_or_default();
+    rules.retain(|rule| !is_disable_rule(rule));
+    if disabled {
+        rules.push(disable_rule());
+    }
+    data.embedding_rules = (!rules.is_empty()).then_some(rules);
+}
+
+async fn change_postgate<F>(
+    agent_state: &AgentState,
+    post_uri: &str,
+    modify: F,
+) -> Result<PostgateInfo, AppError>
+where
+    F: FnOnce(&mut PostgateRecordData),
+{
+    let agent = current_agent(agent_state).await?;
+    let did = current_repo_did()?;
+    update_postgate(&agent, &did, post_uri.trim(), modify).await
+}
+
+/// Detach a quote of one of the user's posts so it no longer shows the embedded post
+#[tauri::command]
+pub async fn detach_quote(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    post_uri: String,
+    quote_uri: String,
+) -> Result<PostgateInfo, AppError> {
+    let quote: AtUri = quote_uri
+        .trim()
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid quote URI".into()))?;
+
+    change_postgate(&agent_state, &post_uri, |data| {
+        let detached = data.detached_embedding_uris.get_or_insert_with(Vec::new);
+        if !detached.contains(&quote) {
+            detached.push(quote);
+        }
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Undo `detach_quote`
+#[tauri::command]
+pub async fn reattach_quote(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    post_uri: String,
+    quote_uri: String,
+) -> Result<PostgateInfo, AppError> {
+    let quote_uri = quote_uri.trim().to_string();
+
+    change_postgate(&agent_state, &post_uri, |data| {
+        if let Some(detached) = data.detached_embedding_uris.as_mut() {
+            detached.retain(|uri| uri.to_string() != quote_uri);
+            if detached.is_empty() {
+                data.detached_embedding_uris = None;
+            }
+        }
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Stop anyone from quoting one of the user's posts
+#[tauri::command]
+pub async fn disable_quotes(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    post_uri: String,
+) -> Result<PostgateInfo, AppError> {
+    change_postgate(&agent_state, &post_uri, |data| {
+        set_quotes_disabled(data, true)
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Allow quoting one of the user's posts again
+#[tauri::command]
+pub async fn enable_quotes(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    post_uri: String,
+) -> Result<PostgateInfo, AppError> {
+    change_postgate(&agent_state, &post_uri, |data| {
+        set_quotes_disabled(data, false)
+    })
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
@@ -0,0 +1,185 @@
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::connectivity;
+use crate::error::AppError;
+use crate::session::get_stored_session;
+use atrium_xrpc::error::XrpcErrorKind;
+use bsky_sdk::api::com::atproto::repo::{get_record, list_records};
+use bsky_sdk::api::types::string::{AtIdentifier, RecordKey};
+use bsky_sdk::api::types::LimitedNonZeroU8;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize)]
+pub struct RecordResponse {
+    pub uri: String,
+    pub cid: Option<String>,
+    pub value: serde_json::Value,
+}
+
+/// Fetch a single raw record from a repo via com.atproto.repo.getRecord.
+/// Used for read-modify-write flows so fields we don't model are preserved.
+pub(crate) async fn fetch_record(
+    agent: &AppAgent,
+    repo: AtIdentifier,
+    collection: &str,
+    rkey: &str,
+) -> Result<RecordResponse, AppError> {
+    let rkey =
+        RecordKey::from_str(rkey).map_err(|_| AppError::ApiError("Invalid record key".into()))?;
+
+    let response = agent
+        .api
+        .com
+        .atproto
+        .repo
+        .get_record(
+            get_record::ParametersData {
+                cid: None,
+                collection: collection
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid collection NSID".into()))?,
+                repo,
+                rkey,
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| match e {
+            atrium_xrpc::Error::XrpcResponse(ref resp)
+                if matches!(
+                    resp.error,
+                    Some(XrpcErrorKind::Custom(get_record::Error::RecordNotFound(_)))
+                ) =>
+            {
+                AppError::NotFound(format!("{collection} record not found"))
+            }
+            other => connectivity::xrpc_error(other),
+        })?;
+
+    let value = serde_json::to_value(&response.data.value)
+        .map_err(|e| AppError::InternalError(format!("record decode failed: {e}")))?;
+
+    Ok(RecordResponse {
+        uri: response.data.uri,
+        cid: response.data.cid.map(|cid| cid.as_ref().to_string()),
+        value,
+    })
+}
+
+/// Get a raw record (e.g. threadgate, postgate, profile) from any repo
+#[tauri::command]
+pub async fn get_record(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    repo: String,
+    collection: String,
+    rkey: String,
+) -> Result<RecordResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let repo: AtIdentifier = repo
+        .trim()
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid repo identifier".into()))?;
+
+    fetch_record(&agent, repo, collection.trim(), rkey.trim())
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+#[derive(Serialize)]
+pub struct RecordListItem {
+    pub uri: String,
+    pub cid: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct RecordListResponse {
+    pub records: Vec<RecordListItem>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ListMyRecordsRequest {
+    pub collection: String,
+    pub cursor: Option<String>,
+    pub limit: Option<u8>,
+}
+
+/// Fetch one page of a collection via com.atproto.repo.listRecords.
+pub(crate) async fn list_records_page(
+    agent: &AppAgent,
+    repo: AtIdentifier,
+    collection: &str,
+    cursor: Option<String>,
+    limit: u8,
+) -> Result<RecordListResponse, AppError> {
+    let limit = LimitedNonZeroU8::<100>::try_from(limit.max(1).min(100)).ok();
+
+    let response = agent
+        .api
+        .com
+        .atproto
+        .repo
+        .list_records(
+            list_records::ParametersData {
+                collection: collection
+                    .parse()
+                    .map_err(|_| AppError::ApiError("Invalid collection NSID".into()))?,
+                cursor,
+                limit,
+                repo,
+                reverse: None,
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    let records = response
+        .data
+        .records
+        .iter()
+        .map(|record| RecordListItem {
+            uri: record.uri.clone(),
+            cid: record.cid.as_ref().to_string(),
+            value: serde_json::to_value(&record.value).unwrap_or(serde_json::Value::Null),
+        })
+        .collect::<Vec<_>>();
+
+    // An empty page with a cursor would loop forever in callers that drain a collection.
+    let cursor = if records.is_empty() {
+        None
+    } else {
+        response.data.cursor
+    };
+
+    Ok(RecordListResponse { records, cursor })
+}
+
+/// List records of a collection in the current user's repo
+#[tauri::command]
+pub async fn list_my_records(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    request: ListMyRecordsRequest,
+) -> Result<RecordListResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let did = get_stored_session()?
+        .did
+        .parse()
+        .map_err(|_| AppError::ApiError("Invalid stored DID".into()))?;
+
+    list_records_page(
+        &agent,
+        AtIdentifier::Did(did),
+        request.collection.trim(),
+        request.cursor,
+        request.limit.unwrap_or(50),
+    )
+    .await
+    .inspect_err(|err| connectivity::watch_error(&app, err))
+}
@@ -1,7 +1,8 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState};
+use crate::connectivity;
 use crate::error::AppError;
 use serde::Serialize;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +21,7 @@ pub struct SearchResultPost {
     pub cid: String,
     pub author: SearchResultAuthor,
     pub text: String,
+    pub facets: Option<serde_json::Value>,
     pub indexed_at: String,
     pub like_count: u32,
     pub repost_count: u32,
@@ -37,13 +39,13 @@ pub struct SearchResults {
 /// Search for actors (users) by query
 #[tauri::command]
 pub async fn search_actors(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     query: String,
     limit: Option<u8>,
     cursor: Option<String>,
 ) -> Result<SearchResults, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let limit_val = limit.unwrap_or(25).max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
@@ -63,7 +65,8 @@ pub async fn search_actors(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let actors: Vec<SearchResultAuthor> = response
         .data
@@ -88,6 +91,7 @@ pub async fn search_actors(
 /// Search for posts by query
 #[tauri::command]
 pub async fn search_posts(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     query: String,
     limit: Option<u8>,
@@ -95,8 +99,7 @@ pub async fn search_posts(
     sort: Option<String>,
     author: Option<String>,
 ) -> Result<SearchResults, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let sort_order = sort.as_deref().unwrap_or("latest");
     let limit_val = limit.unwrap_or(25).max(1).min(100);
@@ -125,7 +128,8 @@ pub async fn search_posts(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     let posts: Vec<SearchResultPost> = response
         .data
@@ -140,6 +144,7 @@ pub async fn search_posts(
             } else {
                 String::new()
             };
+            let facets = super::timeline::extract_post_facets(&post);
 
             SearchResultPost {
                 uri: post.uri.to_string(),
@@ -152,6 +157,7 @@ pub async fn search_posts(
                     description: None, // ProfileViewBasic doesn't have description
                 },
                 text,
+                facets,
                 indexed_at: post.indexed_at.as_ref().to_string(),
                 like_count: post.like_count.unwrap_or(0) as u32,
                 repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -170,11 +176,11 @@ pub async fn search_posts(
 /// Combined search for both actors and posts (quick search)
 #[tauri::command]
 pub async fn search(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     query: String,
 ) -> Result<SearchResults, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(5_u8).ok();
 
@@ -193,7 +199,9 @@ pub async fn search(
             }
             .into(),
         )
-        .await;
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err));
 
     let actors: Vec<SearchResultAuthor> = actors_response
         .map(|r| {
@@ -234,7 +242,9 @@ pub async fn search(
             }
             .into(),
         )
-        .await;
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err));
 
     let posts: Vec<SearchResultPost> = posts_response
         .map(|r| {
@@ -250,6 +260,7 @@ pub async fn search(
                     } else {
                         String::new()
                     };
+                    let facets = super::timeline::extract_post_facets(&post);
 
                     SearchResultPost {
                         uri: post.uri.to_string(),
@@ -262,6 +273,7 @@ pub async fn search(
                             description: None,
                         },
                         text,
+                        facets,
                         indexed_at: post.indexed_at.as_ref().to_string(),
                         like_count: post.like_count.unwrap_or(0) as u32,
                         repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -278,3 +290,101 @@ pub async fn search(
         cursor: None,
     })
 }
+
+const WEB_ORIGIN: &str = "https://bsky.app";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendingTopic {
+    pub topic: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    /// Absolute bsky.app link for the topic's feed or search page
+    pub link: Option<String>,
+    /// Search query when the link points at a search rather than a feed
+    pub search_query: Option<String>,
+}
+
+fn search_query_from_link(link: &str) -> Option<String> {
+    let url = reqwest::Url::parse(link).ok()?;
+    if url.path() != "/search" {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "q")
+        .map(|(_, value)| value.into_owned())
+}
+
+fn parse_trending_topic(value: &serde_json::Value) -> Option<TrendingTopic> {
+    let topic = value.get("topic")?.as_str()?.trim();
+    if topic.is_empty() {
+        return None;
+    }
+
+    let text = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+
+    let link = text("link").map(|link| {
+        if link.starts_with('/') {
+            format!("{WEB_ORIGIN}{link}")
+        } else {
+            link
+        }
+    });
+    let search_query = link.as_deref().and_then(search_query_from_link);
+
+    Some(TrendingTopic {
+        topic: topic.to_string(),
+        display_name: text("displayName"),
+        description: text("description"),
+        link,
+        search_query,
+    })
+}
+
+/// Get currently trending topics for the discovery panel.
+/// The endpoint is unspecced, so anything that doesn't look like the expected shape
+/// yields an empty list instead of an error.
+#[tauri::command]
+pub async fn get_trends(limit: Option<u8>) -> Result<Vec<TrendingTopic>, AppError> {
+    let limit = limit.unwrap_or(10).clamp(1, 25);
+
+    let public_appview = crate::endpoints::current().public_appview_url;
+    let response = crate::http::client()
+        .get(format!(
+            "{public_appview}/xrpc/app.bsky.unspecced.getTrendingTopics"
+        ))
+        .query(&[("limit", limit.to_string())])
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error("trends request failed", e))?;
+
+    if !response.status().is_success() {
+        tracing::warn!("trends request failed with status {}", response.status());
+        return Ok(Vec::new());
+    }
+
+    let body: serde_json::Value = match response.json().await {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::warn!("trends decode failed: {err}");
+            return Ok(Vec::new());
+        }
+    };
+
+    let Some(topics) = body.get("topics").and_then(|t| t.as_array()) else {
+        tracing::warn!("trends response has an unexpected shape");
+        return Ok(Vec::new());
+    };
+
+    Ok(topics
+        .iter()
+        .filter_map(parse_trending_topic)
+        .take(limit as usize)
+        .collect())
+}
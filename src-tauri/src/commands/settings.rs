@@ -0,0 +1,553 @@
+use crate::db::DbState;
+use crate::endpoints::{self, Endpoints};
+use crate::error::AppError;
+use crate::http;
+use crate::logging;
+use crate::media::{self, ImageCachePolicy, ImageEncoding, MediaAutoload};
+use crate::session::{self, get_stored_session, SessionBackend};
+use bsky_sdk::api::types::string::Language;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::State;
+
+/// Only show notifications from accounts the user follows
+pub(crate) const NOTIFICATION_PRIORITY_KEY: &str = "notifications.priority";
+/// Network request timeout in seconds
+pub(crate) const REQUEST_TIMEOUT_KEY: &str = "network.request_timeout_secs";
+/// AppView endpoints for direct HTTP requests (see `endpoints` for how they relate to the PDS)
+pub(crate) const ENDPOINTS_KEY: &str = "network.endpoints";
+/// Log filter directives, e.g. "info" or "warn,cache=debug"
+pub(crate) const LOG_LEVEL_KEY: &str = "logging.level";
+/// Thumbnail size and encoding of cached post images
+pub(crate) const IMAGE_POLICY_KEY: &str = "media.image_policy";
+/// Blur media labelled sexual or graphic until tapped (device-wide, default on)
+pub(crate) const BLUR_SENSITIVE_MEDIA_KEY: &str = "media.blur_sensitive";
+/// Which media kinds download automatically (device-wide, default all)
+pub(crate) const MEDIA_AUTOLOAD_KEY: &str = "media.autoload";
+/// Send attempts before a queued post is marked failed
+pub(crate) const RETRY_MAX_ATTEMPTS_KEY: &str = "retry_queue.max_attempts";
+
+/// BCP-47 tags applied to posts composed without explicit languages
+pub(crate) const DEFAULT_POST_LANGS_KEY: &str = "compose.default_post_langs";
+/// Refuse to post images without alt text (per account, default off)
+pub(crate) const REQUIRE_ALT_TEXT_KEY: &str = "compose.require_alt_text";
+/// Where the session is stored on this machine
+pub(crate) const SESSION_BACKEND_KEY: &str = "session.backend";
+
+const DEFAULT_RETRY_MAX_ATTEMPTS: i64 = 8;
+/// Bluesky clients tag a post with at most this many languages
+const MAX_POST_LANGS: usize = 3;
+
+/// Scope for device-wide settings that apply regardless of the signed-in account
+pub(crate) const DEVICE_SCOPE: &str = "device";
+
+fn current_user_did() -> Result<String, AppError> {
+    Ok(get_stored_session()?.did)
+}
+
+/// Read a per-account setting; `None` if it was never set
+pub(crate) async fn load_setting<T: DeserializeOwned>(
+    db: &SqlitePool,
+    user_did: &str,
+    key: &str,
+) -> Result<Option<T>, AppError> {
+    let value = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT value_json
+        FROM app_settings
+        WHERE user_did = ?1 AND setting_key = ?2
+        "#,
+    )
+    .bind(user_did)
+    .bind(key)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("settings read failed: {e}")))?;
+
+    value
+        .map(|raw| {
+            serde_json::from_str::<T>(&raw)
+                .map_err(|e| AppError::InternalError(format!("settings decode failed: {e}")))
+        })
+        .transpose()
+}
+
+pub(crate) async fn save_setting<T: Serialize>(
+    db: &SqlitePool,
+    user_did: &str,
+    key: &str,
+    value: &T,
+) -> Result<(), AppError> {
+    let value_json = serde_json::to_string(value)
+        .map_err(|e| AppError::InternalError(format!("settings encode failed: {e}")))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO app_settings (user_did, setting_key, value_json, updated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(user_did, setting_key) DO UPDATE SET
+            value_json = excluded.value_json,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(user_did)
+    .bind(key)
+    .bind(value_json)
+    .bind(Utc::now().to_rfc3339())
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("settings write failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Whether priority notifications are enabled for the signed-in account (off by default)
+pub(crate) async fn notification_priority_enabled(db: &SqlitePool) -> bool {
+    let Ok(user_did) = current_user_did() else {
+        return false;
+    };
+
+    match load_setting::<bool>(db, &user_did, NOTIFICATION_PRIORITY_KEY).await {
+        Ok(enabled) => enabled.unwrap_or(false),
+        Err(err) => {
+            tracing::warn!("notification priority read failed: {err}");
+            false
+        }
+    }
+}
+
+/// Get whether priority notifications mode is enabled
+#[tauri::command]
+pub async fn get_notification_priority(db: State<'_, DbState>) -> Result<bool, AppError> {
+    Ok(notification_priority_enabled(db.inner().as_ref()).await)
+}
+
+/// Enable or disable priority notifications mode
+#[tauri::command]
+pub async fn set_notification_priority(
+    db: State<'_, DbState>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let user_did = current_user_did()?;
+    save_setting(
+        db.inner().as_ref(),
+        &user_did,
+        NOTIFICATION_PRIORITY_KEY,
+        &enabled,
+    )
+    .await
+}
+
+/// Load the saved request timeout into the HTTP layer (called once at startup)
+pub(crate) async fn apply_saved_request_timeout(db: &SqlitePool) {
+    match load_setting::<u64>(db, DEVICE_SCOPE, REQUEST_TIMEOUT_KEY).await {
+        Ok(Some(secs)) => {
+            http::set_request_timeout_secs(secs);
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!("request timeout read failed: {err}"),
+    }
+}
+
+/// Get the network request timeout in seconds
+#[tauri::command]
+pub async fn get_request_timeout() -> Result<u64, AppError> {
+    Ok(http::request_timeout_secs())
+}
+
+/// Set the network request timeout in seconds; returns the (clamped) value applied.
+/// Media and AppView requests use it immediately, agent calls after the next sign-in.
+#[tauri::command]
+pub async fn set_request_timeout(db: State<'_, DbState>, secs: u64) -> Result<u64, AppError> {
+    let applied = http::set_request_timeout_secs(secs);
+    save_setting(
+        db.inner().as_ref(),
+        DEVICE_SCOPE,
+        REQUEST_TIMEOUT_KEY,
+        &applied,
+    )
+    .await?;
+    Ok(applied)
+}
+
+/// Load saved AppView endpoints (called once at startup)
+pub(crate) async fn apply_saved_endpoints(db: &SqlitePool) {
+    match load_setting::<Endpoints>(db, DEVICE_SCOPE, ENDPOINTS_KEY).await {
+        Ok(Some(saved)) => {
+            // Saved before plain-http endpoints were refused; drop back to the defaults
+            let defaults = Endpoints::default();
+            let checked = |url: String, default: String| {
+                endpoints::normalize_url(&url).unwrap_or_else(|err| {
+                    tracing::warn!("saved endpoint ignored: {err}");
+                    default
+                })
+            };
+            endpoints::set(Endpoints {
+                appview_url: checked(saved.appview_url, defaults.appview_url),
+                public_appview_url: checked(saved.public_appview_url, defaults.public_appview_url),
+            });
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!("endpoints read failed: {err}"),
+    }
+}
+
+/// Get the AppView endpoints used for direct requests
+#[tauri::command]
+pub async fn get_endpoints() -> Result<Endpoints, AppError> {
+    Ok(endpoints::current())
+}
+
+/// Point direct AppView requests at another AppView. `None` restores a default.
+/// Each custom URL must answer before it is saved; returns the normalized endpoints.
+#[tauri::command]
+pub async fn set_endpoints(
+    db: State<'_, DbState>,
+    appview_url: Option<String>,
+    public_appview_url: Option<String>,
+) -> Result<Endpoints, AppError> {
+    let defaults = Endpoints::default();
+    let appview_url = match appview_url.filter(|url| !url.trim().is_empty()) {
+        Some(url) => endpoints::normalize_url(&url)?,
+        None => defaults.appview_url,
+    };
+    let public_appview_url = match public_appview_url.filter(|url| !url.trim().is_empty()) {
+        Some(url) => endpoints::normalize_url(&url)?,
+        None => defaults.public_appview_url,
+    };
+
+    for url in [&appview_url, &public_appview_url] {
+        if url != endpoints::DEFAULT_APPVIEW_URL && url != endpoints::DEFAULT_PUBLIC_APPVIEW_URL {
+            endpoints::check_appview(url).await?;
+        }
+    }
+
+    let updated = Endpoints {
+        appview_url,
+        public_appview_url,
+    };
+    save_setting(db.inner().as_ref(), DEVICE_SCOPE, ENDPOINTS_KEY, &updated).await?;
+    endpoints::set(updated.clone());
+    Ok(updated)
+}
+
+/// Apply the saved log filter (called once at startup, after logging is initialized)
+pub(crate) async fn apply_saved_log_level(db: &SqlitePool) {
+    match load_setting::<String>(db, DEVICE_SCOPE, LOG_LEVEL_KEY).await {
+        Ok(Some(level)) => {
+            if let Err(err) = logging::set_filter(&level) {
+                tracing::warn!("saved log level ignored: {err}");
+            }
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!("log level read failed: {err}"),
+    }
+}
+
+/// Get the active log filter
+#[tauri::command]
+pub async fn get_log_level() -> Result<String, AppError> {
+    Ok(logging::current_filter())
+}
+
+/// Change log verbosity; accepts a level ("debug") or filter directives ("info,media=debug")
+#[tauri::command]
+pub async fn set_log_level(db: State<'_, DbState>, level: String) -> Result<String, AppError> {
+    let applied = logging::set_filter(&level)?;
+    save_setting(db.inner().as_ref(), DEVICE_SCOPE, LOG_LEVEL_KEY, &applied).await?;
+    Ok(applied)
+}
+
+/// Path of the current log file, for attaching to bug reports
+#[tauri::command]
+pub async fn get_log_file_path() -> Result<Option<String>, AppError> {
+    Ok(logging::log_file_path().map(|path| path.display().to_string()))
+}
+
+/// Load the saved image cache policy (called once at startup)
+pub(crate) async fn apply_saved_image_policy(db: &SqlitePool) {
+    match load_setting::<ImageCachePolicy>(db, DEVICE_SCOPE, IMAGE_POLICY_KEY).await {
+        Ok(Some(policy)) => media::set_image_policy(policy),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("image policy read failed: {err}"),
+    }
+}
+
+fn validate_image_encoding(kind: &str, encoding: &ImageEncoding) -> Result<(), AppError> {
+    if !(128..=4096).contains(&encoding.thumb_max_width) {
+        return Err(AppError::ValidationError {
+            code: "INVALID_IMAGE_POLICY",
+            message: format!("{kind} thumbnail width must be between 128 and 4096 pixels"),
+        });
+    }
+    if encoding
+        .lossy_quality
+        .is_some_and(|quality| !(1..=100).contains(&quality))
+    {
+        return Err(AppError::ValidationError {
+            code: "INVALID_IMAGE_POLICY",
+            message: format!("{kind} quality must be between 1 and 100"),
+        });
+    }
+    Ok(())
+}
+
+/// Get how cached post images are sized and encoded
+#[tauri::command]
+pub async fn get_image_policy() -> Result<ImageCachePolicy, AppError> {
+    Ok(media::image_policy())
+}
+
+/// Change how cached post images are sized and encoded. Images already cached under
+/// another policy are re-encoded the next time they are shown.
+#[tauri::command]
+pub async fn set_image_policy(
+    db: State<'_, DbState>,
+    policy: ImageCachePolicy,
+) -> Result<ImageCachePolicy, AppError> {
+    validate_image_encoding("Photo", &policy.photo)?;
+    validate_image_encoding("Graphic", &policy.graphic)?;
+    save_setting(db.inner().as_ref(), DEVICE_SCOPE, IMAGE_POLICY_KEY, &policy).await?;
+    media::set_image_policy(policy);
+    Ok(policy)
+}
+
+/// Load the saved sensitive-media blur switch (called once at startup)
+pub(crate) async fn apply_saved_blur_sensitive_media(db: &SqlitePool) {
+    match load_setting::<bool>(db, DEVICE_SCOPE, BLUR_SENSITIVE_MEDIA_KEY).await {
+        Ok(Some(enabled)) => media::set_blur_sensitive_media(enabled),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("sensitive media setting read failed: {err}"),
+    }
+}
+
+/// Whether media labelled porn, sexual, nudity or graphic-media comes back `sensitive`
+#[tauri::command]
+pub async fn get_blur_sensitive_media() -> Result<bool, AppError> {
+    Ok(media::blur_sensitive_media())
+}
+
+/// Turn blurring of labelled media on or off; applies to posts loaded from now on
+#[tauri::command]
+pub async fn set_blur_sensitive_media(
+    db: State<'_, DbState>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    save_setting(
+        db.inner().as_ref(),
+        DEVICE_SCOPE,
+        BLUR_SENSITIVE_MEDIA_KEY,
+        &enabled,
+    )
+    .await?;
+    media::set_blur_sensitive_media(enabled);
+    Ok(())
+}
+
+/// Load the saved media autoload switches (called once at startup)
+pub(crate) async fn apply_saved_media_autoload(db: &SqlitePool) {
+    match load_setting::<MediaAutoload>(db, DEVICE_SCOPE, MEDIA_AUTOLOAD_KEY).await {
+        Ok(Some(autoload)) => media::set_media_autoload(autoload),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("media autoload setting read failed: {err}"),
+    }
+}
+
+/// Which media kinds download as soon as they are shown
+#[tauri::command]
+pub async fn get_media_autoload() -> Result<MediaAutoload, AppError> {
+    Ok(media::media_autoload())
+}
+
+/// Turn automatic downloading of images, avatars, link thumbs and video posters on or
+/// off; applies to posts loaded from now on. Deferred media loads with
+/// `load_embed_media`.
+#[tauri::command]
+pub async fn set_media_autoload(
+    db: State<'_, DbState>,
+    autoload: MediaAutoload,
+) -> Result<MediaAutoload, AppError> {
+    save_setting(
+        db.inner().as_ref(),
+        DEVICE_SCOPE,
+        MEDIA_AUTOLOAD_KEY,
+        &autoload,
+    )
+    .await?;
+    media::set_media_autoload(autoload);
+    Ok(autoload)
+}
+
+pub(crate) async fn apply_saved_session_backend(db: &SqlitePool) {
+    match load_setting::<SessionBackend>(db, DEVICE_SCOPE, SESSION_BACKEND_KEY).await {
+        Ok(Some(backend)) => session::set_session_backend(backend),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("session backend setting read failed: {err}"),
+    }
+}
+
+#[derive(Serialize)]
+pub struct SessionBackendInfo {
+    /// The saved choice
+    pub backend: SessionBackend,
+    /// Where the session is actually kept: "keyring" or "encrypted_file"
+    pub active: SessionBackend,
+}
+
+/// Session storage choice and the backend in use (for diagnostics)
+#[tauri::command]
+pub async fn get_session_backend() -> Result<SessionBackendInfo, AppError> {
+    Ok(SessionBackendInfo {
+        backend: session::session_backend(),
+        active: session::active_session_backend(),
+    })
+}
+
+/// Choose where the session is stored; a signed-in session moves to the new backend
+#[tauri::command]
+pub async fn set_session_backend(
+    db: State<'_, DbState>,
+    backend: SessionBackend,
+) -> Result<SessionBackendInfo, AppError> {
+    session::move_session_to(backend)?;
+    save_setting(
+        db.inner().as_ref(),
+        DEVICE_SCOPE,
+        SESSION_BACKEND_KEY,
+        &backend,
+    )
+    .await?;
+    get_session_backend().await
+}
+
+/// Send attempts allowed per queued post (device-wide, default 8)
+pub(crate) async fn retry_max_attempts(db: &SqlitePool) -> i64 {
+    match load_setting::<i64>(db, DEVICE_SCOPE, RETRY_MAX_ATTEMPTS_KEY).await {
+        Ok(attempts) => attempts.unwrap_or(DEFAULT_RETRY_MAX_ATTEMPTS),
+        Err(err) => {
+            tracing::warn!("retry max attempts read failed: {err}");
+            DEFAULT_RETRY_MAX_ATTEMPTS
+        }
+    }
+}
+
+/// Get how many times a queued post is sent before it is marked failed
+#[tauri::command]
+pub async fn get_retry_max_attempts(db: State<'_, DbState>) -> Result<i64, AppError> {
+    Ok(retry_max_attempts(db.inner().as_ref()).await)
+}
+
+/// Set how many times a queued post is sent before it is marked failed (1-20);
+/// returns the clamped value applied
+#[tauri::command]
+pub async fn set_retry_max_attempts(
+    db: State<'_, DbState>,
+    attempts: i64,
+) -> Result<i64, AppError> {
+    let applied = attempts.clamp(1, 20);
+    save_setting(
+        db.inner().as_ref(),
+        DEVICE_SCOPE,
+        RETRY_MAX_ATTEMPTS_KEY,
+        &applied,
+    )
+    .await?;
+    Ok(applied)
+}
+
+/// Whether the signed-in account refuses to post images without alt text
+pub(crate) async fn require_alt_text(db: &SqlitePool) -> bool {
+    let Ok(user_did) = current_user_did() else {
+        return false;
+    };
+
+    match load_setting::<bool>(db, &user_did, REQUIRE_ALT_TEXT_KEY).await {
+        Ok(required) => required.unwrap_or(false),
+        Err(err) => {
+            tracing::warn!("require alt text read failed: {err}");
+            false
+        }
+    }
+}
+
+/// Get whether posting images without alt text is refused
+#[tauri::command]
+pub async fn get_require_alt_text(db: State<'_, DbState>) -> Result<bool, AppError> {
+    Ok(require_alt_text(db.inner().as_ref()).await)
+}
+
+/// Refuse (or allow again) posting images without alt text
+#[tauri::command]
+pub async fn set_require_alt_text(db: State<'_, DbState>, required: bool) -> Result<(), AppError> {
+    let user_did = current_user_did()?;
+    save_setting(
+        db.inner().as_ref(),
+        &user_did,
+        REQUIRE_ALT_TEXT_KEY,
+        &required,
+    )
+    .await
+}
+
+/// Languages tagged on the signed-in account's posts when the composer passes none
+pub(crate) async fn default_post_langs(db: &SqlitePool) -> Vec<String> {
+    let Ok(user_did) = current_user_did() else {
+        return Vec::new();
+    };
+
+    match load_setting::<Vec<String>>(db, &user_did, DEFAULT_POST_LANGS_KEY).await {
+        Ok(langs) => langs.unwrap_or_default(),
+        Err(err) => {
+            tracing::warn!("default post langs read failed: {err}");
+            Vec::new()
+        }
+    }
+}
+
+/// Get the languages new posts are tagged with by default
+#[tauri::command]
+pub async fn get_default_post_langs(db: State<'_, DbState>) -> Result<Vec<String>, AppError> {
+    Ok(default_post_langs(db.inner().as_ref()).await)
+}
+
+/// Set the default post languages (up to 3 BCP-47 tags; empty clears them);
+/// returns the normalized list saved
+#[tauri::command]
+pub async fn set_default_post_langs(
+    db: State<'_, DbState>,
+    langs: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let user_did = current_user_did()?;
+
+    let mut normalized: Vec<String> = Vec::new();
+    for lang in langs {
+        let lang = lang.trim().to_string();
+        if lang.is_empty() || normalized.contains(&lang) {
+            continue;
+        }
+        if lang.parse::<Language>().is_err() {
+            return Err(AppError::ValidationError {
+                code: "INVALID_LANGUAGE",
+                message: format!("Invalid language tag: {lang}"),
+            });
+        }
+        normalized.push(lang);
+    }
+    if normalized.len() > MAX_POST_LANGS {
+        return Err(AppError::ValidationError {
+            code: "INVALID_LANGUAGE",
+            message: format!("Choose at most {MAX_POST_LANGS} languages"),
+        });
+    }
+
+    save_setting(
+        db.inner().as_ref(),
+        &user_did,
+        DEFAULT_POST_LANGS_KEY,
+        &normalized,
+    )
+    .await?;
+    Ok(normalized)
+}
@@ -1,3 +1,4 @@
+use crate::db::{self, DbRecovery};
 use tauri::{AppHandle, Manager, Theme};
 
 #[tauri::command]
@@ -16,3 +17,9 @@ pub async fn get_system_theme(app: AppHandle) -> String {
         "light".to_string()
     }
 }
+
+/// Set when startup found the database unreadable and replaced it with an empty one
+#[tauri::command]
+pub async fn get_db_recovery() -> Option<DbRecovery> {
+    db::db_recovery()
+}
@@ -1,29 +1,108 @@
-use crate::commands::auth::AgentState;
+use crate::commands::auth::{current_agent, AgentState, AppAgent};
+use crate::commands::hidden_posts;
+use crate::commands::history::{self, ViewHistoryEntry};
+use crate::commands::links::resolve_at_uri;
+use crate::commands::lists::{starter_pack_view_to_summary, StarterPackSummary};
+use crate::commands::moderation::{self, ContentLabelPrefs, LabelVisibility};
+use crate::commands::notifications::ActivitySubscription;
+use crate::commands::pagination::fetch_with_cursor_recovery;
+use crate::connectivity;
 use crate::db::DbState;
 use crate::error::AppError;
-use crate::media::{self, EmbedView};
+use crate::media::{self, EmbedView, ProfileImageKind};
 use crate::session::get_stored_session;
 use bsky_sdk::api::app::bsky::actor::defs::ProfileView;
 use bsky_sdk::api::app::bsky::actor::get_profile as get_actor_profile;
-use bsky_sdk::api::app::bsky::feed::defs::{FeedViewPost, FeedViewPostReasonRefs, PostView};
+use bsky_sdk::api::app::bsky::feed::defs::{
+    FeedViewPost, FeedViewPostReasonRefs, PostView, ReplyRefParentRefs, ReplyRefRootRefs,
+    ThreadgateView,
+};
 use bsky_sdk::api::app::bsky::feed::get_actor_likes;
 use bsky_sdk::api::app::bsky::feed::get_author_feed;
-use bsky_sdk::api::app::bsky::graph::{get_followers, get_follows};
-use bsky_sdk::api::types::string::AtIdentifier;
+use bsky_sdk::api::app::bsky::graph::{get_blocks, get_followers, get_follows, get_mutes};
+use bsky_sdk::api::types::string::{AtIdentifier, Did};
 use bsky_sdk::api::types::Union;
 use chrono::Utc;
 use sqlx::SqlitePool;
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, State};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 #[derive(Deserialize, Clone)]
 pub struct TimelineRequest {
     #[serde(default = "default_limit")]
     pub limit: u8,
     pub cursor: Option<String>,
+    /// Incremental refresh: URI of the newest post the caller already has
+    #[serde(default)]
+    pub since_uri: Option<String>,
+    /// Incremental refresh: that post's `indexed_at`, used when the URI itself is gone
+    #[serde(default)]
+    pub since_indexed_at: Option<String>,
+    /// Leave reposts out of the page
+    #[serde(default)]
+    pub hide_reposts: bool,
+    /// Leave replies out of the page
+    #[serde(default)]
+    pub hide_replies: bool,
+}
+
+impl TimelineRequest {
+    fn filter(&self) -> TimelineFilter {
+        TimelineFilter {
+            hide_reposts: self.hide_reposts,
+            hide_replies: self.hide_replies,
+        }
+    }
+}
+
+/// What a timeline request leaves out; `getTimeline` itself can't filter, so items are
+/// dropped as pages are processed. Pages can come back shorter than `limit`.
+#[derive(Clone, Copy)]
+struct TimelineFilter {
+    hide_reposts: bool,
+    hide_replies: bool,
+}
+
+impl TimelineFilter {
+    fn is_empty(self) -> bool {
+        !self.hide_reposts && !self.hide_replies
+    }
+
+    /// A repost is judged as a repost only: reposting a reply puts it in the feed on
+    /// purpose, so hiding replies doesn't hide it
+    fn keeps(self, feed_view: &FeedViewPost) -> bool {
+        if extract_repost_context(feed_view).is_repost() {
+            !self.hide_reposts
+        } else {
+            !(self.hide_replies && is_reply(&feed_view.post))
+        }
+    }
+
+    /// Cache key of a page; filtered pages are kept apart from the unfiltered ones
+    /// cached under the same cursor
+    fn cache_cursor(self, cursor: Option<&str>) -> Option<String> {
+        let view = match (self.hide_reposts, self.hide_replies) {
+            (false, false) => return cursor.map(str::to_string),
+            (true, true) => "originals",
+            (true, false) => "no-reposts",
+            (false, true) => "no-replies",
+        };
+        Some(format!("{view}:{}", cursor.unwrap_or_default()))
+    }
 }
 
+/// Pages walked looking for the known newest post before reporting a gap
+const SINCE_MAX_PAGES: usize = 5;
+/// Pages one `fill_timeline_gap` call walks before leaving the rest of the gap open
+const GAP_FILL_MAX_PAGES: usize = 10;
+/// Extra attempts at fetching a just-published post before the AppView has indexed it
+const OWN_POST_FETCH_RETRIES: u32 = 3;
+const OWN_POST_FETCH_DELAY: std::time::Duration = std::time::Duration::from_millis(750);
+
 fn default_limit() -> u8 {
     50
 }
@@ -41,6 +120,7 @@ pub struct TimelinePost {
     pub reposted_by_handle: Option<String>,
     pub reposted_by_display_name: Option<String>,
     pub text: String,
+    pub facets: Option<serde_json::Value>,
     pub created_at: String,
     pub reply_count: u32,
     pub repost_count: u32,
@@ -50,12 +130,38 @@ pub struct TimelinePost {
     pub viewer_like: Option<String>,
     pub viewer_repost: Option<String>,
     pub embed: Option<serde_json::Value>,
+    /// Opaque per-item context from algorithmic feeds, echoed back in interaction reports
+    #[serde(default)]
+    pub feed_context: Option<String>,
+    /// Why the item is in the feed: "repost", "pin", or another reason type's short name
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Structured form of `reason`, with the reposter for reposts
+    #[serde(default)]
+    pub repost_context: RepostContext,
+    /// Handle of the post this replies to, for "Replying to @x"
+    #[serde(default)]
+    pub reply_parent_author_handle: Option<String>,
+    #[serde(default)]
+    pub reply_root_uri: Option<String>,
+    /// When the item entered the feed (the repost time for reposts); feed order follows it
+    #[serde(default)]
+    pub indexed_at: Option<String>,
+    /// Label the user asked to be warned about; show the post behind a click-through
+    #[serde(default)]
+    pub content_warning: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TimelineResponse {
     pub posts: Vec<TimelinePost>,
     pub cursor: Option<String>,
+    #[serde(default)]
+    pub reset: bool,
+    /// Incremental refresh only: the known post wasn't reached, so older new posts may be
+    /// missing between this slice and the caller's list; `cursor` continues into the gap
+    #[serde(default)]
+    pub gap: bool,
 }
 
 fn extract_post_text(post: &PostView) -> String {
@@ -69,6 +175,21 @@ fn extract_post_text(post: &PostView) -> String {
     String::new()
 }
 
+/// Whether the post record replies to another post
+fn is_reply(post: &PostView) -> bool {
+    serde_json::to_value(&post.record)
+        .ok()
+        .is_some_and(|json| json.get("reply").is_some())
+}
+
+/// Extract the raw rich-text facets (byte ranges + features) from the post record.
+pub(crate) fn extract_post_facets(post: &PostView) -> Option<serde_json::Value> {
+    serde_json::to_value(&post.record)
+        .ok()
+        .and_then(|json| json.get("facets").cloned())
+        .filter(|facets| facets.is_array())
+}
+
 fn extract_created_at(post: &PostView) -> String {
     if let Ok(json) = serde_json::to_value(&post.record) {
         if let Some(created) = json.get("createdAt").and_then(|v| v.as_str()) {
@@ -82,19 +203,99 @@ fn embed_to_json(embed: Option<EmbedView>) -> Option<serde_json::Value> {
     embed.and_then(|value| serde_json::to_value(value).ok())
 }
 
-pub(crate) fn extract_repost_context(
-    feed_view: &FeedViewPost,
-) -> (bool, Option<String>, Option<String>) {
+/// Why a feed item is shown other than being a plain post by its author
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RepostContext {
+    #[default]
+    None,
+    Repost {
+        by_did: String,
+        by_handle: String,
+        by_display_name: Option<String>,
+    },
+    /// Pinned to the top of the author's profile
+    Pin,
+    /// A reason type this app doesn't know yet, by its short name
+    Other { reason: String },
+}
+
+impl RepostContext {
+    pub(crate) fn is_repost(&self) -> bool {
+        matches!(self, Self::Repost { .. })
+    }
+
+    /// (handle, display name) of the reposter
+    pub(crate) fn reposted_by(&self) -> (Option<String>, Option<String>) {
+        match self {
+            Self::Repost {
+                by_handle,
+                by_display_name,
+                ..
+            } => (Some(by_handle.clone()), by_display_name.clone()),
+            _ => (None, None),
+        }
+    }
+}
+
+pub(crate) fn extract_repost_context(feed_view: &FeedViewPost) -> RepostContext {
     match feed_view.reason.as_ref() {
-        Some(Union::Refs(FeedViewPostReasonRefs::ReasonRepost(reason))) => (
-            true,
-            Some(reason.by.handle.to_string()),
-            reason.by.display_name.clone(),
-        ),
-        _ => (false, None, None),
+        None => RepostContext::None,
+        Some(Union::Refs(FeedViewPostReasonRefs::ReasonRepost(reason))) => RepostContext::Repost {
+            by_did: reason.by.did.to_string(),
+            by_handle: reason.by.handle.to_string(),
+            by_display_name: reason.by.display_name.clone(),
+        },
+        Some(Union::Refs(FeedViewPostReasonRefs::ReasonPin(_))) => RepostContext::Pin,
+        Some(Union::Unknown(_)) => extract_feed_reason(feed_view)
+            .map_or(RepostContext::None, |reason| RepostContext::Other {
+                reason,
+            }),
+    }
+}
+
+/// Short name of a feed item's reason, for "why am I seeing this" labels
+pub(crate) fn extract_feed_reason(feed_view: &FeedViewPost) -> Option<String> {
+    match feed_view.reason.as_ref()? {
+        Union::Refs(FeedViewPostReasonRefs::ReasonRepost(_)) => Some("repost".into()),
+        Union::Refs(FeedViewPostReasonRefs::ReasonPin(_)) => Some("pin".into()),
+        Union::Unknown(unknown) => {
+            let short = unknown.r#type.rsplit('#').next().unwrap_or_default();
+            let short = short.strip_prefix("reason").unwrap_or(short);
+            (!short.is_empty()).then(|| short.to_ascii_lowercase())
+        }
+    }
+}
+
+/// Sort time of a feed item: when it was reposted for reposts, otherwise when indexed
+pub(crate) fn feed_item_indexed_at(feed_view: &FeedViewPost) -> String {
+    match feed_view.reason.as_ref() {
+        Some(Union::Refs(FeedViewPostReasonRefs::ReasonRepost(reason))) => {
+            reason.indexed_at.as_ref().to_string()
+        }
+        _ => feed_view.post.indexed_at.as_ref().to_string(),
     }
 }
 
+/// Reply context from the feed item: (parent author handle, root URI).
+/// Blocked or deleted parents/roots leave the corresponding field empty.
+pub(crate) fn extract_reply_context(feed_view: &FeedViewPost) -> (Option<String>, Option<String>) {
+    let Some(reply) = feed_view.reply.as_ref() else {
+        return (None, None);
+    };
+
+    let parent_handle = match &reply.parent {
+        Union::Refs(ReplyRefParentRefs::PostView(parent)) => Some(parent.author.handle.to_string()),
+        _ => None,
+    };
+    let root_uri = match &reply.root {
+        Union::Refs(ReplyRefRootRefs::PostView(root)) => Some(root.uri.to_string()),
+        _ => None,
+    };
+
+    (parent_handle, root_uri)
+}
+
 fn current_user_did() -> Result<String, AppError> {
     Ok(get_stored_session()?.did)
 }
@@ -121,12 +322,15 @@ async fn load_timeline_cache(
     .await
     .map_err(|e| AppError::InternalError(format!("timeline cache read failed: {e}")))?;
 
-    payload
-        .map(|raw| {
-            serde_json::from_str::<TimelineResponse>(&raw)
-                .map_err(|e| AppError::InternalError(format!("timeline cache decode failed: {e}")))
-        })
-        .transpose()
+    let Some(raw) = payload else {
+        return Ok(None);
+    };
+    let mut page = serde_json::from_str::<TimelineResponse>(&raw)
+        .map_err(|e| AppError::InternalError(format!("timeline cache decode failed: {e}")))?;
+    // Pages cached before a post was hidden still hold it
+    let hidden = hidden_posts::hidden_posts_or_none(db, user_did).await;
+    page.posts.retain(|post| !hidden.contains(&post.uri));
+    Ok(Some(page))
 }
 
 async fn save_timeline_cache(
@@ -213,40 +417,150 @@ async fn save_profile_cache(
     Ok(())
 }
 
+/// A relationship action to reflect in cached profiles without waiting for a refresh
+pub(crate) enum ProfileChange {
+    Followed { did: String, follow_uri: String },
+    Unfollowed { follow_uri: String },
+    Muted { did: String, muted: bool },
+    Blocked { did: String, block_uri: String },
+    Unblocked { block_uri: String },
+}
+
+impl ProfileChange {
+    /// JSON path and value identifying the affected `profile_cache` rows; unfollow and
+    /// unblock only know the record URI, which the cached viewer state carries
+    fn cache_match(&self) -> (&'static str, &str) {
+        match self {
+            Self::Followed { did, .. } | Self::Muted { did, .. } | Self::Blocked { did, .. } => {
+                ("$.did", did)
+            }
+            Self::Unfollowed { follow_uri } => ("$.viewer_following", follow_uri),
+            Self::Unblocked { block_uri } => ("$.viewer_blocking", block_uri),
+        }
+    }
+
+    fn apply(&self, profile: &mut ProfileResponse) {
+        match self {
+            Self::Followed { follow_uri, .. } => {
+                if profile.viewer_following.is_none() {
+                    profile.followers_count += 1;
+                }
+                profile.viewer_following = Some(follow_uri.clone());
+                profile.is_following = true;
+            }
+            Self::Unfollowed { .. } => {
+                if profile.viewer_following.take().is_some() {
+                    profile.followers_count = profile.followers_count.saturating_sub(1);
+                }
+                profile.is_following = false;
+            }
+            Self::Muted { muted, .. } => profile.viewer_muted = *muted,
+            Self::Blocked { block_uri, .. } => profile.viewer_blocking = Some(block_uri.clone()),
+            Self::Unblocked { .. } => profile.viewer_blocking = None,
+        }
+    }
+}
+
+/// Patch the cached profiles an action affected and emit `profile_updated` for each, so
+/// the relationship and counts are right before the AppView has indexed the change.
+/// Best-effort: the action already succeeded, so failures are only logged.
+pub(crate) async fn patch_cached_profiles(app: &AppHandle, db: &SqlitePool, change: ProfileChange) {
+    let user_did = match current_user_did() {
+        Ok(did) => did,
+        Err(_) => return,
+    };
+    let (path, value) = change.cache_match();
+
+    let rows = match sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT handle, payload_json
+        FROM profile_cache
+        WHERE user_did = ?1 AND json_extract(payload_json, ?2) = ?3
+        "#,
+    )
+    .bind(&user_did)
+    .bind(path)
+    .bind(value)
+    .fetch_all(db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!(target: "cache", cache = "profile", "patch lookup failed: {err}");
+            return;
+        }
+    };
+
+    for (handle, raw) in rows {
+        let mut profile = match serde_json::from_str::<ProfileResponse>(&raw) {
+            Ok(profile) => profile,
+            Err(err) => {
+                tracing::warn!(target: "cache", cache = "profile", "patch decode failed: {err}");
+                continue;
+            }
+        };
+        change.apply(&mut profile);
+        if let Err(err) = save_profile_cache(db, &user_did, &handle, &profile).await {
+            tracing::warn!(target: "cache", cache = "profile", "patch save failed: {err}");
+        }
+        if let Err(err) = app.emit("profile_updated", ProfileUpdatedEvent { handle, profile }) {
+            tracing::warn!(target: "cache", cache = "profile", "emit patch failed: {err}");
+        }
+    }
+}
+
 async fn fetch_timeline_remote(
     app: &AppHandle,
     agent_state: &AgentState,
     request: &TimelineRequest,
 ) -> Result<TimelineResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    fetch_timeline_page(app, agent_state, request, None).await
+}
+
+/// Fetch and process one timeline page. With a `stream_id`, each post is also emitted
+/// as `timeline_post` as soon as it is processed.
+async fn fetch_timeline_page(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    request: &TimelineRequest,
+    stream_id: Option<&str>,
+) -> Result<TimelineResponse, AppError> {
+    let agent = current_agent(agent_state).await?;
+    let label_prefs = moderation::content_label_prefs(&agent).await;
 
     // Create limit - clamp to valid range (1-100)
     let limit_val = request.limit.max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
 
-    let timeline = agent
-        .api
-        .app
-        .bsky
-        .feed
-        .get_timeline(
+    let (timeline, reset) = fetch_with_cursor_recovery(request.cursor.clone(), |cursor| {
+        agent.api.app.bsky.feed.get_timeline(
             bsky_sdk::api::app::bsky::feed::get_timeline::ParametersData {
                 algorithm: None,
-                cursor: request.cursor.clone(),
-                limit,
+                cursor,
+                limit: limit.clone(),
             }
             .into(),
         )
-        .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+    })
+    .await
+    .map_err(connectivity::xrpc_error)?;
 
+    let hidden = hidden_posts::current_hidden_posts(app.state::<DbState>().inner()).await;
+    let filter = request.filter();
     let mut posts: Vec<TimelinePost> = Vec::new();
     for feed_view in &timeline.data.feed {
         let post = &feed_view.post;
+        if hidden.contains(post.uri.as_str()) || !filter.keeps(feed_view) {
+            continue;
+        }
+        let (label_visibility, content_warning) = label_prefs.moderate_post(post);
+        if label_visibility == LabelVisibility::Hide {
+            continue;
+        }
         let embed = media::process_post_embed(post, app).await?;
-        let (is_repost, reposted_by_handle, reposted_by_display_name) =
-            extract_repost_context(feed_view);
+        let repost_context = extract_repost_context(feed_view);
+        let (reposted_by_handle, reposted_by_display_name) = repost_context.reposted_by();
+        let (reply_parent_author_handle, reply_root_uri) = extract_reply_context(feed_view);
 
         posts.push(TimelinePost {
             uri: post.uri.to_string(),
@@ -254,11 +568,16 @@ async fn fetch_timeline_remote(
             author_did: post.author.did.to_string(),
             author_handle: post.author.handle.to_string(),
             author_display_name: post.author.display_name.clone(),
-            author_avatar: post.author.avatar.clone(),
-            is_repost,
+            author_avatar: media::cached_profile_image(
+                post.author.avatar.as_deref(),
+                ProfileImageKind::Avatar,
+                app,
+            ),
+            is_repost: repost_context.is_repost(),
             reposted_by_handle,
             reposted_by_display_name,
             text: extract_post_text(post),
+            facets: extract_post_facets(post),
             created_at: extract_created_at(post),
             reply_count: post.reply_count.unwrap_or(0) as u32,
             repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -280,21 +599,181 @@ async fn fetch_timeline_remote(
                 .and_then(|v| v.repost.as_ref())
                 .map(|u| u.to_string()),
             embed: embed_to_json(embed),
+            feed_context: feed_view.feed_context.clone(),
+            reason: extract_feed_reason(feed_view),
+            repost_context,
+            reply_parent_author_handle,
+            reply_root_uri,
+            indexed_at: Some(feed_item_indexed_at(feed_view)),
+            content_warning,
         });
+        if let (Some(stream_id), Some(post)) = (stream_id, posts.last()) {
+            emit_streamed_post(app, stream_id, posts.len() - 1, post);
+        }
     }
 
     Ok(TimelineResponse {
         posts,
         cursor: timeline.data.cursor,
+        reset,
+        gap: false,
+    })
+}
+
+/// One post of a streamed timeline page
+#[derive(Serialize)]
+struct TimelinePostEvent<'a> {
+    stream_id: &'a str,
+    /// Position in the page, so posts can be placed even if events interleave
+    index: usize,
+    post: &'a TimelinePost,
+}
+
+fn emit_streamed_post(app: &AppHandle, stream_id: &str, index: usize, post: &TimelinePost) {
+    let event = TimelinePostEvent {
+        stream_id,
+        index,
+        post,
+    };
+    if let Err(err) = app.emit("timeline_post", event) {
+        tracing::warn!("emit streamed post failed: {err}");
+    }
+}
+
+/// Whether a timeline item is the caller's newest known post or older than it
+fn reached_known_post(
+    post: &TimelinePost,
+    since_uri: Option<&str>,
+    since: Option<chrono::DateTime<chrono::FixedOffset>>,
+) -> bool {
+    if since_uri.is_some_and(|uri| uri == post.uri) {
+        return true;
+    }
+    let indexed_at = post
+        .indexed_at
+        .as_deref()
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok());
+    matches!((indexed_at, since), (Some(at), Some(since)) if at < since)
+}
+
+/// Fetch only posts newer than the caller's newest known post, walking pages until it
+/// is reached. Stops after `SINCE_MAX_PAGES` with `gap` set.
+async fn fetch_timeline_since(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    request: &TimelineRequest,
+) -> Result<TimelineResponse, AppError> {
+    let walk = TimelineRequest {
+        cursor: None,
+        ..request.clone()
+    };
+    walk_timeline_until(app, agent_state, walk, SINCE_MAX_PAGES).await
+}
+
+/// Page the timeline from `request.cursor` until the known post (`since_uri`, or
+/// anything older than `since_indexed_at`) is reached, collecting the posts before it.
+/// After `max_pages` the result has `gap` set and a cursor to continue from.
+async fn walk_timeline_until(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    request: TimelineRequest,
+    max_pages: usize,
+) -> Result<TimelineResponse, AppError> {
+    let since = request
+        .since_indexed_at
+        .as_deref()
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok());
+    let known_uri = request.since_uri.filter(|uri| !uri.is_empty());
+    let since_uri = known_uri.as_deref();
+
+    // Each page is a plain fetch; the walk itself checks for the known post
+    let mut page_request = TimelineRequest {
+        since_uri: None,
+        since_indexed_at: None,
+        ..request
+    };
+    let mut posts = Vec::new();
+
+    for _ in 0..max_pages {
+        let page = fetch_timeline_remote(app, agent_state, &page_request).await?;
+
+        let mut reached = false;
+        for post in page.posts {
+            if reached_known_post(&post, since_uri, since) {
+                reached = true;
+                break;
+            }
+            // Reposts can surface the same post twice across pages
+            if !posts.iter().any(|p: &TimelinePost| p.uri == post.uri) {
+                posts.push(post);
+            }
+        }
+
+        if reached || page.cursor.is_none() {
+            return Ok(TimelineResponse {
+                posts,
+                cursor: None,
+                reset: false,
+                gap: false,
+            });
+        }
+        page_request.cursor = page.cursor;
+    }
+
+    Ok(TimelineResponse {
+        posts,
+        cursor: page_request.cursor,
+        reset: false,
+        gap: true,
     })
 }
 
+/// Handle or DID typed by the user, checked before anything goes over the network
+fn parse_profile_actor(handle: &str) -> Result<AtIdentifier, AppError> {
+    handle.parse().map_err(|_| AppError::ValidationError {
+        code: "INVALID_HANDLE",
+        message: format!("Not a valid handle or DID: {handle}"),
+    })
+}
+
+/// Map getProfile failures so the UI can tell a missing account (`NotFound`) from a
+/// deactivated or taken-down one and from the network being down (`NetworkError`). The
+/// AppView reports an unknown profile and a handle that doesn't resolve as an untyped
+/// 400 `InvalidRequest`; the actor was already validated, so that can only mean missing.
+fn profile_fetch_error<E>(handle: &str, err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    let atrium_xrpc::Error::XrpcResponse(ref resp) = err else {
+        return connectivity::xrpc_error(err);
+    };
+    let name = match &resp.error {
+        Some(atrium_xrpc::error::XrpcErrorKind::Undefined(body)) => body.error.as_deref(),
+        _ => None,
+    };
+
+    match (resp.status.as_u16(), name) {
+        (_, Some("AccountDeactivated")) => AppError::ValidationError {
+            code: "ACCOUNT_DEACTIVATED",
+            message: format!("{handle} has deactivated their account"),
+        },
+        (_, Some("AccountTakedown")) => AppError::ValidationError {
+            code: "ACCOUNT_TAKEN_DOWN",
+            message: format!("{handle} has been taken down"),
+        },
+        (404, _) | (400, Some("InvalidRequest" | "NotFound")) => {
+            AppError::NotFound(format!("No account found for {handle}"))
+        }
+        _ => connectivity::xrpc_error(err),
+    }
+}
+
 async fn fetch_profile_remote(
+    app: &AppHandle,
     agent_state: &AgentState,
     handle: &str,
 ) -> Result<ProfileResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(agent_state).await?;
 
     let profile = agent
         .api
@@ -303,24 +782,24 @@ async fn fetch_profile_remote(
         .actor
         .get_profile(
             bsky_sdk::api::app::bsky::actor::get_profile::ParametersData {
-                actor: handle
-                    .parse()
-                    .map_err(|_| AppError::ApiError("Invalid handle".into()))?,
+                actor: parse_profile_actor(handle)?,
             }
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(|e| profile_fetch_error(handle, e))?;
 
     let data = profile.data;
+    // Absent for most accounts and from older AppViews
+    let associated = data.associated.as_ref();
 
     Ok(ProfileResponse {
         did: data.did.to_string(),
         handle: data.handle.to_string(),
         display_name: data.display_name,
         description: data.description,
-        avatar: data.avatar,
-        banner: data.banner,
+        avatar: media::cached_profile_image(data.avatar.as_deref(), ProfileImageKind::Avatar, app),
+        banner: media::cached_profile_image(data.banner.as_deref(), ProfileImageKind::Banner, app),
         followers_count: data.followers_count.unwrap_or(0) as u32,
         follows_count: data.follows_count.unwrap_or(0) as u32,
         posts_count: data.posts_count.unwrap_or(0) as u32,
@@ -349,10 +828,105 @@ async fn fetch_profile_remote(
             .as_ref()
             .and_then(|v| v.blocking.as_ref())
             .map(|u| u.to_string()),
+        // Read from the raw viewer state so it works whether or not the SDK models it
+        activity_subscription: data
+            .viewer
+            .as_ref()
+            .and_then(|v| serde_json::to_value(v).ok())
+            .and_then(|viewer| ActivitySubscription::from_viewer(&viewer)),
+        joined_via_starter_pack: data
+            .joined_via_starter_pack
+            .as_ref()
+            .map(starter_pack_view_to_summary),
+        feedgens_count: associated.and_then(|a| a.feedgens).unwrap_or(0).max(0) as u32,
+        lists_count: associated.and_then(|a| a.lists).unwrap_or(0).max(0) as u32,
+        starter_packs_count: associated.and_then(|a| a.starter_packs).unwrap_or(0).max(0) as u32,
+        is_labeler: associated.and_then(|a| a.labeler).unwrap_or(false),
     })
 }
 
-/// Get home timeline
+/// Sent instead of the `*_updated` event when a background refresh finds items above the
+/// top of the list the user is reading, so the UI can offer them without moving the scroll
+#[derive(Serialize)]
+pub(crate) struct NewItemsCount {
+    pub count: usize,
+    /// The known top wasn't in the refreshed page; there may be more than `count`
+    pub more: bool,
+}
+
+/// Save a refreshed first page and tell the UI. With posts above `shown_top` (the top of
+/// what the user has) it gets `timeline_new_count` and applies the page on request
+/// (`get_timeline` serves it from the cache); otherwise `timeline_updated` as before.
+async fn publish_timeline_refresh(
+    app: &AppHandle,
+    db: &SqlitePool,
+    user_did: &str,
+    shown_top: Option<&TimelinePost>,
+    remote: &TimelineResponse,
+) {
+    if let Err(err) = save_timeline_cache(db, user_did, None, remote).await {
+        tracing::warn!(target: "cache", cache = "timeline", "refresh save failed: {err}");
+    }
+
+    let new_posts = shown_top.map(|top| {
+        let since = top
+            .indexed_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok());
+        let known = remote
+            .posts
+            .iter()
+            .position(|post| reached_known_post(post, Some(&top.uri), since));
+        NewItemsCount {
+            count: known.unwrap_or(remote.posts.len()),
+            more: known.is_none(),
+        }
+    });
+    let emitted = match new_posts {
+        Some(new_posts) if new_posts.count > 0 => app.emit("timeline_new_count", new_posts),
+        _ => app.emit("timeline_updated", remote),
+    };
+    if let Err(err) = emitted {
+        tracing::warn!(target: "cache", cache = "timeline", "emit refresh failed: {err}");
+    }
+}
+
+/// Warm the first timeline page cache in the background (used on session resume)
+pub(crate) async fn prefetch_timeline(app: &AppHandle, agent_state: &AgentState, db: &SqlitePool) {
+    let user_did = match current_user_did() {
+        Ok(did) => did,
+        Err(err) => {
+            tracing::warn!(target: "cache", cache = "timeline", "prefetch skipped: {err}");
+            return;
+        }
+    };
+
+    let request = TimelineRequest {
+        limit: default_limit(),
+        cursor: None,
+        since_uri: None,
+        since_indexed_at: None,
+        hide_reposts: false,
+        hide_replies: false,
+    };
+
+    match fetch_timeline_remote(app, agent_state, &request).await {
+        Ok(remote) => {
+            // The cached page is what an open timeline is showing
+            let shown = load_timeline_cache(db, &user_did, None)
+                .await
+                .ok()
+                .flatten();
+            let shown_top = shown.as_ref().and_then(|page| page.posts.first());
+            publish_timeline_refresh(app, db, &user_did, shown_top, &remote).await;
+        }
+        Err(err) => {
+            tracing::warn!(target: "cache", cache = "timeline", "prefetch fetch failed: {err}");
+        }
+    }
+}
+
+/// Get home timeline, optionally without reposts and/or replies
 #[tauri::command]
 pub async fn get_timeline(
     app: AppHandle,
@@ -362,38 +936,42 @@ pub async fn get_timeline(
 ) -> Result<TimelineResponse, AppError> {
     let user_did = current_user_did()?;
     let db_pool = db.inner().clone();
-    let cursor_for_cache = request.cursor.clone();
+    let cursor_for_cache = request.filter().cache_cursor(request.cursor.as_deref());
+
+    if request.since_uri.is_some() || request.since_indexed_at.is_some() {
+        return fetch_timeline_since(&app, agent_state.inner(), &request)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err));
+    }
 
-    if request.cursor.is_none() {
+    // Only the unfiltered first page is served cached-first; the refresh events don't say
+    // which view they are for
+    if request.cursor.is_none() && request.filter().is_empty() {
         if let Some(cached) = load_timeline_cache(db_pool.as_ref(), &user_did, None).await? {
             let refresh_app = app.clone();
             let refresh_agent_state = agent_state.inner().clone();
             let refresh_db = db_pool.clone();
             let refresh_request = request.clone();
             let refresh_user_did = user_did.clone();
+            let shown_top = cached.posts.first().cloned();
 
             tauri::async_runtime::spawn(async move {
                 match fetch_timeline_remote(&refresh_app, &refresh_agent_state, &refresh_request)
                     .await
                 {
                     Ok(remote) => {
-                        if let Err(err) = save_timeline_cache(
+                        publish_timeline_refresh(
+                            &refresh_app,
                             refresh_db.as_ref(),
                             &refresh_user_did,
-                            None,
+                            shown_top.as_ref(),
                             &remote,
                         )
-                        .await
-                        {
-                            eprintln!("[timeline-cache] refresh save failed: {err}");
-                        }
-
-                        if let Err(err) = refresh_app.emit("timeline_updated", &remote) {
-                            eprintln!("[timeline-cache] emit refresh failed: {err}");
-                        }
+                        .await;
                     }
                     Err(err) => {
-                        eprintln!("[timeline-cache] refresh fetch failed: {err}");
+                        tracing::warn!(target: "cache", cache = "timeline", "refresh fetch failed: {err}");
+                        connectivity::watch_error(&refresh_app, &err);
                     }
                 }
             });
@@ -414,6 +992,7 @@ pub async fn get_timeline(
             Ok(remote)
         }
         Err(remote_err) => {
+            connectivity::watch_error(&app, &remote_err);
             if let Some(cached) =
                 load_timeline_cache(db_pool.as_ref(), &user_did, cursor_for_cache.as_deref())
                     .await?
@@ -426,6 +1005,109 @@ pub async fn get_timeline(
     }
 }
 
+/// Streaming variant of `get_timeline` for large pages: each post is emitted as
+/// `timeline_post` (tagged with `stream_id`) as soon as it is processed, so the first
+/// posts render before the page is done. The returned response has no posts, only the
+/// cursor and flags. The page is cached like `get_timeline`'s; offline, the cached page
+/// is streamed instead.
+#[tauri::command]
+pub async fn stream_timeline(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    request: TimelineRequest,
+    stream_id: String,
+) -> Result<TimelineResponse, AppError> {
+    let user_did = current_user_did()?;
+    let db = db.inner().as_ref();
+    let cache_cursor = request.filter().cache_cursor(request.cursor.as_deref());
+    let cursor = cache_cursor.as_deref();
+
+    let page =
+        match fetch_timeline_page(&app, agent_state.inner(), &request, Some(&stream_id)).await {
+            Ok(page) => {
+                save_timeline_cache(db, &user_did, cursor, &page).await?;
+                page
+            }
+            Err(remote_err) => {
+                connectivity::watch_error(&app, &remote_err);
+                let Some(cached) = load_timeline_cache(db, &user_did, cursor).await? else {
+                    return Err(remote_err);
+                };
+                for (index, post) in cached.posts.iter().enumerate() {
+                    emit_streamed_post(&app, &stream_id, index, post);
+                }
+                cached
+            }
+        };
+
+    Ok(TimelineResponse {
+        posts: Vec::new(),
+        ..page
+    })
+}
+
+#[derive(Deserialize)]
+pub struct TimelineGapRequest {
+    /// `cursor` of the response that reported the gap
+    pub older_cursor: String,
+    /// Newest post the caller has below the gap
+    pub newest_known_uri: String,
+    /// That post's `indexed_at`, used when the URI itself is gone
+    #[serde(default)]
+    pub newest_known_indexed_at: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: u8,
+    /// The filter of the timeline the gap is in
+    #[serde(default)]
+    pub hide_reposts: bool,
+    #[serde(default)]
+    pub hide_replies: bool,
+}
+
+/// Fill a gap reported by an incremental refresh: page from `older_cursor` until the
+/// post below the gap is reached, returning the posts in between. `gap` stays set (with
+/// a cursor to call again with) when the page limit ran out first. Filled segments are
+/// cached under their starting cursor and served from there when offline.
+#[tauri::command]
+pub async fn fill_timeline_gap(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    request: TimelineGapRequest,
+) -> Result<TimelineResponse, AppError> {
+    let user_did = current_user_did()?;
+    let db = db.inner().as_ref();
+    let walk = TimelineRequest {
+        limit: request.limit,
+        cursor: Some(request.older_cursor.clone()),
+        since_uri: Some(request.newest_known_uri),
+        since_indexed_at: request.newest_known_indexed_at,
+        hide_reposts: request.hide_reposts,
+        hide_replies: request.hide_replies,
+    };
+    // Kept apart from regular pages, which are cached under the same cursors
+    let cache_key = walk
+        .filter()
+        .cache_cursor(Some(&format!("gap:{}", request.older_cursor)));
+
+    let filled = walk_timeline_until(&app, agent_state.inner(), walk, GAP_FILL_MAX_PAGES).await;
+
+    match filled {
+        Ok(segment) => {
+            save_timeline_cache(db, &user_did, cache_key.as_deref(), &segment).await?;
+            Ok(segment)
+        }
+        Err(remote_err) => {
+            connectivity::watch_error(&app, &remote_err);
+            if let Some(cached) = load_timeline_cache(db, &user_did, cache_key.as_deref()).await? {
+                return Ok(cached);
+            }
+            Err(remote_err)
+        }
+    }
+}
+
 #[derive(Deserialize, Clone)]
 pub struct ProfileRequest {
     pub handle: String,
@@ -447,6 +1129,23 @@ pub struct ProfileResponse {
     pub viewer_following: Option<String>,
     pub viewer_muted: bool,
     pub viewer_blocking: Option<String>,
+    /// Notifications for this account's new posts; `None` when not subscribed
+    #[serde(default)]
+    pub activity_subscription: Option<ActivitySubscription>,
+    /// Starter pack the account signed up through, when the AppView knows it
+    #[serde(default)]
+    pub joined_via_starter_pack: Option<StarterPackSummary>,
+    /// Feed generators the account publishes; with `is_labeler`, tells service accounts
+    /// apart from regular users. Zero when the AppView doesn't report it.
+    #[serde(default)]
+    pub feedgens_count: u32,
+    #[serde(default)]
+    pub lists_count: u32,
+    #[serde(default)]
+    pub starter_packs_count: u32,
+    /// The account runs a labeler service
+    #[serde(default)]
+    pub is_labeler: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -472,6 +1171,11 @@ pub struct FollowListItem {
     pub description: Option<String>,
     pub is_following: bool,
     pub is_followed_by: bool,
+    #[serde(default)]
+    pub viewer_muted: bool,
+    /// Block record URI, passed to `unblock_actor`
+    #[serde(default)]
+    pub viewer_blocking: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -497,21 +1201,29 @@ fn profile_view_to_follow_list_item(profile: &ProfileView) -> FollowListItem {
             .as_ref()
             .and_then(|v| v.followed_by.as_ref())
             .is_some(),
-    }
-}
-
+        viewer_muted: profile
+            .viewer
+            .as_ref()
+            .map(|v| v.muted.unwrap_or(false))
+            .unwrap_or(false),
+        viewer_blocking: profile
+            .viewer
+            .as_ref()
+            .and_then(|v| v.blocking.as_ref())
+            .map(|u| u.to_string()),
+    }
+}
+
 async fn fetch_actor_likes_via_appview(
     actor: &str,
     limit: u8,
     cursor: Option<&str>,
 ) -> Result<get_actor_likes::Output, AppError> {
-    const APPVIEW_ENDPOINTS: [&str; 2] = ["https://api.bsky.app", "https://public.api.bsky.app"];
-
     let access_jwt = get_stored_session().ok().map(|s| s.access_jwt);
-    let client = reqwest::Client::new();
+    let client = crate::http::client();
     let mut last_error = String::from("no appview attempts made");
 
-    for endpoint in APPVIEW_ENDPOINTS {
+    for endpoint in crate::endpoints::appview_urls() {
         let url = format!("{endpoint}/xrpc/app.bsky.feed.getActorLikes");
         let mut request = client
             .get(&url)
@@ -522,7 +1234,11 @@ async fn fetch_actor_likes_via_appview(
             request = request.query(&[("cursor", cursor)]);
         }
 
-        if let Some(token) = access_jwt.as_deref() {
+        // A custom AppView could be anyone; never hand it the session token
+        if let Some(token) = access_jwt
+            .as_deref()
+            .filter(|_| crate::endpoints::accepts_session_token(&endpoint))
+        {
             request = request.bearer_auth(token);
         }
 
@@ -554,6 +1270,51 @@ async fn fetch_actor_likes_via_appview(
     )))
 }
 
+/// Fetch the signed-in user's profile, cache it under their DID and emit
+/// `my_profile_updated`. Used for background refreshes and after the user changes it.
+pub(crate) async fn refresh_my_profile(
+    app: &AppHandle,
+    agent_state: &AgentState,
+    db: &SqlitePool,
+) -> Result<ProfileResponse, AppError> {
+    let user_did = current_user_did()?;
+    let profile = fetch_profile_remote(app, agent_state, &user_did).await?;
+    save_profile_cache(db, &user_did, &user_did, &profile).await?;
+    if let Err(err) = app.emit("my_profile_updated", &profile) {
+        tracing::warn!(target: "cache", cache = "profile", "emit own profile failed: {err}");
+    }
+    Ok(profile)
+}
+
+/// The signed-in user's own profile for the account menu. Served from the cache when
+/// present and refreshed in the background (`my_profile_updated` carries the result).
+#[tauri::command]
+pub async fn get_my_profile(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+) -> Result<ProfileResponse, AppError> {
+    let user_did = current_user_did()?;
+    let db_pool = db.inner().clone();
+
+    if let Some(mut cached) = load_profile_cache(db_pool.as_ref(), &user_did, &user_did).await? {
+        localize_profile_images(&mut cached, &app);
+        let refresh_agent_state = agent_state.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = refresh_my_profile(&app, &refresh_agent_state, db_pool.as_ref()).await
+            {
+                tracing::warn!(target: "cache", cache = "profile", "own profile refresh failed: {err}");
+                connectivity::watch_error(&app, &err);
+            }
+        });
+        return Ok(cached);
+    }
+
+    refresh_my_profile(&app, agent_state.inner(), db_pool.as_ref())
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
 /// Get user profile
 #[tauri::command]
 pub async fn get_profile(
@@ -566,7 +1327,8 @@ pub async fn get_profile(
     let db_pool = db.inner().clone();
     let handle = request.handle.trim().to_lowercase();
 
-    if let Some(cached) = load_profile_cache(db_pool.as_ref(), &user_did, &handle).await? {
+    if let Some(mut cached) = load_profile_cache(db_pool.as_ref(), &user_did, &handle).await? {
+        localize_profile_images(&mut cached, &app);
         let refresh_app = app.clone();
         let refresh_agent_state = agent_state.inner().clone();
         let refresh_db = db_pool.clone();
@@ -574,7 +1336,7 @@ pub async fn get_profile(
         let refresh_handle = handle.clone();
 
         tauri::async_runtime::spawn(async move {
-            match fetch_profile_remote(&refresh_agent_state, &refresh_handle).await {
+            match fetch_profile_remote(&refresh_app, &refresh_agent_state, &refresh_handle).await {
                 Ok(profile) => {
                     if let Err(err) = save_profile_cache(
                         refresh_db.as_ref(),
@@ -584,7 +1346,7 @@ pub async fn get_profile(
                     )
                     .await
                     {
-                        eprintln!("[profile-cache] refresh save failed: {err}");
+                        tracing::warn!(target: "cache", cache = "profile", "refresh save failed: {err}");
                     }
 
                     let payload = ProfileUpdatedEvent {
@@ -593,25 +1355,39 @@ pub async fn get_profile(
                     };
 
                     if let Err(err) = refresh_app.emit("profile_updated", payload) {
-                        eprintln!("[profile-cache] emit refresh failed: {err}");
+                        tracing::warn!(target: "cache", cache = "profile", "emit refresh failed: {err}");
                     }
                 }
                 Err(err) => {
-                    eprintln!("[profile-cache] refresh fetch failed: {err}");
+                    tracing::warn!(target: "cache", cache = "profile", "refresh fetch failed: {err}");
+                    connectivity::watch_error(&refresh_app, &err);
                 }
             }
         });
 
+        record_profile_view(db_pool.as_ref(), &cached).await;
         return Ok(cached);
     }
 
-    match fetch_profile_remote(agent_state.inner(), &handle).await {
+    match fetch_profile_remote(&app, agent_state.inner(), &handle).await {
         Ok(profile) => {
             save_profile_cache(db_pool.as_ref(), &user_did, &handle, &profile).await?;
+            record_profile_view(db_pool.as_ref(), &profile).await;
             Ok(profile)
         }
+        // A missing or unavailable account shows as such rather than as its last cached
+        // profile
+        Err(
+            remote_err @ (AppError::NotFound(_)
+            | AppError::ValidationError {
+                code: "ACCOUNT_DEACTIVATED" | "ACCOUNT_TAKEN_DOWN",
+                ..
+            }),
+        ) => Err(remote_err),
         Err(remote_err) => {
+            connectivity::watch_error(&app, &remote_err);
             if let Some(cached) = load_profile_cache(db_pool.as_ref(), &user_did, &handle).await? {
+                record_profile_view(db_pool.as_ref(), &cached).await;
                 return Ok(cached);
             }
             Err(remote_err)
@@ -619,14 +1395,84 @@ pub async fn get_profile(
     }
 }
 
+/// Warm a profile's avatar and banner before navigating to it, so the header renders
+/// from local files. Returns the profile with both images pointing at the cache; it is
+/// also cached, so the following `get_profile` serves the local URLs.
+#[tauri::command]
+pub async fn prefetch_profile_header(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
+    handle: String,
+) -> Result<ProfileResponse, AppError> {
+    let user_did = current_user_did()?;
+    let db = db.inner().as_ref();
+    let handle = handle.trim().to_lowercase();
+
+    let mut profile = match load_profile_cache(db, &user_did, &handle).await? {
+        Some(cached) => cached,
+        None => fetch_profile_remote(&app, agent_state.inner(), &handle)
+            .await
+            .inspect_err(|err| connectivity::watch_error(&app, err))?,
+    };
+
+    // Local URLs already point at the cache; only remote ones need downloading
+    let remote = |url: &Option<String>| url.clone().filter(|u| u.starts_with("http"));
+    let (avatar, banner) = (remote(&profile.avatar), remote(&profile.banner));
+    let (avatar_local, banner_local) = futures::join!(
+        media::warm_profile_image(avatar.as_deref(), ProfileImageKind::Avatar, &app),
+        media::warm_profile_image(banner.as_deref(), ProfileImageKind::Banner, &app),
+    );
+    match avatar_local {
+        Ok(Some(url)) => profile.avatar = Some(url),
+        Ok(None) => {}
+        Err(err) => tracing::debug!(target: "media", "avatar prefetch failed: {err}"),
+    }
+    match banner_local {
+        Ok(Some(url)) => profile.banner = Some(url),
+        Ok(None) => {}
+        Err(err) => tracing::debug!(target: "media", "banner prefetch failed: {err}"),
+    }
+
+    save_profile_cache(db, &user_did, &handle, &profile).await?;
+    Ok(profile)
+}
+
+/// Cached profiles can hold remote image URLs saved before the download finished;
+/// route those through the media cache again
+fn localize_profile_images(profile: &mut ProfileResponse, app: &AppHandle) {
+    let is_remote = |url: &Option<String>| url.as_deref().is_some_and(|u| u.starts_with("http"));
+    if is_remote(&profile.avatar) {
+        profile.avatar =
+            media::cached_profile_image(profile.avatar.as_deref(), ProfileImageKind::Avatar, app);
+    }
+    if is_remote(&profile.banner) {
+        profile.banner =
+            media::cached_profile_image(profile.banner.as_deref(), ProfileImageKind::Banner, app);
+    }
+}
+
+async fn record_profile_view(db: &SqlitePool, profile: &ProfileResponse) {
+    history::record_view(
+        db,
+        ViewHistoryEntry::profile(
+            &profile.did,
+            &profile.handle,
+            profile.display_name.as_deref(),
+            profile.avatar.as_deref(),
+        ),
+    )
+    .await;
+}
+
 /// Get profile followers
 #[tauri::command]
 pub async fn get_followers(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: FollowListRequest,
 ) -> Result<FollowListResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let limit_val = request.limit.max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
@@ -651,7 +1497,8 @@ pub async fn get_followers(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(FollowListResponse {
         items: response
@@ -667,11 +1514,11 @@ pub async fn get_followers(
 /// Get profiles followed by actor
 #[tauri::command]
 pub async fn get_follows(
+    app: AppHandle,
     agent_state: State<'_, AgentState>,
     request: FollowListRequest,
 ) -> Result<FollowListResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
 
     let limit_val = request.limit.max(1).min(100);
     let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
@@ -696,7 +1543,8 @@ pub async fn get_follows(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     Ok(FollowListResponse {
         items: response
@@ -709,10 +1557,416 @@ pub async fn get_follows(
     })
 }
 
+/// Get accounts the user has muted
+#[tauri::command]
+pub async fn get_muted_accounts(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    cursor: Option<String>,
+    limit: Option<u8>,
+) -> Result<FollowListResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let limit_val = limit.unwrap_or_else(default_limit).max(1).min(100);
+    let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
+
+    let response = agent
+        .api
+        .app
+        .bsky
+        .graph
+        .get_mutes(get_mutes::ParametersData { cursor, limit }.into())
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(FollowListResponse {
+        items: response
+            .data
+            .mutes
+            .iter()
+            .map(profile_view_to_follow_list_item)
+            .collect(),
+        cursor: response.data.cursor,
+    })
+}
+
+/// Get accounts the user has blocked; each item carries the block URI for unblocking
+#[tauri::command]
+pub async fn get_blocked_accounts(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    cursor: Option<String>,
+    limit: Option<u8>,
+) -> Result<FollowListResponse, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let limit_val = limit.unwrap_or_else(default_limit).max(1).min(100);
+    let limit = bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(limit_val).ok();
+
+    let response = agent
+        .api
+        .app
+        .bsky
+        .graph
+        .get_blocks(get_blocks::ParametersData { cursor, limit }.into())
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+    Ok(FollowListResponse {
+        items: response
+            .data
+            .blocks
+            .iter()
+            .map(profile_view_to_follow_list_item)
+            .collect(),
+        cursor: response.data.cursor,
+    })
+}
+
+/// getPosts accepts at most this many URIs per call
+const VIEWER_STATE_BATCH: usize = 25;
+
+/// A post's counts and the current user's like/repost state
+#[derive(Serialize, Clone)]
+pub struct PostViewerState {
+    pub uri: String,
+    pub is_liked: bool,
+    pub is_reposted: bool,
+    pub viewer_like: Option<String>,
+    pub viewer_repost: Option<String>,
+    pub like_count: u32,
+    pub repost_count: u32,
+    pub reply_count: u32,
+}
+
+fn post_viewer_state(post: &PostView) -> PostViewerState {
+    let viewer_like = post
+        .viewer
+        .as_ref()
+        .and_then(|v| v.like.as_ref())
+        .map(|u| u.to_string());
+    let viewer_repost = post
+        .viewer
+        .as_ref()
+        .and_then(|v| v.repost.as_ref())
+        .map(|u| u.to_string());
+
+    PostViewerState {
+        uri: post.uri.to_string(),
+        is_liked: viewer_like.is_some(),
+        is_reposted: viewer_repost.is_some(),
+        viewer_like,
+        viewer_repost,
+        like_count: post.like_count.unwrap_or(0) as u32,
+        repost_count: post.repost_count.unwrap_or(0) as u32,
+        reply_count: post.reply_count.unwrap_or(0) as u32,
+    }
+}
+
+/// Re-read likes, reposts and counts for posts already on screen (e.g. shown from cache)
+/// without refetching the feed. Emits `post_viewer_state_changed` per post as each batch
+/// arrives and returns all of them; deleted or hidden posts are left out.
+#[tauri::command]
+pub async fn refresh_viewer_states(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uris: Vec<String>,
+) -> Result<Vec<PostViewerState>, AppError> {
+    let agent = current_agent(&agent_state).await?;
+
+    let mut uris = uris;
+    let mut seen = std::collections::HashSet::new();
+    uris.retain(|uri| seen.insert(uri.clone()));
+
+    let mut states = Vec::with_capacity(uris.len());
+    for batch in uris.chunks(VIEWER_STATE_BATCH) {
+        let response = agent
+            .api
+            .app
+            .bsky
+            .feed
+            .get_posts(
+                bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                    uris: batch.to_vec(),
+                }
+                .into(),
+            )
+            .await
+            .map_err(connectivity::xrpc_error)
+            .inspect_err(|err| connectivity::watch_error(&app, err))?;
+
+        for post in &response.data.posts {
+            let state = post_viewer_state(post);
+            if let Err(err) = app.emit("post_viewer_state_changed", &state) {
+                tracing::warn!("emit viewer state failed: {err}");
+            }
+            states.push(state);
+        }
+    }
+
+    Ok(states)
+}
+
+/// Why `getPosts` left a post out: blocked either way, or deleted
+async fn missing_post_error(agent: &AppAgent, uri: &str) -> AppError {
+    use bsky_sdk::api::app::bsky::feed::get_post_thread::OutputThreadRefs;
+
+    let Ok(parsed) = uri.parse() else {
+        return AppError::ApiError("Invalid URI".into());
+    };
+    let thread = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_post_thread(
+            bsky_sdk::api::app::bsky::feed::get_post_thread::ParametersData {
+                uri: parsed,
+                depth: bsky_sdk::api::types::LimitedU16::<1000>::try_from(0).ok(),
+                parent_height: bsky_sdk::api::types::LimitedU16::<1000>::try_from(0).ok(),
+            }
+            .into(),
+        )
+        .await;
+
+    match thread {
+        Ok(output) => match output.data.thread {
+            Union::Refs(OutputThreadRefs::AppBskyFeedDefsBlockedPost(_)) => {
+                AppError::ValidationError {
+                    code: "POST_BLOCKED",
+                    message: "This post is from an account you blocked or that blocked you".into(),
+                }
+            }
+            _ => AppError::NotFound(format!("Post {uri} was deleted")),
+        },
+        Err(err) => post_thread_error(uri, err),
+    }
+}
+
+/// `getPosts` already came back without the post, so the thread lookup saying it isn't
+/// there means deleted; any other failure says nothing about the post and is passed on
+fn post_thread_error(
+    uri: &str,
+    err: atrium_xrpc::Error<bsky_sdk::api::app::bsky::feed::get_post_thread::Error>,
+) -> AppError {
+    use bsky_sdk::api::app::bsky::feed::get_post_thread::Error as ThreadError;
+
+    if let atrium_xrpc::Error::XrpcResponse(resp) = &err {
+        let not_found = match &resp.error {
+            Some(atrium_xrpc::error::XrpcErrorKind::Custom(ThreadError::NotFound(_))) => true,
+            Some(atrium_xrpc::error::XrpcErrorKind::Undefined(body)) => {
+                body.error.as_deref() == Some("NotFound")
+            }
+            _ => false,
+        };
+        if not_found {
+            return AppError::NotFound(format!("Post {uri} was deleted"));
+        }
+    }
+    connectivity::xrpc_error(err)
+}
+
+/// Fetch one post by at:// URI as a timeline item, with its embeds processed
+pub(crate) async fn fetch_timeline_post(
+    app: &AppHandle,
+    agent: &AppAgent,
+    uri: String,
+) -> Result<TimelinePost, AppError> {
+    let response = agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_posts(
+            bsky_sdk::api::app::bsky::feed::get_posts::ParametersData {
+                uris: vec![uri.clone()],
+            }
+            .into(),
+        )
+        .await
+        .map_err(connectivity::xrpc_error)?;
+
+    let Some(post) = response.data.posts.first() else {
+        return Err(missing_post_error(agent, &uri).await);
+    };
+
+    // Opened on purpose, so hidden labels only warn here
+    let content_warning = moderation::content_label_prefs(agent)
+        .await
+        .moderate_post(post)
+        .1;
+    let embed = media::process_post_embed(post, app).await?;
+    let root_uri = extract_thread_root_uri(post);
+    let viewer = post_viewer_state(post);
+
+    Ok(TimelinePost {
+        uri: post.uri.to_string(),
+        cid: post.cid.as_ref().to_string(),
+        author_did: post.author.did.to_string(),
+        author_handle: post.author.handle.to_string(),
+        author_display_name: post.author.display_name.clone(),
+        author_avatar: media::cached_profile_image(
+            post.author.avatar.as_deref(),
+            ProfileImageKind::Avatar,
+            app,
+        ),
+        is_repost: false,
+        reposted_by_handle: None,
+        reposted_by_display_name: None,
+        text: extract_post_text(post),
+        facets: extract_post_facets(post),
+        created_at: extract_created_at(post),
+        reply_count: viewer.reply_count,
+        repost_count: viewer.repost_count,
+        like_count: viewer.like_count,
+        is_liked: viewer.viewer_like.is_some(),
+        is_reposted: viewer.viewer_repost.is_some(),
+        viewer_like: viewer.viewer_like,
+        viewer_repost: viewer.viewer_repost,
+        embed: embed_to_json(embed),
+        feed_context: None,
+        reason: None,
+        repost_context: RepostContext::None,
+        reply_parent_author_handle: None,
+        reply_root_uri: (root_uri != post.uri.as_str()).then_some(root_uri),
+        indexed_at: Some(post.indexed_at.as_ref().to_string()),
+        content_warning,
+    })
+}
+
+/// Fetch one post with its embeds processed, for deep links and link previews. A post
+/// that doesn't exist is `NOT_FOUND`; one hidden by a block is `POST_BLOCKED`.
+#[tauri::command]
+pub async fn get_post(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    uri: String,
+) -> Result<TimelinePost, AppError> {
+    let agent = current_agent(&agent_state).await?;
+    let uri = resolve_at_uri(&agent, &uri)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+    fetch_timeline_post(&app, &agent, uri)
+        .await
+        .inspect_err(|err| connectivity::watch_error(&app, err))
+}
+
+/// Payload of `thread_reply_created`: a reply the user just posted
+#[derive(Serialize, Clone)]
+pub struct ThreadReplyCreatedEvent {
+    pub root_uri: String,
+    pub parent_uri: String,
+    pub post: TimelinePost,
+}
+
+/// Show a post the user just published without waiting for the next refresh. Top-level
+/// posts are prepended to the cached first timeline page and `timeline_updated` emitted;
+/// replies are announced with `thread_reply_created` for the open thread to insert. A
+/// post the cache already holds (a refresh got there first) is left alone, and the next
+/// real refresh replaces the page wholesale so the post is never listed twice.
+pub(crate) async fn insert_own_post(
+    app: &AppHandle,
+    agent: &AppAgent,
+    db: &SqlitePool,
+    uri: String,
+    parent_uri: Option<String>,
+) -> Result<(), AppError> {
+    let user_did = current_user_did()?;
+    // The AppView indexes new records a moment after the PDS accepts them
+    let mut attempt = 0;
+    let mut post = loop {
+        match fetch_timeline_post(app, agent, uri.clone()).await {
+            Err(AppError::NotFound(_)) if attempt < OWN_POST_FETCH_RETRIES => {
+                attempt += 1;
+                tokio::time::sleep(OWN_POST_FETCH_DELAY).await;
+            }
+            result => break result?,
+        }
+    };
+
+    if let Some(parent_uri) = parent_uri {
+        let event = ThreadReplyCreatedEvent {
+            root_uri: post
+                .reply_root_uri
+                .clone()
+                .unwrap_or_else(|| parent_uri.clone()),
+            parent_uri,
+            post,
+        };
+        if let Err(err) = app.emit("thread_reply_created", &event) {
+            tracing::warn!(target: "cache", cache = "thread", "emit reply failed: {err}");
+        }
+        return Ok(());
+    }
+
+    let Some(mut cached) = load_timeline_cache(db, &user_did, None).await? else {
+        // Nothing cached yet; the first load will fetch the post anyway
+        return Ok(());
+    };
+    if cached.posts.iter().any(|p| p.uri == post.uri) {
+        return Ok(());
+    }
+
+    // The feed orders by indexed_at; a fresh post belongs on top even if the AppView's
+    // clock is slightly behind ours
+    post.indexed_at
+        .get_or_insert_with(|| Utc::now().to_rfc3339());
+    cached.posts.insert(0, post);
+    save_timeline_cache(db, &user_did, None, &cached).await?;
+
+    if let Err(err) = app.emit("timeline_updated", &cached) {
+        tracing::warn!(target: "cache", cache = "timeline", "emit own post failed: {err}");
+    }
+    Ok(())
+}
+
 #[derive(Deserialize)]
 pub struct PostThreadRequest {
     pub uri: String,
     pub depth: Option<u8>,
+    /// Reorder direct replies; server order when unset
+    pub sort: Option<ThreadReplySort>,
+    /// Most nodes to return, parents included; replies are kept level by level
+    pub max_nodes: Option<u32>,
+    /// Only the post and its parent chain (inline "replying to" context): no replies
+    /// are fetched, and the view isn't recorded in history or read positions
+    #[serde(default)]
+    pub parents_only: bool,
+}
+
+/// Node budget when the caller doesn't set one; plenty for any thread a person reads
+/// top to bottom, small enough to keep one IPC message manageable
+const DEFAULT_THREAD_MAX_NODES: u32 = 500;
+const MIN_THREAD_MAX_NODES: u32 = 10;
+const MAX_THREAD_MAX_NODES: u32 = 5000;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadReplySort {
+    MostLiked,
+    Oldest,
+    Newest,
+}
+
+fn created_at_timestamp(post: &ThreadPost) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(&post.created_at)
+        .map(|dt| dt.timestamp_millis())
+        .unwrap_or(0)
+}
+
+/// Stable sort of a thread's direct replies; nested replies keep the server's order
+fn sort_thread_replies(replies: &mut [ThreadResponse], sort: ThreadReplySort) {
+    match sort {
+        ThreadReplySort::MostLiked => {
+            replies.sort_by(|a, b| b.post.like_count.cmp(&a.post.like_count))
+        }
+        ThreadReplySort::Oldest => replies.sort_by_key(|reply| created_at_timestamp(&reply.post)),
+        ThreadReplySort::Newest => {
+            replies.sort_by_key(|reply| std::cmp::Reverse(created_at_timestamp(&reply.post)))
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -724,6 +1978,7 @@ pub struct ThreadPost {
     pub author_display_name: Option<String>,
     pub author_avatar: Option<String>,
     pub text: String,
+    pub facets: Option<serde_json::Value>,
     pub created_at: String,
     pub reply_count: u32,
     pub repost_count: u32,
@@ -733,6 +1988,30 @@ pub struct ThreadPost {
     pub viewer_like: Option<String>,
     pub viewer_repost: Option<String>,
     pub embed: Option<EmbedView>,
+    /// The thread's threadgate doesn't let the current user reply
+    pub reply_disabled: bool,
+    /// The author's postgate doesn't let the current user quote this post
+    pub quotes_disabled: bool,
+    /// Label the user asked to be warned about (or to hide, for posts kept for context)
+    pub content_warning: Option<String>,
+}
+
+/// Who may reply to a thread, read from its threadgate
+#[derive(Serialize)]
+pub struct ThreadgateRules {
+    /// No threadgate, or one without an `allow` list: anyone can reply
+    pub everyone: bool,
+    /// Allowed groups when restricted: "mentioned", "followers", "following", "list".
+    /// Empty with `everyone` false means nobody can reply.
+    pub allow: Vec<&'static str>,
+    /// Lists whose members may reply
+    pub lists: Vec<ThreadgateList>,
+}
+
+#[derive(Serialize)]
+pub struct ThreadgateList {
+    pub uri: String,
+    pub name: String,
 }
 
 #[derive(Serialize)]
@@ -740,17 +2019,195 @@ pub struct ThreadResponse {
     pub post: ThreadPost,
     pub parent: Option<Box<ThreadResponse>>,
     pub replies: Vec<ThreadResponse>,
+    /// The node for the URI that was requested
+    pub is_focused: bool,
+    /// The node is the thread's original post
+    pub is_root: bool,
+    /// URI of the thread's original post (only set on the top-level response)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_uri: Option<String>,
+    /// Reply rules of the whole thread (only set on the top-level response)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threadgate: Option<ThreadgateRules>,
+    /// Reply posted after the saved read position
+    pub is_new: bool,
+    /// Where the user left off in this thread (only set on the top-level response)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_position: Option<ThreadReadPosition>,
+    /// Replies below the focused post marked `is_new` (only set on the top-level response)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_reply_count: Option<u32>,
+    /// Some replies were left out to stay within `max_nodes`; load this post's thread
+    /// to see them
+    pub has_more: bool,
+}
+
+/// Last-seen point in a thread, stored locally per account
+#[derive(Serialize, Clone)]
+pub struct ThreadReadPosition {
+    pub last_seen_reply_uri: Option<String>,
+    pub last_seen_at: String,
+}
+
+async fn load_thread_read_position(
+    db: &SqlitePool,
+    user_did: &str,
+    root_uri: &str,
+) -> Result<Option<ThreadReadPosition>, AppError> {
+    let row = sqlx::query_as::<_, (Option<String>, String)>(
+        r#"
+        SELECT last_seen_reply_uri, last_seen_at
+        FROM thread_read_positions
+        WHERE user_did = ?1 AND thread_root_uri = ?2
+        "#,
+    )
+    .bind(user_did)
+    .bind(root_uri)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("thread position read failed: {e}")))?;
+
+    Ok(
+        row.map(|(last_seen_reply_uri, last_seen_at)| ThreadReadPosition {
+            last_seen_reply_uri,
+            last_seen_at,
+        }),
+    )
+}
+
+/// Flag replies created after `seen_at_ms`; returns how many were flagged
+fn mark_new_replies(replies: &mut [ThreadResponse], seen_at_ms: i64) -> u32 {
+    let mut count = 0;
+    for reply in replies {
+        if created_at_timestamp(&reply.post) > seen_at_ms {
+            reply.is_new = true;
+            count += 1;
+        }
+        count += mark_new_replies(&mut reply.replies, seen_at_ms);
+    }
+    count
+}
+
+/// Remember how far the user has read a thread; `last_seen_at` defaults to now
+/// ("mark thread read"). `get_post_thread` reports replies newer than it.
+#[tauri::command]
+pub async fn set_thread_read_position(
+    db: State<'_, DbState>,
+    root_uri: String,
+    last_seen_reply_uri: Option<String>,
+    last_seen_at: Option<String>,
+) -> Result<(), AppError> {
+    let user_did = get_stored_session()?.did;
+    let now = Utc::now().to_rfc3339();
+    let last_seen_at = match last_seen_at {
+        Some(at) => chrono::DateTime::parse_from_rfc3339(&at)
+            .map(|dt| dt.to_rfc3339())
+            .map_err(|_| AppError::ApiError(format!("Invalid timestamp: {at}")))?,
+        None => now.clone(),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO thread_read_positions (
+            user_did, thread_root_uri, last_seen_reply_uri, last_seen_at, updated_at
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(user_did, thread_root_uri) DO UPDATE SET
+            last_seen_reply_uri = excluded.last_seen_reply_uri,
+            last_seen_at = excluded.last_seen_at,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(user_did)
+    .bind(root_uri.trim())
+    .bind(last_seen_reply_uri)
+    .bind(last_seen_at)
+    .bind(now)
+    .execute(db.inner().as_ref())
+    .await
+    .map_err(|e| AppError::InternalError(format!("thread position write failed: {e}")))?;
+
+    Ok(())
+}
+
+fn threadgate_rules(view: Option<&ThreadgateView>) -> ThreadgateRules {
+    let allow = view
+        .and_then(|gate| gate.record.as_ref())
+        .and_then(|record| serde_json::to_value(record).ok())
+        .and_then(|json| json.get("allow")?.as_array().cloned());
+    let Some(allow) = allow else {
+        return ThreadgateRules {
+            everyone: true,
+            allow: Vec::new(),
+            lists: Vec::new(),
+        };
+    };
+
+    let mut groups = Vec::new();
+    for rule in &allow {
+        let group = match rule.get("$type").and_then(|t| t.as_str()) {
+            Some("app.bsky.feed.threadgate#mentionRule") => "mentioned",
+            Some("app.bsky.feed.threadgate#followerRule") => "followers",
+            Some("app.bsky.feed.threadgate#followingRule") => "following",
+            Some("app.bsky.feed.threadgate#listRule") => "list",
+            _ => continue,
+        };
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+
+    ThreadgateRules {
+        everyone: false,
+        allow: groups,
+        lists: view
+            .and_then(|gate| gate.lists.as_ref())
+            .map(|lists| {
+                lists
+                    .iter()
+                    .map(|list| ThreadgateList {
+                        uri: list.uri.to_string(),
+                        name: list.name.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
 }
 
-fn post_view_to_thread_post(post: &PostView, embed: Option<EmbedView>) -> ThreadPost {
+/// URI of the thread root a post replies into, or the post's own URI if it is not a reply
+fn extract_thread_root_uri(post: &PostView) -> String {
+    serde_json::to_value(&post.record)
+        .ok()
+        .and_then(|json| {
+            json.get("reply")?
+                .get("root")?
+                .get("uri")?
+                .as_str()
+                .map(|uri| uri.to_string())
+        })
+        .unwrap_or_else(|| post.uri.to_string())
+}
+
+fn post_view_to_thread_post(
+    post: &PostView,
+    embed: Option<EmbedView>,
+    label_prefs: &ContentLabelPrefs,
+    app: &AppHandle,
+) -> ThreadPost {
     ThreadPost {
         uri: post.uri.to_string(),
         cid: post.cid.as_ref().to_string(),
         author_did: post.author.did.to_string(),
         author_handle: post.author.handle.to_string(),
         author_display_name: post.author.display_name.clone(),
-        author_avatar: post.author.avatar.clone(),
+        author_avatar: media::cached_profile_image(
+            post.author.avatar.as_deref(),
+            ProfileImageKind::Avatar,
+            app,
+        ),
         text: extract_post_text(post),
+        facets: extract_post_facets(post),
         created_at: extract_created_at(post),
         reply_count: post.reply_count.unwrap_or(0) as u32,
         repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -772,20 +2229,37 @@ fn post_view_to_thread_post(post: &PostView, embed: Option<EmbedView>) -> Thread
             .and_then(|v| v.repost.as_ref())
             .map(|u| u.to_string()),
         embed,
+        reply_disabled: post
+            .viewer
+            .as_ref()
+            .and_then(|v| v.reply_disabled)
+            .unwrap_or(false),
+        quotes_disabled: post
+            .viewer
+            .as_ref()
+            .and_then(|v| v.embedding_disabled)
+            .unwrap_or(false),
+        content_warning: label_prefs.moderate_post(post).1,
     }
 }
 
-/// Get a post thread with parent and replies
+/// Get a post thread with parent and replies, or with `parents_only` just the post and
+/// its ancestors
 #[tauri::command]
 pub async fn get_post_thread(
     app: AppHandle,
     agent_state: State<'_, AgentState>,
+    db: State<'_, DbState>,
     request: PostThreadRequest,
 ) -> Result<ThreadResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
+    let label_prefs = moderation::content_label_prefs(&agent).await;
 
-    let depth = request.depth.unwrap_or(6).max(1).min(100) as u16;
+    let depth = if request.parents_only {
+        0
+    } else {
+        request.depth.unwrap_or(6).max(1).min(100) as u16
+    };
     let depth_limit = bsky_sdk::api::types::LimitedU16::<1000>::try_from(depth).ok();
 
     let thread = agent
@@ -795,8 +2269,8 @@ pub async fn get_post_thread(
         .feed
         .get_post_thread(
             bsky_sdk::api::app::bsky::feed::get_post_thread::ParametersData {
-                uri: request
-                    .uri
+                uri: resolve_at_uri(&agent, &request.uri)
+                    .await?
                     .parse()
                     .map_err(|_| AppError::ApiError("Invalid URI".into()))?,
                 depth: depth_limit,
@@ -805,34 +2279,49 @@ pub async fn get_post_thread(
             .into(),
         )
         .await
-        .map_err(|e| AppError::ApiError(e.to_string()))?;
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
 
     use bsky_sdk::api::app::bsky::feed::defs::{
-        ThreadViewPostParentRefs, ThreadViewPostRepliesItem,
+        ThreadViewPost, ThreadViewPostParentRefs, ThreadViewPostRepliesItem,
     };
     use bsky_sdk::api::app::bsky::feed::get_post_thread::OutputThreadRefs;
     use bsky_sdk::api::types::Union;
     use futures::future::BoxFuture;
     use futures::FutureExt;
+    use std::collections::{HashSet, VecDeque};
 
     fn parse_parent<'a>(
         view: &'a Union<ThreadViewPostParentRefs>,
+        label_prefs: &'a ContentLabelPrefs,
         app: &'a AppHandle,
+        root_uri: &'a str,
     ) -> BoxFuture<'a, Result<Option<ThreadResponse>, AppError>> {
         async move {
             match view {
                 Union::Refs(ThreadViewPostParentRefs::ThreadViewPost(tv)) => {
                     let embed = media::process_post_embed(&tv.post, app).await?;
-                    let post = post_view_to_thread_post(&tv.post, embed);
+                    let post = post_view_to_thread_post(&tv.post, embed, label_prefs, app);
                     let parent = if let Some(p) = &tv.parent {
-                        parse_parent(p, app).await?.map(Box::new)
+                        parse_parent(p, label_prefs, app, root_uri)
+                            .await?
+                            .map(Box::new)
                     } else {
                         None
                     };
+                    let is_root = post.uri == root_uri;
                     Ok(Some(ThreadResponse {
                         post,
                         parent,
                         replies: Vec::new(),
+                        is_focused: false,
+                        is_root,
+                        root_uri: None,
+                        threadgate: None,
+                        is_new: false,
+                        read_position: None,
+                        new_reply_count: None,
+                        has_more: false,
                     }))
                 }
                 _ => Ok(None),
@@ -841,31 +2330,87 @@ pub async fn get_post_thread(
         .boxed()
     }
 
+    /// Visible replies, taken level by level until `budget` runs out, so a huge thread
+    /// keeps the first replies of every branch rather than one deep branch
+    fn replies_within_budget<'a>(
+        replies: Option<&'a Vec<Union<ThreadViewPostRepliesItem>>>,
+        label_prefs: &ContentLabelPrefs,
+        mut budget: usize,
+    ) -> HashSet<&'a str> {
+        let mut kept = HashSet::new();
+        let mut queue: VecDeque<&Vec<Union<ThreadViewPostRepliesItem>>> =
+            replies.into_iter().collect();
+        while let Some(level) = queue.pop_front() {
+            for reply in level {
+                let Union::Refs(ThreadViewPostRepliesItem::ThreadViewPost(tv)) = reply else {
+                    continue;
+                };
+                // Hidden replies are dropped with their subtree
+                if label_prefs.moderate_post(&tv.post).0 == LabelVisibility::Hide {
+                    continue;
+                }
+                if budget == 0 {
+                    return kept;
+                }
+                budget -= 1;
+                kept.insert(tv.post.uri.as_str());
+                if let Some(children) = &tv.replies {
+                    queue.push_back(children);
+                }
+            }
+        }
+        kept
+    }
+
+    /// Parse the replies that made the budget; the flag is set when any visible one didn't
+    async fn parse_replies(
+        reply_list: Option<&Vec<Union<ThreadViewPostRepliesItem>>>,
+        kept: &HashSet<&str>,
+        label_prefs: &ContentLabelPrefs,
+        app: &AppHandle,
+        root_uri: &str,
+    ) -> Result<(Vec<ThreadResponse>, bool), AppError> {
+        let mut replies = Vec::new();
+        let mut has_more = false;
+        for reply in reply_list.into_iter().flatten() {
+            let Union::Refs(ThreadViewPostRepliesItem::ThreadViewPost(tv)) = reply else {
+                continue;
+            };
+            if !kept.contains(tv.post.uri.as_str()) {
+                has_more |= label_prefs.moderate_post(&tv.post).0 != LabelVisibility::Hide;
+                continue;
+            }
+            replies.push(parse_reply(tv, kept, label_prefs, app, root_uri).await?);
+        }
+        Ok((replies, has_more))
+    }
+
     fn parse_reply<'a>(
-        view: &'a Union<ThreadViewPostRepliesItem>,
+        tv: &'a ThreadViewPost,
+        kept: &'a HashSet<&'a str>,
+        label_prefs: &'a ContentLabelPrefs,
         app: &'a AppHandle,
-    ) -> BoxFuture<'a, Result<Option<ThreadResponse>, AppError>> {
+        root_uri: &'a str,
+    ) -> BoxFuture<'a, Result<ThreadResponse, AppError>> {
         async move {
-            match view {
-                Union::Refs(ThreadViewPostRepliesItem::ThreadViewPost(tv)) => {
-                    let embed = media::process_post_embed(&tv.post, app).await?;
-                    let post = post_view_to_thread_post(&tv.post, embed);
-                    let mut replies: Vec<ThreadResponse> = Vec::new();
-                    if let Some(ref reply_list) = tv.replies {
-                        for reply in reply_list {
-                            if let Some(parsed) = parse_reply(reply, app).await? {
-                                replies.push(parsed);
-                            }
-                        }
-                    }
-                    Ok(Some(ThreadResponse {
-                        post,
-                        parent: None,
-                        replies,
-                    }))
-                }
-                _ => Ok(None),
-            }
+            let embed = media::process_post_embed(&tv.post, app).await?;
+            let post = post_view_to_thread_post(&tv.post, embed, label_prefs, app);
+            let (replies, has_more) =
+                parse_replies(tv.replies.as_ref(), kept, label_prefs, app, root_uri).await?;
+            let is_root = post.uri == root_uri;
+            Ok(ThreadResponse {
+                post,
+                parent: None,
+                replies,
+                is_focused: false,
+                is_root,
+                root_uri: None,
+                threadgate: None,
+                is_new: false,
+                read_position: None,
+                new_reply_count: None,
+                has_more,
+            })
         }
         .boxed()
     }
@@ -873,25 +2418,102 @@ pub async fn get_post_thread(
     // Parse main thread
     match &thread.data.thread {
         Union::Refs(OutputThreadRefs::AppBskyFeedDefsThreadViewPost(tv)) => {
+            let root_uri = extract_thread_root_uri(&tv.post);
             let embed = media::process_post_embed(&tv.post, &app).await?;
-            let post = post_view_to_thread_post(&tv.post, embed);
+            let post = post_view_to_thread_post(&tv.post, embed, &label_prefs, &app);
             let parent = if let Some(p) = &tv.parent {
-                parse_parent(p, &app).await?.map(Box::new)
+                parse_parent(p, &label_prefs, &app, &root_uri)
+                    .await?
+                    .map(Box::new)
             } else {
                 None
             };
-            let mut replies: Vec<ThreadResponse> = Vec::new();
-            if let Some(ref reply_list) = tv.replies {
-                for reply in reply_list {
-                    if let Some(parsed) = parse_reply(reply, &app).await? {
-                        replies.push(parsed);
-                    }
-                }
+            let is_root = post.uri == root_uri;
+            if request.parents_only {
+                return Ok(ThreadResponse {
+                    has_more: post.reply_count > 0,
+                    post,
+                    parent,
+                    replies: Vec::new(),
+                    is_focused: true,
+                    is_root,
+                    root_uri: Some(root_uri),
+                    threadgate: None,
+                    is_new: false,
+                    read_position: None,
+                    new_reply_count: None,
+                });
+            }
+            let mut parent_count = 0_usize;
+            let mut next = parent.as_deref();
+            while let Some(node) = next {
+                parent_count += 1;
+                next = node.parent.as_deref();
+            }
+            let max_nodes = request
+                .max_nodes
+                .unwrap_or(DEFAULT_THREAD_MAX_NODES)
+                .clamp(MIN_THREAD_MAX_NODES, MAX_THREAD_MAX_NODES)
+                as usize;
+            let kept = replies_within_budget(
+                tv.replies.as_ref(),
+                &label_prefs,
+                max_nodes.saturating_sub(parent_count + 1),
+            );
+            let (mut replies, has_more) =
+                parse_replies(tv.replies.as_ref(), &kept, &label_prefs, &app, &root_uri).await?;
+            if let Some(sort) = request.sort {
+                sort_thread_replies(&mut replies, sort);
             }
+            // The output carries the root's threadgate even when a reply is focused
+            let threadgate = threadgate_rules(
+                thread
+                    .data
+                    .threadgate
+                    .as_ref()
+                    .or(tv.post.threadgate.as_ref().filter(|_| is_root)),
+            );
+            // A missing position only loses the "new replies" marker, so don't fail the thread
+            let read_position = match get_stored_session() {
+                Ok(session) => {
+                    load_thread_read_position(db.inner().as_ref(), &session.did, &root_uri)
+                        .await
+                        .unwrap_or_else(|err| {
+                            tracing::warn!("{err}");
+                            None
+                        })
+                }
+                Err(_) => None,
+            };
+            let new_reply_count = read_position.as_ref().map(|position| {
+                let seen_at_ms = chrono::DateTime::parse_from_rfc3339(&position.last_seen_at)
+                    .map(|dt| dt.timestamp_millis())
+                    .unwrap_or(i64::MAX);
+                mark_new_replies(&mut replies, seen_at_ms)
+            });
+            history::record_view(
+                db.inner().as_ref(),
+                ViewHistoryEntry::post(
+                    &post.uri,
+                    &post.author_handle,
+                    post.author_display_name.as_deref(),
+                    post.author_avatar.as_deref(),
+                    &post.text,
+                ),
+            )
+            .await;
             Ok(ThreadResponse {
                 post,
                 parent,
                 replies,
+                is_focused: true,
+                is_root,
+                root_uri: Some(root_uri),
+                threadgate: Some(threadgate),
+                is_new: false,
+                read_position,
+                new_reply_count,
+                has_more,
             })
         }
         _ => Err(AppError::ApiError("Thread not found or blocked".into())),
@@ -914,8 +2536,8 @@ pub async fn get_author_feed(
     agent_state: State<'_, AgentState>,
     request: AuthorFeedRequest,
 ) -> Result<TimelineResponse, AppError> {
-    let guard = agent_state.lock().await;
-    let agent = guard.as_ref().ok_or(AppError::SessionNotFound)?;
+    let agent = current_agent(&agent_state).await?;
+    let label_prefs = moderation::content_label_prefs(&agent).await;
 
     // Create limit - clamp to valid range (1-100)
     let limit_val = request.limit.max(1).min(100);
@@ -924,6 +2546,7 @@ pub async fn get_author_feed(
     // For now, we'll implement basic filtering by using different API calls
     let mut posts: Vec<TimelinePost> = Vec::new();
     let cursor: Option<String>;
+    let reset: bool;
 
     if request.filter.as_deref() == Some("likes") {
         // Use get_actor_likes for likes.
@@ -934,22 +2557,27 @@ pub async fn get_author_feed(
             .parse()
             .map_err(|_| AppError::ApiError("Invalid handle".into()))?;
 
-        let likes_feed = match agent
-            .api
-            .app
-            .bsky
-            .feed
-            .get_actor_likes(
+        let primary = fetch_with_cursor_recovery(request.cursor.clone(), |cursor| {
+            agent.api.app.bsky.feed.get_actor_likes(
                 get_actor_likes::ParametersData {
                     actor: actor.clone(),
-                    cursor: request.cursor.clone(),
+                    cursor,
                     limit: limit.clone(),
                 }
                 .into(),
             )
-            .await
-        {
-            Ok(feed) => feed,
+        })
+        .await;
+        reset = matches!(primary, Ok((_, true)));
+
+        let likes_feed = match primary {
+            Ok((feed, _)) => feed,
+            // Offline, the fallbacks below would fail the same way
+            Err(primary_err) if connectivity::is_transport_error(&primary_err) => {
+                let err = connectivity::xrpc_error(primary_err);
+                connectivity::watch_error(&app, &err);
+                return Err(err);
+            }
             Err(primary_err) => {
                 let primary_message = primary_err.to_string();
 
@@ -1038,9 +2666,14 @@ pub async fn get_author_feed(
 
         for like_item in &likes_feed.data.feed {
             let post = &like_item.post;
+            let (label_visibility, content_warning) = label_prefs.moderate_post(post);
+            if label_visibility == LabelVisibility::Hide {
+                continue;
+            }
             let embed = media::process_post_embed(post, &app).await?;
-            let (is_repost, reposted_by_handle, reposted_by_display_name) =
-                extract_repost_context(like_item);
+            let repost_context = extract_repost_context(like_item);
+            let (reposted_by_handle, reposted_by_display_name) = repost_context.reposted_by();
+            let (reply_parent_author_handle, reply_root_uri) = extract_reply_context(like_item);
 
             posts.push(TimelinePost {
                 uri: post.uri.to_string(),
@@ -1048,11 +2681,16 @@ pub async fn get_author_feed(
                 author_did: post.author.did.to_string(),
                 author_handle: post.author.handle.to_string(),
                 author_display_name: post.author.display_name.clone(),
-                author_avatar: post.author.avatar.clone(),
-                is_repost,
+                author_avatar: media::cached_profile_image(
+                    post.author.avatar.as_deref(),
+                    ProfileImageKind::Avatar,
+                    &app,
+                ),
+                is_repost: repost_context.is_repost(),
                 reposted_by_handle,
                 reposted_by_display_name,
                 text: extract_post_text(post),
+                facets: extract_post_facets(post),
                 created_at: extract_created_at(post),
                 reply_count: post.reply_count.unwrap_or(0) as u32,
                 repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -1074,6 +2712,13 @@ pub async fn get_author_feed(
                     .and_then(|v| v.repost.as_ref())
                     .map(|u| u.to_string()),
                 embed: embed_to_json(embed),
+                feed_context: None,
+                reason: None,
+                repost_context,
+                reply_parent_author_handle,
+                reply_root_uri,
+                indexed_at: Some(post.indexed_at.as_ref().to_string()),
+                content_warning,
             });
         }
         cursor = likes_feed.data.cursor;
@@ -1085,26 +2730,26 @@ pub async fn get_author_feed(
             _ => Some("posts_no_replies".to_string()),
         };
 
-        let author_feed = agent
-            .api
-            .app
-            .bsky
-            .feed
-            .get_author_feed(
+        let actor: AtIdentifier = request
+            .handle
+            .parse()
+            .map_err(|_| AppError::ApiError("Invalid handle".into()))?;
+        let (author_feed, feed_reset) = fetch_with_cursor_recovery(request.cursor, |cursor| {
+            agent.api.app.bsky.feed.get_author_feed(
                 get_author_feed::ParametersData {
-                    actor: request
-                        .handle
-                        .parse()
-                        .map_err(|_| AppError::ApiError("Invalid handle".into()))?,
-                    cursor: request.cursor,
-                    limit,
+                    actor: actor.clone(),
+                    cursor,
+                    limit: limit.clone(),
                     filter: filter_param.clone(),
                     include_pins: Some(false),
                 }
                 .into(),
             )
-            .await
-            .map_err(|e| AppError::ApiError(e.to_string()))?;
+        })
+        .await
+        .map_err(connectivity::xrpc_error)
+        .inspect_err(|err| connectivity::watch_error(&app, err))?;
+        reset = feed_reset;
 
         for feed_view in &author_feed.data.feed {
             let post = &feed_view.post;
@@ -1121,9 +2766,14 @@ pub async fn get_author_feed(
                 continue;
             }
 
+            let (label_visibility, content_warning) = label_prefs.moderate_post(post);
+            if label_visibility == LabelVisibility::Hide {
+                continue;
+            }
             let embed = media::process_post_embed(post, &app).await?;
-            let (is_repost, reposted_by_handle, reposted_by_display_name) =
-                extract_repost_context(feed_view);
+            let repost_context = extract_repost_context(feed_view);
+            let (reposted_by_handle, reposted_by_display_name) = repost_context.reposted_by();
+            let (reply_parent_author_handle, reply_root_uri) = extract_reply_context(feed_view);
 
             posts.push(TimelinePost {
                 uri: post.uri.to_string(),
@@ -1131,11 +2781,16 @@ pub async fn get_author_feed(
                 author_did: post.author.did.to_string(),
                 author_handle: post.author.handle.to_string(),
                 author_display_name: post.author.display_name.clone(),
-                author_avatar: post.author.avatar.clone(),
-                is_repost,
+                author_avatar: media::cached_profile_image(
+                    post.author.avatar.as_deref(),
+                    ProfileImageKind::Avatar,
+                    &app,
+                ),
+                is_repost: repost_context.is_repost(),
                 reposted_by_handle,
                 reposted_by_display_name,
                 text: extract_post_text(post),
+                facets: extract_post_facets(post),
                 created_at: extract_created_at(post),
                 reply_count: post.reply_count.unwrap_or(0) as u32,
                 repost_count: post.repost_count.unwrap_or(0) as u32,
@@ -1157,10 +2812,447 @@ pub async fn get_author_feed(
                     .and_then(|v| v.repost.as_ref())
                     .map(|u| u.to_string()),
                 embed: embed_to_json(embed),
+                feed_context: None,
+                reason: None,
+                repost_context,
+                reply_parent_author_handle,
+                reply_root_uri,
+                indexed_at: Some(post.indexed_at.as_ref().to_string()),
+                content_warning,
             });
         }
         cursor = author_feed.data.cursor;
     }
 
-    Ok(TimelineResponse { posts, cursor })
+    Ok(TimelineResponse {
+        posts,
+        cursor,
+        reset,
+        gap: false,
+    })
+}
+
+/// How long a last-active lookup is reused before the author feed is checked again
+const LAST_ACTIVE_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+/// Recent feed items scanned for the newest activity
+const LAST_ACTIVE_SCAN: u8 = 10;
+
+/// Last-active lookups by (viewer DID, actor DID): when checked and what was found
+static LAST_ACTIVE: LazyLock<Mutex<HashMap<(String, String), (Instant, Option<String>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Newest `indexed_at` among the actor's own posts and reposts in a feed page.
+/// Pinned posts are excluded by the request, so an old pin can't count.
+fn newest_activity(feed: &[FeedViewPost], actor_did: &str) -> Option<String> {
+    feed.iter()
+        .filter_map(|item| match item.reason.as_ref() {
+            Some(Union::Refs(FeedViewPostReasonRefs::ReasonRepost(reason))) => {
+                Some(reason.indexed_at.as_ref().to_string())
+            }
+            None if item.post.author.did.as_str() == actor_did => {
+                Some(item.post.indexed_at.as_ref().to_string())
+            }
+            _ => None,
+        })
+        .filter_map(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+        .max()
+        .map(|at| at.to_rfc3339())
+}
+
+/// Whether getAuthorFeed refused because the account can't be shown to us at all, which
+/// is as lasting an answer as an empty feed
+fn author_feed_unavailable(err: &atrium_xrpc::Error<get_author_feed::Error>) -> bool {
+    let atrium_xrpc::Error::XrpcResponse(resp) = err else {
+        return false;
+    };
+    match &resp.error {
+        Some(atrium_xrpc::error::XrpcErrorKind::Custom(
+            get_author_feed::Error::BlockedActor(_) | get_author_feed::Error::BlockedByActor(_),
+        )) => true,
+        Some(atrium_xrpc::error::XrpcErrorKind::Undefined(body)) => {
+            matches!(
+                body.error.as_deref(),
+                Some("NotFound" | "AccountDeactivated" | "AccountTakedown")
+            )
+        }
+        None => false,
+    }
+}
+
+/// Approximate "last active" time for an account: ATProto has no presence, so this is
+/// just when their newest post or repost was indexed. Someone who only reads, likes or
+/// chats shows as inactive. `None` when the feed is empty or can't be seen (blocked,
+/// deactivated, taken down). Lookups are reused for ten minutes.
+#[tauri::command]
+pub async fn get_last_active(
+    app: AppHandle,
+    agent_state: State<'_, AgentState>,
+    did: String,
+) -> Result<Option<String>, AppError> {
+    let actor: Did = did.trim().parse().map_err(|_| AppError::ValidationError {
+        code: "INVALID_DID",
+        message: format!("Invalid DID: {did}"),
+    })?;
+    let key = (get_stored_session()?.did, actor.as_str().to_string());
+    {
+        let cache = LAST_ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((checked, last_active)) = cache.get(&key) {
+            if checked.elapsed() < LAST_ACTIVE_TTL {
+                return Ok(last_active.clone());
+            }
+        }
+    }
+
+    let agent = current_agent(&agent_state).await?;
+    let last_active = match agent
+        .api
+        .app
+        .bsky
+        .feed
+        .get_author_feed(
+            get_author_feed::ParametersData {
+                actor: AtIdentifier::Did(actor.clone()),
+                cursor: None,
+                limit: bsky_sdk::api::types::LimitedNonZeroU8::<100>::try_from(LAST_ACTIVE_SCAN)
+                    .ok(),
+                filter: Some("posts_with_replies".to_string()),
+                include_pins: Some(false),
+            }
+            .into(),
+        )
+        .await
+    {
+        Ok(feed) => newest_activity(&feed.data.feed, actor.as_str()),
+        Err(err) if author_feed_unavailable(&err) => {
+            tracing::debug!("last active unavailable for {}: {err}", actor.as_str());
+            None
+        }
+        // Network trouble, rate limits and upstream failures say nothing about the
+        // account; don't cache them
+        Err(err) => {
+            let err = connectivity::xrpc_error(err);
+            connectivity::watch_error(&app, &err);
+            return Err(err);
+        }
+    };
+
+    let mut cache = LAST_ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.retain(|_, (checked, _)| checked.elapsed() < LAST_ACTIVE_TTL);
+    cache.insert(key, (Instant::now(), last_active.clone()));
+    Ok(last_active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity::{undefined, undefined_with_message, xrpc_failure};
+    use atrium_xrpc::error::XrpcErrorKind;
+    use bsky_sdk::api::app::bsky::feed::get_post_thread;
+
+    const CID: &str = "bafyreie5737gdxlw5i64vzichcalba3z2v5n6icifvx5xytvske7mr3hpm";
+
+    /// Feed item for a post by alice, optionally a reply and with a feed reason
+    fn feed_item(reply: bool, reason: Option<serde_json::Value>) -> FeedViewPost {
+        let mut record = serde_json::json!({
+            "$type": "app.bsky.feed.post",
+            "text": "hello",
+            "createdAt": "2026-01-01T00:00:00Z",
+        });
+        if reply {
+            let parent = serde_json::json!({
+                "uri": "at://did:plc:bob/app.bsky.feed.post/1",
+                "cid": CID,
+            });
+            record["reply"] = serde_json::json!({ "root": parent, "parent": parent });
+        }
+        let mut item = serde_json::json!({
+            "post": {
+                "uri": "at://did:plc:alice/app.bsky.feed.post/2",
+                "cid": CID,
+                "author": { "did": "did:plc:alice", "handle": "alice.test" },
+                "record": record,
+                "indexedAt": "2026-01-01T00:00:00Z",
+            },
+        });
+        if let Some(reason) = reason {
+            item["reason"] = reason;
+        }
+        serde_json::from_value(item).expect("feed item fixture")
+    }
+
+    fn repost_reason() -> serde_json::Value {
+        serde_json::json!({
+            "$type": "app.bsky.feed.defs#reasonRepost",
+            "by": { "did": "did:plc:carol", "handle": "carol.test", "displayName": "Carol" },
+            "indexedAt": "2026-01-01T00:00:00Z",
+        })
+    }
+
+    fn filter(hide_reposts: bool, hide_replies: bool) -> TimelineFilter {
+        TimelineFilter {
+            hide_reposts,
+            hide_replies,
+        }
+    }
+
+    #[test]
+    fn filter_hides_reposts_and_replies() {
+        let post = feed_item(false, None);
+        let reply = feed_item(true, None);
+        let repost = feed_item(false, Some(repost_reason()));
+
+        assert!(filter(true, true).keeps(&post));
+        assert!(!filter(true, false).keeps(&repost));
+        assert!(filter(true, false).keeps(&reply));
+        assert!(!filter(false, true).keeps(&reply));
+        assert!(filter(false, true).keeps(&repost));
+        assert!(filter(false, false).keeps(&reply));
+    }
+
+    #[test]
+    fn reposted_reply_is_judged_as_a_repost() {
+        let reposted_reply = feed_item(true, Some(repost_reason()));
+        assert!(filter(false, true).keeps(&reposted_reply));
+        assert!(!filter(true, false).keeps(&reposted_reply));
+        assert!(!filter(true, true).keeps(&reposted_reply));
+    }
+
+    #[test]
+    fn repost_context_follows_the_feed_reason() {
+        assert!(matches!(
+            extract_repost_context(&feed_item(false, None)),
+            RepostContext::None
+        ));
+
+        let context = extract_repost_context(&feed_item(false, Some(repost_reason())));
+        assert!(context.is_repost());
+        assert!(matches!(
+            &context,
+            RepostContext::Repost { by_did, .. } if by_did == "did:plc:carol"
+        ));
+        assert_eq!(
+            context.reposted_by(),
+            (Some("carol.test".to_string()), Some("Carol".to_string()))
+        );
+
+        let pin = feed_item(
+            false,
+            Some(serde_json::json!({ "$type": "app.bsky.feed.defs#reasonPin" })),
+        );
+        let context = extract_repost_context(&pin);
+        assert!(matches!(context, RepostContext::Pin));
+        assert!(!context.is_repost());
+        assert_eq!(context.reposted_by(), (None, None));
+    }
+
+    #[test]
+    fn missing_or_blocked_accounts_have_no_last_active() {
+        let blocked = Some(XrpcErrorKind::Custom(get_author_feed::Error::BlockedActor(
+            None,
+        )));
+        assert!(author_feed_unavailable(&xrpc_failure(400, blocked)));
+        assert!(author_feed_unavailable(&xrpc_failure(
+            400,
+            undefined("NotFound")
+        )));
+        assert!(author_feed_unavailable(&xrpc_failure(
+            400,
+            undefined("AccountTakedown")
+        )));
+    }
+
+    #[test]
+    fn temporary_author_feed_failures_are_not_cached() {
+        assert!(!author_feed_unavailable(&xrpc_failure(
+            429,
+            undefined("RateLimitExceeded")
+        )));
+        assert!(!author_feed_unavailable(&xrpc_failure(502, None)));
+        let offline = atrium_xrpc::Error::<get_author_feed::Error>::HttpClient(Box::new(
+            std::io::Error::other("connection refused"),
+        ));
+        assert!(!author_feed_unavailable(&offline));
+    }
+
+    #[test]
+    fn filtered_pages_have_their_own_cache_cursor() {
+        assert_eq!(filter(false, false).cache_cursor(None), None);
+        assert_eq!(
+            filter(false, false).cache_cursor(Some("c1")),
+            Some("c1".into())
+        );
+        assert_eq!(
+            filter(true, false).cache_cursor(None),
+            Some("no-reposts:".into())
+        );
+        assert_eq!(
+            filter(false, true).cache_cursor(Some("c1")),
+            Some("no-replies:c1".into())
+        );
+        assert_eq!(
+            filter(true, true).cache_cursor(Some("c1")),
+            Some("originals:c1".into())
+        );
+        assert!(filter(false, false).is_empty());
+        assert!(!filter(false, true).is_empty());
+    }
+
+    #[test]
+    fn invalid_handle_is_rejected_before_fetching() {
+        let err = parse_profile_actor("not a handle").unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::ValidationError {
+                code: "INVALID_HANDLE",
+                ..
+            }
+        ));
+        assert!(parse_profile_actor("alice.test").is_ok());
+        assert!(parse_profile_actor("did:plc:alice").is_ok());
+    }
+
+    #[test]
+    fn unknown_profile_is_not_found() {
+        assert!(matches!(
+            profile_fetch_error::<get_actor_profile::Error>(
+                "alice.test",
+                xrpc_failure(400, undefined("InvalidRequest"))
+            ),
+            AppError::NotFound(_)
+        ));
+        assert!(matches!(
+            profile_fetch_error::<get_actor_profile::Error>(
+                "alice.test",
+                xrpc_failure(404, undefined("NotFound"))
+            ),
+            AppError::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn unavailable_accounts_are_reported_as_such() {
+        assert!(matches!(
+            profile_fetch_error::<get_actor_profile::Error>(
+                "alice.test",
+                xrpc_failure(400, undefined("AccountDeactivated"))
+            ),
+            AppError::ValidationError {
+                code: "ACCOUNT_DEACTIVATED",
+                ..
+            }
+        ));
+        assert!(matches!(
+            profile_fetch_error::<get_actor_profile::Error>(
+                "alice.test",
+                xrpc_failure(400, undefined("AccountTakedown"))
+            ),
+            AppError::ValidationError {
+                code: "ACCOUNT_TAKEN_DOWN",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn server_and_transport_failures_are_not_not_found() {
+        // A message saying "not found" doesn't matter without the status and name
+        assert!(matches!(
+            profile_fetch_error::<get_actor_profile::Error>(
+                "alice.test",
+                xrpc_failure(
+                    502,
+                    undefined_with_message("UpstreamFailure", "Profile not found"),
+                ),
+            ),
+            AppError::ApiError(_)
+        ));
+        let offline = atrium_xrpc::Error::<get_actor_profile::Error>::HttpClient(Box::new(
+            std::io::Error::other("connection refused"),
+        ));
+        assert!(matches!(
+            profile_fetch_error("alice.test", offline),
+            AppError::NetworkError(_)
+        ));
+    }
+
+    #[test]
+    fn missing_post_is_deleted_only_when_the_thread_is_not_found() {
+        const URI: &str = "at://did:plc:alice/app.bsky.feed.post/gone";
+        let not_found = Some(XrpcErrorKind::Custom(get_post_thread::Error::NotFound(
+            None,
+        )));
+        assert!(matches!(
+            post_thread_error(URI, xrpc_failure(400, not_found)),
+            AppError::NotFound(_)
+        ));
+        assert!(matches!(
+            post_thread_error(URI, xrpc_failure(400, undefined("NotFound"))),
+            AppError::NotFound(_)
+        ));
+
+        assert!(matches!(
+            post_thread_error(URI, xrpc_failure(502, undefined("UpstreamFailure"))),
+            AppError::ApiError(_)
+        ));
+        assert!(matches!(
+            post_thread_error(URI, xrpc_failure(429, undefined("RateLimitExceeded"))),
+            AppError::ApiError(_)
+        ));
+        let offline = atrium_xrpc::Error::HttpClient(Box::new(std::io::Error::other("reset")));
+        assert!(matches!(
+            post_thread_error(URI, offline),
+            AppError::NetworkError(_)
+        ));
+    }
+
+    fn cached_post(uri: &str) -> TimelinePost {
+        serde_json::from_value(serde_json::json!({
+            "uri": uri,
+            "cid": CID,
+            "author_did": "did:plc:alice",
+            "author_handle": "alice.test",
+            "text": "hello",
+            "created_at": "2026-01-01T00:00:00Z",
+            "reply_count": 0,
+            "repost_count": 0,
+            "like_count": 0,
+            "is_liked": false,
+            "is_reposted": false,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn cached_page_is_served_when_hidden_posts_cannot_be_read() {
+        const ME: &str = "did:plc:me";
+        let db = crate::db::test_pool().await;
+        let page = TimelineResponse {
+            posts: vec![
+                cached_post("at://did:plc:alice/app.bsky.feed.post/1"),
+                cached_post("at://did:plc:alice/app.bsky.feed.post/2"),
+            ],
+            cursor: Some("next".into()),
+            reset: false,
+            gap: false,
+        };
+        save_timeline_cache(&db, ME, None, &page).await.unwrap();
+
+        sqlx::query("INSERT INTO hidden_posts (user_did, post_uri, hidden_at) VALUES (?1, ?2, ?3)")
+            .bind(ME)
+            .bind("at://did:plc:alice/app.bsky.feed.post/1")
+            .bind("2026-01-01T00:00:00Z")
+            .execute(&db)
+            .await
+            .unwrap();
+        let filtered = load_timeline_cache(&db, ME, None).await.unwrap().unwrap();
+        assert_eq!(filtered.posts.len(), 1);
+
+        sqlx::query("DROP TABLE hidden_posts")
+            .execute(&db)
+            .await
+            .unwrap();
+        let unfiltered = load_timeline_cache(&db, ME, None).await.unwrap().unwrap();
+        assert_eq!(unfiltered.posts.len(), 2);
+        assert_eq!(unfiltered.cursor.as_deref(), Some("next"));
+    }
 }
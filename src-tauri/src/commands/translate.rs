@@ -0,0 +1,369 @@
+use crate::commands::settings::{load_setting, save_setting, DEVICE_SCOPE};
+use crate::db::DbState;
+use crate::error::AppError;
+use bsky_sdk::api::types::string::Language;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tauri::State;
+
+/// Base URL of a LibreTranslate-compatible API (device-wide)
+pub(crate) const TRANSLATE_ENDPOINT_KEY: &str = "translate.endpoint";
+/// API key for the translation endpoint, if it needs one (device-wide, never exported)
+pub(crate) const TRANSLATE_API_KEY: &str = "translate.api_key";
+/// Cached translations kept; the oldest are dropped past this
+const TRANSLATION_CACHE_CAP: i64 = 2000;
+
+#[derive(Serialize)]
+pub struct Translation {
+    pub translated_text: String,
+    /// Language the provider detected (or the one passed in), when known
+    pub source_lang: Option<String>,
+    /// Served from the local cache
+    pub cached: bool,
+}
+
+#[derive(Serialize)]
+pub struct TranslationBackend {
+    pub endpoint: Option<String>,
+    pub has_api_key: bool,
+}
+
+#[derive(Serialize)]
+struct TranslateRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranslateResponse {
+    translated_text: String,
+    #[serde(default)]
+    detected_language: Option<DetectedLanguage>,
+}
+
+#[derive(Deserialize)]
+struct DetectedLanguage {
+    language: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ProviderError {
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn unavailable(message: impl Into<String>) -> AppError {
+    AppError::ValidationError {
+        code: "TRANSLATION_UNAVAILABLE",
+        message: message.into(),
+    }
+}
+
+/// Cache key for a translation: the same text can come back differently from another
+/// service or when the source language is given instead of detected
+fn cache_key(endpoint: &str, source: Option<&str>, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [endpoint, source.unwrap_or("auto"), text] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn normalize_lang(lang: &str) -> Result<String, AppError> {
+    let lang = lang.trim();
+    if lang.parse::<Language>().is_err() {
+        return Err(AppError::ValidationError {
+            code: "INVALID_LANGUAGE",
+            message: format!("Invalid language tag: {lang}"),
+        });
+    }
+    Ok(lang.to_string())
+}
+
+async fn load_translation(
+    db: &SqlitePool,
+    hash: &str,
+    target: &str,
+) -> Result<Option<Translation>, AppError> {
+    let row = sqlx::query_as::<_, (String, Option<String>)>(
+        r#"
+        SELECT translated_text, source_lang
+        FROM translation_cache
+        WHERE text_hash = ?1 AND target_lang = ?2
+        "#,
+    )
+    .bind(hash)
+    .bind(target)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("translation cache read failed: {e}")))?;
+
+    Ok(row.map(|(translated_text, source_lang)| Translation {
+        translated_text,
+        source_lang,
+        cached: true,
+    }))
+}
+
+async fn save_translation(
+    db: &SqlitePool,
+    hash: &str,
+    target: &str,
+    translation: &Translation,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO translation_cache (text_hash, target_lang, translated_text, source_lang, cached_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(text_hash, target_lang) DO UPDATE SET
+            translated_text = excluded.translated_text,
+            source_lang = excluded.source_lang,
+            cached_at = excluded.cached_at
+        "#,
+    )
+    .bind(hash)
+    .bind(target)
+    .bind(&translation.translated_text)
+    .bind(&translation.source_lang)
+    .bind(Utc::now().to_rfc3339())
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("translation cache write failed: {e}")))?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM translation_cache
+        WHERE rowid NOT IN (
+          SELECT rowid FROM translation_cache ORDER BY cached_at DESC LIMIT ?1
+        )
+        "#,
+    )
+    .bind(TRANSLATION_CACHE_CAP)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::InternalError(format!("translation cache trim failed: {e}")))?;
+    Ok(())
+}
+
+async fn request_translation(
+    endpoint: &str,
+    api_key: Option<&str>,
+    text: &str,
+    source: Option<&str>,
+    target: &str,
+) -> Result<Translation, AppError> {
+    let response = crate::http::client()
+        .post(format!("{endpoint}/translate"))
+        .json(&TranslateRequest {
+            q: text,
+            source: source.unwrap_or("auto"),
+            target,
+            format: "text",
+            api_key,
+        })
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::warn!("translation request failed: {e}");
+            unavailable("Translation unavailable: the translation service can't be reached")
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let detail = response
+            .json::<ProviderError>()
+            .await
+            .unwrap_or_default()
+            .error
+            .unwrap_or_else(|| status.to_string());
+        tracing::warn!("translation failed with status {status}: {detail}");
+        return Err(match status.as_u16() {
+            429 => unavailable("Translation unavailable: too many requests, try again shortly"),
+            401 | 403 => unavailable(format!(
+                "Translation unavailable: the service rejected the API key ({detail})"
+            )),
+            _ => unavailable(format!("Translation unavailable: {detail}")),
+        });
+    }
+
+    let parsed = response.json::<TranslateResponse>().await.map_err(|e| {
+        tracing::warn!("translation decode failed: {e}");
+        unavailable("Translation unavailable: unexpected response from the service")
+    })?;
+
+    Ok(Translation {
+        translated_text: parsed.translated_text,
+        source_lang: parsed
+            .detected_language
+            .map(|detected| detected.language)
+            .or_else(|| source.map(str::to_string)),
+        cached: false,
+    })
+}
+
+/// Translate text (usually a post) into `target_lang` through the configured
+/// LibreTranslate-compatible service. The source language is detected unless given.
+/// Results are cached per service, source language, text and target, so translating a
+/// post again is free.
+/// Provider failures, including rate limits, are `TRANSLATION_UNAVAILABLE`.
+#[tauri::command]
+pub async fn translate_text(
+    db: State<'_, DbState>,
+    text: String,
+    target_lang: String,
+    source_lang: Option<String>,
+) -> Result<Translation, AppError> {
+    let db = db.inner().as_ref();
+    let target = normalize_lang(&target_lang)?;
+    let source = source_lang
+        .filter(|lang| !lang.trim().is_empty())
+        .map(|lang| normalize_lang(&lang))
+        .transpose()?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(Translation {
+            translated_text: String::new(),
+            source_lang: source,
+            cached: false,
+        });
+    }
+
+    let endpoint = load_setting::<String>(db, DEVICE_SCOPE, TRANSLATE_ENDPOINT_KEY)
+        .await?
+        .filter(|endpoint| !endpoint.is_empty())
+        .ok_or_else(|| AppError::ValidationError {
+            code: "TRANSLATION_NOT_CONFIGURED",
+            message: "Set up a translation service in settings to translate posts".into(),
+        })?;
+    let api_key = load_setting::<String>(db, DEVICE_SCOPE, TRANSLATE_API_KEY)
+        .await?
+        .filter(|key| !key.is_empty());
+
+    let hash = cache_key(&endpoint, source.as_deref(), text);
+    if let Some(cached) = load_translation(db, &hash, &target).await? {
+        return Ok(cached);
+    }
+
+    let translation = request_translation(
+        &endpoint,
+        api_key.as_deref(),
+        text,
+        source.as_deref(),
+        &target,
+    )
+    .await?;
+    if let Err(err) = save_translation(db, &hash, &target, &translation).await {
+        tracing::warn!("{err}");
+    }
+    Ok(translation)
+}
+
+/// The configured translation service; the API key itself is never returned
+#[tauri::command]
+pub async fn get_translation_backend(
+    db: State<'_, DbState>,
+) -> Result<TranslationBackend, AppError> {
+    let db = db.inner().as_ref();
+    Ok(TranslationBackend {
+        endpoint: load_setting::<String>(db, DEVICE_SCOPE, TRANSLATE_ENDPOINT_KEY)
+            .await?
+            .filter(|endpoint| !endpoint.is_empty()),
+        has_api_key: load_setting::<String>(db, DEVICE_SCOPE, TRANSLATE_API_KEY)
+            .await?
+            .is_some_and(|key| !key.is_empty()),
+    })
+}
+
+/// Set or clear (`None`/empty) the translation endpoint. The API key is kept when `None`
+/// and cleared when empty, so changing only the endpoint doesn't drop the key.
+#[tauri::command]
+pub async fn set_translation_backend(
+    db: State<'_, DbState>,
+    endpoint: Option<String>,
+    api_key: Option<String>,
+) -> Result<TranslationBackend, AppError> {
+    store_translation_backend(db.inner().as_ref(), endpoint, api_key).await?;
+    get_translation_backend(db).await
+}
+
+async fn store_translation_backend(
+    pool: &SqlitePool,
+    endpoint: Option<String>,
+    api_key: Option<String>,
+) -> Result<(), AppError> {
+    let endpoint = match endpoint.filter(|url| !url.trim().is_empty()) {
+        Some(url) => crate::endpoints::normalize_url(&url)?,
+        None => String::new(),
+    };
+
+    save_setting(pool, DEVICE_SCOPE, TRANSLATE_ENDPOINT_KEY, &endpoint).await?;
+    if let Some(api_key) = api_key {
+        save_setting(pool, DEVICE_SCOPE, TRANSLATE_API_KEY, &api_key.trim()).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+
+    #[test]
+    fn cache_key_covers_service_and_source_language() {
+        let key = cache_key("https://a.example", None, "hola");
+        assert_eq!(key, cache_key("https://a.example", None, "hola"));
+        assert_ne!(key, cache_key("https://b.example", None, "hola"));
+        assert_ne!(key, cache_key("https://a.example", Some("es"), "hola"));
+        assert_ne!(key, cache_key("https://a.example", None, "hola!"));
+        // Parts are separated, so shifting text between them changes the key
+        assert_ne!(
+            cache_key("https://a.example", Some("es"), "x"),
+            cache_key("https://a.example", Some("e"), "sx")
+        );
+    }
+
+    async fn stored_api_key(pool: &SqlitePool) -> Option<String> {
+        load_setting::<String>(pool, DEVICE_SCOPE, TRANSLATE_API_KEY)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn api_key_is_kept_unless_given() {
+        let pool = test_pool().await;
+        store_translation_backend(
+            &pool,
+            Some("https://translate.example".into()),
+            Some(" secret ".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored_api_key(&pool).await.as_deref(), Some("secret"));
+
+        store_translation_backend(&pool, Some("https://other.example".into()), None)
+            .await
+            .unwrap();
+        assert_eq!(stored_api_key(&pool).await.as_deref(), Some("secret"));
+
+        store_translation_backend(
+            &pool,
+            Some("https://other.example".into()),
+            Some(String::new()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored_api_key(&pool).await.as_deref(), Some(""));
+    }
+}
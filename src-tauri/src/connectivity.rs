@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::auth::{current_agent, AgentState};
+use crate::db::DbState;
+use crate::error::AppError;
+
+/// First health check delay after a network failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+/// Health checks back off up to this interval while offline
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// Set while a health check loop is running so failures don't stack monitors
+static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Whether an XRPC error never reached the server (DNS, connect, timeout, dropped connection)
+pub fn is_transport_error<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    matches!(err, atrium_xrpc::Error::HttpClient(_))
+}
+
+/// Map an XRPC error to `AppError`, keeping transport failures distinct from API errors
+pub fn xrpc_error<E>(err: atrium_xrpc::Error<E>) -> AppError
+where
+    atrium_xrpc::Error<E>: std::fmt::Display,
+{
+    if crate::http::is_timeout(&err) {
+        AppError::NetworkError("timeout".into())
+    } else if is_transport_error(&err) {
+        AppError::NetworkError(err.to_string())
+    } else {
+        AppError::ApiError(err.to_string())
+    }
+}
+
+/// An XRPC error response with `status` and an optional typed body, as a test fixture
+#[cfg(test)]
+pub(crate) fn xrpc_failure<E>(
+    status: u16,
+    error: Option<atrium_xrpc::error::XrpcErrorKind<E>>,
+) -> atrium_xrpc::Error<E> {
+    atrium_xrpc::Error::XrpcResponse(atrium_xrpc::error::XrpcError {
+        status: reqwest::StatusCode::from_u16(status).unwrap(),
+        error,
+    })
+}
+
+/// An error body the lexicon doesn't declare, named `name` (e.g. "AccountDeactivated")
+#[cfg(test)]
+pub(crate) fn undefined<E>(name: &str) -> Option<atrium_xrpc::error::XrpcErrorKind<E>> {
+    Some(atrium_xrpc::error::XrpcErrorKind::Undefined(
+        atrium_xrpc::error::ErrorResponseBody {
+            error: Some(name.into()),
+            message: None,
+        },
+    ))
+}
+
+/// `undefined` with a message, for code that also reads what the server said
+#[cfg(test)]
+pub(crate) fn undefined_with_message<E>(
+    name: &str,
+    message: &str,
+) -> Option<atrium_xrpc::error::XrpcErrorKind<E>> {
+    Some(atrium_xrpc::error::XrpcErrorKind::Undefined(
+        atrium_xrpc::error::ErrorResponseBody {
+            error: Some(name.into()),
+            message: Some(message.into()),
+        },
+    ))
+}
+
+/// Start the reconnect monitor if a command failed because the network is down
+pub fn watch_error(app: &AppHandle, err: &AppError) {
+    if matches!(err, AppError::NetworkError(_)) {
+        schedule_health_check(app);
+    }
+}
+
+/// Probe the AppView with backoff until it answers, then emit "connectivity_restored",
+/// flush the post retry queue and refresh the home timeline cache
+pub fn schedule_health_check(app: &AppHandle) {
+    if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let agent_state = app.state::<AgentState>().inner().clone();
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            tokio::time::sleep(backoff).await;
+
+            let Ok(agent) = current_agent(&agent_state).await else {
+                // Signed out while offline; nothing to reconnect
+                break;
+            };
+
+            let result = agent
+                .api
+                .app
+                .bsky
+                .notification
+                .get_unread_count(
+                    bsky_sdk::api::app::bsky::notification::get_unread_count::ParametersData {
+                        seen_at: None,
+                        priority: None,
+                    }
+                    .into(),
+                )
+                .await;
+
+            match result {
+                Err(err) if is_transport_error(&err) => {
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    continue;
+                }
+                // Any server response, even an error, means the network is back
+                _ => {
+                    tracing::info!("network restored");
+                    let _ = app.emit("connectivity_restored", ());
+
+                    let db = app.state::<DbState>().inner().clone();
+                    crate::commands::actions::trigger_retry_now(
+                        app.clone(),
+                        agent_state.clone(),
+                        db.clone(),
+                    );
+                    crate::commands::timeline::prefetch_timeline(&app, &agent_state, db.as_ref())
+                        .await;
+                    break;
+                }
+            }
+        }
+
+        MONITOR_RUNNING.store(false, Ordering::SeqCst);
+    });
+}
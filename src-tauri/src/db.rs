@@ -1,12 +1,121 @@
 use crate::error::AppError;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::migrate::MigrateError;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
 use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
 
 pub type DbState = Arc<SqlitePool>;
 
+const DB_FILE_NAME: &str = "blue-horizon.db";
+/// SQLite keeps uncommitted pages beside the database in WAL mode
+const DB_SIDE_FILES: [&str; 2] = ["-wal", "-shm"];
+
+/// Sent (as `db_recovered`) when the database could not be opened and was replaced
+#[derive(Serialize, Clone)]
+pub struct DbRecovery {
+    /// Where the unreadable database was moved
+    pub backup_path: String,
+    pub reason: String,
+}
+
+/// Kept for the frontend, which is not listening yet when startup recovers the database
+static DB_RECOVERY: Mutex<Option<DbRecovery>> = Mutex::new(None);
+
+pub fn db_recovery() -> Option<DbRecovery> {
+    DB_RECOVERY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// SQLITE_CORRUPT and SQLITE_NOTADB, including their extended codes
+fn is_corruption(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(db_err) = err else {
+        return false;
+    };
+    db_err
+        .code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 11 | 26))
+}
+
+/// Why opening the database failed; only an unreadable file is worth replacing. A
+/// database written by a newer app version, or with a migration that stopped halfway, is
+/// still readable and is left alone so nothing in it is lost.
+enum OpenError {
+    Corrupt(String),
+    Fatal(AppError),
+}
+
+async fn open_db(db_path: &Path) -> Result<SqlitePool, OpenError> {
+    let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+    let connect_options = SqliteConnectOptions::from_str(&db_url)
+        .map_err(|e| {
+            OpenError::Fatal(AppError::InternalError(format!(
+                "invalid sqlite connection string: {e}"
+            )))
+        })?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .foreign_keys(true);
+
+    let pool = SqlitePool::connect_with(connect_options)
+        .await
+        .map_err(|e| {
+            if is_corruption(&e) {
+                OpenError::Corrupt(format!("failed to connect sqlite: {e}"))
+            } else {
+                OpenError::Fatal(AppError::InternalError(format!(
+                    "failed to connect sqlite: {e}"
+                )))
+            }
+        })?;
+
+    if let Err(e) = sqlx::migrate!("./migrations").run(&pool).await {
+        pool.close().await;
+        let message = format!("failed to run sqlite migrations: {e}");
+        return Err(match &e {
+            MigrateError::Execute(err) | MigrateError::ExecuteMigration(err, _)
+                if is_corruption(err) =>
+            {
+                OpenError::Corrupt(message)
+            }
+            _ => OpenError::Fatal(AppError::InternalError(message)),
+        });
+    }
+
+    Ok(pool)
+}
+
+/// Move the database and its WAL files aside, returning the backup path
+fn back_up_db(db_path: &Path) -> Result<PathBuf, AppError> {
+    let suffix = format!(".corrupt-{}", Utc::now().format("%Y%m%d%H%M%S"));
+    let backup = PathBuf::from(format!("{}{suffix}", db_path.display()));
+    std::fs::rename(db_path, &backup)
+        .map_err(|e| AppError::InternalError(format!("failed to back up database: {e}")))?;
+
+    for side in DB_SIDE_FILES {
+        let side_path = PathBuf::from(format!("{}{side}", db_path.display()));
+        if side_path.exists() {
+            let side_backup = PathBuf::from(format!("{}{suffix}{side}", db_path.display()));
+            if let Err(e) = std::fs::rename(&side_path, side_backup) {
+                tracing::warn!("failed to back up {}: {e}", side_path.display());
+                let _ = std::fs::remove_file(&side_path);
+            }
+        }
+    }
+    Ok(backup)
+}
+
+/// Open (and migrate) the local database. An unreadable database is moved aside and
+/// recreated so the app still starts, with only cached data and local settings lost;
+/// `db_recovered` is emitted and `get_db_recovery` reports it afterwards.
 pub async fn init_db_state(app: &AppHandle) -> Result<DbState, AppError> {
     let data_dir = app
         .path()
@@ -16,23 +125,125 @@ pub async fn init_db_state(app: &AppHandle) -> Result<DbState, AppError> {
     std::fs::create_dir_all(&data_dir)
         .map_err(|e| AppError::InternalError(format!("failed to create app data dir: {e}")))?;
 
-    let db_path = data_dir.join("blue-horizon.db");
-    let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+    let db_path = data_dir.join(DB_FILE_NAME);
+    let reason = match open_db(&db_path).await {
+        Ok(pool) => return Ok(Arc::new(pool)),
+        Err(OpenError::Fatal(err)) => return Err(err),
+        Err(OpenError::Corrupt(reason)) => reason,
+    };
 
-    let connect_options = SqliteConnectOptions::from_str(&db_url)
-        .map_err(|e| AppError::InternalError(format!("invalid sqlite connection string: {e}")))?
-        .create_if_missing(true)
-        .journal_mode(SqliteJournalMode::Wal)
-        .foreign_keys(true);
+    tracing::error!("database unreadable, recreating it: {reason}");
+    let backup = back_up_db(&db_path)?;
+    let pool = open_db(&db_path).await.map_err(|e| match e {
+        OpenError::Corrupt(reason) => AppError::InternalError(reason),
+        OpenError::Fatal(err) => err,
+    })?;
 
-    let pool = SqlitePool::connect_with(connect_options)
+    let recovery = DbRecovery {
+        backup_path: backup.display().to_string(),
+        reason,
+    };
+    let _ = app.emit("db_recovered", &recovery);
+    *DB_RECOVERY.lock().unwrap_or_else(|e| e.into_inner()) = Some(recovery);
+
+    Ok(Arc::new(pool))
+}
+
+/// Remove everything stored locally for one account (caches, drafts, retry queue, graph
+/// jobs, thread read positions, hidden convos and posts, view history, per-account
+/// settings). Device-wide settings are not tied to an account.
+pub async fn purge_user_data(db: &SqlitePool, user_did: &str) -> Result<(), AppError> {
+    const TABLES: [&str; 11] = [
+        "timeline_cache",
+        "profile_cache",
+        "notifications_cache",
+        "draft_slots",
+        "post_retry_queue",
+        "bulk_graph_items",
+        "thread_read_positions",
+        "hidden_convos",
+        "hidden_posts",
+        "view_history",
+        "app_settings",
+    ];
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::InternalError(format!("purge begin failed: {e}")))?;
+
+    for table in TABLES {
+        sqlx::query(&format!("DELETE FROM {table} WHERE user_did = ?1"))
+            .bind(user_did)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalError(format!("purge {table} failed: {e}")))?;
+    }
+
+    tx.commit()
         .await
-        .map_err(|e| AppError::InternalError(format!("failed to connect sqlite: {e}")))?;
+        .map_err(|e| AppError::InternalError(format!("purge commit failed: {e}")))
+}
 
+/// Fresh in-memory database with every migration applied
+#[cfg(test)]
+pub(crate) async fn test_pool() -> SqlitePool {
+    // One connection that never closes; each in-memory connection is its own database
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory sqlite");
     sqlx::migrate!("./migrations")
         .run(&pool)
         .await
-        .map_err(|e| AppError::InternalError(format!("failed to run sqlite migrations: {e}")))?;
+        .expect("migrations");
+    pool
+}
 
-    Ok(Arc::new(pool))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_db_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blue-horizon-db-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(DB_FILE_NAME)
+    }
+
+    #[tokio::test]
+    async fn garbage_database_file_is_corrupt() {
+        let path = scratch_db_path();
+        std::fs::write(&path, vec![0x5a; 8192]).unwrap();
+
+        assert!(matches!(open_db(&path).await, Err(OpenError::Corrupt(_))));
+
+        let backup = back_up_db(&path).unwrap();
+        assert!(!path.exists());
+        let pool = open_db(&path).await.ok().expect("recreated database opens");
+        pool.close().await;
+
+        let _ = std::fs::remove_dir_all(backup.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn half_applied_migration_is_not_treated_as_corruption() {
+        let path = scratch_db_path();
+        let pool = open_db(&path).await.ok().expect("fresh database opens");
+        sqlx::query(
+            "UPDATE _sqlx_migrations SET success = 0 \
+             WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        assert!(matches!(open_db(&path).await, Err(OpenError::Fatal(_))));
+        assert!(path.exists());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
 }
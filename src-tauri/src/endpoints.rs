@@ -0,0 +1,175 @@
+//! Service endpoints.
+//!
+//! The PDS (chosen at login, stored in the session) hosts the account: auth, token refresh
+//! and every record write go there. Agent reads under `app.bsky.*` are also sent to the PDS,
+//! which proxies them to whatever AppView it is configured with, so a self-hosted PDS
+//! automatically gets its own AppView for the normal code paths.
+//!
+//! The AppView endpoints below are only used for the few requests the backend makes
+//! directly over HTTP (actor likes fallback, trending topics). They default to Bluesky's
+//! AppView and can be pointed at an alternative one in settings; changing them never
+//! changes where the account's data lives. Only Bluesky's default AppView is sent the
+//! session token, and custom endpoints must use https unless they are on localhost.
+
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+pub const DEFAULT_APPVIEW_URL: &str = "https://api.bsky.app";
+/// Unauthenticated, cached AppView used for public data and as a fallback
+pub const DEFAULT_PUBLIC_APPVIEW_URL: &str = "https://public.api.bsky.app";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Endpoints {
+    pub appview_url: String,
+    pub public_appview_url: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            appview_url: DEFAULT_APPVIEW_URL.to_string(),
+            public_appview_url: DEFAULT_PUBLIC_APPVIEW_URL.to_string(),
+        }
+    }
+}
+
+static ENDPOINTS: LazyLock<RwLock<Endpoints>> = LazyLock::new(|| RwLock::new(Endpoints::default()));
+
+pub fn current() -> Endpoints {
+    ENDPOINTS
+        .read()
+        .map(|endpoints| endpoints.clone())
+        .unwrap_or_default()
+}
+
+pub fn set(endpoints: Endpoints) {
+    if let Ok(mut current) = ENDPOINTS.write() {
+        *current = endpoints;
+    }
+}
+
+/// AppViews to try for direct requests, configured one first, without duplicates
+pub fn appview_urls() -> Vec<String> {
+    let Endpoints {
+        appview_url,
+        public_appview_url,
+    } = current();
+
+    if appview_url == public_appview_url {
+        vec![appview_url]
+    } else {
+        vec![appview_url, public_appview_url]
+    }
+}
+
+/// Whether direct requests to an AppView may carry the session's access token. Only
+/// Bluesky's own AppView gets it; a custom endpoint could be anyone.
+pub fn accepts_session_token(appview_url: &str) -> bool {
+    appview_url == DEFAULT_APPVIEW_URL
+}
+
+/// Validate a user-supplied service URL and reduce it to `scheme://host[:port][/path]`
+/// without a trailing slash, so `format!("{url}/xrpc/...")` is always well-formed.
+/// Plain http is only accepted for a service on this machine.
+pub fn normalize_url(raw: &str) -> Result<String, AppError> {
+    let trimmed = raw.trim();
+    let url = reqwest::Url::parse(trimmed)
+        .map_err(|_| AppError::ApiError(format!("Invalid service URL: {trimmed}")))?;
+
+    let Some(host) = url.host_str() else {
+        return Err(AppError::ApiError(format!(
+            "Service URL must be an https address: {trimmed}"
+        )));
+    };
+    let local = matches!(host, "localhost" | "127.0.0.1" | "[::1]");
+    match url.scheme() {
+        "https" => {}
+        "http" if local => {}
+        _ => {
+            return Err(AppError::ApiError(format!(
+                "Service URL must be an https address: {trimmed}"
+            )))
+        }
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(AppError::ApiError(format!(
+            "Service URL must not contain a query or fragment: {trimmed}"
+        )));
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+async fn probe(url: &str, what: &str) -> Result<(), AppError> {
+    let response = crate::http::client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error(&format!("could not reach {what}"), e))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::ApiError(format!(
+            "{what} answered with status {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that a PDS answers `describeServer` before we try to sign in against it
+pub async fn check_pds(service_url: &str) -> Result<(), AppError> {
+    probe(
+        &format!("{service_url}/xrpc/com.atproto.server.describeServer"),
+        &format!("PDS {service_url}"),
+    )
+    .await
+}
+
+/// Check that an AppView answers its health endpoint
+pub async fn check_appview(appview_url: &str) -> Result<(), AppError> {
+    probe(
+        &format!("{appview_url}/xrpc/_health"),
+        &format!("AppView {appview_url}"),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_url_trims_trailing_slash() {
+        assert_eq!(
+            normalize_url(" https://appview.example.com/ ").unwrap(),
+            "https://appview.example.com"
+        );
+    }
+
+    #[test]
+    fn normalize_url_rejects_remote_http() {
+        assert!(normalize_url("http://appview.example.com").is_err());
+        assert!(normalize_url("ftp://appview.example.com").is_err());
+    }
+
+    #[test]
+    fn normalize_url_allows_local_http() {
+        assert_eq!(
+            normalize_url("http://localhost:2583").unwrap(),
+            "http://localhost:2583"
+        );
+        assert!(normalize_url("http://127.0.0.1:2583").is_ok());
+        assert!(normalize_url("http://[::1]:2583").is_ok());
+    }
+
+    #[test]
+    fn session_token_only_goes_to_the_default_appview() {
+        assert!(accepts_session_token(DEFAULT_APPVIEW_URL));
+        assert!(!accepts_session_token(DEFAULT_PUBLIC_APPVIEW_URL));
+        assert!(!accepts_session_token("https://appview.example.com"));
+    }
+}
@@ -15,11 +15,23 @@ pub enum AppError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Keyring error: {0}")]
     KeyringError(String),
 
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    /// Another command has held the signed-in agent too long; the UI should report
+    /// that something is stuck and let the user retry
+    #[error("App busy: {0}")]
+    Busy(String),
+
+    /// Input the server rejected; `code` tells the UI which field to flag
+    #[error("{message}")]
+    ValidationError { code: &'static str, message: String },
 }
 
 // Serializable error for frontend
@@ -36,8 +48,11 @@ impl From<AppError> for ErrorResponse {
             AppError::SessionNotFound => "SESSION_NOT_FOUND",
             AppError::NetworkError(_) => "NETWORK_ERROR",
             AppError::ApiError(_) => "API_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
             AppError::KeyringError(_) => "KEYRING_ERROR",
             AppError::InternalError(_) => "INTERNAL_ERROR",
+            AppError::Busy(_) => "APP_BUSY",
+            AppError::ValidationError { code, .. } => code,
         };
 
         ErrorResponse {
@@ -71,8 +86,14 @@ impl Clone for AppError {
             AppError::SessionNotFound => AppError::SessionNotFound,
             AppError::NetworkError(s) => AppError::NetworkError(s.clone()),
             AppError::ApiError(s) => AppError::ApiError(s.clone()),
+            AppError::NotFound(s) => AppError::NotFound(s.clone()),
             AppError::KeyringError(s) => AppError::KeyringError(s.clone()),
             AppError::InternalError(s) => AppError::InternalError(s.clone()),
+            AppError::Busy(s) => AppError::Busy(s.clone()),
+            AppError::ValidationError { code, message } => AppError::ValidationError {
+                code,
+                message: message.clone(),
+            },
         }
     }
 }
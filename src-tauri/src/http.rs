@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use atrium_xrpc_client::reqwest::{ReqwestClient, ReqwestClientBuilder};
+
+use crate::error::AppError;
+
+/// Default whole-request timeout for PDS/AppView and media requests
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Bounds accepted from settings
+pub const MIN_REQUEST_TIMEOUT_SECS: u64 = 5;
+pub const MAX_REQUEST_TIMEOUT_SECS: u64 = 300;
+/// Connection setup gets a shorter budget than the whole request
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+/// Shared client; rebuilt when the timeout setting changes
+static CLIENT: LazyLock<RwLock<reqwest::Client>> =
+    LazyLock::new(|| RwLock::new(build_client(DEFAULT_REQUEST_TIMEOUT_SECS)));
+
+fn build_client(timeout_secs: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_default()
+}
+
+pub fn request_timeout_secs() -> u64 {
+    REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed)
+}
+
+/// Apply a new request timeout (clamped to the accepted range) and return the value used
+pub fn set_request_timeout_secs(secs: u64) -> u64 {
+    let secs = secs.clamp(MIN_REQUEST_TIMEOUT_SECS, MAX_REQUEST_TIMEOUT_SECS);
+    REQUEST_TIMEOUT_SECS.store(secs, Ordering::Relaxed);
+    if let Ok(mut client) = CLIENT.write() {
+        *client = build_client(secs);
+    }
+    secs
+}
+
+/// HTTP client with the configured timeout (cheap to clone, shares the connection pool)
+pub fn client() -> reqwest::Client {
+    CLIENT
+        .read()
+        .map(|client| client.clone())
+        .unwrap_or_else(|_| build_client(request_timeout_secs()))
+}
+
+/// Client for long downloads (account export): no whole-request timeout, which a large
+/// repo or blob would outlast, but the connection and each read use the usual limits
+pub fn streaming_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .read_timeout(Duration::from_secs(request_timeout_secs()))
+        .build()
+        .unwrap_or_default()
+}
+
+/// XRPC client for `BskyAgent` using the configured timeout.
+/// The agent keeps the client it was built with, so a changed timeout applies from the
+/// next login/resume.
+pub fn xrpc_client(endpoint: &str) -> ReqwestClient {
+    ReqwestClientBuilder::new(endpoint).client(client()).build()
+}
+
+/// Map a reqwest error to `AppError::NetworkError`, reporting timeouts as "timeout"
+pub fn network_error(context: &str, err: reqwest::Error) -> AppError {
+    if err.is_timeout() {
+        AppError::NetworkError("timeout".into())
+    } else {
+        AppError::NetworkError(format!("{context}: {err}"))
+    }
+}
+
+/// Error body returned by XRPC endpoints called over plain HTTP
+#[derive(serde::Deserialize, Default)]
+pub(crate) struct XrpcErrorBody {
+    #[serde(default)]
+    pub error: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Whether an XRPC transport failure was our request timeout firing
+pub fn is_timeout<E>(err: &atrium_xrpc::Error<E>) -> bool {
+    match err {
+        atrium_xrpc::Error::HttpClient(inner) => inner
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout()),
+        _ => false,
+    }
+}
@@ -1,21 +1,23 @@
 mod commands;
+mod connectivity;
 mod db;
+mod endpoints;
 mod error;
+mod http;
+mod logging;
 mod media;
 mod session;
 mod session_store;
+mod unread;
 
 use commands::auth::AgentState;
 use db::DbState;
 use std::sync::Arc;
-use tauri::{Emitter, Manager};
+use tauri::Manager;
 use tokio::sync::Mutex;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize keyring for persistent credential storage on Linux
-    session::init_keyring();
-
     // Initialize agent state
     let agent_state: AgentState = Arc::new(Mutex::new(None));
 
@@ -30,12 +32,35 @@ pub fn run() {
             commands::auth::logout,
             commands::auth::get_session,
             commands::auth::resume_session,
+            // Account commands
+            commands::account::describe_server,
+            commands::account::check_handle_available,
+            commands::account::create_account,
+            commands::account::request_email_confirmation,
+            commands::account::confirm_email,
+            commands::account::request_password_reset,
+            commands::account::reset_password,
+            commands::account::list_app_passwords,
+            commands::account::create_app_password,
+            commands::account::revoke_app_password,
+            commands::account::deactivate_account,
+            commands::account::request_account_deletion,
+            commands::account::delete_account,
+            commands::account::update_handle,
             // Post actions
             commands::actions::like_post,
             commands::actions::unlike_post,
             commands::actions::repost_post,
             commands::actions::unrepost_post,
             commands::actions::create_post,
+            commands::actions::create_thread,
+            commands::link_cards::fetch_link_card,
+            commands::gifs::search_gifs,
+            commands::gifs::get_gif_search_enabled,
+            commands::gifs::set_tenor_api_key,
+            commands::translate::translate_text,
+            commands::translate::get_translation_backend,
+            commands::translate::set_translation_backend,
             commands::actions::follow_user,
             commands::actions::unfollow_user,
             commands::actions::mute_actor,
@@ -45,20 +70,50 @@ pub fn run() {
             commands::actions::save_post_draft,
             commands::actions::get_post_draft,
             commands::actions::clear_post_draft,
+            commands::actions::save_named_draft,
+            commands::actions::list_drafts,
+            commands::actions::load_draft,
+            commands::actions::delete_draft,
+            // Postgate commands
+            commands::postgate::detach_quote,
+            commands::postgate::reattach_quote,
+            commands::postgate::disable_quotes,
+            commands::postgate::enable_quotes,
+            // Bulk graph commands
+            commands::bulk::bulk_follow,
+            commands::bulk::bulk_unfollow,
+            commands::bulk::resume_bulk_graph_job,
             // Timeline commands
             commands::timeline::get_timeline,
+            commands::timeline::stream_timeline,
+            commands::timeline::fill_timeline_gap,
             commands::timeline::get_profile,
+            commands::timeline::prefetch_profile_header,
+            commands::timeline::get_my_profile,
             commands::timeline::get_followers,
             commands::timeline::get_follows,
+            commands::timeline::get_muted_accounts,
+            commands::timeline::get_blocked_accounts,
             commands::timeline::get_post_thread,
+            commands::timeline::set_thread_read_position,
+            commands::timeline::refresh_viewer_states,
+            commands::timeline::get_post,
             commands::timeline::get_author_feed,
+            commands::timeline::get_last_active,
             // Feeds commands
             commands::feeds::get_suggested_feeds,
+            commands::feeds::search_feed_generators,
             commands::feeds::get_feed,
+            commands::feeds::get_home_config,
+            commands::feeds::reorder_saved_feeds,
+            commands::feeds::get_feed_generators,
             // Lists commands
             commands::lists::get_actor_lists,
+            commands::lists::get_actor_starter_packs,
+            commands::lists::search_lists,
             commands::lists::get_list,
             commands::lists::get_subject_list_memberships,
+            commands::lists::get_lists_with_membership,
             commands::lists::create_list,
             commands::lists::update_list,
             commands::lists::delete_list,
@@ -71,16 +126,83 @@ pub fn run() {
             commands::chat::send_message,
             commands::chat::get_convo_for_members,
             commands::chat::get_convo,
+            commands::chat::hide_convo,
+            commands::chat::unhide_convo,
             commands::chat::update_read,
             commands::chat::get_chat_unread_count,
+            commands::chat::mark_all_convos_read,
+            commands::chat::can_message,
             // Notification commands
             commands::notifications::get_notifications,
             commands::notifications::get_unread_count,
             commands::notifications::mark_notifications_read,
+            commands::notifications::subscribe_to_user_activity,
+            commands::notifications::unsubscribe_to_user_activity,
+            // Moderation commands
+            commands::moderation::report_post,
+            commands::moderation::report_account,
+            commands::moderation::get_content_label_prefs,
+            commands::moderation::set_content_label_pref,
+            commands::moderation::set_adult_content_enabled,
+            commands::moderation::get_labeler_services,
+            // Repo commands
+            commands::repo::get_record,
+            commands::repo::list_my_records,
+            // Feed interaction commands
+            commands::interactions::send_feed_interactions,
+            // Hidden posts commands
+            commands::hidden_posts::hide_post,
+            commands::hidden_posts::unhide_post,
+            // Link commands
+            commands::links::resolve_bsky_url,
+            commands::links::post_share_url,
+            commands::links::profile_share_url,
+            commands::links::resolve_atproto_uri,
+            // Settings commands
+            commands::settings::get_notification_priority,
+            commands::settings::set_notification_priority,
+            commands::settings::get_request_timeout,
+            commands::settings::set_request_timeout,
+            commands::settings::get_endpoints,
+            commands::settings::set_endpoints,
+            commands::settings::get_log_level,
+            commands::settings::set_log_level,
+            commands::settings::get_log_file_path,
+            commands::settings::get_image_policy,
+            commands::settings::set_image_policy,
+            commands::settings::get_blur_sensitive_media,
+            commands::settings::set_blur_sensitive_media,
+            commands::settings::get_media_autoload,
+            commands::settings::set_media_autoload,
+            commands::settings::get_session_backend,
+            commands::settings::set_session_backend,
+            commands::settings::get_retry_max_attempts,
+            commands::settings::set_retry_max_attempts,
+            commands::settings::get_default_post_langs,
+            commands::settings::set_default_post_langs,
+            commands::settings::get_require_alt_text,
+            commands::settings::set_require_alt_text,
             // Search commands
             commands::search::search,
             commands::search::search_actors,
             commands::search::search_posts,
+            commands::search::get_trends,
+            // Export commands
+            commands::export::export_account,
+            commands::export::cancel_account_export,
+            commands::export::export_follows,
+            commands::export::export_followers,
+            commands::local_data::export_local_data,
+            commands::local_data::import_local_data,
+            // Account migration commands
+            commands::migration::migrate_account,
+            commands::migration::get_account_migration,
+            commands::migration::cancel_account_migration,
+            // View history commands
+            commands::history::get_view_history,
+            commands::history::clear_view_history,
+            commands::history::get_view_history_enabled,
+            commands::history::set_view_history_enabled,
             // Window commands
             commands::window::minimize_window,
             commands::window::maximize_window,
@@ -88,31 +210,72 @@ pub fn run() {
             commands::window::is_maximized,
             // System
             commands::system::get_system_theme,
+            commands::system::get_db_recovery,
             // Media
             commands::media::save_image,
             commands::media::save_video,
             commands::media::download_and_save_gif,
             commands::media::get_cached_image,
+            commands::media::preload_avatars,
+            commands::media::load_embed_media,
+            commands::media::rebuild_media_cache,
+            commands::media::cancel_media_cache_rebuild,
+            commands::post_card::render_post_card,
         ])
         .setup(|app| {
+            logging::init(app.path().app_log_dir().ok());
+
+            // Initialize keyring for persistent credential storage on Linux
+            // (before any command can touch the keyring)
+            session::init_keyring();
+            session::init_session_file(app.path().app_data_dir().ok());
+            session::init_session_events(app.handle().clone());
+
             let db_state = tauri::async_runtime::block_on(db::init_db_state(&app.handle()))
                 .map_err(|e| std::io::Error::other(e.to_string()))?;
             app.manage(db_state.clone());
+            tauri::async_runtime::block_on(commands::settings::apply_saved_request_timeout(
+                db_state.as_ref(),
+            ));
+            tauri::async_runtime::block_on(commands::settings::apply_saved_endpoints(
+                db_state.as_ref(),
+            ));
+            tauri::async_runtime::block_on(commands::settings::apply_saved_log_level(
+                db_state.as_ref(),
+            ));
+            tauri::async_runtime::block_on(commands::settings::apply_saved_image_policy(
+                db_state.as_ref(),
+            ));
+            tauri::async_runtime::block_on(
+                commands::settings::apply_saved_blur_sensitive_media(db_state.as_ref()),
+            );
+            tauri::async_runtime::block_on(commands::settings::apply_saved_media_autoload(
+                db_state.as_ref(),
+            ));
+            tauri::async_runtime::block_on(commands::settings::apply_saved_session_backend(
+                db_state.as_ref(),
+            ));
+            if let Ok(session) = session::get_stored_session() {
+                if let Err(err) = tauri::async_runtime::block_on(
+                    commands::actions::claim_legacy_drafts(db_state.as_ref(), &session.did),
+                ) {
+                    tracing::warn!("legacy drafts not claimed: {err}");
+                }
+            }
 
             let handle = app.handle().clone();
             let agent_state = app.state::<AgentState>();
             let agent_state_clone = (*agent_state).clone();
+            let poll_db_state = db_state.clone();
             let retry_agent_state = agent_state_clone.clone();
             let retry_db_state = app.state::<DbState>().inner().clone();
             let retry_handle = handle.clone();
+            let interactions_agent_state = agent_state_clone.clone();
 
-            // Debug-only: print cache directory for media inspection
-            #[cfg(debug_assertions)]
-            {
-                match app.path().app_cache_dir() {
-                    Ok(path) => println!("[debug] app cache dir: {}", path.display()),
-                    Err(err) => println!("[debug] failed to resolve app cache dir: {err}"),
-                }
+            // Cache directory for media inspection
+            match app.path().app_cache_dir() {
+                Ok(path) => tracing::debug!(target: "media", "app cache dir: {}", path.display()),
+                Err(err) => tracing::debug!(target: "media", "failed to resolve app cache dir: {err}"),
             }
 
             // Background polling task (every 3 minutes)
@@ -121,10 +284,15 @@ pub fn run() {
                 loop {
                     interval.tick().await;
 
+                    // Badge follows whichever notification mode is active
+                    let priority =
+                        commands::settings::notification_priority_enabled(poll_db_state.as_ref())
+                            .await;
+
                     // Skip if no session
-                    let guard = agent_state_clone.lock().await;
-                    if let Some(agent) = guard.as_ref() {
+                    if let Ok(agent) = commands::auth::current_agent(&agent_state_clone).await {
                         // Check unread count
+                        let started = std::time::Instant::now();
                         let result = agent
                             .api
                             .app
@@ -133,21 +301,48 @@ pub fn run() {
                             .get_unread_count(
                                 bsky_sdk::api::app::bsky::notification::get_unread_count::ParametersData {
                                     seen_at: None,
-                                    priority: None,
+                                    priority: Some(priority),
                                 }
                                 .into(),
                             )
                             .await;
 
-                        if let Ok(response) = result {
-                            let count = response.data.count as u32;
-                            // Emit event to frontend
-                            let _ = handle.emit("unread-count", count);
+                        match result {
+                            Ok(response) => {
+                                // Emits "unread-count" / "unread_counts_changed" only on change
+                                unread::store_notification_count(
+                                    &handle,
+                                    priority,
+                                    response.data.count as u32,
+                                    started,
+                                );
 
-                            // Update tray icon title/tooltip (if tray exists)
-                            // Note: In a real app we'd construct the tray properly.
-                            // For this MVP we assume the default tray or just rely on the event.
+                                if response.data.count > 0 {
+                                    if let Err(err) =
+                                        commands::notifications::poll_new_notifications(
+                                            &handle,
+                                            &agent_state_clone,
+                                            poll_db_state.as_ref(),
+                                            priority,
+                                        )
+                                        .await
+                                    {
+                                        tracing::debug!("new notification check failed: {err}");
+                                    }
+                                }
+
+                                // Update tray icon title/tooltip (if tray exists)
+                                // Note: In a real app we'd construct the tray properly.
+                                // For this MVP we assume the default tray or just rely on the event.
+                            }
+                            Err(err) if connectivity::is_transport_error(&err) => {
+                                connectivity::schedule_health_check(&handle);
+                            }
+                            Err(_) => {}
                         }
+
+                        // Chat unread rides along so the combined event stays current
+                        let _ = commands::chat::refresh_chat_unread_count(&handle, &agent).await;
                     }
                 }
             });
@@ -164,11 +359,23 @@ pub fn run() {
                     )
                     .await
                     {
-                        eprintln!("[retry-queue] cycle failed: {err}");
+                        tracing::warn!(target: "retry_queue", "cycle failed: {err}");
                     }
                 }
             });
 
+            // Flush queued feed interactions in batches.
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                    commands::interactions::FLUSH_INTERVAL_SECS,
+                ));
+                loop {
+                    interval.tick().await;
+                    commands::interactions::flush_feed_interactions(&interactions_agent_state)
+                        .await;
+                }
+            });
+
             Ok(())
         })
         .run(tauri::generate_context!())
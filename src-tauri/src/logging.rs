@@ -0,0 +1,96 @@
+//! Structured logging.
+//!
+//! Events go to `blue-horizon.log` in the app log dir (and to stdout in debug builds).
+//! Cross-cutting areas log under fixed targets so they can be filtered together:
+//! `auth`, `cache`, `media` and `retry_queue`; everything else uses its module path.
+//! Verbosity is an `EnvFilter` directive string such as `info` or `warn,cache=debug`,
+//! saved in settings and swapped at runtime without a restart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::error::AppError;
+
+pub const DEFAULT_FILTER: &str = "info";
+const LOG_FILE_NAME: &str = "blue-horizon.log";
+/// The previous log is kept as `blue-horizon.log.old` once the file grows past this
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Keeps the background writer alive; dropping it would stop file logging
+static WRITER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+fn parse_filter(directives: &str) -> Result<EnvFilter, AppError> {
+    EnvFilter::try_new(directives.trim())
+        .map_err(|e| AppError::ApiError(format!("Invalid log level: {e}")))
+}
+
+fn rotate_if_large(path: &Path) {
+    if fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_LOG_BYTES) {
+        let _ = fs::rename(path, path.with_extension("log.old"));
+    }
+}
+
+/// Install the global subscriber (called once at startup). Without a log dir only the
+/// console layer is active.
+pub fn init(log_dir: Option<PathBuf>) {
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(DEFAULT_FILTER));
+
+    let file_layer = log_dir.and_then(|dir| {
+        fs::create_dir_all(&dir).ok()?;
+        let path = dir.join(LOG_FILE_NAME);
+        rotate_if_large(&path);
+
+        let (writer, guard) =
+            tracing_appender::non_blocking(tracing_appender::rolling::never(&dir, LOG_FILE_NAME));
+        let _ = WRITER_GUARD.set(guard);
+        let _ = LOG_FILE.set(path);
+        Some(fmt::layer().with_writer(writer).with_ansi(false))
+    });
+
+    // Release builds keep stdout quiet
+    let console_layer = cfg!(debug_assertions).then(fmt::layer);
+
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(console_layer)
+        .try_init()
+        .is_ok()
+    {
+        let _ = FILTER.set(handle);
+    }
+}
+
+/// Swap the active filter; returns the directives as applied
+pub fn set_filter(directives: &str) -> Result<String, AppError> {
+    let filter = parse_filter(directives)?;
+    let applied = filter.to_string();
+
+    let handle = FILTER
+        .get()
+        .ok_or_else(|| AppError::InternalError("logging is not initialized".into()))?;
+    handle
+        .reload(filter)
+        .map_err(|e| AppError::InternalError(format!("log filter reload failed: {e}")))?;
+
+    Ok(applied)
+}
+
+pub fn current_filter() -> String {
+    FILTER
+        .get()
+        .and_then(|handle| handle.with_current(|filter| filter.to_string()).ok())
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string())
+}
+
+pub fn log_file_path() -> Option<PathBuf> {
+    LOG_FILE.get().cloned()
+}
@@ -1,10 +1,14 @@
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex, RwLock};
 
 use bsky_sdk::api::app::bsky::feed::defs::PostView;
+use futures::StreamExt;
+use image::codecs::jpeg::JpegEncoder;
 use image::codecs::webp::WebPEncoder;
-use image::{imageops, ColorType};
+use image::{imageops, ColorType, DynamicImage};
 use infer;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager};
@@ -19,6 +23,221 @@ const MAX_CONCURRENT_DOWNLOADS: usize = 4;
 static DOWNLOAD_SEMAPHORE: LazyLock<Semaphore> =
     LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_DOWNLOADS));
 
+/// Avatars render small, so they are cached at a fixed small size
+const AVATAR_MAX_DIMENSION: u32 = 128;
+/// Files kept per profile image directory before the oldest are evicted
+const MAX_CACHED_PROFILE_IMAGES: usize = 2000;
+/// Run eviction once every this many profile image writes
+const PROFILE_IMAGE_PRUNE_INTERVAL: usize = 50;
+
+/// Profile image keys currently downloading (the same avatar repeats across a feed)
+static PROFILE_IMAGES_IN_FLIGHT: LazyLock<Mutex<HashSet<u64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+static PROFILE_IMAGE_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// How cached post images of one kind are stored
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ImageEncoding {
+    /// Thumbnails are scaled down to at most this width
+    pub thumb_max_width: u32,
+    /// Lossy quality (1-100), stored as JPEG since the WebP encoder is lossless-only;
+    /// `None` stores lossless WebP
+    pub lossy_quality: Option<u8>,
+}
+
+impl ImageEncoding {
+    fn extension(self) -> &'static str {
+        if self.lossy_quality.is_some() {
+            "jpg"
+        } else {
+            "webp"
+        }
+    }
+
+    /// File name tag, so changing the policy misses the old files and re-encodes
+    fn quality_tag(self) -> String {
+        match self.lossy_quality {
+            Some(quality) => format!("q{quality}"),
+            None => "lossless".to_string(),
+        }
+    }
+}
+
+/// Storage policy for post images; photos and graphics (screenshots, drawings,
+/// transparency) compress differently, so each has its own encoding
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ImageCachePolicy {
+    pub photo: ImageEncoding,
+    pub graphic: ImageEncoding,
+}
+
+impl Default for ImageCachePolicy {
+    fn default() -> Self {
+        Self {
+            // Sized for 2x displays; lossy keeps photo caches small at little visible cost
+            photo: ImageEncoding {
+                thumb_max_width: 1024,
+                lossy_quality: Some(85),
+            },
+            graphic: ImageEncoding {
+                thumb_max_width: 1024,
+                lossy_quality: None,
+            },
+        }
+    }
+}
+
+impl ImageCachePolicy {
+    /// JPEG (and other camera formats) are photos; PNG, GIF, WebP and unknown are graphics
+    fn encoding_for(&self, mime: Option<&str>) -> ImageEncoding {
+        match mime {
+            Some("image/jpeg" | "image/heif" | "image/avif") => self.photo,
+            _ => self.graphic,
+        }
+    }
+}
+
+static IMAGE_POLICY: LazyLock<RwLock<ImageCachePolicy>> =
+    LazyLock::new(|| RwLock::new(ImageCachePolicy::default()));
+
+pub fn image_policy() -> ImageCachePolicy {
+    IMAGE_POLICY
+        .read()
+        .map(|policy| *policy)
+        .unwrap_or_default()
+}
+
+pub fn set_image_policy(policy: ImageCachePolicy) {
+    if let Ok(mut current) = IMAGE_POLICY.write() {
+        *current = policy;
+    }
+}
+
+/// Which media downloads as soon as a post or profile is shown (data saver). Anything
+/// switched off comes back deferred, with no request made, until `load_post_embed`.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct MediaAutoload {
+    pub autoload_images: bool,
+    pub autoload_avatars: bool,
+    pub autoload_link_thumbs: bool,
+    pub autoload_video_posters: bool,
+}
+
+impl MediaAutoload {
+    const ALL: Self = Self {
+        autoload_images: true,
+        autoload_avatars: true,
+        autoload_link_thumbs: true,
+        autoload_video_posters: true,
+    };
+}
+
+impl Default for MediaAutoload {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+static MEDIA_AUTOLOAD: RwLock<MediaAutoload> = RwLock::new(MediaAutoload::ALL);
+
+pub fn media_autoload() -> MediaAutoload {
+    MEDIA_AUTOLOAD
+        .read()
+        .map(|autoload| *autoload)
+        .unwrap_or_default()
+}
+
+pub fn set_media_autoload(autoload: MediaAutoload) {
+    if let Ok(mut current) = MEDIA_AUTOLOAD.write() {
+        *current = autoload;
+    }
+}
+
+/// Labels whose media is flagged `sensitive`
+const SENSITIVE_MEDIA_LABELS: [&str; 4] = ["porn", "sexual", "graphic-media", "nudity"];
+
+/// Flag labelled media as sensitive; off shows it unblurred
+static BLUR_SENSITIVE_MEDIA: AtomicBool = AtomicBool::new(true);
+
+pub fn blur_sensitive_media() -> bool {
+    BLUR_SENSITIVE_MEDIA.load(Ordering::Relaxed)
+}
+
+pub fn set_blur_sensitive_media(enabled: bool) {
+    BLUR_SENSITIVE_MEDIA.store(enabled, Ordering::Relaxed);
+}
+
+fn is_sensitive_label(val: &str) -> bool {
+    SENSITIVE_MEDIA_LABELS.contains(&val)
+}
+
+/// Whether a post's or its author's labels call for blurring its media
+fn post_media_sensitive(post: &PostView) -> bool {
+    blur_sensitive_media()
+        && post
+            .labels
+            .iter()
+            .chain(post.author.labels.iter())
+            .flatten()
+            .any(|label| is_sensitive_label(&label.val))
+}
+
+/// Same as `post_media_sensitive` for an embedded record view in JSON form
+fn record_media_sensitive(record: &serde_json::Value) -> bool {
+    let labelled = |labels: Option<&serde_json::Value>| {
+        labels
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|label| label.get("val").and_then(|v| v.as_str()))
+            .any(is_sensitive_label)
+    };
+    blur_sensitive_media()
+        && (labelled(record.get("labels"))
+            || labelled(record.get("author").and_then(|a| a.get("labels"))))
+}
+
+/// Flag the post's own media (not a quoted post's) as sensitive
+fn mark_sensitive(embed: &mut EmbedView) {
+    match embed {
+        EmbedView::Images { images }
+        | EmbedView::RecordWithMedia {
+            media: MediaView::Images { images },
+            ..
+        } => images.iter_mut().for_each(|image| image.sensitive = true),
+        EmbedView::Video { video } => video.sensitive = true,
+        _ => {}
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ProfileImageKind {
+    Avatar,
+    Banner,
+}
+
+impl ProfileImageKind {
+    fn dir_name(self) -> &'static str {
+        match self {
+            ProfileImageKind::Avatar => "avatars",
+            ProfileImageKind::Banner => "banners",
+        }
+    }
+
+    /// Avatars are small and stay lossless; banners are photo-sized and follow the
+    /// photo image policy
+    fn encoding(self) -> ImageEncoding {
+        match self {
+            ProfileImageKind::Avatar => ImageEncoding {
+                thumb_max_width: AVATAR_MAX_DIMENSION,
+                lossy_quality: None,
+            },
+            ProfileImageKind::Banner => image_policy().photo,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct AspectRatio {
     pub width: u32,
@@ -40,6 +259,13 @@ pub struct CachedImage {
     /// Whether this is an animated GIF
     #[serde(default)]
     pub is_gif: bool,
+    /// Labelled as sexual or graphic; blur until tapped
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Not downloaded because image autoload is off; `thumb` and `fullsize` stay empty
+    /// until the post's media is loaded on demand
+    #[serde(default)]
+    pub deferred: bool,
 }
 
 /// Event payload emitted when media finishes downloading
@@ -58,7 +284,12 @@ pub struct ExternalView {
     pub uri: String,
     pub title: String,
     pub description: String,
+    /// Local file:// thumb once cached, otherwise the remote URL (see `media_ready`)
     pub thumb: Option<String>,
+    /// Host of `uri` for display, without a leading "www."
+    pub host: Option<String>,
+    /// The card has a thumb that wasn't loaded because link thumb autoload is off
+    pub thumb_deferred: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -67,6 +298,10 @@ pub struct VideoView {
     pub thumbnail: Option<String>,
     pub alt: Option<String>,
     pub aspect_ratio: Option<AspectRatio>,
+    /// Labelled as sexual or graphic; blur the thumbnail until tapped
+    pub sensitive: bool,
+    /// The video has a poster that wasn't loaded because poster autoload is off
+    pub thumbnail_deferred: bool,
 }
 
 #[derive(Serialize)]
@@ -160,16 +395,83 @@ async fn cache_base_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
     Ok(dir)
 }
 
-fn build_image_paths(base: &Path, key: u64, is_gif: bool) -> (PathBuf, PathBuf) {
+fn build_image_paths(
+    base: &Path,
+    key: u64,
+    is_gif: bool,
+    encoding: ImageEncoding,
+) -> (PathBuf, PathBuf) {
+    let ext = encoding.extension();
+    let quality = encoding.quality_tag();
     let mut thumb = base.to_path_buf();
-    thumb.push(format!("{key}_thumb.webp")); // Thumb is always WebP (static preview)
+    // Thumb is always a static preview, even for GIFs
+    thumb.push(format!(
+        "{key}_thumb_{}_{quality}.{ext}",
+        encoding.thumb_max_width
+    ));
     let mut full = base.to_path_buf();
-    // GIFs keep their original format, others convert to WebP
-    let ext = if is_gif { "gif" } else { "webp" };
-    full.push(format!("{key}_full.{ext}"));
+    // GIFs keep their original format, others are re-encoded per the policy
+    if is_gif {
+        full.push(format!("{key}_full.gif"));
+    } else {
+        full.push(format!("{key}_full_{quality}.{ext}"));
+    }
     (thumb, full)
 }
 
+/// Write an image as lossy JPEG or lossless WebP
+fn encode_image(img: &DynamicImage, encoding: ImageEncoding, path: &Path) -> Result<(), AppError> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| AppError::InternalError(format!("create {}: {e}", path.display())))?;
+    match encoding.lossy_quality {
+        Some(quality) => JpegEncoder::new_with_quality(&mut file, quality.clamp(1, 100))
+            .encode_image(&img.to_rgb8())
+            .map_err(|e| AppError::InternalError(format!("encode {}: {e}", path.display()))),
+        None => WebPEncoder::new_lossless(&mut file)
+            .encode(
+                img.to_rgba8().as_raw(),
+                img.width(),
+                img.height(),
+                ColorType::Rgba8,
+            )
+            .map_err(|e| AppError::InternalError(format!("encode {}: {e}", path.display()))),
+    }
+}
+
+fn resize_thumb(img: &DynamicImage, max_width: u32) -> DynamicImage {
+    if img.width() > max_width {
+        img.resize(max_width, u32::MAX, imageops::FilterType::Triangle)
+    } else {
+        img.clone()
+    }
+}
+
+/// Decode a downloaded post image and write its thumb and fullsize files per `encoding`
+fn write_image_files(
+    bytes: &[u8],
+    is_gif: bool,
+    encoding: ImageEncoding,
+    thumb_path: &Path,
+    full_path: &Path,
+) -> Result<(), AppError> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|e| AppError::InternalError(format!("decode image: {e}")))?;
+
+    // Thumb: scaled to the policy's width (static preview for GIFs)
+    let thumb_img = resize_thumb(&img, encoding.thumb_max_width);
+    encode_image(&thumb_img, encoding, thumb_path)?;
+
+    if is_gif {
+        // GIF: save original bytes directly
+        std::fs::write(full_path, bytes)
+            .map_err(|e| AppError::InternalError(format!("save gif: {e}")))?;
+    } else {
+        encode_image(&img, encoding, full_path)?;
+    }
+
+    Ok(())
+}
+
 fn build_meta_path(base: &Path, key: u64) -> PathBuf {
     let mut meta = base.to_path_buf();
     meta.push(format!("{key}_meta.json"));
@@ -191,19 +493,21 @@ async fn cache_image(
     let meta_path = build_meta_path(&cache_dir, key);
 
     // Download image first to detect type
-    let bytes = reqwest::get(url)
+    let bytes = crate::http::client()
+        .get(url)
+        .send()
         .await
-        .map_err(|e| AppError::NetworkError(format!("fetch image {url}: {e}")))?
+        .map_err(|e| crate::http::network_error(&format!("fetch image {url}"), e))?
         .bytes()
         .await
-        .map_err(|e| AppError::NetworkError(format!("read image {url}: {e}")))?;
+        .map_err(|e| crate::http::network_error(&format!("read image {url}"), e))?;
 
-    // Detect if it's a GIF
-    let is_gif = infer::get(&bytes)
-        .map(|t| t.mime_type() == "image/gif")
-        .unwrap_or(false);
+    // Detect if it's a GIF, and whether it's stored as a photo or a graphic
+    let mime = infer::get(&bytes).map(|t| t.mime_type());
+    let is_gif = mime == Some("image/gif");
+    let encoding = image_policy().encoding_for(mime);
 
-    let (thumb_path, full_path) = build_image_paths(&cache_dir, key, is_gif);
+    let (thumb_path, full_path) = build_image_paths(&cache_dir, key, is_gif, encoding);
 
     // Cache hit reuse (async file check)
     let thumb_exists = tokio::fs::try_exists(&thumb_path).await.unwrap_or(false);
@@ -243,48 +547,14 @@ async fn cache_image(
     let thumb_path_clone = thumb_path.clone();
     let full_path_clone = full_path.clone();
 
-    tokio::task::spawn_blocking(move || -> Result<(), AppError> {
-        let img = image::load_from_memory(&bytes_for_processing)
-            .map_err(|e| AppError::InternalError(format!("decode image: {e}")))?;
-
-        // Thumb: max width 512px, always WebP (static preview for GIFs)
-        let thumb_img = if img.width() > 512 {
-            img.resize(512, u32::MAX, imageops::FilterType::Triangle)
-        } else {
-            img.clone()
-        };
-
-        // Save thumb as WebP
-        let mut thumb_file = std::fs::File::create(&thumb_path_clone)
-            .map_err(|e| AppError::InternalError(format!("create thumb: {e}")))?;
-        WebPEncoder::new_lossless(&mut thumb_file)
-            .encode(
-                thumb_img.to_rgba8().as_raw(),
-                thumb_img.width(),
-                thumb_img.height(),
-                ColorType::Rgba8,
-            )
-            .map_err(|e| AppError::InternalError(format!("encode thumb: {e}")))?;
-
-        if is_gif {
-            // GIF: save original bytes directly
-            std::fs::write(&full_path_clone, &bytes_for_processing)
-                .map_err(|e| AppError::InternalError(format!("save gif: {e}")))?;
-        } else {
-            // Non-GIF: convert to WebP
-            let mut full_file = std::fs::File::create(&full_path_clone)
-                .map_err(|e| AppError::InternalError(format!("create full: {e}")))?;
-            WebPEncoder::new_lossless(&mut full_file)
-                .encode(
-                    img.to_rgba8().as_raw(),
-                    img.width(),
-                    img.height(),
-                    ColorType::Rgba8,
-                )
-                .map_err(|e| AppError::InternalError(format!("encode full: {e}")))?;
-        }
-
-        Ok(())
+    tokio::task::spawn_blocking(move || {
+        write_image_files(
+            &bytes_for_processing,
+            is_gif,
+            encoding,
+            &thumb_path_clone,
+            &full_path_clone,
+        )
     })
     .await
     .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")))??;
@@ -297,15 +567,17 @@ async fn cache_image(
             width: w,
             height: h,
         })),
-        original_mime: infer::get(&bytes).map(|m| m.mime_type().to_string()),
+        original_mime: mime.map(str::to_string),
         suggested_download: if is_gif {
             vec!["gif".to_string()]
         } else {
-            vec!["png".to_string(), "webp".to_string()]
+            vec!["png".to_string(), encoding.extension().to_string()]
         },
         source_url: Some(url.to_string()),
         loading: false,
         is_gif,
+        sensitive: false,
+        deferred: false,
     };
 
     let _ = tokio::fs::write(&meta_path, serde_json::to_vec(&cached).unwrap_or_default()).await;
@@ -329,7 +601,8 @@ fn check_cache_sync(
     // Check metadata first to get is_gif flag
     if let Ok(meta_bytes) = std::fs::read(&meta_path) {
         if let Ok(mut meta) = serde_json::from_slice::<CachedImage>(&meta_bytes) {
-            let (thumb_path, full_path) = build_image_paths(&dir, key, meta.is_gif);
+            let encoding = image_policy().encoding_for(meta.original_mime.as_deref());
+            let (thumb_path, full_path) = build_image_paths(&dir, key, meta.is_gif, encoding);
             if thumb_path.exists() && full_path.exists() {
                 meta.thumb = as_file_url(&thumb_path);
                 meta.fullsize = as_file_url(&full_path);
@@ -353,6 +626,276 @@ pub fn get_cached_image_by_source(url: &str, app: &AppHandle) -> Option<CachedIm
     check_cache_sync(url, app, None, None)
 }
 
+fn profile_image_dir(app: &AppHandle, kind: ProfileImageKind) -> Option<PathBuf> {
+    let mut dir = app.path().app_cache_dir().ok()?;
+    dir.push("media");
+    dir.push(kind.dir_name());
+    Some(dir)
+}
+
+/// Keep only the newest `MAX_CACHED_PROFILE_IMAGES` files in a profile image directory
+fn prune_profile_images(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+
+    if files.len() <= MAX_CACHED_PROFILE_IMAGES {
+        return;
+    }
+
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, path) in files.into_iter().skip(MAX_CACHED_PROFILE_IMAGES) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Where a profile image is cached; banner names carry the policy so a policy change
+/// misses the old files and re-encodes
+fn profile_image_path(dir: &Path, key: u64, kind: ProfileImageKind) -> (PathBuf, ImageEncoding) {
+    let encoding = kind.encoding();
+    let path = match kind {
+        ProfileImageKind::Avatar => dir.join(format!("{key}.webp")),
+        ProfileImageKind::Banner => dir.join(format!(
+            "{key}_{}_{}.{}",
+            encoding.thumb_max_width,
+            encoding.quality_tag(),
+            encoding.extension()
+        )),
+    };
+    (path, encoding)
+}
+
+async fn cache_profile_image(
+    url: &str,
+    dir: &Path,
+    path: &Path,
+    kind: ProfileImageKind,
+    encoding: ImageEncoding,
+) -> Result<(), AppError> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| AppError::InternalError(format!("cache dir create failed: {e}")))?;
+
+    let bytes = crate::http::client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error(&format!("fetch image {url}"), e))?
+        .bytes()
+        .await
+        .map_err(|e| crate::http::network_error(&format!("read image {url}"), e))?;
+
+    let dir_owned = dir.to_path_buf();
+    let path_owned = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<(), AppError> {
+        let img = image::load_from_memory(&bytes)
+            .map_err(|e| AppError::InternalError(format!("decode image: {e}")))?;
+
+        let resized = match kind {
+            ProfileImageKind::Avatar
+                if img.width() > AVATAR_MAX_DIMENSION || img.height() > AVATAR_MAX_DIMENSION =>
+            {
+                img.thumbnail(AVATAR_MAX_DIMENSION, AVATAR_MAX_DIMENSION)
+            }
+            ProfileImageKind::Banner => resize_thumb(&img, encoding.thumb_max_width),
+            _ => img,
+        };
+        encode_image(&resized, encoding, &path_owned)?;
+
+        if PROFILE_IMAGE_WRITES.fetch_add(1, Ordering::Relaxed) % PROFILE_IMAGE_PRUNE_INTERVAL == 0
+        {
+            prune_profile_images(&dir_owned);
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")))?
+}
+
+/// Resolve an avatar/banner URL through the local cache.
+/// Returns the file:// URL when cached; otherwise returns the remote URL and downloads
+/// in the background, emitting "media_ready" keyed by the remote URL when done.
+/// Uncached avatars are `None` while avatar autoload is off.
+pub fn cached_profile_image(
+    url: Option<&str>,
+    kind: ProfileImageKind,
+    app: &AppHandle,
+) -> Option<String> {
+    let url = url?;
+    let Some(dir) = profile_image_dir(app, kind) else {
+        return Some(url.to_string());
+    };
+
+    let key = url_hash(url);
+    let (path, encoding) = profile_image_path(&dir, key, kind);
+    if path.exists() {
+        return Some(as_file_url(&path));
+    }
+    if matches!(kind, ProfileImageKind::Avatar) && !media_autoload().autoload_avatars {
+        return None;
+    }
+
+    let newly_queued = PROFILE_IMAGES_IN_FLIGHT
+        .lock()
+        .map(|mut in_flight| in_flight.insert(key))
+        .unwrap_or(false);
+    if !newly_queued {
+        return Some(url.to_string());
+    }
+
+    let app_handle = app.clone();
+    let url_owned = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) =
+            download_profile_image(&app_handle, url_owned, &dir, &path, key, kind, encoding).await
+        {
+            tracing::warn!(target: "media", "background profile image download failed: {e}")
+        }
+    });
+
+    Some(url.to_string())
+}
+
+/// Download a queued profile image under the shared download limit, release its
+/// in-flight slot and emit "media_ready" keyed by the remote URL
+async fn download_profile_image(
+    app: &AppHandle,
+    url: String,
+    dir: &Path,
+    path: &Path,
+    key: u64,
+    kind: ProfileImageKind,
+    encoding: ImageEncoding,
+) -> Result<(), AppError> {
+    let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
+
+    let result = cache_profile_image(&url, dir, path, kind, encoding).await;
+    if let Ok(mut in_flight) = PROFILE_IMAGES_IN_FLIGHT.lock() {
+        in_flight.remove(&key);
+    }
+    result?;
+
+    let local = as_file_url(path);
+    let event = MediaReadyEvent {
+        source_url: url,
+        thumb: local.clone(),
+        fullsize: local,
+    };
+    let _ = app.emit("media_ready", event);
+    Ok(())
+}
+
+/// Like `cached_profile_image`, but waits for the download so the returned URL is the
+/// local file (used to warm a profile header before navigating to it). An image
+/// already being fetched by another caller keeps its remote URL.
+pub async fn warm_profile_image(
+    url: Option<&str>,
+    kind: ProfileImageKind,
+    app: &AppHandle,
+) -> Result<Option<String>, AppError> {
+    let Some(url) = url else {
+        return Ok(None);
+    };
+    let Some(dir) = profile_image_dir(app, kind) else {
+        return Ok(Some(url.to_string()));
+    };
+
+    let key = url_hash(url);
+    let (path, encoding) = profile_image_path(&dir, key, kind);
+    if path.exists() {
+        return Ok(Some(as_file_url(&path)));
+    }
+
+    let newly_queued = PROFILE_IMAGES_IN_FLIGHT
+        .lock()
+        .map(|mut in_flight| in_flight.insert(key))
+        .unwrap_or(false);
+    if !newly_queued {
+        return Ok(Some(url.to_string()));
+    }
+
+    download_profile_image(app, url.to_string(), &dir, &path, key, kind, encoding).await?;
+    Ok(Some(as_file_url(&path)))
+}
+
+#[derive(Serialize, Default)]
+pub struct AvatarPreloadSummary {
+    /// Already on disk (or being fetched by another caller)
+    pub cached: u32,
+    pub downloaded: u32,
+    pub failed: u32,
+}
+
+/// Warm the avatar cache for a whole list at once (conversations, list members) instead
+/// of one request per rendered row. Duplicate and already cached URLs are skipped; each
+/// download emits "media_ready" like `cached_profile_image`. Returns once all are done.
+/// Nothing is fetched while avatar autoload is off.
+pub async fn preload_avatars(urls: Vec<String>, app: &AppHandle) -> AvatarPreloadSummary {
+    let mut summary = AvatarPreloadSummary::default();
+    if !media_autoload().autoload_avatars {
+        return summary;
+    }
+    let Some(dir) = profile_image_dir(app, ProfileImageKind::Avatar) else {
+        return summary;
+    };
+
+    let mut seen = HashSet::new();
+    let mut queued = Vec::new();
+    for url in urls {
+        let url = url.trim().to_string();
+        if !url.starts_with("http") {
+            continue;
+        }
+        let key = url_hash(&url);
+        if !seen.insert(key) {
+            continue;
+        }
+        let (path, _) = profile_image_path(&dir, key, ProfileImageKind::Avatar);
+        let newly_queued = !path.exists()
+            && PROFILE_IMAGES_IN_FLIGHT
+                .lock()
+                .map(|mut in_flight| in_flight.insert(key))
+                .unwrap_or(false);
+        if newly_queued {
+            queued.push((url, key, path));
+        } else {
+            summary.cached += 1;
+        }
+    }
+
+    let results: Vec<Result<(), AppError>> = futures::stream::iter(queued)
+        .map(|(url, key, path)| {
+            let dir = &dir;
+            async move {
+                let kind = ProfileImageKind::Avatar;
+                download_profile_image(app, url, dir, &path, key, kind, kind.encoding()).await
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    for result in results {
+        match result {
+            Ok(()) => summary.downloaded += 1,
+            Err(e) => {
+                summary.failed += 1;
+                tracing::debug!(target: "media", "avatar preload failed: {e}");
+            }
+        }
+    }
+    summary
+}
+
 /// Create a placeholder image entry with remote URLs (for async loading)
 fn create_placeholder(
     url: &str,
@@ -369,12 +912,174 @@ fn create_placeholder(
         source_url: Some(url.to_string()),
         loading: true,
         is_gif: false, // Unknown until downloaded, defaults to false
+        sensitive: false,
+        deferred: false,
+    }
+}
+
+/// Cached copy of a post image, or a placeholder while it downloads in the background
+/// (emitting "media_ready"). With image autoload off nothing is fetched and the
+/// placeholder is marked deferred.
+fn post_image(
+    url: &str,
+    alt: Option<&str>,
+    aspect_hint: Option<AspectRatio>,
+    autoload: MediaAutoload,
+    app: &AppHandle,
+) -> CachedImage {
+    if let Some(cached) = check_cache_sync(url, app, alt, aspect_hint) {
+        return cached;
+    }
+
+    let mut placeholder = create_placeholder(url, alt, aspect_hint);
+    if !autoload.autoload_images {
+        placeholder.thumb.clear();
+        placeholder.fullsize.clear();
+        placeholder.loading = false;
+        placeholder.deferred = true;
+        return placeholder;
+    }
+
+    // Spawn background task with semaphore-limited concurrency
+    let app_handle = app.clone();
+    let url_owned = url.to_string();
+    let alt_owned = alt.map(|s| s.to_string());
+    tauri::async_runtime::spawn(async move {
+        // Acquire semaphore permit (limits concurrent downloads)
+        let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
+
+        match cache_image(&url_owned, &app_handle, alt_owned.as_deref(), aspect_hint).await {
+            Ok(cached) => {
+                let event = MediaReadyEvent {
+                    source_url: url_owned,
+                    thumb: cached.thumb,
+                    fullsize: cached.fullsize,
+                };
+                let _ = app_handle.emit("media_ready", event);
+            }
+            Err(e) => {
+                tracing::warn!(target: "media", "background media download failed: {e}");
+            }
+        }
+        // Permit is dropped here, allowing next download
+    });
+
+    placeholder
+}
+
+/// Link-card thumbs are stored with the photo policy; the source format isn't known
+/// until downloaded, and card images are overwhelmingly photos
+fn thumb_path(base: &Path, key: u64, encoding: ImageEncoding) -> PathBuf {
+    build_image_paths(base, key, false, encoding).0
+}
+
+/// Download a link-card thumb and store only the thumbnail (no fullsize copy)
+async fn cache_thumb_only(url: &str, app: &AppHandle) -> Result<String, AppError> {
+    let cache_dir = cache_base_dir(app).await?;
+    let encoding = image_policy().photo;
+    let path = thumb_path(&cache_dir, url_hash(url), encoding);
+    if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(as_file_url(&path));
+    }
+
+    let bytes = crate::http::client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| crate::http::network_error(&format!("fetch thumb {url}"), e))?
+        .bytes()
+        .await
+        .map_err(|e| crate::http::network_error(&format!("read thumb {url}"), e))?;
+
+    let path_clone = path.clone();
+    tokio::task::spawn_blocking(move || -> Result<(), AppError> {
+        let img = image::load_from_memory(&bytes)
+            .map_err(|e| AppError::InternalError(format!("decode thumb: {e}")))?;
+        let thumb_img = resize_thumb(&img, encoding.thumb_max_width);
+        encode_image(&thumb_img, encoding, &path_clone)
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("spawn_blocking failed: {e}")))??;
+
+    Ok(as_file_url(&path))
+}
+
+/// Cached link-card thumb if present; otherwise the remote URL is returned and the thumb
+/// downloads in the background, emitting "media_ready" keyed by the remote URL. `None`
+/// when it isn't cached and link thumb autoload is off.
+fn external_thumb(url: &str, autoload: MediaAutoload, app: &AppHandle) -> Option<String> {
+    if let Ok(mut dir) = app.path().app_cache_dir() {
+        dir.push("media");
+        let path = thumb_path(&dir, url_hash(url), image_policy().photo);
+        if path.exists() {
+            return Some(as_file_url(&path));
+        }
+    }
+    if !autoload.autoload_link_thumbs {
+        return None;
+    }
+
+    let app_handle = app.clone();
+    let url_owned = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
+        match cache_thumb_only(&url_owned, &app_handle).await {
+            Ok(local) => {
+                let event = MediaReadyEvent {
+                    source_url: url_owned,
+                    thumb: local.clone(),
+                    fullsize: local,
+                };
+                let _ = app_handle.emit("media_ready", event);
+            }
+            Err(e) => tracing::warn!(target: "media", "background link thumb download failed: {e}"),
+        }
+    });
+
+    Some(url.to_string())
+}
+
+fn display_host(uri: &str) -> Option<String> {
+    let url = reqwest::Url::parse(uri).ok()?;
+    let host = url.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+}
+
+/// Build an external (link card) view, caching its thumb like image embeds
+fn external_view(
+    external: &serde_json::Value,
+    autoload: MediaAutoload,
+    app: &AppHandle,
+) -> ExternalView {
+    let field = |name: &str| {
+        external
+            .get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let uri = field("uri");
+    let remote_thumb = external
+        .get("thumb")
+        .and_then(|v| v.as_str())
+        .filter(|thumb| !thumb.is_empty());
+    let thumb = remote_thumb.and_then(|thumb| external_thumb(thumb, autoload, app));
+
+    ExternalView {
+        host: display_host(&uri),
+        title: field("title"),
+        description: field("description"),
+        thumb_deferred: remote_thumb.is_some() && thumb.is_none(),
+        thumb,
+        uri,
     }
 }
 
 /// Parse nested embeds from a viewRecord's embeds array
 fn parse_nested_embeds(
     embeds_arr: Option<&Vec<serde_json::Value>>,
+    sensitive: bool,
+    autoload: MediaAutoload,
     app: &AppHandle,
 ) -> Vec<NestedEmbed> {
     let Some(arr) = embeds_arr else {
@@ -404,43 +1109,13 @@ fn parse_nested_embeds(
                             });
                             let alt = img.get("alt").and_then(|v| v.as_str());
 
-                            if let Some(cached) = check_cache_sync(full_url, app, alt, aspect_hint)
-                            {
-                                images.push(cached);
-                            } else {
-                                images.push(create_placeholder(full_url, alt, aspect_hint));
-
-                                // Spawn background download
-                                let app_handle = app.clone();
-                                let url_owned = full_url.to_string();
-                                let alt_owned = alt.map(|s| s.to_string());
-                                tauri::async_runtime::spawn(async move {
-                                    let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
-                                    match cache_image(
-                                        &url_owned,
-                                        &app_handle,
-                                        alt_owned.as_deref(),
-                                        aspect_hint,
-                                    )
-                                    .await
-                                    {
-                                        Ok(cached) => {
-                                            let event = MediaReadyEvent {
-                                                source_url: url_owned,
-                                                thumb: cached.thumb,
-                                                fullsize: cached.fullsize,
-                                            };
-                                            let _ = app_handle.emit("media_ready", event);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Background media download failed: {e}")
-                                        }
-                                    }
-                                });
-                            }
+                            images.push(post_image(full_url, alt, aspect_hint, autoload, app));
                         }
                     }
                     if !images.is_empty() {
+                        images
+                            .iter_mut()
+                            .for_each(|image| image.sensitive = sensitive);
                         result.push(NestedEmbed::Images { images });
                     }
                 }
@@ -448,27 +1123,7 @@ fn parse_nested_embeds(
             "app.bsky.embed.external#view" => {
                 if let Some(external) = embed_item.get("external") {
                     result.push(NestedEmbed::External {
-                        external: ExternalView {
-                            uri: external
-                                .get("uri")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            title: external
-                                .get("title")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            description: external
-                                .get("description")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            thumb: external
-                                .get("thumb")
-                                .and_then(|v| v.as_str())
-                                .map(String::from),
-                        },
+                        external: external_view(external, autoload, app),
                     });
                 }
             }
@@ -480,9 +1135,40 @@ fn parse_nested_embeds(
 
 /// Process post embed - returns immediately with cached or placeholder data.
 /// Spawns background tasks to download uncached images and emits "media_ready" events.
+/// Media of posts carrying a sensitive label comes back flagged `sensitive`. Media
+/// kinds whose autoload is off come back deferred instead of downloading.
 pub async fn process_post_embed(
     post: &PostView,
     app: &AppHandle,
+) -> Result<Option<EmbedView>, AppError> {
+    moderated_embed(post, media_autoload(), app).await
+}
+
+/// Like `process_post_embed`, but downloads everything regardless of the autoload
+/// settings (the user asked for this post's media)
+pub async fn load_post_embed(
+    post: &PostView,
+    app: &AppHandle,
+) -> Result<Option<EmbedView>, AppError> {
+    moderated_embed(post, MediaAutoload::ALL, app).await
+}
+
+async fn moderated_embed(
+    post: &PostView,
+    autoload: MediaAutoload,
+    app: &AppHandle,
+) -> Result<Option<EmbedView>, AppError> {
+    let mut embed = build_post_embed(post, autoload, app).await?;
+    if let Some(embed) = embed.as_mut().filter(|_| post_media_sensitive(post)) {
+        mark_sensitive(embed);
+    }
+    Ok(embed)
+}
+
+async fn build_post_embed(
+    post: &PostView,
+    autoload: MediaAutoload,
+    app: &AppHandle,
 ) -> Result<Option<EmbedView>, AppError> {
     let embed_value = serde_json::to_value(&post.embed)
         .map_err(|e| AppError::InternalError(format!("embed serialize error: {e}")))?;
@@ -516,45 +1202,7 @@ pub async fn process_post_embed(
 
                     let alt = item.get("alt").and_then(|v| v.as_str());
 
-                    // Check if already cached (quick sync check)
-                    if let Some(cached) = check_cache_sync(full_url, app, alt, aspect_hint) {
-                        images.push(cached);
-                    } else {
-                        // Return placeholder and spawn background download
-                        let placeholder = create_placeholder(full_url, alt, aspect_hint);
-                        images.push(placeholder);
-
-                        // Spawn background task with semaphore-limited concurrency
-                        let app_handle = app.clone();
-                        let url_owned = full_url.to_string();
-                        let alt_owned = alt.map(|s| s.to_string());
-                        tauri::async_runtime::spawn(async move {
-                            // Acquire semaphore permit (limits concurrent downloads)
-                            let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
-
-                            match cache_image(
-                                &url_owned,
-                                &app_handle,
-                                alt_owned.as_deref(),
-                                aspect_hint,
-                            )
-                            .await
-                            {
-                                Ok(cached) => {
-                                    let event = MediaReadyEvent {
-                                        source_url: url_owned,
-                                        thumb: cached.thumb,
-                                        fullsize: cached.fullsize,
-                                    };
-                                    let _ = app_handle.emit("media_ready", event);
-                                }
-                                Err(e) => {
-                                    eprintln!("Background media download failed: {e}");
-                                }
-                            }
-                            // Permit is dropped here, allowing next download
-                        });
-                    }
+                    images.push(post_image(full_url, alt, aspect_hint, autoload, app));
                 }
             }
 
@@ -565,29 +1213,9 @@ pub async fn process_post_embed(
                 .get("external")
                 .ok_or_else(|| AppError::InternalError("external missing".into()))?;
 
-            let external = ExternalView {
-                uri: external_val
-                    .get("uri")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-                title: external_val
-                    .get("title")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-                description: external_val
-                    .get("description")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
-                thumb: external_val
-                    .get("thumb")
-                    .and_then(|v| v.as_str())
-                    .map(String::from),
-            };
-
-            Ok(Some(EmbedView::External { external }))
+            Ok(Some(EmbedView::External {
+                external: external_view(external_val, autoload, app),
+            }))
         }
         "app.bsky.embed.video#view" => {
             let aspect_hint = embed_value.get("aspectRatio").and_then(|ratio| {
@@ -599,21 +1227,23 @@ pub async fn process_post_embed(
                 })
             });
 
+            let thumbnail = embed_value.get("thumbnail").and_then(|v| v.as_str());
             let video = VideoView {
                 playlist: embed_value
                     .get("playlist")
                     .and_then(|v| v.as_str())
                     .unwrap_or("")
                     .to_string(),
-                thumbnail: embed_value
-                    .get("thumbnail")
-                    .and_then(|v| v.as_str())
+                thumbnail: thumbnail
+                    .filter(|_| autoload.autoload_video_posters)
                     .map(String::from),
                 alt: embed_value
                     .get("alt")
                     .and_then(|v| v.as_str())
                     .map(String::from),
                 aspect_ratio: aspect_hint,
+                sensitive: false,
+                thumbnail_deferred: thumbnail.is_some() && !autoload.autoload_video_posters,
             };
 
             Ok(Some(EmbedView::Video { video }))
@@ -662,6 +1292,7 @@ pub async fn process_post_embed(
                             avatar: author_val
                                 .and_then(|a| a.get("avatar"))
                                 .and_then(|v| v.as_str())
+                                .filter(|_| autoload.autoload_avatars)
                                 .map(String::from),
                         },
                         value: RecordViewValue {
@@ -683,6 +1314,8 @@ pub async fn process_post_embed(
                             .to_string(),
                         embeds: parse_nested_embeds(
                             record_val.get("embeds").and_then(|v| v.as_array()),
+                            record_media_sensitive(record_val),
+                            autoload,
                             app,
                         ),
                     }
@@ -755,6 +1388,7 @@ pub async fn process_post_embed(
                             avatar: author_val
                                 .and_then(|a| a.get("avatar"))
                                 .and_then(|v| v.as_str())
+                                .filter(|_| autoload.autoload_avatars)
                                 .map(String::from),
                         },
                         value: RecordViewValue {
@@ -776,6 +1410,8 @@ pub async fn process_post_embed(
                             .to_string(),
                         embeds: parse_nested_embeds(
                             record_val.get("embeds").and_then(|v| v.as_array()),
+                            record_media_sensitive(record_val),
+                            autoload,
                             app,
                         ),
                     }
@@ -833,41 +1469,7 @@ pub async fn process_post_embed(
 
                             let alt = item.get("alt").and_then(|v| v.as_str());
 
-                            if let Some(cached) = check_cache_sync(full_url, app, alt, aspect_hint)
-                            {
-                                images.push(cached);
-                            } else {
-                                let placeholder = create_placeholder(full_url, alt, aspect_hint);
-                                images.push(placeholder);
-
-                                let app_handle = app.clone();
-                                let url_owned = full_url.to_string();
-                                let alt_owned = alt.map(|s| s.to_string());
-                                tauri::async_runtime::spawn(async move {
-                                    let _permit = DOWNLOAD_SEMAPHORE.acquire().await;
-
-                                    match cache_image(
-                                        &url_owned,
-                                        &app_handle,
-                                        alt_owned.as_deref(),
-                                        aspect_hint,
-                                    )
-                                    .await
-                                    {
-                                        Ok(cached) => {
-                                            let event = MediaReadyEvent {
-                                                source_url: url_owned,
-                                                thumb: cached.thumb,
-                                                fullsize: cached.fullsize,
-                                            };
-                                            let _ = app_handle.emit("media_ready", event);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Background media download failed: {e}");
-                                        }
-                                    }
-                                });
-                            }
+                            images.push(post_image(full_url, alt, aspect_hint, autoload, app));
                         }
                     }
                     MediaView::Images { images }
@@ -878,27 +1480,7 @@ pub async fn process_post_embed(
                         .ok_or_else(|| AppError::InternalError("media external missing".into()))?;
 
                     MediaView::External {
-                        external: ExternalView {
-                            uri: external_val
-                                .get("uri")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            title: external_val
-                                .get("title")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            description: external_val
-                                .get("description")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                            thumb: external_val
-                                .get("thumb")
-                                .and_then(|v| v.as_str())
-                                .map(String::from),
-                        },
+                        external: external_view(external_val, autoload, app),
                     }
                 }
                 _ => {
@@ -911,3 +1493,302 @@ pub async fn process_post_embed(
         _ => Ok(None),
     }
 }
+
+/// Counts reported while rebuilding the media cache
+#[derive(Serialize, Clone, Default)]
+pub struct MediaCacheRebuildProgress {
+    /// Cache entries (an image's thumb/full/meta set, or a link-card thumb) looked at
+    pub processed: u32,
+    pub total: u32,
+    /// Entries that decoded and already matched the current policy
+    pub valid: u32,
+    /// Entries re-encoded to the current policy
+    pub reencoded: u32,
+    /// Corrupt or orphaned entries deleted; they download again when next shown
+    pub removed: u32,
+    pub cancelled: bool,
+}
+
+/// Emit a progress event every this many entries
+const REBUILD_PROGRESS_EVERY: u32 = 25;
+
+enum RebuildOutcome {
+    Valid,
+    Reencoded,
+    Removed,
+}
+
+fn remove_files(files: &[PathBuf]) {
+    for file in files {
+        if let Err(err) = std::fs::remove_file(file) {
+            tracing::warn!(target: "media", "remove {} failed: {err}", file.display());
+        }
+    }
+}
+
+/// Validate one image's cache set against its meta and re-encode it to `encoding`
+/// from the fullsize copy when its files don't match the current policy
+fn rebuild_image_entry(
+    base: &Path,
+    key: u64,
+    meta_path: &Path,
+    files: &[PathBuf],
+) -> RebuildOutcome {
+    let meta = std::fs::read(meta_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<CachedImage>(&bytes).ok());
+    let Some(mut meta) = meta else {
+        remove_files(files);
+        return RebuildOutcome::Removed;
+    };
+
+    let encoding = image_policy().encoding_for(meta.original_mime.as_deref());
+    let (thumb_path, full_path) = build_image_paths(base, key, meta.is_gif, encoding);
+
+    // Re-encode from the current fullsize, or from one left by an earlier policy
+    let full_prefix = format!("{key}_full");
+    let full_source = files.iter().find(|file| **file == full_path).or_else(|| {
+        files.iter().find(|file| {
+            file.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&full_prefix))
+        })
+    });
+    let Some(img) = full_source.and_then(|source| image::open(source).ok()) else {
+        remove_files(files);
+        return RebuildOutcome::Removed;
+    };
+
+    let thumb_ok = thumb_path.exists() && image::open(&thumb_path).is_ok();
+    if thumb_ok && full_path.exists() {
+        remove_files(
+            &files
+                .iter()
+                .filter(|file| **file != thumb_path && **file != full_path && **file != meta_path)
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        return RebuildOutcome::Valid;
+    }
+
+    let reencoded = encode_image(
+        &resize_thumb(&img, encoding.thumb_max_width),
+        encoding,
+        &thumb_path,
+    )
+    .and_then(|()| {
+        if full_path.exists() {
+            Ok(())
+        } else {
+            // A GIF's full path never changes, so only re-encoded formats get here
+            encode_image(&img, encoding, &full_path)
+        }
+    });
+    if let Err(err) = reencoded {
+        tracing::warn!(target: "media", "re-encode of cache entry {key} failed: {err}");
+        remove_files(files);
+        let _ = std::fs::remove_file(&thumb_path);
+        let _ = std::fs::remove_file(&full_path);
+        return RebuildOutcome::Removed;
+    }
+
+    remove_files(
+        &files
+            .iter()
+            .filter(|file| **file != thumb_path && **file != full_path && **file != meta_path)
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+    meta.thumb = as_file_url(&thumb_path);
+    meta.fullsize = as_file_url(&full_path);
+    if !meta.is_gif {
+        meta.suggested_download = vec!["png".to_string(), encoding.extension().to_string()];
+    }
+    let _ = std::fs::write(meta_path, serde_json::to_vec(&meta).unwrap_or_default());
+    RebuildOutcome::Reencoded
+}
+
+/// Link-card thumbs are stored without meta or fullsize; re-encode them from themselves
+fn rebuild_thumb_entry(base: &Path, key: u64, files: &[PathBuf]) -> RebuildOutcome {
+    let encoding = image_policy().photo;
+    let expected = thumb_path(base, key, encoding);
+    let source = files
+        .iter()
+        .find(|file| **file == expected)
+        .or_else(|| files.first());
+    let Some(img) = source.and_then(|source| image::open(source).ok()) else {
+        remove_files(files);
+        return RebuildOutcome::Removed;
+    };
+
+    let outcome = if source == Some(&expected) {
+        RebuildOutcome::Valid
+    } else {
+        match encode_image(
+            &resize_thumb(&img, encoding.thumb_max_width),
+            encoding,
+            &expected,
+        ) {
+            Ok(()) => RebuildOutcome::Reencoded,
+            Err(err) => {
+                tracing::warn!(target: "media", "re-encode of link thumb {key} failed: {err}");
+                remove_files(files);
+                return RebuildOutcome::Removed;
+            }
+        }
+    };
+    remove_files(
+        &files
+            .iter()
+            .filter(|file| **file != expected)
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+    outcome
+}
+
+/// Walk the post media cache, drop entries that no longer decode or have lost their
+/// meta/fullsize, and re-encode the rest to the current `ImageCachePolicy`. Blocking;
+/// checks `cancelled` between entries and reports through `on_progress`.
+pub(crate) fn rebuild_media_cache_blocking(
+    app: &AppHandle,
+    cancelled: &AtomicBool,
+    mut on_progress: impl FnMut(&MediaCacheRebuildProgress),
+) -> Result<MediaCacheRebuildProgress, AppError> {
+    let mut base = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::InternalError(format!("cache dir not available: {e}")))?;
+    base.push("media");
+
+    let mut progress = MediaCacheRebuildProgress::default();
+    let Ok(entries) = std::fs::read_dir(&base) else {
+        return Ok(progress);
+    };
+
+    // Group files into cache entries by their hash key; avatar/banner subdirectories
+    // are managed separately and left alone
+    let mut groups: std::collections::BTreeMap<u64, Vec<PathBuf>> =
+        std::collections::BTreeMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let key = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('_').next())
+            .and_then(|key| key.parse::<u64>().ok());
+        match key {
+            Some(key) => groups.entry(key).or_default().push(path),
+            None => tracing::debug!(target: "media", "ignoring {} in media cache", path.display()),
+        }
+    }
+
+    progress.total = groups.len() as u32;
+    on_progress(&progress);
+
+    for (key, files) in groups {
+        if cancelled.load(Ordering::SeqCst) {
+            progress.cancelled = true;
+            break;
+        }
+
+        let meta_path = build_meta_path(&base, key);
+        let has_full = files.iter().any(|file| {
+            file.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.contains("_full"))
+        });
+        let outcome = if files.contains(&meta_path) || has_full {
+            rebuild_image_entry(&base, key, &meta_path, &files)
+        } else {
+            rebuild_thumb_entry(&base, key, &files)
+        };
+        match outcome {
+            RebuildOutcome::Valid => progress.valid += 1,
+            RebuildOutcome::Reencoded => progress.reencoded += 1,
+            RebuildOutcome::Removed => progress.removed += 1,
+        }
+
+        progress.processed += 1;
+        if progress.processed % REBUILD_PROGRESS_EVERY == 0 {
+            on_progress(&progress);
+        }
+    }
+
+    on_progress(&progress);
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba([30, 120, 200, 255]),
+        ));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    fn written(path: &Path) -> (Option<&'static str>, (u32, u32)) {
+        let bytes = std::fs::read(path).unwrap();
+        let mime = infer::get(&bytes).map(|t| t.mime_type());
+        (mime, image::image_dimensions(path).unwrap())
+    }
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blue-horizon-media-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn policy_picks_encoding_by_source_format() {
+        let policy = ImageCachePolicy::default();
+        assert!(policy.encoding_for(Some("image/jpeg")) == policy.photo);
+        assert!(policy.encoding_for(Some("image/png")) == policy.graphic);
+        assert!(policy.encoding_for(None) == policy.graphic);
+    }
+
+    #[test]
+    fn lossy_policy_writes_sized_jpegs() {
+        let dir = scratch_dir();
+        let encoding = ImageEncoding {
+            thumb_max_width: 120,
+            lossy_quality: Some(70),
+        };
+        let (thumb, full) = build_image_paths(&dir, 1, false, encoding);
+        write_image_files(&png_bytes(480, 240), false, encoding, &thumb, &full).unwrap();
+
+        assert_eq!(written(&thumb), (Some("image/jpeg"), (120, 60)));
+        assert_eq!(written(&full), (Some("image/jpeg"), (480, 240)));
+        assert!(thumb.to_string_lossy().ends_with("_thumb_120_q70.jpg"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn lossless_policy_writes_webp_and_keeps_small_images() {
+        let dir = scratch_dir();
+        let encoding = ImageEncoding {
+            thumb_max_width: 640,
+            lossy_quality: None,
+        };
+        let (thumb, full) = build_image_paths(&dir, 2, false, encoding);
+        write_image_files(&png_bytes(300, 200), false, encoding, &thumb, &full).unwrap();
+
+        assert_eq!(written(&thumb), (Some("image/webp"), (300, 200)));
+        assert_eq!(written(&full), (Some("image/webp"), (300, 200)));
+        assert!(full.to_string_lossy().ends_with("_full_lossless.webp"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
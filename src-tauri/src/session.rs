@@ -1,5 +1,15 @@
 use crate::error::AppError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
 
 const SERVICE_NAME: &str = "blue-horizon";
 const SESSION_KEY: &str = "session";
@@ -11,6 +21,30 @@ pub struct StoredSession {
     pub access_jwt: String,
     pub refresh_jwt: String,
     pub service_url: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// `None` until the server has told us (sessions stored by older versions)
+    #[serde(default)]
+    pub email_confirmed: Option<bool>,
+}
+
+/// OAuth scope granting DM access
+const OAUTH_CHAT_SCOPE: &str = "transition:chat.bsky";
+
+/// How the session was signed in, read from the access token's `scope` claim
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// Account password; full access
+    Password,
+    /// App password without DM access
+    AppPassword,
+    /// App password created with "Allow access to your direct messages"
+    AppPasswordPrivileged,
+    #[serde(rename = "oauth")]
+    OAuth,
+    /// Token scope this app doesn't recognize
+    Unknown,
 }
 
 #[derive(Clone, Serialize)]
@@ -19,19 +53,111 @@ pub struct SessionInfo {
     pub handle: String,
     pub service_url: String,
     pub is_authenticated: bool,
+    pub email: Option<String>,
+    pub email_confirmed: Option<bool>,
+    pub auth_method: AuthMethod,
+    /// Scopes granted to the access token
+    pub scopes: Vec<String>,
+    /// Whether direct messages are available with this session
+    pub can_chat: bool,
+    /// `false` when the session couldn't be saved and will be gone after a restart
+    pub persisted: bool,
 }
 
 impl From<&StoredSession> for SessionInfo {
     fn from(session: &StoredSession) -> Self {
+        let scopes = session.scopes();
+        let auth_method = auth_method(&scopes);
         SessionInfo {
             did: session.did.clone(),
             handle: session.handle.clone(),
             service_url: session.service_url.clone(),
             is_authenticated: true,
+            email: session.email.clone(),
+            email_confirmed: session.email_confirmed,
+            auth_method,
+            can_chat: can_chat(auth_method, &scopes),
+            scopes,
+            persisted: session_persisted(),
         }
     }
 }
 
+impl StoredSession {
+    /// Space-separated `scope` claim of the access token. The token is only decoded, not
+    /// verified: the PDS enforces scopes, this just explains them up front.
+    pub fn scopes(&self) -> Vec<String> {
+        jwt_claims(&self.access_jwt)
+            .as_ref()
+            .and_then(|claims| claims.get("scope"))
+            .and_then(|scope| scope.as_str())
+            .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Claims of a JWT, decoded without verifying the signature
+fn jwt_claims(jwt: &str) -> Option<serde_json::Value> {
+    jwt.split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok())
+        .and_then(|json| serde_json::from_slice(&json).ok())
+}
+
+/// Whether a JWT's `exp` claim falls within `margin_secs` from now. A token without a
+/// readable `exp` is treated as still valid; the server has the final say.
+pub(crate) fn jwt_expires_within(jwt: &str, margin_secs: i64) -> bool {
+    jwt_claims(jwt)
+        .and_then(|claims| claims.get("exp").and_then(|exp| exp.as_i64()))
+        .is_some_and(|exp| exp - chrono::Utc::now().timestamp() < margin_secs)
+}
+
+fn auth_method(scopes: &[String]) -> AuthMethod {
+    let has = |scope: &str| scopes.iter().any(|s| s == scope);
+    if has("com.atproto.access") {
+        AuthMethod::Password
+    } else if has("com.atproto.appPassPrivileged") {
+        AuthMethod::AppPasswordPrivileged
+    } else if has("com.atproto.appPass") {
+        AuthMethod::AppPassword
+    } else if has("atproto") {
+        AuthMethod::OAuth
+    } else {
+        AuthMethod::Unknown
+    }
+}
+
+fn can_chat(method: AuthMethod, scopes: &[String]) -> bool {
+    match method {
+        AuthMethod::AppPassword => false,
+        AuthMethod::OAuth => scopes.iter().any(|s| s == OAUTH_CHAT_SCOPE),
+        // Unknown scopes are left for the server to judge
+        _ => true,
+    }
+}
+
+/// Fail DM commands up front when the session can't use chat, instead of surfacing the
+/// server's bare "Bad token scope"
+pub fn require_chat_scope() -> Result<(), AppError> {
+    let session = get_stored_session()?;
+    let scopes = session.scopes();
+    match auth_method(&scopes) {
+        method if can_chat(method, &scopes) => Ok(()),
+        AuthMethod::OAuth => Err(AppError::ValidationError {
+            code: "CHAT_SCOPE_MISSING",
+            message: "This sign-in didn't grant access to direct messages; sign in again \
+                      and allow DM access"
+                .into(),
+        }),
+        _ => Err(AppError::ValidationError {
+            code: "CHAT_SCOPE_MISSING",
+            message: "This app password can't access direct messages; create one with \
+                      \"Allow access to your direct messages\" enabled and sign in with it"
+                .into(),
+        }),
+    }
+}
+
 /// Initialize keyring to use persistent storage on Linux
 /// This must be called early in the application startup
 #[cfg(target_os = "linux")]
@@ -43,15 +169,16 @@ pub fn init_keyring() {
 
     // CredentialPersistence values: 0 = EntryOnly, 1 = ProcessOnly, 2 = UntilLogout, 3 = UntilDelete
     let persistence = builder.persistence();
-    println!(
-        "Keyring persistence level: {} (3=UntilDelete is persistent)",
+    tracing::debug!(
+        target: "auth",
+        "keyring persistence level: {} (3=UntilDelete is persistent)",
         persistence as u8
     );
 
     // Set the default credential builder for all Entry::new() calls
     keyring::set_default_credential_builder(Box::new(builder));
 
-    println!("Keyring initialized. Target collection: 'default' (persistent)");
+    tracing::debug!(target: "auth", "keyring initialized, target collection 'default' (persistent)");
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -59,83 +186,499 @@ pub fn init_keyring() {
     // No special initialization needed on other platforms
 }
 
-/// Store session credentials securely in the OS keyring
-pub fn store_session(session: &StoredSession) -> Result<(), AppError> {
-    println!("Storing session for user: {}", session.handle);
+/// Where the session is kept
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionBackend {
+    /// The OS keyring, or the encrypted file once the keyring turns out to be unavailable
+    #[default]
+    Auto,
+    Keyring,
+    /// For systems without a keyring daemon (headless, minimal window managers)
+    EncryptedFile,
+}
 
-    let json =
-        serde_json::to_string(session).map_err(|e| AppError::InternalError(e.to_string()))?;
+static SESSION_BACKEND: RwLock<SessionBackend> = RwLock::new(SessionBackend::Auto);
+/// Set in `Auto` mode after the keyring failed with a platform error
+static KEYRING_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+/// App data directory holding the encrypted session file
+static SESSION_FILE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// The session last stored or loaded. Reads are served from here, which also keeps the
+/// app signed in for this run when the backend failed to persist it.
+static CURRENT_SESSION: RwLock<Option<StoredSession>> = RwLock::new(None);
+/// Why the last session write didn't persist; cleared by the next good write
+static PERSISTENCE_FAILURE: RwLock<Option<String>> = RwLock::new(None);
+/// For `session_persistence_failed`, which can come from a token refresh deep in the SDK
+static EVENT_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+const SESSION_FILE_NAME: &str = "session.enc";
+/// Domain separator for the file key; bump to invalidate files written by a broken scheme
+const SESSION_FILE_KEY_CONTEXT: &[u8] = b"blue-horizon session file v1";
+
+pub fn session_backend() -> SessionBackend {
+    *SESSION_BACKEND.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_session_backend(backend: SessionBackend) {
+    *SESSION_BACKEND.write().unwrap_or_else(|e| e.into_inner()) = backend;
+}
+
+/// Call once at startup with the app data directory
+pub fn init_session_file(dir: Option<PathBuf>) {
+    if let Some(dir) = dir {
+        let _ = SESSION_FILE_DIR.set(dir);
+    }
+}
+
+/// Call once at startup so persistence failures reach the frontend
+pub fn init_session_events(app: AppHandle) {
+    let _ = EVENT_HANDLE.set(app);
+}
+
+/// Whether the current session was saved and will survive a restart
+pub fn session_persisted() -> bool {
+    PERSISTENCE_FAILURE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_none()
+}
+
+#[derive(Serialize, Clone)]
+struct SessionPersistenceFailed {
+    did: String,
+    message: String,
+}
+
+/// Record a failed write and emit `session_persistence_failed` the first time it happens
+fn report_persistence_failure(did: &str, err: &AppError) {
+    let message = err.to_string();
+    let previous = PERSISTENCE_FAILURE
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(message.clone());
+    if previous.as_deref() == Some(message.as_str()) {
+        return;
+    }
+    if let Some(app) = EVENT_HANDLE.get() {
+        let event = SessionPersistenceFailed {
+            did: did.to_string(),
+            message,
+        };
+        if let Err(e) = app.emit("session_persistence_failed", event) {
+            tracing::warn!(target: "auth", "emit persistence failure failed: {e}");
+        }
+    }
+}
+
+fn set_current_session(session: Option<StoredSession>) {
+    *CURRENT_SESSION.write().unwrap_or_else(|e| e.into_inner()) = session;
+}
+
+/// The backend session reads and writes go to right now: `Keyring` or `EncryptedFile`
+pub fn active_session_backend() -> SessionBackend {
+    match session_backend() {
+        SessionBackend::Auto if KEYRING_UNAVAILABLE.load(Ordering::Relaxed) => {
+            SessionBackend::EncryptedFile
+        }
+        SessionBackend::Auto => SessionBackend::Keyring,
+        backend => backend,
+    }
+}
 
-    println!("Session JSON size: {} bytes", json.len());
+/// Whether the keyring failed to reach its platform store (as opposed to missing entries)
+fn keyring_unavailable(err: &keyring::Error) -> bool {
+    matches!(
+        err,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+/// In `Auto` mode, switch to the encrypted file after a platform failure
+fn fall_back_from_keyring(err: &keyring::Error) -> bool {
+    if session_backend() == SessionBackend::Auto && keyring_unavailable(err) {
+        tracing::warn!(target: "auth", "keyring unavailable, using the encrypted session file: {err}");
+        KEYRING_UNAVAILABLE.store(true, Ordering::Relaxed);
+        return true;
+    }
+    false
+}
 
+fn keyring_entry() -> Result<keyring::Entry, keyring::Error> {
     // Use new_with_target to explicitly specify the "default" collection
     // This ensures the credential is stored in the persistent collection
-    let entry =
-        keyring::Entry::new_with_target("default", SERVICE_NAME, SESSION_KEY).map_err(|e| {
-            println!("Failed to create keyring entry: {}", e);
-            AppError::KeyringError(e.to_string())
-        })?;
-
-    match entry.set_password(&json) {
-        Ok(_) => {
-            println!("Session stored successfully in keyring");
-            // Verify by reading it back immediately
-            match entry.get_password() {
-                Ok(retrieved) if retrieved == json => {
-                    println!("Verification: Successfully retrieved stored session");
-                }
-                Ok(retrieved) => {
-                    println!("Warning: Retrieved data differs from stored data (stored: {}, retrieved: {})", json.len(), retrieved.len());
-                }
-                Err(e) => {
-                    println!("Warning: Could not verify stored session: {}", e);
-                }
+    keyring::Entry::new_with_target("default", SERVICE_NAME, SESSION_KEY)
+}
+
+fn keyring_store(json: &str) -> Result<(), keyring::Error> {
+    keyring_entry()?.set_password(json)?;
+    tracing::debug!(target: "auth", "session stored in keyring");
+    Ok(())
+}
+
+/// `Ok(None)` when there is no stored session
+fn keyring_read() -> Result<Option<String>, keyring::Error> {
+    match keyring_entry()?.get_password() {
+        Ok(json) => Ok(Some(json)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn keyring_delete() -> Result<(), keyring::Error> {
+    match keyring_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+fn session_file_path() -> Result<PathBuf, AppError> {
+    SESSION_FILE_DIR
+        .get()
+        .map(|dir| dir.join(SESSION_FILE_NAME))
+        .ok_or_else(|| AppError::InternalError("session file location not initialized".into()))
+}
+
+fn machine_id() -> String {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default()
+}
+
+/// Key for the session file, derived from this machine and OS user. It keeps the file
+/// from being usable when copied elsewhere; it does not protect against other programs
+/// running as the same user, which is why the keyring stays the default.
+fn session_file_cipher() -> XChaCha20Poly1305 {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(SESSION_FILE_KEY_CONTEXT);
+    hasher.update(machine_id().as_bytes());
+    hasher.update([0]);
+    hasher.update(user.as_bytes());
+    XChaCha20Poly1305::new(&hasher.finalize())
+}
+
+/// Nonce followed by the sealed session JSON, written atomically and readable only by
+/// the current user
+fn file_store(json: &str) -> Result<(), AppError> {
+    let path = session_file_path()?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = session_file_cipher()
+        .encrypt(&nonce, json.as_bytes())
+        .map_err(|_| AppError::InternalError("session encryption failed".into()))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AppError::InternalError(format!("session dir create failed: {e}")))?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| AppError::InternalError(format!("session file write failed: {e}")))?;
+    file.write_all(&nonce)
+        .and_then(|()| file.write_all(&sealed))
+        .and_then(|()| file.sync_all())
+        .map_err(|e| AppError::InternalError(format!("session file write failed: {e}")))?;
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| AppError::InternalError(format!("session file write failed: {e}")))?;
+
+    tracing::debug!(target: "auth", "session stored in encrypted file");
+    Ok(())
+}
+
+/// `Ok(None)` when there is no session file. A file that no longer decrypts (another
+/// machine or user, or damaged) is treated like a malformed session.
+fn file_read() -> Result<Option<String>, AppError> {
+    let path = session_file_path()?;
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(AppError::InternalError(format!(
+                "session file read failed: {e}"
+            )))
+        }
+    };
+
+    const NONCE_LEN: usize = 24;
+    let opened = (bytes.len() > NONCE_LEN)
+        .then(|| {
+            let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+            session_file_cipher()
+                .decrypt(XNonce::from_slice(nonce), sealed)
+                .ok()
+        })
+        .flatten()
+        .and_then(|plain| String::from_utf8(plain).ok());
+    match opened {
+        Some(json) => Ok(Some(json)),
+        None => {
+            tracing::error!(target: "auth", "session file unreadable, clearing it");
+            let _ = std::fs::remove_file(&path);
+            Ok(None)
+        }
+    }
+}
+
+fn file_delete() -> Result<(), AppError> {
+    match std::fs::remove_file(session_file_path()?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(AppError::InternalError(format!(
+            "session file delete failed: {e}"
+        ))),
+    }
+}
+
+fn store_session_json(json: &str) -> Result<(), AppError> {
+    if active_session_backend() == SessionBackend::Keyring {
+        match keyring_store(json) {
+            Ok(()) => return Ok(()),
+            Err(e) if fall_back_from_keyring(&e) => {}
+            Err(e) => {
+                tracing::error!(target: "auth", "failed to store session in keyring: {e}");
+                return Err(AppError::KeyringError(e.to_string()));
             }
-            Ok(())
         }
-        Err(e) => {
-            println!("Failed to set password in keyring: {}", e);
-            Err(AppError::KeyringError(e.to_string()))
+    }
+    file_store(json)
+}
+
+fn read_session_json() -> Result<Option<String>, AppError> {
+    if active_session_backend() == SessionBackend::Keyring {
+        match keyring_read() {
+            Ok(Some(json)) => return Ok(Some(json)),
+            // A session stored while the keyring was down is still in the file
+            Ok(None)
+                if session_backend() == SessionBackend::Auto
+                    && SESSION_FILE_DIR.get().is_some() => {}
+            Ok(None) => return Ok(None),
+            Err(e) if fall_back_from_keyring(&e) => {}
+            Err(e) => {
+                tracing::warn!(target: "auth", "failed to read session from keyring: {e}");
+                return Ok(None);
+            }
         }
     }
+    file_read()
 }
 
-/// Retrieve session from OS keyring
-pub fn get_stored_session() -> Result<StoredSession, AppError> {
-    println!("Attempting to retrieve session from keyring");
-    // Use same target to ensure we look in the right collection
-    let entry =
-        keyring::Entry::new_with_target("default", SERVICE_NAME, SESSION_KEY).map_err(|e| {
-            println!("Failed to create keyring entry: {}", e);
-            AppError::KeyringError(e.to_string())
-        })?;
-
-    match entry.get_password() {
-        Ok(json) => {
-            println!("Session retrieved from keyring");
-            serde_json::from_str(&json).map_err(|e| {
-                println!("Failed to parse session json: {}", e);
-                AppError::InternalError(e.to_string())
-            })
+/// Write the session and read it back, so a backend that silently drops or mangles it
+/// is caught now rather than at the next start
+fn persist_session_json(json: &str) -> Result<(), AppError> {
+    store_session_json(json)?;
+    match read_session_json()? {
+        Some(retrieved) if retrieved == json => {
+            tracing::trace!(target: "auth", "stored session verified");
+            Ok(())
         }
-        Err(keyring::Error::NoEntry) => {
-            println!("No session found in keyring (NoEntry)");
-            Err(AppError::SessionNotFound)
+        Some(retrieved) => Err(AppError::KeyringError(format!(
+            "stored session reads back different data (stored {} bytes, read {})",
+            json.len(),
+            retrieved.len()
+        ))),
+        None => Err(AppError::KeyringError(
+            "stored session could not be read back".into(),
+        )),
+    }
+}
+
+/// Store session credentials in the active backend (OS keyring by default). If that
+/// fails the session stays usable for this run: the failure is reported through
+/// `session_persistence_failed` and `SessionInfo::persisted` instead of failing sign-in.
+pub fn store_session(session: &StoredSession) -> Result<(), AppError> {
+    tracing::debug!(target: "auth", handle = %session.handle, "storing session");
+
+    let json =
+        serde_json::to_string(session).map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    tracing::trace!(target: "auth", "session JSON size: {} bytes", json.len());
+
+    let persisted = persist_session_json(&json);
+    set_current_session(Some(session.clone()));
+    match persisted {
+        Ok(()) => {
+            *PERSISTENCE_FAILURE
+                .write()
+                .unwrap_or_else(|e| e.into_inner()) = None;
         }
-        Err(e) => {
-            println!("Failed to get password from keyring: {}", e);
-            Err(AppError::SessionNotFound)
+        Err(err) => {
+            tracing::error!(target: "auth", "session not persisted, kept for this run only: {err}");
+            report_persistence_failure(&session.did, &err);
         }
     }
+    Ok(())
+}
+
+/// The current session, loaded from the active backend on first use
+pub fn get_stored_session() -> Result<StoredSession, AppError> {
+    if let Some(session) = CURRENT_SESSION
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        return Ok(session);
+    }
+
+    let Some(json) = read_session_json()? else {
+        tracing::debug!(target: "auth", "no stored session found");
+        return Err(AppError::SessionNotFound);
+    };
+    tracing::trace!(target: "auth", "session retrieved");
+
+    let Some(session) = parse_stored_session(&json) else {
+        // Unreadable either way; clear it so the user can sign in again
+        let _ = clear_session();
+        return Err(AppError::SessionNotFound);
+    };
+    set_current_session(Some(session.clone()));
+    Ok(session)
+}
+
+/// `None` for a stored session that doesn't parse (damaged, or not a session at all)
+fn parse_stored_session(json: &str) -> Option<StoredSession> {
+    serde_json::from_str(json)
+        .map_err(|e| {
+            tracing::error!(target: "auth", "failed to parse stored session, clearing it: {e}");
+        })
+        .ok()
 }
 
-/// Clear session from OS keyring
+/// Clear the session from every backend, so switching backends never leaves one behind
 pub fn clear_session() -> Result<(), AppError> {
-    let entry = keyring::Entry::new_with_target("default", SERVICE_NAME, SESSION_KEY)
-        .map_err(|e| AppError::KeyringError(e.to_string()))?;
+    set_current_session(None);
+    *PERSISTENCE_FAILURE
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+    if let Err(e) = keyring_delete() {
+        if !keyring_unavailable(&e) {
+            return Err(AppError::KeyringError(e.to_string()));
+        }
+    }
+    if SESSION_FILE_DIR.get().is_some() {
+        file_delete()?;
+    }
+    Ok(())
+}
 
-    // Ignore error if entry doesn't exist
-    let _ = entry.delete_credential();
+/// Switch backends, moving the signed-in session over so the user stays signed in (and
+/// a session that failed to persist gets another try). On failure the previous backend
+/// stays in use.
+pub fn move_session_to(backend: SessionBackend) -> Result<(), AppError> {
+    let session = get_stored_session().ok();
+    let previous_setting = session_backend();
+    let previous = active_session_backend();
+    set_session_backend(backend);
+    if backend != SessionBackend::EncryptedFile {
+        // Give the keyring another chance
+        KEYRING_UNAVAILABLE.store(false, Ordering::Relaxed);
+    }
+
+    let Some(session) = session else {
+        return Ok(());
+    };
+    if active_session_backend() == previous && session_persisted() {
+        return Ok(());
+    }
+    let json =
+        serde_json::to_string(&session).map_err(|e| AppError::InternalError(e.to_string()))?;
+    if let Err(err) = persist_session_json(&json) {
+        set_session_backend(previous_setting);
+        KEYRING_UNAVAILABLE.store(
+            previous == SessionBackend::EncryptedFile && previous_setting == SessionBackend::Auto,
+            Ordering::Relaxed,
+        );
+        return Err(err);
+    }
+    *PERSISTENCE_FAILURE
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = None;
 
+    // Only drop the old copy once the session landed somewhere else
+    if active_session_backend() != previous {
+        match previous {
+            SessionBackend::EncryptedFile => file_delete()?,
+            _ => {
+                let _ = keyring_delete();
+            }
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: serde_json::Value) -> String {
+        format!("e30.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+    }
+
+    #[test]
+    fn jwt_expiry_is_read_from_the_exp_claim() {
+        let now = chrono::Utc::now().timestamp();
+        assert!(jwt_expires_within(
+            &jwt(serde_json::json!({ "exp": now + 60 })),
+            300
+        ));
+        assert!(jwt_expires_within(
+            &jwt(serde_json::json!({ "exp": now - 60 })),
+            300
+        ));
+        assert!(!jwt_expires_within(
+            &jwt(serde_json::json!({ "exp": now + 3600 })),
+            300
+        ));
+        assert!(!jwt_expires_within(
+            &jwt(serde_json::json!({ "scope": "atproto" })),
+            300
+        ));
+        assert!(!jwt_expires_within("not-a-jwt", 300));
+    }
+
+    #[test]
+    fn malformed_session_blobs_are_rejected() {
+        assert!(parse_stored_session("").is_none());
+        assert!(parse_stored_session("{\"did\":\"did:plc:abc\",\"han").is_none());
+        assert!(parse_stored_session("{\"did\":\"did:plc:abc\"}").is_none());
+        assert!(parse_stored_session("[1, 2, 3]").is_none());
+
+        let stored = parse_stored_session(
+            r#"{"did":"did:plc:abc","handle":"alice.test","access_jwt":"a","refresh_jwt":"r",
+                "service_url":"https://pds.test"}"#,
+        )
+        .expect("valid session");
+        assert_eq!(stored.handle, "alice.test");
+        assert_eq!(stored.email_confirmed, None);
+    }
+
+    #[test]
+    fn damaged_session_file_is_cleared() {
+        let dir =
+            std::env::temp_dir().join(format!("blue-horizon-session-{}", uuid::Uuid::new_v4()));
+        init_session_file(Some(dir.clone()));
+        let path = session_file_path().unwrap();
+
+        file_store("{\"did\":\"did:plc:abc\"}").unwrap();
+        assert_eq!(
+            file_read().unwrap().as_deref(),
+            Some("{\"did\":\"did:plc:abc\"}")
+        );
+
+        std::fs::write(&path, b"not an encrypted session").unwrap();
+        assert!(file_read().unwrap().is_none());
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
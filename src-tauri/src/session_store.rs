@@ -5,7 +5,7 @@
 //! automatic token refreshes.
 
 use crate::error::AppError;
-use crate::session::{store_session, StoredSession};
+use crate::session::{get_stored_session, store_session, StoredSession};
 use atrium_common::store::Store;
 use atrium_xrpc::types::AuthorizationToken;
 use bsky_sdk::api::agent::atp_agent::store::AtpSessionStore;
@@ -56,9 +56,9 @@ impl KeyringSessionStore {
                 handle,
                 active: Some(true),
                 did_doc: None,
-                email: None,
+                email: stored.email.clone(),
                 email_auth_factor: None,
-                email_confirmed: None,
+                email_confirmed: stored.email_confirmed,
                 status: None,
             },
             extra_data: ipld_core::ipld::Ipld::Null,
@@ -82,20 +82,30 @@ impl KeyringSessionStore {
     async fn persist_to_keyring(&self, session: &AtpSession) -> Result<(), AppError> {
         let service_url = self.service_url.read().await.clone();
 
+        // Token refreshes don't carry the email fields; keep what we already know
+        let previous = get_stored_session()
+            .ok()
+            .filter(|prev| prev.did == session.data.did.to_string());
+
         let stored = StoredSession {
             did: session.data.did.to_string(),
             handle: session.data.handle.to_string(),
             access_jwt: session.data.access_jwt.clone(),
             refresh_jwt: session.data.refresh_jwt.clone(),
             service_url,
+            email: session
+                .data
+                .email
+                .clone()
+                .or_else(|| previous.as_ref().and_then(|prev| prev.email.clone())),
+            email_confirmed: session
+                .data
+                .email_confirmed
+                .or_else(|| previous.as_ref().and_then(|prev| prev.email_confirmed)),
         };
 
-        println!(
-            "KeyringSessionStore: persisting session to keyring for {}",
-            stored.handle
-        );
+        tracing::debug!(target: "auth", handle = %stored.handle, "persisting refreshed session");
         store_session(&stored)?;
-        println!("KeyringSessionStore: session persisted successfully");
 
         Ok(())
     }
@@ -128,11 +138,9 @@ impl Store<(), AtpSession> for KeyringSessionStore {
     }
 
     async fn set(&self, _key: (), value: AtpSession) -> Result<(), Self::Error> {
-        println!("KeyringSessionStore::set() called - persisting updated tokens");
-
         // Persist to keyring first
         if let Err(e) = self.persist_to_keyring(&value).await {
-            println!("KeyringSessionStore: failed to persist to keyring: {}", e);
+            tracing::error!(target: "auth", "failed to persist refreshed session: {e}");
             return Err(StoreError(e.to_string()));
         }
 
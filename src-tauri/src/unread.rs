@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Foreground reads reuse a cached count younger than this
+const FRESH_FOR: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct UnreadCounts {
+    pub notifications: u32,
+    pub chat: u32,
+}
+
+struct NotificationCount {
+    count: u32,
+    priority: bool,
+    fetched_at: Instant,
+}
+
+/// Unread badge counts shared by the background poller and foreground commands
+#[derive(Default)]
+struct UnreadCache {
+    notifications: Option<NotificationCount>,
+    chat: Option<(u32, Instant)>,
+    /// Unread count per conversation behind the chat total, so reading one convo can
+    /// adjust the total without paging through every conversation again
+    chat_convos: HashMap<String, u32>,
+    /// When notifications were last marked seen; counts fetched before it are stale
+    seen_at: Option<Instant>,
+    /// Last counts sent to the frontend, so unchanged polls don't emit
+    last_emitted: Option<UnreadCounts>,
+}
+
+static CACHE: LazyLock<Mutex<UnreadCache>> = LazyLock::new(|| Mutex::new(UnreadCache::default()));
+
+fn lock() -> std::sync::MutexGuard<'static, UnreadCache> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn emit_if_changed(app: &AppHandle, cache: &mut UnreadCache) {
+    let counts = UnreadCounts {
+        notifications: cache.notifications.as_ref().map_or(0, |n| n.count),
+        chat: cache.chat.map_or(0, |(count, _)| count),
+    };
+    let previous = cache.last_emitted.replace(counts);
+    if previous == Some(counts) {
+        return;
+    }
+
+    if previous.map(|p| p.notifications) != Some(counts.notifications) {
+        let _ = app.emit("unread-count", counts.notifications);
+    }
+    if let Err(err) = app.emit("unread_counts_changed", counts) {
+        tracing::warn!("emit failed: {err}");
+    }
+}
+
+/// Notification count for `priority` mode if it was fetched recently
+pub fn cached_notification_count(priority: bool) -> Option<u32> {
+    let cache = lock();
+    let cached = cache.notifications.as_ref()?;
+    let fresh = cached.priority == priority && cached.fetched_at.elapsed() < FRESH_FOR;
+    fresh.then_some(cached.count)
+}
+
+/// Record a fetched notification count. `fetched_at` is when the request started, so a
+/// response that raced a mark-as-read doesn't resurrect the old count.
+pub fn store_notification_count(app: &AppHandle, priority: bool, count: u32, fetched_at: Instant) {
+    let mut cache = lock();
+    if cache.seen_at.is_some_and(|seen| seen > fetched_at) {
+        return;
+    }
+    cache.notifications = Some(NotificationCount {
+        count,
+        priority,
+        fetched_at,
+    });
+    emit_if_changed(app, &mut cache);
+}
+
+/// Notifications were marked seen; the badge drops to zero until the next fetch
+pub fn mark_notifications_seen(app: &AppHandle) {
+    let mut cache = lock();
+    let now = Instant::now();
+    cache.seen_at = Some(now);
+    if let Some(cached) = cache.notifications.as_mut() {
+        cached.count = 0;
+        cached.fetched_at = now;
+    }
+    emit_if_changed(app, &mut cache);
+}
+
+/// Only some notifications were marked seen; drop the count so the next read refetches it
+pub fn invalidate_notification_count() {
+    let mut cache = lock();
+    cache.seen_at = Some(Instant::now());
+    cache.notifications = None;
+}
+
+/// Chat unread count if it was fetched recently
+pub fn cached_chat_count() -> Option<u32> {
+    let cache = lock();
+    let (count, fetched_at) = cache.chat?;
+    (fetched_at.elapsed() < FRESH_FOR).then_some(count)
+}
+
+/// Record the unread conversations from a full recount; returns the total
+pub fn store_chat_counts(app: &AppHandle, convos: &[(String, u32)]) -> u32 {
+    let mut cache = lock();
+    let count = cache.set_chat_convos(convos);
+    emit_if_changed(app, &mut cache);
+    count
+}
+
+/// A conversation was read down to `unread` messages. Returns the adjusted total, or
+/// `None` when no count is cached and a recount is needed.
+pub fn update_chat_convo(app: &AppHandle, convo_id: &str, unread: u32) -> Option<u32> {
+    let mut cache = lock();
+    let count = cache.set_chat_convo(convo_id, unread)?;
+    emit_if_changed(app, &mut cache);
+    Some(count)
+}
+
+impl UnreadCache {
+    fn set_chat_convos(&mut self, convos: &[(String, u32)]) -> u32 {
+        self.chat_convos = convos.iter().cloned().collect();
+        let count = self.chat_convos.values().sum();
+        self.chat = Some((count, Instant::now()));
+        count
+    }
+
+    /// Keeps the recount time, so the total still goes stale on schedule
+    fn set_chat_convo(&mut self, convo_id: &str, unread: u32) -> Option<u32> {
+        let (total, fetched_at) = self.chat?;
+        let previous = match unread {
+            0 => self.chat_convos.remove(convo_id),
+            n => self.chat_convos.insert(convo_id.to_string(), n),
+        }
+        .unwrap_or(0);
+        let count = (total + unread).saturating_sub(previous);
+        self.chat = Some((count, fetched_at));
+        Some(count)
+    }
+}
+
+/// Forget everything (sign-out), so the next account starts from fresh counts
+pub fn reset() {
+    *lock() = UnreadCache::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convos(counts: &[(&str, u32)]) -> Vec<(String, u32)> {
+        counts.iter().map(|(id, n)| (id.to_string(), *n)).collect()
+    }
+
+    #[test]
+    fn reading_a_convo_adjusts_the_cached_total() {
+        let mut cache = UnreadCache::default();
+        assert_eq!(cache.set_chat_convo("a", 0), None);
+
+        assert_eq!(cache.set_chat_convos(&convos(&[("a", 3), ("b", 2)])), 5);
+        assert_eq!(cache.set_chat_convo("a", 0), Some(2));
+        assert_eq!(cache.set_chat_convo("b", 1), Some(1));
+        // Reading again, or a convo that had nothing unread, changes nothing
+        assert_eq!(cache.set_chat_convo("a", 0), Some(1));
+        assert_eq!(cache.set_chat_convo("c", 0), Some(1));
+        assert_eq!(cache.chat.map(|(count, _)| count), Some(1));
+    }
+}
@@ -2,6 +2,16 @@ import { invoke } from "@tauri-apps/api/core";
 
 import type { Embed } from "@/types/bluesky";
 
+import type { StarterPackSummary } from "./feeds";
+import type { GifResult } from "./gifs";
+
+/** Why a feed item is shown other than being a plain post by its author */
+export type RepostContext =
+  | { kind: "none" }
+  | { kind: "repost"; by_did: string; by_handle: string; by_display_name: string | null }
+  | { kind: "pin" }
+  | { kind: "other"; reason: string };
+
 export interface TimelinePost {
   uri: string;
   cid: string;
@@ -22,11 +32,26 @@ export interface TimelinePost {
   viewer_like?: string | null;
   viewer_repost?: string | null;
   embed?: Embed | null;
+  feed_context?: string | null;
+  /** Why the item is in the feed: "repost", "pin", ... */
+  reason?: string | null;
+  repost_context?: RepostContext;
+  /** Handle of the replied-to author ("Replying to @x"); empty if blocked or deleted */
+  reply_parent_author_handle?: string | null;
+  reply_root_uri?: string | null;
+  /** Feed sort time (repost time for reposts); pass as `sinceIndexedAt` */
+  indexed_at?: string | null;
+  /** Label to warn about before showing the post; posts set to "hide" are left out */
+  content_warning?: string | null;
 }
 
 export interface TimelineResponse {
   posts: TimelinePost[];
   cursor: string | null;
+  /** Set when the server rejected the cursor and this is the first page again */
+  reset?: boolean;
+  /** Incremental refresh hit its page limit; `cursor` continues into the gap */
+  gap?: boolean;
 }
 
 export interface ProfileResponse {
@@ -44,6 +69,21 @@ export interface ProfileResponse {
   viewer_following: string | null;
   viewer_muted: boolean;
   viewer_blocking: string | null;
+  /** Notifications for this account's new posts; null when not subscribed */
+  activity_subscription: ActivitySubscription | null;
+  /** Starter pack the account signed up through, when known */
+  joined_via_starter_pack?: StarterPackSummary | null;
+  /** Feed generators the account publishes (service accounts have these) */
+  feedgens_count: number;
+  lists_count: number;
+  starter_packs_count: number;
+  /** The account runs a labeler; render the labeler profile variant */
+  is_labeler: boolean;
+}
+
+export interface ActivitySubscription {
+  posts: boolean;
+  replies: boolean;
 }
 
 export interface FollowListItem {
@@ -54,6 +94,9 @@ export interface FollowListItem {
   description: string | null;
   is_following: boolean;
   is_followed_by: boolean;
+  viewer_muted: boolean;
+  /** Block record URI, passed to unblockActor */
+  viewer_blocking: string | null;
 }
 
 export interface FollowListResponse {
@@ -61,20 +104,106 @@ export interface FollowListResponse {
   cursor: string | null;
 }
 
+/**
+ * Home timeline items to leave out (both by default shown). Filtered pages can hold
+ * fewer than `limit` posts; keep paging with `cursor`.
+ */
+export interface TimelineFilter {
+  hide_reposts?: boolean;
+  hide_replies?: boolean;
+}
+
 /**
  * Get home timeline
  */
 export async function getTimeline(
   limit: number = 50,
   cursor?: string,
+  filter?: TimelineFilter,
 ): Promise<TimelineResponse> {
   return invoke<TimelineResponse>("get_timeline", {
-    request: { limit, cursor },
+    request: { limit, cursor, ...filter },
   });
 }
 
 /**
- * Get user profile
+ * Payload of `timeline_new_count` / `notifications_new_count`, sent instead of the
+ * `*_updated` event when a background refresh finds items above the shown top.
+ * Fetch the list again to apply them.
+ */
+export interface NewItemsCount {
+  count: number;
+  /** There may be more than `count` new items */
+  more: boolean;
+}
+
+/** Payload of the `timeline_post` event sent by streamTimeline */
+export interface TimelinePostEvent {
+  stream_id: string;
+  index: number;
+  post: TimelinePost;
+}
+
+/**
+ * Like getTimeline, but posts arrive as `timeline_post` events tagged with
+ * `streamId` while the page is processed; the result has no posts, only the cursor.
+ */
+export async function streamTimeline(
+  streamId: string,
+  limit: number = 50,
+  cursor?: string,
+  filter?: TimelineFilter,
+): Promise<TimelineResponse> {
+  return invoke<TimelineResponse>("stream_timeline", {
+    request: { limit, cursor, ...filter },
+    streamId,
+  });
+}
+
+/**
+ * Fetch only timeline posts newer than the newest one already shown.
+ * Returns just the new slice; when `gap` is set, older new posts remain behind `cursor`.
+ */
+export async function getTimelineSince(
+  newest: Pick<TimelinePost, "uri" | "indexed_at">,
+  limit: number = 50,
+  filter?: TimelineFilter,
+): Promise<TimelineResponse> {
+  return invoke<TimelineResponse>("get_timeline", {
+    request: {
+      limit,
+      since_uri: newest.uri,
+      since_indexed_at: newest.indexed_at,
+      ...filter,
+    },
+  });
+}
+
+/**
+ * Fill a gap reported by getTimelineSince: pages from the gap's `cursor` down to the
+ * newest post already shown below it. If `gap` is still set, call again with `cursor`.
+ */
+export async function fillTimelineGap(
+  olderCursor: string,
+  newestKnown: Pick<TimelinePost, "uri" | "indexed_at">,
+  limit: number = 50,
+  filter?: TimelineFilter,
+): Promise<TimelineResponse> {
+  return invoke<TimelineResponse>("fill_timeline_gap", {
+    request: {
+      older_cursor: olderCursor,
+      newest_known_uri: newestKnown.uri,
+      newest_known_indexed_at: newestKnown.indexed_at,
+      limit,
+      ...filter,
+    },
+  });
+}
+
+/**
+ * Get user profile. Fails with INVALID_HANDLE for input that isn't a handle or DID,
+ * NOT_FOUND when no such account exists, ACCOUNT_DEACTIVATED or ACCOUNT_TAKEN_DOWN for
+ * accounts that can't be shown and NETWORK_ERROR when offline without a cached copy.
  */
 export async function getProfile(handle: string): Promise<ProfileResponse> {
   return invoke<ProfileResponse>("get_profile", {
@@ -82,6 +211,24 @@ export async function getProfile(handle: string): Promise<ProfileResponse> {
   });
 }
 
+/**
+ * Download a profile's avatar and banner ahead of navigation; the returned
+ * profile points both at local files
+ */
+export async function prefetchProfileHeader(
+  handle: string,
+): Promise<ProfileResponse> {
+  return invoke<ProfileResponse>("prefetch_profile_header", { handle });
+}
+
+/**
+ * The signed-in user's profile for the account menu; cached, with refreshes
+ * delivered through the `my_profile_updated` event
+ */
+export async function getMyProfile(): Promise<ProfileResponse> {
+  return invoke<ProfileResponse>("get_my_profile");
+}
+
 /**
  * Get account followers
  */
@@ -108,6 +255,65 @@ export async function getFollows(
   });
 }
 
+/** Payload of `graph_export_progress` events */
+export interface GraphExportProgressEvent {
+  /** Id to cancel the export with */
+  job_id: string;
+  kind: "follows" | "followers";
+  rows: number;
+  done: boolean;
+}
+
+export interface GraphExportResponse {
+  cancelled: boolean;
+  rows: number;
+  path: string;
+}
+
+/**
+ * Write everyone you follow to a CSV file (did, handle, display_name). The file only
+ * appears once complete; pass `jobId` to be able to cancel this export on its own.
+ */
+export async function exportFollows(
+  destPath: string,
+  jobId?: string,
+): Promise<GraphExportResponse> {
+  return invoke<GraphExportResponse>("export_follows", { destPath, jobId });
+}
+
+/** Write all your followers to a CSV file; see `exportFollows` */
+export async function exportFollowers(
+  destPath: string,
+  jobId?: string,
+): Promise<GraphExportResponse> {
+  return invoke<GraphExportResponse>("export_followers", { destPath, jobId });
+}
+
+/** Cancel the export started with `jobId`, or every running export without one */
+export async function cancelExport(jobId?: string): Promise<void> {
+  return invoke<void>("cancel_account_export", { jobId });
+}
+
+/**
+ * Get accounts the user has muted
+ */
+export async function getMutedAccounts(
+  cursor?: string,
+  limit: number = 50,
+): Promise<FollowListResponse> {
+  return invoke<FollowListResponse>("get_muted_accounts", { cursor, limit });
+}
+
+/**
+ * Get accounts the user has blocked
+ */
+export async function getBlockedAccounts(
+  cursor?: string,
+  limit: number = 50,
+): Promise<FollowListResponse> {
+  return invoke<FollowListResponse>("get_blocked_accounts", { cursor, limit });
+}
+
 /**
  * Get author's posts feed
  */
@@ -122,6 +328,64 @@ export async function getAuthorFeed(
   });
 }
 
+/**
+ * Approximate last-active time: when the account's newest post or repost was indexed.
+ * Null for empty or hidden feeds. Fails with INVALID_DID for malformed DIDs.
+ */
+export async function getLastActive(did: string): Promise<string | null> {
+  return invoke<string | null>("get_last_active", { did });
+}
+
+export interface PostViewerState {
+  uri: string;
+  is_liked: boolean;
+  is_reposted: boolean;
+  viewer_like: string | null;
+  viewer_repost: string | null;
+  like_count: number;
+  repost_count: number;
+  reply_count: number;
+}
+
+/**
+ * Refresh like/repost state and counts for on-screen posts without refetching the feed.
+ * Each post is also emitted as a `post_viewer_state_changed` event.
+ */
+export async function refreshViewerStates(uris: string[]): Promise<PostViewerState[]> {
+  return invoke<PostViewerState[]>("refresh_viewer_states", { uris });
+}
+
+/** Payload of `thread_reply_created`, emitted after the user's reply is published */
+export interface ThreadReplyCreatedEvent {
+  root_uri: string;
+  parent_uri: string;
+  post: TimelinePost;
+}
+
+/**
+ * Fetch a single post (deep links, link previews). Accepts at:// or bsky.app URLs.
+ * Fails with NOT_FOUND when deleted or POST_BLOCKED when hidden by a block.
+ */
+export async function getPost(uri: string): Promise<TimelinePost> {
+  return invoke<TimelinePost>("get_post", { uri });
+}
+
+/** Download a post's deferred media; returns its embed, finished downloads emit media_ready */
+export async function loadEmbedMedia(postUri: string): Promise<Embed | null> {
+  return invoke<Embed | null>("load_embed_media", { postUri });
+}
+
+export type CardTheme = "light" | "dark";
+
+/** Render a post as a PNG image card at `destPath` (pick it with the save dialog) */
+export async function renderPostCard(
+  uri: string,
+  destPath: string,
+  theme?: CardTheme,
+): Promise<void> {
+  return invoke<void>("render_post_card", { uri, destPath, theme });
+}
+
 export interface ThreadPost {
   uri: string;
   cid: string;
@@ -139,12 +403,60 @@ export interface ThreadPost {
   viewer_like?: string | null;
   viewer_repost?: string | null;
   embed?: Embed | null;
+  /** The threadgate doesn't let you reply; disable the reply button */
+  reply_disabled: boolean;
+  /** The author's postgate doesn't let you quote this post */
+  quotes_disabled: boolean;
+  /** Label to warn about; hidden replies are left out, hidden parents are kept with this set */
+  content_warning: string | null;
+}
+
+export interface ThreadgateRules {
+  /** Anyone can reply */
+  everyone: boolean;
+  /** Allowed groups when restricted; empty with `everyone` false means nobody */
+  allow: ("mentioned" | "followers" | "following" | "list")[];
+  lists: { uri: string; name: string }[];
 }
 
 export interface ThreadResponse {
   post: ThreadPost;
   parent: ThreadResponse | null;
   replies: ThreadResponse[];
+  is_focused: boolean;
+  is_root: boolean;
+  /** Only present on the top-level (focused) node */
+  root_uri?: string;
+  /** Who can reply to the thread; only present on the top-level node */
+  threadgate?: ThreadgateRules;
+  /** Reply posted since the saved read position */
+  is_new: boolean;
+  /** Where you left off; only present on the top-level node */
+  read_position?: ThreadReadPosition;
+  /** Replies marked is_new; only present on the top-level node when a position exists */
+  new_reply_count?: number;
+  /**
+   * Replies were cut to fit `maxNodes` (or not loaded at all by getPostParents);
+   * open this post's thread to load them
+   */
+  has_more: boolean;
+}
+
+export interface ThreadReadPosition {
+  last_seen_reply_uri: string | null;
+  last_seen_at: string;
+}
+
+/**
+ * Save where you left off in a thread (keyed by its root URI).
+ * Omit `lastSeenAt` to mark the thread read as of now.
+ */
+export async function setThreadReadPosition(
+  rootUri: string,
+  lastSeenReplyUri?: string,
+  lastSeenAt?: string,
+): Promise<void> {
+  return invoke<void>("set_thread_read_position", { rootUri, lastSeenReplyUri, lastSeenAt });
 }
 
 export interface ImageInput {
@@ -153,6 +465,8 @@ export interface ImageInput {
 }
 
 export interface PostDraft {
+  slot_id: string;
+  name: string | null;
   text: string;
   reply_to: string | null;
   quote_uri: string | null;
@@ -161,18 +475,53 @@ export interface PostDraft {
   updated_at: string;
 }
 
+export type ThreadReplySort = "most_liked" | "oldest" | "newest";
+
 /**
- * Get post thread with parent chain and replies
+ * Get post thread with parent chain and replies.
+ * `sort` reorders direct replies only; omit it for server order.
  */
 export async function getPostThread(
   uri: string,
   depth?: number,
+  sort?: ThreadReplySort,
+  /** Node budget, parents included (default 500) */
+  maxNodes?: number,
 ): Promise<ThreadResponse> {
   return invoke<ThreadResponse>("get_post_thread", {
-    request: { uri, depth },
+    request: { uri, depth, sort, max_nodes: maxNodes },
   });
 }
 
+/**
+ * Just a post and its parent chain, for "replying to" context in a feed. No replies
+ * are loaded; `has_more` is set when the post has any.
+ */
+export async function getPostParents(uri: string): Promise<ThreadResponse> {
+  return invoke<ThreadResponse>("get_post_thread", {
+    request: { uri, parents_only: true },
+  });
+}
+
+export interface LinkCard {
+  /** Final URL after redirects */
+  uri: string;
+  title: string;
+  description: string;
+  /** Uploaded thumbnail blob ref; pass through unchanged */
+  thumb: unknown | null;
+  /** Source image URL for the composer preview */
+  thumb_url: string | null;
+}
+
+/**
+ * Build a link card for a URL pasted into the composer. Pages without meta tags or
+ * non-HTML links still return a card titled with the URL.
+ */
+export async function fetchLinkCard(url: string): Promise<LinkCard> {
+  return invoke<LinkCard>("fetch_link_card", { url });
+}
+
 /** Create post (queued automatically on offline/network failure). */
 export async function createPost(input: {
   text: string;
@@ -180,19 +529,177 @@ export async function createPost(input: {
   quoteUri?: string;
   quoteCid?: string;
   images: ImageInput[];
+  /** Pass false to disable quoting of the new post */
+  allowQuotes?: boolean;
+  /** BCP-47 language tags; omit to use the account default (getDefaultPostLangs) */
+  langs?: string[];
+  /** Self-applied content labels */
+  labels?: string[];
+  /** Link card from fetchLinkCard; can't be combined with images */
+  external?: LinkCard;
+  /** GIF from searchGifs, posted as a GIF link card (replaces `external`) */
+  gif?: GifResult;
+  /** Alt text for the GIF; defaults to the provider's description */
+  gifAlt?: string;
+  /** Draft slot to clear once sent; defaults to the context's autosave slot */
+  draftSlot?: string;
+  /**
+   * Thread refs of `replyTo` when the composer has them (thread view); lets a reply
+   * composed offline be queued and sent later without resolving the parent again
+   */
+  replyRefs?: ReplyRefs;
+  /**
+   * Show the post right away (default true): prepended to the cached timeline with a
+   * `timeline_updated` event, or for replies announced via `thread_reply_created`
+   */
+  showInTimeline?: boolean;
 }): Promise<void> {
-  return invoke<void>("create_post", input);
+  return invoke<void>("create_post", {
+    request: {
+      text: input.text,
+      reply_to: input.replyTo,
+      quote_uri: input.quoteUri,
+      quote_cid: input.quoteCid,
+      images: input.images,
+      allow_quotes: input.allowQuotes,
+      langs: input.langs,
+      labels: input.labels,
+      external: input.external,
+      gif: input.gif,
+      gif_alt: input.gifAlt,
+      draft_slot: input.draftSlot,
+      reply_refs: input.replyRefs,
+      show_in_timeline: input.showInTimeline,
+    },
+  });
 }
 
-/** Save or update composer draft in backend SQLite cache. */
+export interface PostRef {
+  uri: string;
+  cid: string;
+}
+
+export interface ReplyRefs {
+  root: PostRef;
+  parent: PostRef;
+}
+
+export interface ThreadSegment {
+  text: string;
+  /** Only honoured on the first segment; later ones reply to the previous segment */
+  reply_to?: string | null;
+  quote_uri?: string | null;
+  quote_cid?: string | null;
+  images?: ImageInput[];
+  allow_quotes?: boolean | null;
+  langs?: string[];
+  labels?: string[];
+  external?: LinkCard | null;
+  reply_refs?: ReplyRefs | null;
+}
+
+export interface CreateThreadResponse {
+  posted: PostRef[];
+  /** Index of the segment that failed; later segments were not attempted */
+  failed_index: number | null;
+  error: string | null;
+  /** Unsent segments; pass them back to createThread to continue the thread */
+  remaining: ThreadSegment[];
+}
+
+/**
+ * Publish a self-thread, each segment replying to the previous one. With required alt
+ * text on, fails with ALT_TEXT_MISSING before anything is posted if any segment lacks it.
+ * Segments without `langs` are tagged with the default post languages.
+ */
+export async function createThread(posts: ThreadSegment[]): Promise<CreateThreadResponse> {
+  return invoke<CreateThreadResponse>("create_thread", { posts });
+}
+
+export interface PostgateInfo {
+  post_uri: string;
+  detached_quote_uris: string[];
+  quotes_disabled: boolean;
+}
+
+/** Detach a quote of one of your posts */
+export async function detachQuote(postUri: string, quoteUri: string): Promise<PostgateInfo> {
+  return invoke<PostgateInfo>("detach_quote", { postUri, quoteUri });
+}
+
+/** Re-attach a previously detached quote */
+export async function reattachQuote(postUri: string, quoteUri: string): Promise<PostgateInfo> {
+  return invoke<PostgateInfo>("reattach_quote", { postUri, quoteUri });
+}
+
+/** Stop others from quoting one of your posts */
+export async function disableQuotes(postUri: string): Promise<PostgateInfo> {
+  return invoke<PostgateInfo>("disable_quotes", { postUri });
+}
+
+/** Allow quoting one of your posts again */
+export async function enableQuotes(postUri: string): Promise<PostgateInfo> {
+  return invoke<PostgateInfo>("enable_quotes", { postUri });
+}
+
+/**
+ * Save or update composer draft in backend SQLite cache. Rapid calls are coalesced
+ * (written 500ms after the last one); pass `flush` when the composer closes.
+ */
 export async function savePostDraft(input: {
   text: string;
   replyTo?: string;
   quoteUri?: string;
   quoteCid?: string;
   images: ImageInput[];
+  /** Autosave into this slot instead of the context's default slot */
+  slotId?: string;
+  /** Write immediately instead of waiting for typing to pause */
+  flush?: boolean;
 }): Promise<void> {
-  return invoke<void>("save_post_draft", input);
+  return invoke<void>("save_post_draft", {
+    request: {
+      text: input.text,
+      reply_to: input.replyTo,
+      quote_uri: input.quoteUri,
+      quote_cid: input.quoteCid,
+      images: input.images,
+      slot_id: input.slotId,
+      flush: input.flush,
+    },
+  });
+}
+
+export interface DraftInput {
+  text: string;
+  reply_to?: string | null;
+  quote_uri?: string | null;
+  quote_cid?: string | null;
+  images?: ImageInput[];
+}
+
+/** Save a draft into a named slot; omit `slotId` to create a new slot. */
+export async function saveNamedDraft(
+  payload: DraftInput,
+  slotId?: string,
+  name?: string,
+): Promise<PostDraft> {
+  return invoke<PostDraft>("save_named_draft", { slotId, name, payload });
+}
+
+/** All saved drafts, most recently edited first. */
+export async function listDrafts(): Promise<PostDraft[]> {
+  return invoke<PostDraft[]>("list_drafts");
+}
+
+/** Load a draft by slot id. */
+export async function loadDraft(slotId: string): Promise<PostDraft | null> {
+  return invoke<PostDraft | null>("load_draft", { slotId });
+}
+
+/** Delete a draft slot. */
+export async function deleteDraft(slotId: string): Promise<void> {
+  return invoke<void>("delete_draft", { slotId });
 }
 
 /** Load draft for a composer context (new/reply/quote). */
@@ -228,7 +735,7 @@ export async function unrepostPost(repostUri: string): Promise<void> {
   return invoke<void>("unrepost_post", { repostUri });
 }
 
-/** Follow a user - returns the follow record URI */
+/** Follow a user - returns the follow record URI (the existing one if already following) */
 export async function followUser(did: string): Promise<string> {
   return invoke<string>("follow_user", { did });
 }
@@ -248,7 +755,7 @@ export async function unmuteActor(did: string): Promise<void> {
   return invoke<void>("unmute_actor", { did });
 }
 
-/** Block a user - returns the block record URI */
+/** Block a user - returns the block record URI (the existing one if already blocking) */
 export async function blockActor(did: string): Promise<string> {
   return invoke<string>("block_actor", { did });
 }
@@ -257,3 +764,18 @@ export async function blockActor(did: string): Promise<string> {
 export async function unblockActor(blockUri: string): Promise<void> {
   return invoke<void>("unblock_actor", { blockUri });
 }
+
+export interface AvatarPreloadSummary {
+  /** Already cached or being fetched elsewhere */
+  cached: number;
+  downloaded: number;
+  failed: number;
+}
+
+/**
+ * Cache a screen's avatars in one batch (conversation and member lists).
+ * Each download emits `media_ready` keyed by its remote URL.
+ */
+export async function preloadAvatars(urls: string[]): Promise<AvatarPreloadSummary> {
+  return invoke<AvatarPreloadSummary>("preload_avatars", { urls });
+}
@@ -12,11 +12,31 @@ export interface LoginResponse {
   service: string;
 }
 
+/** How the session signed in, read from the access token's scope */
+export type AuthMethod =
+  | "password"
+  | "app_password"
+  | "app_password_privileged"
+  | "oauth"
+  | "unknown";
+
 export interface SessionInfo {
   did: string;
   handle: string;
   service_url: string;
   is_authenticated: boolean;
+  email: string | null;
+  /** null when the server hasn't reported it yet */
+  email_confirmed: boolean | null;
+  auth_method: AuthMethod;
+  scopes: string[];
+  /** false for app passwords without DM access; chat commands fail with CHAT_SCOPE_MISSING */
+  can_chat: boolean;
+  /**
+   * false when the session couldn't be saved (also sent as the
+   * `session_persistence_failed` event); the user is signed out on restart
+   */
+  persisted: boolean;
 }
 
 export interface AppError {
@@ -46,8 +66,235 @@ export async function getSession(): Promise<SessionInfo | null> {
 }
 
 /**
- * Resume session from stored credentials
+ * Resume session from stored credentials.
+ * Pass `prefetch` to warm the timeline/notifications caches in the background.
+ */
+export async function resumeSession(prefetch = false): Promise<SessionInfo> {
+  return invoke<SessionInfo>("resume_session", { prefetch });
+}
+
+export interface ServerDescription {
+  did: string;
+  available_user_domains: string[];
+  invite_code_required: boolean;
+  phone_verification_required: boolean;
+}
+
+export interface HandleAvailability {
+  handle: string;
+  available: boolean;
+  reason: string | null;
+}
+
+export interface CreateAccountRequest {
+  email: string;
+  handle: string;
+  password: string;
+  invite_code?: string;
+  service?: string;
+}
+
+/**
+ * Signup requirements of a PDS (handle domains, invite codes)
+ */
+export async function describeServer(service?: string): Promise<ServerDescription> {
+  return invoke<ServerDescription>("describe_server", { service });
+}
+
+/**
+ * Check whether a handle is free to register. Rejects with a network or API error when the
+ * server can't answer, rather than reporting the handle as available.
+ */
+export async function checkHandleAvailable(
+  handle: string,
+  service?: string,
+): Promise<HandleAvailability> {
+  return invoke<HandleAvailability>("check_handle_available", { handle, service });
+}
+
+/**
+ * Create an account and sign in to it.
+ * Validation failures reject with codes such as HANDLE_TAKEN, INVALID_HANDLE,
+ * INVALID_PASSWORD, INVALID_EMAIL, INVALID_INVITE_CODE and INVITE_CODE_REQUIRED.
+ */
+export async function createAccount(request: CreateAccountRequest): Promise<LoginResponse> {
+  return invoke<LoginResponse>("create_account", { request });
+}
+
+/**
+ * Email the signed-in user a confirmation code
+ */
+export async function requestEmailConfirmation(): Promise<void> {
+  return invoke<void>("request_email_confirmation");
+}
+
+/**
+ * Confirm the signed-in user's email; rejects with EXPIRED_TOKEN / INVALID_TOKEN on a bad code
+ */
+export async function confirmEmail(token: string): Promise<SessionInfo> {
+  return invoke<SessionInfo>("confirm_email", { token });
+}
+
+/**
+ * Email a password reset code (works signed out)
+ */
+export async function requestPasswordReset(email: string, service?: string): Promise<void> {
+  return invoke<void>("request_password_reset", { email, service });
+}
+
+/**
+ * Set a new password with the emailed reset code (works signed out)
+ */
+export async function resetPassword(
+  token: string,
+  newPassword: string,
+  service?: string,
+): Promise<void> {
+  return invoke<void>("reset_password", { token, newPassword, service });
+}
+
+export interface AppPasswordInfo {
+  name: string;
+  created_at: string;
+  privileged: boolean;
+}
+
+export interface CreatedAppPassword extends AppPasswordInfo {
+  /** Shown once: the server never returns the secret again */
+  password: string;
+}
+
+/**
+ * List app passwords (requires signing in with the account password)
  */
-export async function resumeSession(): Promise<SessionInfo> {
-  return invoke<SessionInfo>("resume_session");
+export async function listAppPasswords(): Promise<AppPasswordInfo[]> {
+  return invoke<AppPasswordInfo[]>("list_app_passwords");
+}
+
+/**
+ * Create an app password; display `password` immediately, it can't be retrieved later
+ */
+export async function createAppPassword(
+  name: string,
+  privileged = false,
+): Promise<CreatedAppPassword> {
+  return invoke<CreatedAppPassword>("create_app_password", { name, privileged });
+}
+
+/**
+ * Revoke an app password by name
+ */
+export async function revokeAppPassword(name: string): Promise<void> {
+  return invoke<void>("revoke_app_password", { name });
+}
+
+/**
+ * Deactivate the account after confirming the password. Fails with INVALID_PASSWORD
+ * or RATE_LIMITED; signing in again later reactivates it.
+ */
+export async function deactivateAccount(password: string): Promise<void> {
+  return invoke<void>("deactivate_account", { password });
+}
+
+/**
+ * Email a deletion code to the account's address
+ */
+export async function requestAccountDeletion(): Promise<void> {
+  return invoke<void>("request_account_deletion");
+}
+
+/**
+ * Permanently delete the account. On success the session and all local data for the
+ * account are gone; route back to sign-in. Fails with INVALID_TOKEN, EXPIRED_TOKEN,
+ * INVALID_PASSWORD or RATE_LIMITED.
+ */
+export async function deleteAccount(token: string, password: string): Promise<void> {
+  return invoke<void>("delete_account", { token, password });
+}
+
+export interface HandleChangedEvent {
+  did: string;
+  old_handle: string;
+  handle: string;
+}
+
+/**
+ * Switch to a new handle (e.g. a custom domain). Fails with INVALID_HANDLE, HANDLE_TAKEN
+ * (the domain points to another account) or HANDLE_NOT_VERIFIED (no DNS TXT record or
+ * well-known file for this account yet). Emits `handle_changed` on success.
+ */
+export async function updateHandle(newHandle: string): Promise<SessionInfo> {
+  return invoke<SessionInfo>("update_handle", { newHandle });
+}
+
+export type MigrationStep =
+  | "create_account"
+  | "import_repo"
+  | "transfer_blobs"
+  | "preferences"
+  | "identity"
+  | "activate"
+  | "done";
+
+export interface MigrateAccountRequest {
+  new_service: string;
+  /** Handle on the new PDS */
+  handle: string;
+  email: string;
+  /** Password for the new account; pass the same one when resuming */
+  password: string;
+  invite_code?: string;
+  /** Code emailed by the old PDS; leave out until `awaiting_plc_token` */
+  plc_token?: string;
+}
+
+export interface MigrationStatus {
+  step: MigrationStep;
+  awaiting_plc_token: boolean;
+  cancelled: boolean;
+  /** Blobs that failed to copy this run; call again to retry them */
+  blobs_failed: number;
+  /** CIDs of blobs given up on (rejected, or failed three runs); they stay missing */
+  blobs_skipped: string[];
+}
+
+export interface MigrationState {
+  did: string;
+  old_service: string;
+  new_service: string;
+  handle: string;
+  step: MigrationStep;
+  plc_token_requested: boolean;
+  /** Failed copy attempts per blob CID */
+  blob_attempts: Record<string, number>;
+  skipped_blobs: string[];
+  started_at: string;
+  updated_at: string;
+}
+
+/** Payload of `account_migration_progress` events */
+export interface MigrationProgressEvent {
+  step: MigrationStep;
+  blobs_done: number;
+  blobs_failed: number;
+  blobs_skipped: number;
+}
+
+/**
+ * Move the account to another PDS, resuming a saved migration. Stops with
+ * `awaiting_plc_token` once the old PDS has emailed its confirmation code; call again
+ * with `plc_token` set. Ends with the session switched to the new PDS.
+ */
+export async function migrateAccount(request: MigrateAccountRequest): Promise<MigrationStatus> {
+  return invoke<MigrationStatus>("migrate_account", { request });
+}
+
+/** Saved progress of a migration started from this device, if any */
+export async function getAccountMigration(): Promise<MigrationState | null> {
+  return invoke<MigrationState | null>("get_account_migration");
+}
+
+/** Stop a running migration after the current request; progress is kept */
+export async function cancelAccountMigration(): Promise<void> {
+  return invoke<void>("cancel_account_migration");
 }
@@ -13,6 +13,19 @@ export interface MessageInfo {
   sender_did: string;
   text: string;
   sent_at: string;
+  embed: MessageEmbedInfo | null;
+}
+
+export interface MessageEmbedInfo {
+  uri: string;
+  status: "available" | "not_found" | "blocked" | "unsupported";
+  cid: string | null;
+  author_did: string | null;
+  author_handle: string | null;
+  author_display_name: string | null;
+  author_avatar: string | null;
+  text: string | null;
+  created_at: string | null;
 }
 
 export interface ConversationInfo {
@@ -22,6 +35,8 @@ export interface ConversationInfo {
   last_message: MessageInfo | null;
   unread_count: number;
   muted: boolean;
+  /** Hidden on this device; only set by getConversations with includeHidden */
+  hidden: boolean;
 }
 
 export interface ConversationsResponse {
@@ -34,8 +49,24 @@ export interface MessagesResponse {
   cursor: string | null;
 }
 
-export async function getConversations(cursor?: string): Promise<ConversationsResponse> {
-  return invoke<ConversationsResponse>("get_conversations", { cursor });
+/**
+ * List conversations. Convos hidden with hideConvo are left out unless `includeHidden`;
+ * a hidden convo that received a new message shows up again.
+ */
+export async function getConversations(
+  cursor?: string,
+  includeHidden?: boolean,
+): Promise<ConversationsResponse> {
+  return invoke<ConversationsResponse>("get_conversations", { cursor, includeHidden });
+}
+
+/** Hide a convo from the list on this device without leaving it */
+export async function hideConvo(convoId: string): Promise<void> {
+  return invoke<void>("hide_convo", { convoId });
+}
+
+export async function unhideConvo(convoId: string): Promise<void> {
+  return invoke<void>("unhide_convo", { convoId });
 }
 
 export async function getMessages(
@@ -47,9 +78,13 @@ export async function getMessages(
   });
 }
 
-export async function sendMessage(convoId: string, text: string): Promise<MessageInfo> {
+export async function sendMessage(
+  convoId: string,
+  text: string,
+  embed?: { uri: string; cid?: string },
+): Promise<MessageInfo> {
   return invoke<MessageInfo>("send_message", {
-    request: { convo_id: convoId, text },
+    request: { convo_id: convoId, text, embed_uri: embed?.uri, embed_cid: embed?.cid },
   });
 }
 
@@ -86,3 +121,24 @@ export interface ChatUnreadCountResponse {
 export async function getChatUnreadCount(): Promise<ChatUnreadCountResponse> {
   return invoke<ChatUnreadCountResponse>("get_chat_unread_count");
 }
+
+export interface MarkAllConvosReadResponse {
+  marked: number;
+}
+
+/**
+ * Mark every conversation read. A `chat_unread_changed` event follows with the new count.
+ */
+export async function markAllConvosRead(): Promise<MarkAllConvosReadResponse> {
+  return invoke<MarkAllConvosReadResponse>("mark_all_convos_read");
+}
+
+export interface CanMessageResponse {
+  can_chat: boolean;
+  reason: string | null;
+  convo_id: string | null;
+}
+
+export async function canMessage(did: string): Promise<CanMessageResponse> {
+  return invoke<CanMessageResponse>("can_message", { did });
+}
@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 
-import type { TimelinePost } from "./api";
+import type { RepostContext, TimelinePost, TimelineResponse } from "./api";
 
 // Feed types
 export interface FeedInfo {
@@ -26,6 +26,8 @@ export interface SuggestedFeedsResponse {
 export interface FeedPostsResponse {
   posts: TimelinePost[];
   cursor: string | null;
+  /** Set when the server rejected the cursor and this is the first page again */
+  reset?: boolean;
 }
 
 // List types
@@ -41,6 +43,10 @@ export interface ListInfo {
   creator_display_name: string | null;
   creator_avatar: string | null;
   member_count: number;
+  /** The viewer mutes everyone on this (moderation) list */
+  viewer_muted: boolean;
+  /** The viewer's list block record, when they block everyone on it */
+  viewer_blocked: string | null;
 }
 
 export interface ListMember {
@@ -54,12 +60,33 @@ export interface ListMember {
 export interface ActorListsResponse {
   lists: ListInfo[];
   cursor: string | null;
+  reset?: boolean;
+}
+
+export interface StarterPackSummary {
+  uri: string;
+  cid: string;
+  name: string;
+  description: string | null;
+  creator_did: string;
+  creator_handle: string;
+  /** Accounts in the pack's list */
+  list_item_count: number;
+  joined_all_time_count: number;
+  joined_week_count: number;
+}
+
+export interface ActorStarterPacksResponse {
+  starter_packs: StarterPackSummary[];
+  cursor: string | null;
+  reset?: boolean;
 }
 
 export interface ListDetailsResponse {
   list: ListInfo;
   members: ListMember[];
   cursor: string | null;
+  reset?: boolean;
 }
 
 // Re-export from api.ts
@@ -72,6 +99,17 @@ export async function getSuggestedFeeds(
   return invoke<SuggestedFeedsResponse>("get_suggested_feeds", { cursor });
 }
 
+/** Popular feeds matching `query` (all popular feeds when empty), for feed discovery */
+export async function searchFeedGenerators(
+  query: string,
+  cursor?: string,
+): Promise<SuggestedFeedsResponse> {
+  return invoke<SuggestedFeedsResponse>("search_feed_generators", {
+    query,
+    cursor,
+  });
+}
+
 export async function getFeed(
   feedUri: string,
   limit?: number,
@@ -82,6 +120,95 @@ export async function getFeed(
   });
 }
 
+// Home screen
+export interface HomeFeedTab {
+  kind: "following" | "feed" | "list";
+  uri: string | null;
+  display_name: string;
+  avatar: string | null;
+  missing: boolean;
+}
+
+export interface HomeConfig {
+  tabs: HomeFeedTab[];
+  timeline: TimelineResponse;
+}
+
+/** Pinned feed tabs plus the first following page; listen for `home_tabs_updated`. */
+export async function getHomeConfig(): Promise<HomeConfig> {
+  return invoke<HomeConfig>("get_home_config");
+}
+
+/**
+ * Persist a drag-reordered feed bar. `orderedUris` (feed/list URIs or
+ * "following") swap among the positions they held; all must be saved.
+ * Resolves with every saved feed in the new order.
+ */
+export async function reorderSavedFeeds(orderedUris: string[]): Promise<string[]> {
+  return invoke<string[]>("reorder_saved_feeds", { orderedUris });
+}
+
+// Feed interaction reporting
+export type FeedInteractionEvent =
+  | "seen"
+  | "clickthrough"
+  | "like"
+  | "repost"
+  | "reply"
+  | "quote"
+  | "share"
+  | "requestLess"
+  | "requestMore";
+
+export interface FeedInteraction {
+  item_uri: string;
+  event: FeedInteractionEvent;
+  feed_context?: string | null;
+}
+
+/** Queue interactions for an algorithmic feed; they are sent in periodic batches */
+export async function sendFeedInteractions(
+  feedUri: string,
+  interactions: FeedInteraction[],
+): Promise<void> {
+  return invoke<void>("send_feed_interactions", { feedUri, interactions });
+}
+
+function recordFeedInteraction(
+  feedUri: string,
+  post: TimelinePost,
+  event: FeedInteractionEvent,
+): Promise<void> {
+  return sendFeedInteractions(feedUri, [
+    { item_uri: post.uri, event, feed_context: post.feed_context },
+  ]);
+}
+
+export const recordFeedSeen = (feedUri: string, post: TimelinePost) =>
+  recordFeedInteraction(feedUri, post, "seen");
+export const recordFeedClickthrough = (feedUri: string, post: TimelinePost) =>
+  recordFeedInteraction(feedUri, post, "clickthrough");
+export const recordFeedLike = (feedUri: string, post: TimelinePost) =>
+  recordFeedInteraction(feedUri, post, "like");
+export const recordFeedShare = (feedUri: string, post: TimelinePost) =>
+  recordFeedInteraction(feedUri, post, "share");
+
+/**
+ * Hide a post from every feed on this device (`post_hidden` is emitted). Pass the
+ * algorithmic feed it was hidden from to also tell the feed "show less like this".
+ */
+export async function hidePost(
+  uri: string,
+  feedUri?: string,
+  feedContext?: string | null,
+): Promise<void> {
+  return invoke<void>("hide_post", { uri, feedUri, feedContext });
+}
+
+export async function unhidePost(uri: string): Promise<void> {
+  return invoke<void>("unhide_post", { uri });
+}
+
 // List functions
 export async function getActorLists(
   actor: string,
@@ -90,6 +217,27 @@ export async function getActorLists(
   return invoke<ActorListsResponse>("get_actor_lists", { actor, cursor });
 }
 
+export interface SearchListsResponse {
+  lists: ListInfo[];
+  cursor: string | null;
+}
+
+/**
+ * Find public lists by searching accounts and gathering their lists; lists whose name
+ * or description matches come first
+ */
+export async function searchLists(query: string, cursor?: string): Promise<SearchListsResponse> {
+  return invoke<SearchListsResponse>("search_lists", { query, cursor });
+}
+
+/** Starter packs an actor created; empty when they have none */
+export async function getActorStarterPacks(
+  actor: string,
+  cursor?: string,
+): Promise<ActorStarterPacksResponse> {
+  return invoke<ActorStarterPacksResponse>("get_actor_starter_packs", { actor, cursor });
+}
+
 export async function getList(
   listUri: string,
   cursor?: string,
@@ -187,19 +335,32 @@ export interface ListFeedPost {
   is_reposted: boolean;
   viewer_like: string | null;
   viewer_repost: string | null;
+  feed_context: string | null;
+  reason: string | null;
+  repost_context: RepostContext;
+  /** Label to warn about before showing the post */
+  content_warning: string | null;
 }
 
 export interface ListFeedResponse {
   posts: ListFeedPost[];
   cursor: string | null;
+  reset?: boolean;
 }
 
 export async function getListFeed(
   listUri: string,
   limit: number = 50,
   cursor?: string,
+  filter?: { includeReplies?: boolean; includeReposts?: boolean },
 ): Promise<ListFeedResponse> {
   return invoke<ListFeedResponse>("get_list_feed", {
-    request: { list_uri: listUri, limit, cursor },
+    request: {
+      list_uri: listUri,
+      limit,
+      cursor,
+      include_replies: filter?.includeReplies,
+      include_reposts: filter?.includeReposts,
+    },
   });
 }
@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface GifResult {
+  id: string;
+  title: string;
+  /** Provider description, used as default alt text */
+  alt: string;
+  url: string;
+  /** Small animated preview for the picker grid */
+  preview_url: string;
+  still_url: string | null;
+  width: number;
+  height: number;
+}
+
+export interface GifSearchResponse {
+  gifs: GifResult[];
+  cursor: string | null;
+  /** Provider attribution ("Powered by Tenor"); must be shown with the results */
+  attribution: string;
+}
+
+/**
+ * Search Tenor; an empty query returns featured GIFs.
+ * Fails with GIF_API_KEY_MISSING / GIF_API_KEY_INVALID when the key isn't usable.
+ */
+export async function searchGifs(
+  query: string,
+  cursor?: string,
+  limit?: number,
+): Promise<GifSearchResponse> {
+  return invoke<GifSearchResponse>("search_gifs", { query, cursor, limit });
+}
+
+/** Whether a Tenor API key is configured */
+export async function getGifSearchEnabled(): Promise<boolean> {
+  return invoke<boolean>("get_gif_search_enabled");
+}
+
+/** Set the Tenor API key; pass nothing to clear it */
+export async function setTenorApiKey(apiKey?: string): Promise<void> {
+  return invoke<void>("set_tenor_api_key", { apiKey });
+}
@@ -0,0 +1,66 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type LinkKind = "post" | "profile" | "feed" | "list" | "starter_pack" | "record";
+
+export interface ResolvedLink {
+  at_uri: string;
+  kind: LinkKind;
+  did: string;
+}
+
+/**
+ * Resolve a pasted bsky.app URL (or an at:// URI with a handle) to a DID-based at:// URI
+ */
+export async function resolveBskyUrl(url: string): Promise<ResolvedLink> {
+  return invoke<ResolvedLink>("resolve_bsky_url", { url });
+}
+
+export interface ShareLinks {
+  /** Canonical https://bsky.app URL, using the handle rather than the DID when possible */
+  url: string;
+  at_uri: string;
+}
+
+/**
+ * Shareable links for a post (accepts an at:// URI or bsky.app link)
+ */
+export async function postShareUrl(uri: string): Promise<ShareLinks> {
+  return invoke<ShareLinks>("post_share_url", { uri });
+}
+
+/**
+ * Shareable links for a profile
+ */
+export async function profileShareUrl(didOrHandle: string): Promise<ShareLinks> {
+  return invoke<ShareLinks>("profile_share_url", { didOrHandle });
+}
+
+export type AtprotoTarget =
+  | {
+      kind: "post";
+      uri: string;
+      cid: string;
+      author_did: string;
+      author_handle: string;
+      /** Thread root, when the post is a reply */
+      root_uri: string | null;
+    }
+  | { kind: "profile"; did: string; handle: string; display_name: string | null }
+  | { kind: "feed"; uri: string; display_name: string; creator_did: string }
+  | { kind: "list"; uri: string; name: string; purpose: string; creator_did: string }
+  | {
+      kind: "starter_pack";
+      uri: string;
+      name: string;
+      creator_did: string;
+      list_uri: string | null;
+    }
+  | { kind: "record"; uri: string; collection: string; cid: string | null; value: unknown };
+
+/**
+ * Resolve an at:// URI, bare DID or bsky.app link to the view needed to open it.
+ * Collections without a screen come back as `kind: "record"` with the raw value.
+ */
+export async function resolveAtprotoUri(uri: string): Promise<AtprotoTarget> {
+  return invoke<AtprotoTarget>("resolve_atproto_uri", { uri });
+}
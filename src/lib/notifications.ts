@@ -27,22 +27,69 @@ export interface NotificationInfo {
   record: NotificationRecord | null;
 }
 
+/**
+ * Payload of `notifications_new`: unread notifications not alerted on before, oldest
+ * first. Emitted by the background poller; safe to raise desktop alerts for each.
+ */
+export type NewNotificationsEvent = NotificationInfo[];
+
 export interface NotificationsResponse {
   notifications: NotificationInfo[];
   cursor: string | null;
+  /** Set when the server rejected the cursor and this is the first page again */
+  reset?: boolean;
 }
 
+/**
+ * `priority` restricts results to accounts you follow; omit it to use the saved setting.
+ */
 export async function getNotifications(
   cursor?: string,
   limit: number = 25,
+  priority?: boolean,
 ): Promise<NotificationsResponse> {
-  return invoke<NotificationsResponse>("get_notifications", { cursor, limit });
+  return invoke<NotificationsResponse>("get_notifications", { cursor, limit, priority });
+}
+
+/** Payload of the `unread_counts_changed` event, emitted only when a count changes */
+export interface UnreadCounts {
+  notifications: number;
+  chat: number;
+}
+
+/** Served from the shared cache when the last fetch is under a minute old */
+export async function getUnreadCount(priority?: boolean): Promise<number> {
+  return invoke<number>("get_unread_count", { priority });
+}
+
+export async function getNotificationPriority(): Promise<boolean> {
+  return invoke<boolean>("get_notification_priority");
+}
+
+export async function setNotificationPriority(enabled: boolean): Promise<void> {
+  return invoke<void>("set_notification_priority", { enabled });
+}
+
+/**
+ * Mark everything read, or only notifications indexed at or before `upTo`
+ * (a notification's `indexed_at`); cached pages update via `notifications_updated`
+ */
+export async function markNotificationsRead(upTo?: string): Promise<void> {
+  return invoke<void>("mark_notifications_read", { upTo });
 }
 
-export async function getUnreadCount(): Promise<number> {
-  return invoke<number>("get_unread_count");
+/**
+ * Rejects with code ACTIVITY_SUBSCRIPTION_LIMIT once the account cap is reached, or
+ * RATE_LIMITED when the server is throttling requests
+ */
+export async function subscribeToUserActivity(
+  did: string,
+  includePosts: boolean,
+  includeReplies: boolean,
+): Promise<void> {
+  return invoke<void>("subscribe_to_user_activity", { did, includePosts, includeReplies });
 }
 
-export async function markNotificationsRead(): Promise<void> {
-  return invoke<void>("mark_notifications_read");
+export async function unsubscribeToUserActivity(did: string): Promise<void> {
+  return invoke<void>("unsubscribe_to_user_activity", { did });
 }
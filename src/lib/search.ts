@@ -25,6 +25,14 @@ export interface SearchResults {
   cursor?: string;
 }
 
+export interface TrendingTopic {
+  topic: string;
+  displayName?: string;
+  description?: string;
+  link?: string;
+  searchQuery?: string;
+}
+
 export interface ParsedSearchQuery {
   author: string | null;
   searchText: string;
@@ -77,3 +85,8 @@ export async function searchPosts(
 ): Promise<SearchResults> {
   return await invoke("search_posts", { query, limit, cursor, sort, author });
 }
+
+/** Trending topics; empty when the unspecced endpoint changes shape */
+export async function getTrends(limit?: number): Promise<TrendingTopic[]> {
+  return await invoke("get_trends", { limit });
+}
@@ -0,0 +1,316 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Network request timeout in seconds
+ */
+export async function getRequestTimeout(): Promise<number> {
+  return invoke<number>("get_request_timeout");
+}
+
+/**
+ * Set the network request timeout; returns the clamped value that was applied.
+ * Agent requests pick it up after the next sign-in or session resume.
+ */
+export async function setRequestTimeout(secs: number): Promise<number> {
+  return invoke<number>("set_request_timeout", { secs });
+}
+
+/**
+ * AppView endpoints used for the backend's direct HTTP requests (likes fallback, trends).
+ * Normal reads go through the signed-in PDS, which proxies them to its own AppView.
+ */
+export interface Endpoints {
+  appview_url: string;
+  public_appview_url: string;
+}
+
+export async function getEndpoints(): Promise<Endpoints> {
+  return invoke<Endpoints>("get_endpoints");
+}
+
+/**
+ * Point direct requests at another AppView; omitted values restore the defaults.
+ * Custom URLs must use https (http only for localhost) and be reachable, or the call
+ * fails without saving. Custom AppViews are never sent the session token.
+ */
+export async function setEndpoints(
+  appviewUrl?: string,
+  publicAppviewUrl?: string,
+): Promise<Endpoints> {
+  return invoke<Endpoints>("set_endpoints", { appviewUrl, publicAppviewUrl });
+}
+
+/**
+ * Active log filter: a level ("info") or directives such as "warn,cache=debug".
+ * Targets: auth, cache, media, retry_queue.
+ */
+export async function getLogLevel(): Promise<string> {
+  return invoke<string>("get_log_level");
+}
+
+/** Change log verbosity; returns the filter as applied and saves it for next launch */
+export async function setLogLevel(level: string): Promise<string> {
+  return invoke<string>("set_log_level", { level });
+}
+
+/** Current log file, for attaching to bug reports; null if file logging is unavailable */
+export async function getLogFilePath(): Promise<string | null> {
+  return invoke<string | null>("get_log_file_path");
+}
+
+export interface ImageEncoding {
+  /** Thumbnails are scaled down to at most this width (128-4096) */
+  thumb_max_width: number;
+  /** Lossy quality 1-100 (stored as JPEG); null stores lossless WebP */
+  lossy_quality: number | null;
+}
+
+/** Storage of cached post images; photos are JPEG sources, graphics everything else */
+export interface ImageCachePolicy {
+  photo: ImageEncoding;
+  graphic: ImageEncoding;
+}
+
+export async function getImagePolicy(): Promise<ImageCachePolicy> {
+  return invoke<ImageCachePolicy>("get_image_policy");
+}
+
+/** Change the image cache policy; cached images are re-encoded when next shown */
+export async function setImagePolicy(policy: ImageCachePolicy): Promise<ImageCachePolicy> {
+  return invoke<ImageCachePolicy>("set_image_policy", { policy });
+}
+
+/** Whether labelled media (porn, sexual, nudity, graphic-media) is flagged `sensitive` */
+export async function getBlurSensitiveMedia(): Promise<boolean> {
+  return invoke<boolean>("get_blur_sensitive_media");
+}
+
+/** Turn blurring of labelled media on or off; applies to posts loaded afterwards */
+export async function setBlurSensitiveMedia(enabled: boolean): Promise<void> {
+  return invoke<void>("set_blur_sensitive_media", { enabled });
+}
+
+/** Media kinds downloaded as soon as they are shown; off leaves them deferred */
+export interface MediaAutoload {
+  autoload_images: boolean;
+  autoload_avatars: boolean;
+  autoload_link_thumbs: boolean;
+  autoload_video_posters: boolean;
+}
+
+export async function getMediaAutoload(): Promise<MediaAutoload> {
+  return invoke<MediaAutoload>("get_media_autoload");
+}
+
+/** Applies to posts loaded afterwards; deferred media loads with loadEmbedMedia */
+export async function setMediaAutoload(autoload: MediaAutoload): Promise<MediaAutoload> {
+  return invoke<MediaAutoload>("set_media_autoload", { autoload });
+}
+
+/** "auto" uses the OS keyring and falls back to the encrypted file without one */
+export type SessionBackend = "auto" | "keyring" | "encrypted_file";
+
+export interface SessionBackendInfo {
+  backend: SessionBackend;
+  /** Where the session is kept right now */
+  active: Exclude<SessionBackend, "auto">;
+}
+
+export async function getSessionBackend(): Promise<SessionBackendInfo> {
+  return invoke<SessionBackendInfo>("get_session_backend");
+}
+
+/** Moves a signed-in session to the chosen backend */
+export async function setSessionBackend(
+  backend: SessionBackend,
+): Promise<SessionBackendInfo> {
+  return invoke<SessionBackendInfo>("set_session_backend", { backend });
+}
+
+/** Send attempts before a queued post is marked failed */
+export async function getRetryMaxAttempts(): Promise<number> {
+  return invoke<number>("get_retry_max_attempts");
+}
+
+/** Set retry attempts (1-20); returns the value applied */
+export async function setRetryMaxAttempts(attempts: number): Promise<number> {
+  return invoke<number>("set_retry_max_attempts", { attempts });
+}
+
+/** Payload of `media_cache_rebuild_progress` events and of rebuildMediaCache's result */
+export interface MediaCacheRebuildProgress {
+  processed: number;
+  total: number;
+  valid: number;
+  reencoded: number;
+  /** Corrupt or orphaned entries deleted; they download again when next shown */
+  removed: number;
+  cancelled: boolean;
+}
+
+/** Validate the media cache and re-encode it to the current image policy */
+export async function rebuildMediaCache(): Promise<MediaCacheRebuildProgress> {
+  return invoke<MediaCacheRebuildProgress>("rebuild_media_cache");
+}
+
+export async function cancelMediaCacheRebuild(): Promise<void> {
+  return invoke<void>("cancel_media_cache_rebuild");
+}
+
+/** How imported rows that already exist locally are handled */
+export type ImportConflict = "skip" | "overwrite" | "newer";
+
+export interface LocalDataImportSummary {
+  settings_imported: number;
+  settings_skipped: number;
+  drafts_imported: number;
+  drafts_skipped: number;
+  view_history_imported: number;
+}
+
+/**
+ * Write drafts and settings to a JSON file; credentials and API keys are not included.
+ * View history is left out unless `includeViewHistory` is set.
+ */
+export async function exportLocalData(
+  path: string,
+  includeViewHistory?: boolean,
+): Promise<void> {
+  return invoke<void>("export_local_data", { path, includeViewHistory });
+}
+
+/**
+ * Merge a file from exportLocalData into the signed-in account.
+ * Existing rows are kept by default ("skip").
+ */
+export async function importLocalData(
+  path: string,
+  conflict?: ImportConflict,
+): Promise<LocalDataImportSummary> {
+  return invoke<LocalDataImportSummary>("import_local_data", { path, conflict });
+}
+
+/** Startup found the local database unreadable and moved it aside */
+export interface DbRecovery {
+  backup_path: string;
+  reason: string;
+}
+
+/**
+ * Non-null when the database was recreated at startup (also sent as the
+ * `db_recovered` event, usually before the UI is listening)
+ */
+export async function getDbRecovery(): Promise<DbRecovery | null> {
+  return invoke<DbRecovery | null>("get_db_recovery");
+}
+
+// View history (local "recently viewed" quick-switcher)
+export type ViewKind = "profile" | "post";
+
+export interface ViewHistoryEntry {
+  kind: ViewKind;
+  /** Profile DID or post AT-URI */
+  subject: string;
+  title: string;
+  subtitle: string | null;
+  avatar: string | null;
+  viewed_at: string;
+}
+
+/** Recently opened profiles and/or posts, newest first */
+export async function getViewHistory(
+  kind?: ViewKind,
+  limit?: number,
+): Promise<ViewHistoryEntry[]> {
+  return invoke<ViewHistoryEntry[]>("get_view_history", { kind, limit });
+}
+
+export async function clearViewHistory(kind?: ViewKind): Promise<void> {
+  return invoke<void>("clear_view_history", { kind });
+}
+
+export async function getViewHistoryEnabled(): Promise<boolean> {
+  return invoke<boolean>("get_view_history_enabled");
+}
+
+/** Turning history off also clears it */
+export async function setViewHistoryEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>("set_view_history_enabled", { enabled });
+}
+
+export type LabelVisibility = "show" | "warn" | "hide";
+
+export interface ContentLabelPref {
+  label: string;
+  visibility: LabelVisibility;
+  /** Always hidden while adult content is disabled */
+  adult_only: boolean;
+}
+
+export interface ContentLabelPrefs {
+  adult_content_enabled: boolean;
+  /** The account is under 18; disable the adult content switch */
+  adult_content_locked: boolean;
+  labels: ContentLabelPref[];
+}
+
+export async function getContentLabelPrefs(): Promise<ContentLabelPrefs> {
+  return invoke<ContentLabelPrefs>("get_content_label_prefs");
+}
+
+/** Fails with INVALID_LABEL for labels that aren't configurable */
+export async function setContentLabelPref(
+  label: string,
+  visibility: LabelVisibility,
+): Promise<ContentLabelPrefs> {
+  return invoke<ContentLabelPrefs>("set_content_label_pref", { label, visibility });
+}
+
+/** Fails with ADULT_CONTENT_NOT_ALLOWED when the account is age-restricted */
+export async function setAdultContentEnabled(enabled: boolean): Promise<ContentLabelPrefs> {
+  return invoke<ContentLabelPrefs>("set_adult_content_enabled", { enabled });
+}
+
+export interface LabelDefinition {
+  value: string;
+  /** Localized name; the raw value when the labeler doesn't define one */
+  name: string;
+  description: string | null;
+  severity: "inform" | "alert" | "none" | null;
+  adult_only: boolean;
+}
+
+export interface LabelerServiceInfo {
+  did: string;
+  labels: LabelDefinition[];
+  /** The labeler couldn't be reached and nothing was cached */
+  available: boolean;
+  cached_at: string | null;
+}
+
+/** Fails with INVALID_DID for malformed DIDs */
+export async function getLabelerServices(
+  dids: string[],
+  lang?: string,
+): Promise<LabelerServiceInfo[]> {
+  return invoke<LabelerServiceInfo[]>("get_labeler_services", { dids, lang });
+}
+
+/** Languages posts are tagged with when createPost gets no `langs` */
+export async function getDefaultPostLangs(): Promise<string[]> {
+  return invoke<string[]>("get_default_post_langs");
+}
+
+/** Up to 3 BCP-47 tags (empty clears); fails with INVALID_LANGUAGE. Returns the saved list. */
+export async function setDefaultPostLangs(langs: string[]): Promise<string[]> {
+  return invoke<string[]>("set_default_post_langs", { langs });
+}
+
+export async function getRequireAltText(): Promise<boolean> {
+  return invoke<boolean>("get_require_alt_text");
+}
+
+/** When on, createPost fails with ALT_TEXT_MISSING listing images without alt text */
+export async function setRequireAltText(required: boolean): Promise<void> {
+  return invoke<void>("set_require_alt_text", { required });
+}
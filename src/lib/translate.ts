@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface Translation {
+  translated_text: string;
+  /** Detected (or given) source language, when known */
+  source_lang: string | null;
+  /** Served from the local cache */
+  cached: boolean;
+}
+
+export interface TranslationBackend {
+  endpoint: string | null;
+  has_api_key: boolean;
+}
+
+/**
+ * Translate text through the configured LibreTranslate-compatible service.
+ * Fails with TRANSLATION_NOT_CONFIGURED when no service is set up and with
+ * TRANSLATION_UNAVAILABLE when the service errors or rate-limits.
+ */
+export async function translateText(
+  text: string,
+  targetLang: string,
+  sourceLang?: string,
+): Promise<Translation> {
+  return invoke<Translation>("translate_text", { text, targetLang, sourceLang });
+}
+
+/** The configured translation service (the API key is never returned) */
+export async function getTranslationBackend(): Promise<TranslationBackend> {
+  return invoke<TranslationBackend>("get_translation_backend");
+}
+
+/**
+ * Set or clear (nothing or empty) the translation endpoint. The API key is kept when
+ * omitted and cleared when empty.
+ */
+export async function setTranslationBackend(
+  endpoint?: string,
+  apiKey?: string,
+): Promise<TranslationBackend> {
+  return invoke<TranslationBackend>("set_translation_backend", { endpoint, apiKey });
+}
@@ -93,6 +93,10 @@ export interface ImageEmbed {
     loading?: boolean;
     /** Whether this is an animated GIF */
     is_gif?: boolean;
+    /** Labelled sexual or graphic; blur until tapped */
+    sensitive?: boolean;
+    /** Not downloaded (image autoload off); thumb and fullsize are empty until loadEmbedMedia */
+    deferred?: boolean;
   }[];
 }
 
@@ -109,7 +113,12 @@ export interface ExternalEmbed {
     uri: string;
     title: string;
     description: string;
+    /** file:// once cached; otherwise remote until a media_ready event arrives */
     thumb?: string;
+    /** Display host of `uri` (no "www.") */
+    host?: string;
+    /** Has a thumb that wasn't loaded (link thumb autoload off) */
+    thumb_deferred?: boolean;
   };
 }
 
@@ -153,6 +162,10 @@ export interface VideoEmbed {
     thumbnail?: string;
     alt?: string;
     aspect_ratio?: { width: number; height: number };
+    /** Labelled sexual or graphic; blur the thumbnail until tapped */
+    sensitive: boolean;
+    /** Has a poster that wasn't loaded (video poster autoload off) */
+    thumbnail_deferred: boolean;
   };
 }
 